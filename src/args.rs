@@ -1,19 +1,74 @@
 //! Parses the arguments to the program, if present.
 
-use crate::util;
-use std::{borrow::Cow, env, fs, io};
+use crate::{
+    grid::{builder::ClueLayout, colors::BuiltinPalette},
+    source::{self, url::UrlSource, PuzzleSource},
+    util,
+};
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use terminal::util::Size;
 
 /// The maximum grid size must not have more than 2 digits
 /// because such numbers cannot be displayed correctly on the grid
 /// due to the grid being based on two characters for numbers.
-const MAX_GRID_SIZE: u16 = 99;
+pub const MAX_GRID_SIZE: u16 = 99;
 
 /// The values that can be created out of the arguments.
 #[derive(Debug)]
 pub enum Arg {
-    File { name: String, content: String },
+    File {
+        name: String,
+        content: String,
+    },
+    /// The filename of a puzzle pack to play.
+    Pack(String),
+    /// Adds the grid in the given `.yaya` or `.g` file to a puzzle pack.
+    PackAdd {
+        pack: String,
+        name: String,
+        content: String,
+    },
+    /// Lists the puzzles of a puzzle pack.
+    PackList(String),
+    /// Checks that nothing is lost when saving, exporting or sharing the progress on the given `.yaya` or `.g` file.
+    SelfCheck {
+        name: String,
+        content: String,
+    },
+    /// Prints a description of the progress on the puzzle in the given `.yaya` or `.g` file or snapshot in words.
+    Describe {
+        name: String,
+        content: String,
+    },
+    /// Solves the puzzle in the given `.yaya` or `.g` file without playing it.
+    Solve {
+        name: String,
+        content: String,
+    },
+    /// Compares the puzzles in two `.yaya` or `.g` files, given by their names and contents.
+    DiffPuzzles {
+        old: (String, String),
+        new: (String, String),
+    },
+    /// The path of a folder of grid files to pick puzzles from.
+    Folder(String),
+    /// The path of a folder of grid files to time the solver on.
+    Bench(String),
+    Code(String),
+    /// A picture made of lines of text, read from the standard input.
+    Picture(String),
+    /// The content of a text snapshot of the progress on a puzzle.
+    Snapshot(String),
     GridSize(Size),
+    /// Prints how every subsystem handles every kind of cell. This is for development and not listed in the help.
+    AuditCells,
     Help,
     Version,
 }
@@ -66,6 +121,65 @@ fn parse_size(width_str: &str, height_str: &str) -> Result<Option<Arg>, SizeErro
     Err(SizeError::FileNotFound)
 }
 
+pub fn has_extension(filename: &str, expected_extension: &str) -> bool {
+    let path = Path::new(filename);
+    if let Some(extension) = path.extension() {
+        extension == expected_extension
+    } else {
+        false
+    }
+}
+
+/// Parses the arguments for the pack maintenance subcommands.
+fn parse_pack_command(
+    command: &str,
+    mut strings: impl Iterator<Item = String>,
+) -> Result<Arg, Cow<'static, str>> {
+    let pack = strings
+        .next()
+        .filter(|pack| has_extension(pack, "yyp"))
+        .ok_or_else(|| format!("Expected a .yyp pack after {}", command))?;
+
+    if command == "--pack-list" {
+        return Ok(Arg::PackList(pack));
+    }
+
+    let filename = strings
+        .next()
+        .filter(|filename| has_extension(filename, "yaya") || has_extension(filename, "g"))
+        .ok_or("Expected a .yaya or .g grid file after the pack")?;
+    let content = fs::read_to_string(&filename).map_err(|_| "File reading error")?;
+    let name = Path::new(&filename)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(Arg::PackAdd {
+        pack,
+        name,
+        content,
+    })
+}
+
+fn parse_diff_command(strings: impl Iterator<Item = String>) -> Result<Arg, Cow<'static, str>> {
+    let mut files = strings.take(2).map(|filename| {
+        if !(has_extension(&filename, "yaya") || has_extension(&filename, "g")) {
+            return Err(Cow::from(
+                "Expected two .yaya or .g grid files after --diff-puzzles",
+            ));
+        }
+        let content = source::File(PathBuf::from(&filename)).read()?;
+        Ok((filename, content))
+    });
+
+    let missing_file = || Cow::from("Expected two .yaya or .g grid files after --diff-puzzles");
+    let old = files.next().ok_or_else(missing_file)??;
+    let new = files.next().ok_or_else(missing_file)??;
+
+    Ok(Arg::DiffPuzzles { old, new })
+}
+
 fn parse_strings(
     first_string: String,
     second_string: Option<String>,
@@ -76,17 +190,11 @@ fn parse_strings(
 
     match open_options.open(&first_string) {
         Ok(mut file) => {
-            fn valid_extension(str: &str) -> bool {
-                let path = std::path::Path::new(str);
-                if let Some(extension) = path.extension() {
-                    extension == "yaya"
-                } else {
-                    false
-                }
+            if has_extension(&first_string, "yyp") {
+                return Ok(Some(Arg::Pack(first_string)));
             }
-
-            if !valid_extension(&first_string) {
-                return Err("Filename extension must be \"yaya\"".into());
+            if !has_extension(&first_string, "yaya") && !has_extension(&first_string, "g") {
+                return Err("Filename extension must be \"yaya\", \"g\" or \"yyp\"".into());
             }
 
             let content = util::read_file_content(&mut file).map_err(|_| "File reading error")?;
@@ -96,59 +204,292 @@ fn parse_strings(
                 content,
             }))
         }
-        Err(err) => match err.kind() {
-            io::ErrorKind::NotFound => {
-                if first_string == "--help" || first_string == "-h" {
-                    Ok(Some(Arg::Help))
-                } else if first_string == "--version" || first_string == "-V" {
-                    Ok(Some(Arg::Version))
-                } else {
-                    let result = if let Some(second_string) = second_string {
-                        parse_size(&first_string, &second_string)
+        Err(err) => {
+            match err.kind() {
+                io::ErrorKind::NotFound => {
+                    if first_string == "--help" || first_string == "-h" {
+                        Ok(Some(Arg::Help))
+                    } else if first_string == "--audit-cells" {
+                        Ok(Some(Arg::AuditCells))
+                    } else if first_string == "--version" || first_string == "-V" {
+                        Ok(Some(Arg::Version))
+                    } else if first_string == "-" || first_string == "--stdin" {
+                        Ok(Some(Arg::Picture(source::Stdin.read()?)))
+                    } else if first_string == "--snapshot" {
+                        if let Some(filename) = second_string {
+                            let snapshot = source::File(filename.into()).read()?;
+                            Ok(Some(Arg::Snapshot(snapshot)))
+                        } else {
+                            Err("Expected a snapshot file after --snapshot".into())
+                        }
+                    } else if first_string == "--self-check" {
+                        let filename = second_string
+                            .filter(|filename| {
+                                has_extension(filename, "yaya") || has_extension(filename, "g")
+                            })
+                            .ok_or("Expected a .yaya or .g grid file after --self-check")?;
+                        let content = source::File(PathBuf::from(&filename)).read()?;
+                        Ok(Some(Arg::SelfCheck {
+                            name: filename,
+                            content,
+                        }))
+                    } else if first_string == "--describe" {
+                        let filename = second_string
+                        .filter(|filename| {
+                            has_extension(filename, "yaya")
+                                || has_extension(filename, "g")
+                                || has_extension(filename, "txt")
+                        })
+                        .ok_or("Expected a .yaya or .g grid file or a .txt snapshot after --describe")?;
+                        let content = source::File(PathBuf::from(&filename)).read()?;
+                        Ok(Some(Arg::Describe {
+                            name: filename,
+                            content,
+                        }))
+                    } else if first_string == "--solve" {
+                        let filename = second_string
+                            .filter(|filename| {
+                                has_extension(filename, "yaya") || has_extension(filename, "g")
+                            })
+                            .ok_or("Expected a .yaya or .g grid file after --solve")?;
+                        let content = source::File(PathBuf::from(&filename)).read()?;
+                        Ok(Some(Arg::Solve {
+                            name: filename,
+                            content,
+                        }))
+                    } else if first_string == "--bench" {
+                        let path = second_string.ok_or("Expected a folder after --bench")?;
+                        Ok(Some(Arg::Bench(path)))
+                    } else if first_string == "--dir" {
+                        if let Some(path) = second_string {
+                            Ok(Some(Arg::Folder(path)))
+                        } else {
+                            Err("Expected a folder after --dir".into())
+                        }
+                    } else if first_string == "--url" {
+                        let url = second_string.ok_or("Expected a URL after --url")?;
+                        if !cfg!(feature = "url") {
+                            return Err("This build of yayagram can't fetch puzzles".into());
+                        }
+
+                        let mut source = UrlSource::new(&url)?;
+                        let content = source.read()?;
+                        Ok(Some(Arg::File {
+                            name: source.name().into_owned(),
+                            content,
+                        }))
+                    } else if first_string == "--code" {
+                        if let Some(code) = second_string {
+                            Ok(Some(Arg::Code(code)))
+                        } else {
+                            Err("Expected a puzzle code after --code".into())
+                        }
                     } else {
-                        parse_squared_size(&first_string)
-                    };
-
-                    match result {
-                        Ok(size) => Ok(size),
-                        Err(SizeError::OutOfRange(thing)) => Err(format!(
-                            "Grid {} must be in range 1 to {}",
-                            thing, MAX_GRID_SIZE
-                        )
-                        .into()),
-                        Err(SizeError::FileNotFound) => Err("File not found".into()),
+                        let result = if let Some(second_string) = second_string {
+                            parse_size(&first_string, &second_string)
+                        } else {
+                            parse_squared_size(&first_string)
+                        };
+
+                        match result {
+                            Ok(size) => Ok(size),
+                            Err(SizeError::OutOfRange(thing)) => Err(format!(
+                                "Grid {} must be in range 1 to {}",
+                                thing, MAX_GRID_SIZE
+                            )
+                            .into()),
+                            Err(SizeError::FileNotFound) => Err("File not found".into()),
+                        }
                     }
                 }
+                _ => Err("File opening error".into()),
             }
-            _ => Err("File opening error".into()),
-        },
+        }
     }
 }
 
-pub fn parse() -> Result<Option<Arg>, Cow<'static, str>> {
+/// Options that can be combined with any of the other arguments.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Whether the progress should not be saved automatically.
+    pub no_autosave: bool,
+    /// The directory to save all files to and load them from instead of the default ones (see the `paths` module).
+    pub data_dir: Option<PathBuf>,
+    /// The seed for random grids, so that the same grid can be generated again.
+    pub seed: Option<u64>,
+    /// Whether random grids must be solvable one row or column at a time instead of just having one solution.
+    pub logic_only: bool,
+    /// Whether the rows and columns without clues of imported puzzles should not start crossed out.
+    pub no_cross_empty: bool,
+    /// Whether the cells forced by the overlaps of their row's or column's clues should start filled in.
+    pub prefill: bool,
+    /// How many mistakes end the game in the lives mode, if it is on.
+    pub lives: Option<u32>,
+    /// How many operations can be undone at most, if not the default.
+    pub undo_limit: Option<usize>,
+    /// Within how long of each other cell placements are undone together, if they are at all.
+    pub merge_window: Option<Duration>,
+    /// Whether two rows of cells are drawn on each line of the terminal.
+    pub half_blocks: bool,
+    /// Whether the 256 color palette is used even if the terminal supports 24-bit colors.
+    pub no_truecolor: bool,
+    /// The built-in palette to draw with, if not the default.
+    pub palette: Option<BuiltinPalette>,
+    /// The file with the colors that override those of the palette, if any.
+    pub palette_file: Option<PathBuf>,
+    /// Every how many cells the color of empty cells alternates, if not the default. 0 means never.
+    pub separation_interval: Option<u16>,
+    /// Whether the numbers of the rows and columns are drawn along the grid.
+    pub coordinates: bool,
+    /// Whether the grid is drawn with plain characters instead of colors.
+    pub ascii: bool,
+    /// Whether a border is drawn around the cells.
+    pub border: bool,
+    /// Which sides of the grid the clues are drawn on, if not above and left of it.
+    pub clue_layout: Option<ClueLayout>,
+}
+
+fn parse_args(args: impl Iterator<Item = OsString>) -> ParseResult {
+    let mut options = Options::default();
+    let mut strings = Vec::<String>::with_capacity(2);
+
+    let mut args = args.enumerate();
+    while let Some((index, arg)) = args.next() {
+        // `--save-dir` is the older name
+        if arg == "--data-dir" || arg == "--save-dir" {
+            let (_, data_dir) = args
+                .next()
+                .ok_or_else(|| format!("Expected a directory after {}", arg.to_string_lossy()))?;
+            // Unlike the other arguments, paths don't have to be valid UTF-8
+            options.data_dir = Some(PathBuf::from(data_dir));
+            continue;
+        }
+        if arg == "--palette-file" {
+            let (_, palette_file) = args.next().ok_or("Expected a file after --palette-file")?;
+            options.palette_file = Some(PathBuf::from(palette_file));
+            continue;
+        }
+
+        let string = arg.into_string().map_err(|_| match index {
+            0 => "First argument is not valid UTF-8".into(),
+            1 => "Second argument is not valid UTF-8".into(),
+            _ => Cow::from("Argument is not valid UTF-8"),
+        })?;
+
+        match string.as_str() {
+            "--no-autosave" => options.no_autosave = true,
+            "--logic-only" => options.logic_only = true,
+            "--no-cross-empty" => options.no_cross_empty = true,
+            "--prefill" => options.prefill = true,
+            "--half-blocks" => options.half_blocks = true,
+            "--coordinates" => options.coordinates = true,
+            "--ascii" => options.ascii = true,
+            "--border" => options.border = true,
+            "--no-truecolor" => options.no_truecolor = true,
+            "--seed" => {
+                let seed = args
+                    .next()
+                    .and_then(|(_, seed)| seed.into_string().ok()?.parse::<u64>().ok())
+                    .ok_or("Expected a number after --seed")?;
+                options.seed = Some(seed);
+            }
+            "--lives" => {
+                let lives = args
+                    .next()
+                    .and_then(|(_, lives)| lives.into_string().ok()?.parse::<u32>().ok())
+                    .filter(|lives| *lives != 0)
+                    .ok_or("Expected a positive number after --lives")?;
+                options.lives = Some(lives);
+            }
+            "--undo-limit" => {
+                let undo_limit = args
+                    .next()
+                    .and_then(|(_, limit)| limit.into_string().ok()?.parse::<usize>().ok())
+                    .filter(|limit| *limit != 0)
+                    .ok_or("Expected a positive number after --undo-limit")?;
+                options.undo_limit = Some(undo_limit);
+            }
+            "--separation" => {
+                let interval = args
+                    .next()
+                    .and_then(|(_, interval)| interval.into_string().ok()?.parse::<u16>().ok())
+                    .ok_or("Expected a number of cells after --separation")?;
+                options.separation_interval = Some(interval);
+            }
+            "--palette" => {
+                let names = || {
+                    BuiltinPalette::ALL
+                        .iter()
+                        .map(|palette| palette.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let palette = args
+                    .next()
+                    .and_then(|(_, name)| BuiltinPalette::from_name(name.to_str()?))
+                    .ok_or_else(|| format!("Expected one of {} after --palette", names()))?;
+                options.palette = Some(palette);
+            }
+            "--clues" => {
+                let names = || {
+                    ClueLayout::ALL
+                        .iter()
+                        .map(|layout| layout.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let clue_layout = args
+                    .next()
+                    .and_then(|(_, name)| ClueLayout::from_name(name.to_str()?))
+                    .ok_or_else(|| format!("Expected one of {} after --clues", names()))?;
+                options.clue_layout = Some(clue_layout);
+            }
+            "--merge-within" => {
+                let milliseconds = args
+                    .next()
+                    .and_then(|(_, milliseconds)| {
+                        milliseconds.into_string().ok()?.parse::<u64>().ok()
+                    })
+                    .filter(|milliseconds| *milliseconds != 0)
+                    .ok_or("Expected a positive number of milliseconds after --merge-within")?;
+                options.merge_window = Some(Duration::from_millis(milliseconds));
+            }
+            _ => strings.push(string),
+        }
+    }
+
+    if options.ascii && options.half_blocks {
+        return Err(
+            "--ascii can't be combined with --half-blocks, which draws the cells as colors".into(),
+        );
+    }
+
+    let mut strings = strings.into_iter();
+
+    let arg = match strings.next() {
+        Some(first_string) if first_string == "--pack-add" || first_string == "--pack-list" => {
+            Some(parse_pack_command(&first_string, strings)?)
+        }
+        Some(first_string) if first_string == "--diff-puzzles" => {
+            Some(parse_diff_command(strings)?)
+        }
+        Some(first_string) => parse_strings(first_string, strings.next())?,
+        None => None,
+    };
+
+    Ok((arg, options))
+}
+
+type ParseResult = Result<(Option<Arg>, Options), Cow<'static, str>>;
+
+pub fn parse() -> ParseResult {
     // See https://github.com/rust-lang/rust/pull/84551#discussion_r620728070
     // on why it's better to use `env::args_os` than `env::args`.
     let mut args = env::args_os();
 
     args.next(); // This is usually the program name
 
-    if let Some(arg) = args.next() {
-        if let Ok(first_string) = arg.into_string() {
-            if let Some(arg) = args.next() {
-                if let Ok(second_string) = arg.into_string() {
-                    parse_strings(first_string, Some(second_string))
-                } else {
-                    Err("Second argument is not valid UTF-8".into())
-                }
-            } else {
-                parse_strings(first_string, None)
-            }
-        } else {
-            Err("First argument is not valid UTF-8".into())
-        }
-    } else {
-        Ok(None)
-    }
+    parse_args(args)
 }
 
 #[cfg(test)]
@@ -164,6 +505,242 @@ mod tests {
                 content: _
             }))
         ));
+        assert!(matches!(
+            parse_strings(String::from("--dir"), Some(String::from("puzzles"))),
+            Ok(Some(Arg::Folder(path))) if path == "puzzles"
+        ));
+        assert!(parse_strings(String::from("--dir"), None).is_err());
+        assert!(matches!(
+            parse_strings(String::from("--bench"), Some(String::from("puzzles"))),
+            Ok(Some(Arg::Bench(path))) if path == "puzzles"
+        ));
+        assert!(parse_strings(String::from("--bench"), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        fn parse(args: &[&str]) -> ParseResult {
+            parse_args(args.iter().map(OsString::from))
+        }
+
+        assert!(matches!(
+            parse(&[]),
+            Ok((
+                None,
+                Options {
+                    no_autosave: false,
+                    data_dir: None,
+                    seed: None,
+                    logic_only: false,
+                    no_cross_empty: false,
+                    prefill: false,
+                    lives: None,
+                    undo_limit: None,
+                    merge_window: None,
+                    half_blocks: false,
+                    no_truecolor: false,
+                    palette: None,
+                    palette_file: None,
+                    separation_interval: None,
+                    coordinates: false,
+                    ascii: false,
+                    border: false,
+                    clue_layout: None
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--no-autosave", "3", "4"]),
+            Ok((
+                Some(Arg::GridSize(Size {
+                    width: 3,
+                    height: 4
+                })),
+                Options {
+                    no_autosave: true,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse(&["example.yaya", "--no-autosave"]),
+            Ok((
+                Some(Arg::File { .. }),
+                Options {
+                    no_autosave: true,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--data-dir", "saves", "5"]),
+            Ok((Some(Arg::GridSize(_)), Options { data_dir: Some(data_dir), .. }))
+                if data_dir == Path::new("saves")
+        ));
+        assert!(matches!(
+            parse(&["--save-dir", "saves"]),
+            Ok((None, Options { data_dir: Some(data_dir), .. })) if data_dir == Path::new("saves")
+        ));
+        assert!(parse(&["5", "--data-dir"]).is_err());
+        assert!(matches!(
+            parse(&["--seed", "42", "10"]),
+            Ok((Some(Arg::GridSize(_)), Options { seed: Some(42), .. }))
+        ));
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(matches!(
+            parse(&["--lives", "3"]),
+            Ok((None, Options { lives: Some(3), .. }))
+        ));
+        assert!(parse(&["--lives", "0"]).is_err());
+        assert!(parse(&["--lives"]).is_err());
+        assert!(matches!(
+            parse(&["--undo-limit", "100"]),
+            Ok((
+                None,
+                Options {
+                    undo_limit: Some(100),
+                    ..
+                }
+            ))
+        ));
+        assert!(parse(&["--undo-limit", "0"]).is_err());
+        assert!(matches!(
+            parse(&["--merge-within", "300"]),
+            Ok((
+                None,
+                Options {
+                    merge_window: Some(window),
+                    ..
+                }
+            )) if window == Duration::from_millis(300)
+        ));
+        assert!(parse(&["--merge-within", "soon"]).is_err());
+        assert!(matches!(
+            parse(&["--half-blocks", "30"]),
+            Ok((
+                Some(Arg::GridSize(_)),
+                Options {
+                    half_blocks: true,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--ascii", "10"]),
+            Ok((Some(Arg::GridSize(_)), Options { ascii: true, .. }))
+        ));
+        assert!(parse(&["--ascii", "--half-blocks", "10"]).is_err());
+        assert!(matches!(
+            parse(&["--border", "10"]),
+            Ok((Some(Arg::GridSize(_)), Options { border: true, .. }))
+        ));
+        assert!(matches!(
+            parse(&["--coordinates", "40", "40"]),
+            Ok((
+                Some(Arg::GridSize(_)),
+                Options {
+                    coordinates: true,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--clues", "bottom-right", "15"]),
+            Ok((
+                Some(Arg::GridSize(_)),
+                Options {
+                    clue_layout: Some(ClueLayout::BottomRight),
+                    ..
+                }
+            ))
+        ));
+        assert!(parse(&["--clues", "middle"]).is_err());
+        assert!(matches!(
+            parse(&["--palette", "deuteranopia", "--palette-file", "colors.txt"]),
+            Ok((
+                None,
+                Options {
+                    palette: Some(BuiltinPalette::Deuteranopia),
+                    palette_file: Some(palette_file),
+                    ..
+                }
+            )) if palette_file == Path::new("colors.txt")
+        ));
+        assert!(parse(&["--palette", "sepia"]).is_err());
+        assert!(parse(&["--palette-file"]).is_err());
+        assert!(matches!(
+            parse(&["--no-truecolor"]),
+            Ok((
+                None,
+                Options {
+                    no_truecolor: true,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(
+            parse(&["--separation", "0", "20"]),
+            Ok((
+                Some(Arg::GridSize(_)),
+                Options {
+                    separation_interval: Some(0),
+                    ..
+                }
+            ))
+        ));
+        assert!(parse(&["--separation", "-4"]).is_err());
+        assert!(matches!(
+            parse(&["--prefill", "example.yaya"]),
+            Ok((Some(Arg::File { .. }), Options { prefill: true, .. }))
+        ));
+        assert!(matches!(
+            parse(&["20", "--logic-only"]),
+            Ok((
+                Some(Arg::GridSize(_)),
+                Options {
+                    logic_only: true,
+                    ..
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_parse_pack_command() {
+        fn parse(args: &[&str]) -> Result<Arg, Cow<'static, str>> {
+            let mut strings = args.iter().map(|arg| arg.to_string());
+            let command = strings.next().unwrap();
+            parse_pack_command(&command, strings)
+        }
+
+        assert!(matches!(
+            parse(&["--pack-list", "pack.yyp"]),
+            Ok(Arg::PackList(pack)) if pack == "pack.yyp"
+        ));
+        assert!(matches!(
+            parse(&["--pack-add", "pack.yyp", "example.yaya"]),
+            Ok(Arg::PackAdd { pack, name, .. }) if pack == "pack.yyp" && name == "example"
+        ));
+        assert!(parse(&["--pack-list"]).is_err());
+        assert!(parse(&["--pack-list", "example.yaya"]).is_err());
+        assert!(parse(&["--pack-add", "pack.yyp"]).is_err());
+        assert!(parse(&["--pack-add", "pack.yyp", "missing.yaya"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_diff_command() {
+        fn parse(args: &[&str]) -> Result<Arg, Cow<'static, str>> {
+            parse_diff_command(args.iter().map(|arg| arg.to_string()))
+        }
+
+        assert!(matches!(
+            parse(&["example.yaya", "tests/fixtures/empty_row.g"]),
+            Ok(Arg::DiffPuzzles { old, new })
+                if old.0 == "example.yaya" && new.0 == "tests/fixtures/empty_row.g"
+        ));
+        assert!(parse(&["example.yaya"]).is_err());
+        assert!(parse(&["example.yaya", "pack.yyp"]).is_err());
+        assert!(parse(&["example.yaya", "missing.yaya"]).is_err());
     }
 
     #[test]
@@ -0,0 +1,145 @@
+//! An overview of how every part of yayagram handles every kind of cell, printed with the hidden `--audit-cells`.
+//!
+//! Every part listed here handles the cell kinds with an exhaustive match, so a new kind of cell doesn't compile
+//! until each of them decides what to do with it. The overview is made by calling those parts, not written by hand.
+
+use crate::{
+    editor::Editor,
+    export,
+    grid::{self, colors::Palette, tools, CellKind},
+    save, solver,
+};
+use std::fmt::Write;
+
+pub struct Subsystem {
+    pub name: &'static str,
+    /// Describes how the subsystem handles cells of the kind.
+    pub handle: fn(CellKind) -> String,
+}
+
+fn describe(condition: bool, if_true: &str, if_false: &str) -> String {
+    if condition { if_true } else { if_false }.into()
+}
+
+pub const SUBSYSTEMS: &[Subsystem] = &[
+    Subsystem {
+        name: "drawing",
+        handle: |kind| format!("{:?}", kind.example().get_color(&Palette::default(), false)),
+    },
+    Subsystem {
+        name: "clues and pictures",
+        handle: |kind| describe(kind.example().is_filled(), "filled", "empty"),
+    },
+    Subsystem {
+        name: "saves",
+        handle: |kind| format!("'{}'", save::cell_char(kind.example())),
+    },
+    Subsystem {
+        name: ".yaya files",
+        handle: |kind| format!("'{}'", Editor::cell_half(kind.example()).0),
+    },
+    Subsystem {
+        name: "snapshots",
+        handle: |kind| format!("'{}'", export::text::cell_str(kind.example())),
+    },
+    Subsystem {
+        name: "descriptions",
+        handle: |kind| format!("\"{}\"", export::description::cell_words(kind.example())),
+    },
+    Subsystem {
+        name: "solver input",
+        handle: |kind| match solver::input(kind) {
+            solver::LineDeduction::Filled => "filled".into(),
+            solver::LineDeduction::Crossed => "crossed".into(),
+            solver::LineDeduction::Unknown => "unknown".into(),
+        },
+    },
+    Subsystem {
+        name: "fill tool",
+        handle: |kind| describe(tools::fill::fills_as_one(kind), "any data", "same cell"),
+    },
+    Subsystem {
+        name: "measuring",
+        handle: |kind| describe(grid::is_measurable(kind), "measured", "kept"),
+    },
+    Subsystem {
+        name: "crossing out",
+        handle: |kind| describe(tools::auto_cross::is_crossable(kind), "crossed", "kept"),
+    },
+];
+
+/// Returns the table of how each subsystem handles each kind of cell, with one line for the kinds and one per subsystem.
+pub fn matrix() -> Vec<String> {
+    let rows: Vec<(&str, Vec<String>)> = SUBSYSTEMS
+        .iter()
+        .map(|subsystem| {
+            let entries = CellKind::ALL.iter().map(|kind| (subsystem.handle)(*kind));
+            (subsystem.name, entries.collect())
+        })
+        .collect();
+    let kinds: Vec<String> = CellKind::ALL
+        .iter()
+        .map(|kind| kind.name().into())
+        .collect();
+
+    let name_width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let entry_width = rows
+        .iter()
+        .flat_map(|(_, entries)| entries)
+        .chain(&kinds)
+        .map(String::len)
+        .max()
+        .unwrap_or(0);
+    let format_row = |name: &str, entries: &[String]| {
+        let mut line = format!("{:<width$}", name, width = name_width);
+        for entry in entries {
+            write!(line, "  {:<width$}", entry, width = entry_width).unwrap();
+        }
+        line.trim_end().to_string()
+    };
+
+    std::iter::once(format_row("", &kinds))
+        .chain(rows.iter().map(|(name, entries)| format_row(name, entries)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinds() {
+        for kind in CellKind::ALL {
+            assert_eq!(kind.example().kind(), kind);
+        }
+    }
+
+    #[test]
+    fn test_matrix() {
+        let matrix = matrix();
+        assert_eq!(matrix.len(), SUBSYSTEMS.len() + 1);
+        for kind in CellKind::ALL {
+            assert!(matrix[0].contains(kind.name()));
+        }
+        for (line, subsystem) in matrix[1..].iter().zip(SUBSYSTEMS) {
+            assert!(line.starts_with(subsystem.name), "{}", line);
+        }
+
+        let line = |name: &str| {
+            matrix
+                .iter()
+                .find(|line| line.starts_with(name))
+                .unwrap()
+                .split_whitespace()
+                .rev()
+                .take(CellKind::ALL.len())
+                .collect::<Vec<&str>>()
+        };
+        // Reversed, from measured to empty
+        assert_eq!(
+            line("solver input"),
+            ["unknown", "crossed", "unknown", "filled", "unknown"]
+        );
+        assert_eq!(line("saves"), ["'R'", "'X'", "'?'", "'1'", "'.'"]);
+    }
+}
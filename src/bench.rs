@@ -0,0 +1,114 @@
+//! Timing the solver on every puzzle in a folder with `--bench`, without any terminal UI.
+//!
+//! Every puzzle gets a line of tab-separated fields so that the output of two versions can be compared with a diff:
+//!
+//! ```text
+//! name            result  microseconds  guesses  rounds  line_solves
+//! rust-logo.yaya  unique  825           0        4       81
+//! ```
+//!
+//! The last line with the name `total` sums up all puzzles that could be loaded.
+
+use crate::{
+    folder::Folder,
+    solver::{self, Solutions, Stats},
+};
+use std::time::{Duration, Instant};
+
+const HEADER: &str = "name\tresult\tmicroseconds\tguesses\trounds\tline_solves";
+
+fn result_name(result: &Result<Vec<bool>, Solutions>) -> &'static str {
+    match result {
+        Ok(_) => "unique",
+        Err(Solutions::None) => "none",
+        Err(Solutions::Multiple) => "multiple",
+        Err(Solutions::Unknown) => "unknown",
+        Err(Solutions::Unique) => unreachable!(),
+    }
+}
+
+fn line(name: &str, result: &str, duration: Duration, stats: Stats) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}",
+        name,
+        result,
+        duration.as_micros(),
+        stats.guesses,
+        stats.rounds,
+        stats.line_solves
+    )
+}
+
+/// Solves every puzzle in the folder, returning the header and a line for each puzzle and for the total.
+pub fn run(folder: &Folder) -> Vec<String> {
+    let mut lines = vec![HEADER.to_string()];
+
+    let mut puzzle_count = 0;
+    let mut total_duration = Duration::ZERO;
+    let mut total_stats = Stats::default();
+    for entry in &folder.entries {
+        let grid = match entry.grid() {
+            Ok(grid) => grid,
+            Err(_) => {
+                lines.push(format!("{}\tinvalid\t-\t-\t-\t-", entry.name));
+                continue;
+            }
+        };
+
+        let start = Instant::now();
+        let (result, stats) = solver::solve_with_stats(&grid);
+        let duration = start.elapsed();
+        lines.push(line(&entry.name, result_name(&result), duration, stats));
+
+        puzzle_count += 1;
+        total_duration += duration;
+        total_stats.guesses += stats.guesses;
+        total_stats.rounds += stats.rounds;
+        total_stats.line_solves += stats.line_solves;
+    }
+
+    lines.push(line(
+        "total",
+        &format!("{} puzzles", puzzle_count),
+        total_duration,
+        total_stats,
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir().join(format!("yayagram-bench-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("a.yaya"),
+            "+----+\n|1111|\n|1111|\n|11  |\n|11  |\n+----+\n",
+        )
+        .unwrap();
+        fs::write(dir.join("b.g"), ": rows\n1\n1\n: columns\n1\n1\n").unwrap();
+        fs::write(dir.join("c.yaya"), "garbage").unwrap();
+
+        let lines = run(&Folder::open(Path::new(&dir)).unwrap());
+        let fields: Vec<Vec<&str>> = lines
+            .iter()
+            .map(|line| line.split('\t').collect())
+            .collect();
+        assert_eq!(fields.len(), 5);
+        assert!(fields.iter().all(|fields| fields.len() == 6));
+        assert_eq!(fields[0], HEADER.split('\t').collect::<Vec<&str>>());
+
+        assert_eq!(fields[1][..2], ["a.yaya", "unique"]);
+        assert_eq!(fields[2][..2], ["b.g", "multiple"]);
+        assert_eq!(fields[3], ["c.yaya", "invalid", "-", "-", "-", "-"]);
+        assert_eq!(fields[4][..2], ["total", "2 puzzles"]);
+        // The diagonal takes a guess to find its two solutions
+        assert_eq!(fields[2][3], "2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
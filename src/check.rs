@@ -0,0 +1,273 @@
+//! A self-check making sure that nothing about the player's progress is lost when it is saved, exported or shared.
+//!
+//! Every section converts the grid and back and then compares the result with the grid field by field.
+//! The fields are destructured so that a new field can't be forgotten: it has to be compared or explicitly skipped.
+
+use crate::{
+    export::text,
+    grid::Grid,
+    save::{self, history, Progress},
+    undo_redo_buffer::UndoRedoBuffer,
+};
+use std::borrow::Cow;
+
+/// The result of a round trip through one of the formats.
+pub struct Section {
+    pub name: &'static str,
+    /// The fields that differ after the round trip, or why the round trip failed.
+    pub differences: Vec<Cow<'static, str>>,
+}
+
+/// Round-trips the grid through every format that can also be loaded again. The grid itself is left untouched.
+pub fn run(grid: &Grid) -> Vec<Section> {
+    vec![
+        Section {
+            name: "save",
+            differences: check_save(grid),
+        },
+        Section {
+            name: "puzzle code",
+            differences: check_code(grid),
+        },
+        Section {
+            name: "snapshot",
+            differences: check_snapshot(grid),
+        },
+    ]
+}
+
+fn compare<T: PartialEq>(
+    differences: &mut Vec<Cow<'static, str>>,
+    field: &'static str,
+    original: &T,
+    round_tripped: &T,
+) {
+    if original != round_tripped {
+        differences.push(field.into());
+    }
+}
+
+fn check_save(grid: &Grid) -> Vec<Cow<'static, str>> {
+    let Grid {
+        size,
+        cells,
+        horizontal_clues_solutions,
+        vertical_clues_solutions,
+        // A save is only ever loaded for the puzzle it was saved for, so it doesn't need these
+        solution: _,
+        max_clues_size: _,
+        undo_redo_buffer:
+            UndoRedoBuffer {
+                buffer,
+                index,
+                // This only counts the pushes of the current session
+                push_count: _,
+                // This is recorded again from the operations
+                changes: _,
+                // This only matters while the mouse is dragged
+                group_start: _,
+                // These are only kept for the current session
+                checkpoints: _,
+                branch: _,
+                last_push: _,
+                // These are set on the command line
+                limit: _,
+                merge_window: _,
+            },
+        playing_time,
+        tags,
+    } = grid;
+
+    let progress = match save::deserialize(&save::serialize(grid)) {
+        Ok(progress) => progress,
+        Err(err) => return vec![format!("loading failed: {}", err.message).into()],
+    };
+    let Progress {
+        size: loaded_size,
+        horizontal_clues_solutions: loaded_horizontal_clues_solutions,
+        vertical_clues_solutions: loaded_vertical_clues_solutions,
+        cells: loaded_cells,
+        undo_redo_buffer:
+            UndoRedoBuffer {
+                buffer: loaded_buffer,
+                index: loaded_index,
+                push_count: _,
+                changes: _,
+                group_start: _,
+                checkpoints: _,
+                branch: _,
+                last_push: _,
+                limit: _,
+                merge_window: _,
+            },
+        playing_time: loaded_playing_time,
+        tags: loaded_tags,
+    } = progress;
+
+    let mut differences = Vec::new();
+    compare(&mut differences, "size", size, &loaded_size);
+    compare(
+        &mut differences,
+        "rows",
+        horizontal_clues_solutions,
+        &loaded_horizontal_clues_solutions,
+    );
+    compare(
+        &mut differences,
+        "columns",
+        vertical_clues_solutions,
+        &loaded_vertical_clues_solutions,
+    );
+    compare(&mut differences, "cells", cells, &loaded_cells);
+    compare(&mut differences, "tags", tags, &loaded_tags);
+    // Longer histories are merged on purpose
+    if buffer.len() <= history::MAX_SAVED_OPERATIONS {
+        compare(&mut differences, "history", buffer, &loaded_buffer);
+        compare(&mut differences, "history index", index, &loaded_index);
+    }
+    // Only whole seconds are saved
+    compare(
+        &mut differences,
+        "playing time",
+        &playing_time.as_secs(),
+        &loaded_playing_time.as_secs(),
+    );
+    differences
+}
+
+fn check_code(grid: &Grid) -> Vec<Cow<'static, str>> {
+    let decoded_grid = match Grid::decode(&grid.encode()) {
+        Ok(decoded_grid) => decoded_grid,
+        Err(err) => return vec![format!("decoding failed: {}", err).into()],
+    };
+    let Grid {
+        size,
+        // A code only contains the puzzle but not the progress on it
+        cells: _,
+        horizontal_clues_solutions,
+        vertical_clues_solutions,
+        solution,
+        max_clues_size,
+        undo_redo_buffer: _,
+        playing_time: _,
+        tags: _,
+    } = decoded_grid;
+
+    let mut differences = Vec::new();
+    compare(&mut differences, "size", &grid.size, &size);
+    compare(
+        &mut differences,
+        "rows",
+        &grid.horizontal_clues_solutions,
+        &horizontal_clues_solutions,
+    );
+    compare(
+        &mut differences,
+        "columns",
+        &grid.vertical_clues_solutions,
+        &vertical_clues_solutions,
+    );
+    compare(&mut differences, "solution", &grid.solution, &solution);
+    compare(
+        &mut differences,
+        "clue size",
+        &grid.max_clues_size,
+        &max_clues_size,
+    );
+    differences
+}
+
+fn check_snapshot(grid: &Grid) -> Vec<Cow<'static, str>> {
+    let imported_grid = match text::import(&text::snapshot(grid)) {
+        Ok(imported_grid) => imported_grid,
+        Err(err) => return vec![format!("importing failed: {}", err).into()],
+    };
+    let Grid {
+        size,
+        cells,
+        horizontal_clues_solutions,
+        vertical_clues_solutions,
+        solution,
+        max_clues_size: _,
+        // A snapshot only contains the cells, which are imported as a single step
+        undo_redo_buffer: _,
+        playing_time: _,
+        tags: _,
+    } = imported_grid;
+
+    let mut differences = Vec::new();
+    compare(&mut differences, "size", &grid.size, &size);
+    compare(
+        &mut differences,
+        "rows",
+        &grid.horizontal_clues_solutions,
+        &horizontal_clues_solutions,
+    );
+    compare(
+        &mut differences,
+        "columns",
+        &grid.vertical_clues_solutions,
+        &vertical_clues_solutions,
+    );
+    compare(&mut differences, "cells", &grid.cells, &cells);
+    compare(&mut differences, "solution", &grid.solution, &solution);
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::{fixture, formats, Cell},
+        undo_redo_buffer::Operation,
+    };
+    use std::{fs, time::Duration};
+    use terminal::util::Point;
+
+    fn assert_verified(grid: &Grid, name: &str) {
+        for section in run(grid) {
+            assert!(
+                section.differences.is_empty(),
+                "{}: {} differs in {:?}",
+                name,
+                section.name,
+                section.differences
+            );
+        }
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        grid.undo_redo_buffer.push(Operation::SetCell {
+            point: Point { x: 1, y: 0 },
+            cell: Cell::Filled,
+        });
+        grid.undo_redo_buffer.push(Operation::Clear);
+        assert!(grid.undo_last_cell());
+        grid.cells[2] = Cell::Crossed;
+        grid.cells[6] = Cell::Measured(Some(3));
+        grid.cells[7] = Cell::Maybed;
+        grid.playing_time = Duration::from_millis(83_500);
+
+        assert_verified(&grid, "progress");
+    }
+
+    #[test]
+    fn test_fixtures() {
+        for path in fs::read_dir(fixture::path("")).unwrap() {
+            let path = path.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+            let grid = if formats::is_grid_file(&path) {
+                formats::load(&name, &fs::read_to_string(&path).unwrap()).unwrap()
+            } else if path.extension() == Some("fixture".as_ref()) {
+                fixture::load(&path).unwrap().grid
+            } else {
+                // Other files, e.g. expected output
+                continue;
+            };
+            assert_verified(&grid, &name);
+        }
+    }
+}
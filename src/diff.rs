@@ -0,0 +1,185 @@
+//! Comparing the puzzles of two grid files, for making changes to puzzles of a pack.
+//!
+//! Only the puzzles are compared: their sizes, their clues and, if both pictures are known, their cells.
+//! The progress on them is not.
+
+use crate::grid::{Clue, Grid};
+use std::fmt::Write;
+
+fn format_clues(clues: Option<&Vec<Clue>>) -> String {
+    match clues {
+        Some(clues) => {
+            let clues: Vec<String> = clues.iter().map(Clue::to_string).collect();
+            format!("[{}]", clues.join(","))
+        }
+        None => "none".into(),
+    }
+}
+
+/// Returns the changed lines, e.g. `("row 7", "[3,1,2]", "[3,2,2]")`. Lines beyond the end of a grid have no clues.
+fn line_differences(
+    name: &str,
+    old_clues: &[Vec<Clue>],
+    new_clues: &[Vec<Clue>],
+) -> Vec<(String, String, String)> {
+    (0..old_clues.len().max(new_clues.len()))
+        .filter(|index| old_clues.get(*index) != new_clues.get(*index))
+        .map(|index| {
+            (
+                format!("{} {}", name, index + 1),
+                format_clues(old_clues.get(index)),
+                format_clues(new_clues.get(index)),
+            )
+        })
+        .collect()
+}
+
+/// Returns the differences between the puzzles, one per line of the report, or nothing if they are the same.
+pub fn diff(old: &Grid, new: &Grid) -> Vec<String> {
+    let mut report = Vec::<String>::new();
+
+    if old.size != new.size {
+        report.push(format!(
+            "size: {}x{} -> {}x{}",
+            old.size.width, old.size.height, new.size.width, new.size.height
+        ));
+    }
+
+    let lines: Vec<(String, String, String)> = line_differences(
+        "row",
+        &old.horizontal_clues_solutions,
+        &new.horizontal_clues_solutions,
+    )
+    .into_iter()
+    .chain(line_differences(
+        "column",
+        &old.vertical_clues_solutions,
+        &new.vertical_clues_solutions,
+    ))
+    .collect();
+    // The arrows line up no matter how many clues there are
+    let name_width = lines.iter().map(|(name, _, _)| name.len()).max();
+    let old_clues_width = lines.iter().map(|(_, old_clues, _)| old_clues.len()).max();
+    for (name, old_clues, new_clues) in &lines {
+        report.push(format!(
+            "{:<name_width$} {:<old_clues_width$} -> {}",
+            format!("{}:", name),
+            old_clues,
+            new_clues,
+            name_width = name_width.unwrap_or_default() + 1,
+            old_clues_width = old_clues_width.unwrap_or_default(),
+        ));
+    }
+
+    // The pictures are only known for some formats
+    if !old.solution.is_empty() && !new.solution.is_empty() {
+        let width = old.size.width.min(new.size.width);
+        let height = old.size.height.min(new.size.height);
+        let filled = |grid: &Grid, x: u16, y: u16| {
+            grid.solution[y as usize * grid.size.width as usize + x as usize]
+        };
+
+        let changed_cells: Vec<(u16, u16)> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|(x, y)| filled(old, *x, *y) != filled(new, *x, *y))
+            .collect();
+        if !changed_cells.is_empty() {
+            let noun = if changed_cells.len() == 1 {
+                "cell"
+            } else {
+                "cells"
+            };
+            let mut line = format!("{} {} changed:", changed_cells.len(), noun);
+            for (x, y) in changed_cells {
+                write!(line, " ({}, {})", x + 1, y + 1).unwrap();
+            }
+            report.push(line);
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::formats;
+
+    fn load(filename: &str, content: &str) -> Grid {
+        formats::load(filename, content).unwrap()
+    }
+
+    /// Returns a `.yaya` grid file with the rows, in which every cell takes up four characters on two lines.
+    fn yaya(rows: &[&str]) -> String {
+        let border = format!("+{}+\n", "-".repeat(rows[0].len() * 4));
+        let mut content = border.clone();
+        for row in rows {
+            let cells: String = row.chars().flat_map(|char| [char; 4]).collect();
+            content.push_str(&format!("|{}|\n", cells).repeat(2));
+        }
+        content + &border
+    }
+
+    const ROWS: &[&str] = &["11 1 ", "11  1", "1 111"];
+
+    #[test]
+    fn test_identical() {
+        let grid = load("a.yaya", &yaya(ROWS));
+        assert!(diff(&grid, &grid).is_empty());
+    }
+
+    #[test]
+    fn test_size() {
+        let old = load("a.yaya", &yaya(ROWS));
+        let new = load("b.yaya", &yaya(&ROWS[..2]));
+
+        assert_eq!(
+            diff(&old, &new),
+            [
+                "size: 5x3 -> 5x2",
+                "row 3:    [1,3] -> none",
+                "column 1: [3]   -> [2]",
+                "column 3: [1]   -> []",
+                "column 4: [1,1] -> [1]",
+                "column 5: [2]   -> [1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clues() {
+        let old = load("a.yaya", &yaya(ROWS));
+        // The first row loses a clue, the last one gains one
+        let new = load("b.yaya", &yaya(&["111  ", "11  1", "1 1 1"]));
+
+        assert_eq!(
+            diff(&old, &new),
+            [
+                "row 1:    [2,1] -> [3]",
+                "row 3:    [1,3] -> [1,1,1]",
+                "column 3: [1]   -> [1,1]",
+                "column 4: [1,1] -> []",
+                "3 cells changed: (3, 1) (4, 1) (4, 3)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_formats() {
+        let yaya = load("a.yaya", &yaya(ROWS));
+        let clues = load("a.g", ": rows\n2 1\n2 1\n1 3\n: columns\n3\n2\n1\n1 1\n2\n");
+
+        // Without the picture of the clue file, only the clues are compared
+        assert!(diff(&yaya, &clues).is_empty());
+        assert!(diff(&clues, &yaya).is_empty());
+
+        let other_clues = load(
+            "b.g",
+            ": rows\n2 1\n2 1\n1 3\n: columns\n3\n2\n1\n1 1\n2\n0\n",
+        );
+        assert_eq!(
+            diff(&clues, &other_clues),
+            ["size: 5x3 -> 6x3", "column 6: none -> []"]
+        );
+    }
+}
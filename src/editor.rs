@@ -1,4 +1,5 @@
 use crate::{
+    args::MAX_GRID_SIZE,
     grid::{builder::Builder, Cell, Grid},
     util,
 };
@@ -21,6 +22,17 @@ impl Editor {
         self.toggled = !self.toggled;
     }
 
+    /// Returns the half of a cell's characters in a `.yaya` file and the index and text of its help, if it needs any.
+    pub const fn cell_half(cell: Cell) -> (&'static str, Option<(usize, &'static str)>) {
+        match cell {
+            Cell::Empty => ("    ", None), // Represents emptiness.
+            Cell::Filled => ("1111", Some((0, "1: filled"))), // Represents true, i.e. filled.
+            Cell::Crossed => ("XXXX", Some((1, "X: crossed"))), // Looks like a cross.
+            Cell::Maybed => ("????", Some((2, "?: maybed"))), // Indicates the unclearness.
+            Cell::Measured(_) => ("RRRR", Some((3, "R: measured"))), // Resembles 尺 which is a unit of measure.
+        }
+    }
+
     fn serialize(grid: &Grid, writer: &mut io::BufWriter<fs::File>) -> io::Result<()> {
         fn write_dash_line(writer: &mut io::BufWriter<fs::File>, width: u16) -> io::Result<()> {
             writer.write_all(b"+")?;
@@ -40,27 +52,10 @@ impl Editor {
             for _ in 0..2 {
                 writer.write_all(b"|")?;
                 for cell in cells {
-                    let cell_half = match cell {
-                        Cell::Empty => {
-                            "    " // Represents emptiness.
-                        }
-                        Cell::Filled => {
-                            help[0] = Some("1: filled");
-                            "1111" // Represents true, i.e. filled.
-                        }
-                        Cell::Crossed => {
-                            help[1] = Some("X: crossed");
-                            "XXXX" // Looks like a cross.
-                        }
-                        Cell::Maybed => {
-                            help[2] = Some("?: maybed");
-                            "????" // Indicates the unclearness.
-                        }
-                        Cell::Measured(_) => {
-                            help[3] = Some("R: measured");
-                            "RRRR" // Resembles 尺 which is a unit of measure.
-                        }
-                    };
+                    let (cell_half, cell_help) = Self::cell_half(*cell);
+                    if let Some((index, text)) = cell_help {
+                        help[index] = Some(text);
+                    }
                     writer.write_all(cell_half.as_bytes())?;
                 }
                 writer.write_all(b"|\n")?;
@@ -93,27 +88,8 @@ impl Editor {
         &mut self,
         builder: &Builder,
     ) -> Result<io::BufWriter<fs::File>, &'static str> {
-        let mut open_options = fs::OpenOptions::new();
-        open_options.create_new(true).write(true);
-
-        let mut index = 1;
-        let file = loop {
-            self.filename = format!("grid-{}.yaya", index);
-            let file = open_options.open(&self.filename);
-            match file {
-                Err(err) => match err.kind() {
-                    io::ErrorKind::AlreadyExists => {
-                        if index == 9 {
-                            return Err("Too many grid files");
-                        }
-                        index += 1;
-                    }
-                    io::ErrorKind::PermissionDenied => return Err("Permission denied"),
-                    _ => return Err("File saving error"),
-                },
-                Ok(file) => break file,
-            }
-        };
+        let (filename, file) = util::create_numbered_file("yaya")?;
+        self.filename = filename;
 
         let writer = io::BufWriter::with_capacity(builder.grid.size.product() as usize, file);
 
@@ -130,7 +106,7 @@ impl Editor {
                 // but does the file for it still exist?
                 if !Path::new(&self.filename).exists() {
                     match self.new_writer(builder) {
-                        Ok(writer) => (writer),
+                        Ok(writer) => writer,
                         Err(err) => {
                             return Err(err);
                         }
@@ -146,7 +122,7 @@ impl Editor {
             None => {
                 // This is the first time we are saving the grid
                 match self.new_writer(builder) {
-                    Ok(writer) => (writer),
+                    Ok(writer) => writer,
                     Err(err) => {
                         return Err(err);
                     }
@@ -164,6 +140,7 @@ impl Editor {
     }
 }
 
+#[derive(Debug)]
 pub struct LoadError {
     pub message: &'static str,
     pub line_number: Option<usize>,
@@ -184,6 +161,8 @@ fn deserialize(str: &str) -> Result<(Size, Vec<Cell>), LoadError> {
     let mut height: Option<u16> = None;
 
     for (index, line) in lines.step_by(2).enumerate() {
+        // Every row takes up two lines, after the dash line
+        let line_number = index * 2 + 2;
         let mut chars = line.chars();
 
         match chars.next() {
@@ -192,7 +171,7 @@ fn deserialize(str: &str) -> Result<(Size, Vec<Cell>), LoadError> {
             _ => {
                 return Err(LoadError {
                     message: "expected '|' or '+' at start of line",
-                    line_number: Some(index),
+                    line_number: Some(line_number),
                 })
             }
         }
@@ -212,7 +191,7 @@ fn deserialize(str: &str) -> Result<(Size, Vec<Cell>), LoadError> {
                 _ => {
                     return Err(LoadError {
                         message: "expected ' ', '1', 'X', '?' or 'R'",
-                        line_number: Some(index),
+                        line_number: Some(line_number),
                     })
                 }
             };
@@ -223,13 +202,28 @@ fn deserialize(str: &str) -> Result<(Size, Vec<Cell>), LoadError> {
             } else {
                 line_width = Some(1);
             }
+            if line_width > Some(MAX_GRID_SIZE) {
+                return Err(LoadError {
+                    message: "grid is too wide",
+                    line_number: Some(line_number),
+                });
+            }
         }
 
-        if width.is_none() {
-            width = Some(line_width.ok_or(LoadError {
-                message: "no width",
-                line_number: Some(index),
-            })?);
+        match width {
+            None => {
+                width = Some(line_width.ok_or(LoadError {
+                    message: "no width",
+                    line_number: Some(line_number),
+                })?);
+            }
+            Some(width) if line_width != Some(width) => {
+                return Err(LoadError {
+                    message: "row has a different width",
+                    line_number: Some(line_number),
+                })
+            }
+            _ => {}
         }
 
         if let Some(height) = &mut height {
@@ -237,6 +231,12 @@ fn deserialize(str: &str) -> Result<(Size, Vec<Cell>), LoadError> {
         } else {
             height = Some(1);
         }
+        if height > Some(MAX_GRID_SIZE) {
+            return Err(LoadError {
+                message: "grid is too high",
+                line_number: Some(line_number),
+            });
+        }
     }
 
     let width = width.ok_or(LoadError {
@@ -255,3 +255,68 @@ pub fn load_grid(file_content: &str) -> Result<Grid, LoadError> {
     let (size, cells) = deserialize(file_content)?;
     Ok(Grid::new(size, cells))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = "+--------+\n|1111    |\n|1111    |\n|    XXXX|\n|    XXXX|\n+--------+\n";
+
+    #[test]
+    fn test_load_grid() {
+        let grid = load_grid(GRID).unwrap();
+
+        assert_eq!(
+            grid.size,
+            Size {
+                width: 2,
+                height: 2
+            }
+        );
+        assert_eq!(grid.horizontal_clues_solutions, vec![vec![1], vec![]]);
+        assert_eq!(grid.cells[3], Cell::Crossed);
+    }
+
+    #[test]
+    fn test_broken_grids() {
+        fn line_number(str: &str) -> Option<usize> {
+            load_grid(str).err().unwrap().line_number
+        }
+
+        assert_eq!(
+            line_number(&GRID.replace("|    XXXX|\n|", "|    |\n|")),
+            Some(4)
+        );
+        assert_eq!(
+            line_number(&GRID.replace("|    XXXX|\n|", "|        XXXX|\n|")),
+            Some(4)
+        );
+        assert_eq!(
+            line_number(&GRID.replacen("|    XXXX", "!    XXXX", 1)),
+            Some(4)
+        );
+        assert_eq!(line_number(&GRID.replace("XXXX|\n|", "AAAA|\n|")), Some(4));
+        let wide_row = format!("|{}|\n", "1111".repeat(MAX_GRID_SIZE as usize + 1));
+        assert_eq!(
+            line_number(&format!("+\n{}{}", wide_row, wide_row)),
+            Some(2)
+        );
+        let high_grid = format!("+\n{}", "|1111|\n".repeat(MAX_GRID_SIZE as usize * 2 + 2));
+        assert_eq!(
+            load_grid(&high_grid).err().unwrap().message,
+            "grid is too high"
+        );
+
+        // No matter where the grid is cut off or what bytes it was changed to, it must not panic
+        for len in 0..GRID.len() {
+            let _ = load_grid(&GRID[..len]);
+        }
+        for index in 0..GRID.len() {
+            for char in ['1', ' ', '|', '+', '\n'] {
+                let mut broken_grid = GRID.to_string();
+                broken_grid.replace_range(index..index + 1, &char.to_string());
+                let _ = load_grid(&broken_grid);
+            }
+        }
+    }
+}
@@ -1,20 +1,35 @@
 mod alert;
 pub mod input;
+pub mod latency;
 
 use crate::{
     editor::Editor,
     grid::{builder::Builder, CellPlacement},
+    lives::Lives,
+    sandbox::Sandbox,
+    save::Autosave,
 };
 use alert::Alert;
-use std::{borrow::Cow, time::Duration};
+use latency::{LatencyMonitor, Quality};
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
 use terminal::Terminal;
 
+/// How often the playing time is updated while there are no events.
+const CLOCK_INTERVAL: Duration = Duration::from_millis(200);
+
 #[must_use]
 pub enum State {
     /// Execution is to be continued normally.
     Continue,
     /// The grid has been solved.
     Solved(Duration),
+    /// The solution has been revealed, which doesn't count as solving the grid.
+    Revealed,
+    /// Too many mistakes were made in the lives mode.
+    GameOver,
     /// Display an alert.
     Alert(Cow<'static, str>),
     /// Clear the alert if present.
@@ -23,48 +38,121 @@ pub enum State {
     Exit,
 }
 
-pub fn r#loop(terminal: &mut Terminal, builder: &mut Builder) -> State {
+pub fn r#loop(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    mut autosave: Option<&mut Autosave>,
+    lives: Option<Lives>,
+    recap: Option<String>,
+) -> State {
     let mut editor = Editor::default();
 
-    let mut alert: Option<Alert> = None;
+    // The recap is only there to look at before playing on
+    let mut recap_shown = recap.is_some();
+    let mut alert: Option<Alert> = recap.map(|recap| Alert::with_duration(recap.into(), None));
+    if let Some(alert) = &alert {
+        alert.draw(terminal, builder);
+        terminal.flush();
+    }
+
+    let mut cell_placement = CellPlacement {
+        lives,
+        ..Default::default()
+    };
+    if let Some(lives) = &cell_placement.lives {
+        lives.draw(terminal, builder);
+    }
+
+    let mut sandbox = Sandbox::default();
 
-    let mut cell_placement = CellPlacement::default();
+    let mut clock = Instant::now();
+
+    let mut latency_monitor = LatencyMonitor::default();
 
     loop {
-        if let Some(event) = terminal.read_event() {
+        let event = terminal.poll_event(CLOCK_INTERVAL);
+
+        let previous_seconds = builder.grid.playing_time.as_secs();
+        let now = Instant::now();
+        sandbox.add_playing_time(&mut builder.grid, now - clock);
+        clock = now;
+        // Slow terminals are spared most updates of the time
+        let time_interval = match latency_monitor.quality() {
+            Quality::Full => 1,
+            Quality::Reduced => 5,
+        };
+        if builder.grid.playing_time.as_secs() / time_interval != previous_seconds / time_interval {
+            builder.draw_playing_time(terminal);
+            terminal.flush();
+        }
+
+        // Alerts are cleared after some time, even while there are no events
+        if alert.as_ref().is_some_and(|alert| alert.is_expired(now)) {
+            if let Some(mut expired_alert) = alert.take() {
+                expired_alert.clear(terminal, builder);
+                terminal.flush();
+            }
+        }
+
+        if let Some(event) = event {
             // The order of statements matters
 
-            if let Some(ref mut alert_to_clear) = alert {
-                if alert_to_clear.clear_delay != 0 {
-                    if alert_to_clear.clear_delay == 0 {
-                        alert_to_clear.clear(terminal, builder);
-                        alert = None;
-                    } else {
-                        alert_to_clear.clear_delay -= 1;
-                    }
+            if recap_shown {
+                if let Some(mut recap_alert) = alert.take() {
+                    recap_alert.clear(terminal, builder);
                 }
+                recap_shown = false;
             }
 
-            let state = input::handle(
+            let mut state = input::handle(
                 terminal,
                 event,
                 builder,
                 &mut editor,
                 &alert,
                 &mut cell_placement,
+                &mut sandbox,
             );
 
+            // The grid's puzzle changes while editing so there is nothing to save progress for
+            // and a revealed solution isn't progress
+            let save_progress =
+                !editor.toggled && !matches!(state, State::Revealed | State::GameOver);
+            if let (Some(autosave), true) = (&mut autosave, save_progress) {
+                let real_grid = sandbox.real_grid(&builder.grid);
+                let result = if let State::Exit = state {
+                    autosave.save(real_grid)
+                } else {
+                    autosave.save_if_due(real_grid)
+                };
+                if let (Err(err), State::Continue) = (result, &state) {
+                    state = State::Alert(err.into());
+                }
+            }
+
+            // The mistakes are drawn after everything else so that they stay visible
+            if let Some(lives) = &cell_placement.lives {
+                lives.draw(terminal, builder);
+            }
+
             #[cfg(debug_assertions)]
             {
                 crate::grid::debug::display(terminal, builder);
             }
 
+            let flush_start = Instant::now();
             terminal.flush();
+            if let Some(quality) = latency_monitor.record(flush_start.elapsed()) {
+                cell_placement.quality = quality;
+                if let State::Continue | State::ClearAlert = state {
+                    state = State::Alert(quality.description().into());
+                }
+            }
 
             match state {
                 State::Continue => continue,
                 State::Alert(alert_message) => {
-                    // Draw a new alert. Alerts are cleared after some time.
+                    // Draw a new alert over the previous one, which might have been longer
 
                     if let Some(mut previous_alert) = alert {
                         previous_alert.clear(terminal, builder);
@@ -84,7 +172,7 @@ pub fn r#loop(terminal: &mut Terminal, builder: &mut Builder) -> State {
                         alert = None;
                     }
                 }
-                State::Solved(_) | State::Exit => break state,
+                State::Solved(_) | State::Revealed | State::GameOver | State::Exit => break state,
             }
         }
     }
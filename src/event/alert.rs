@@ -1,31 +1,92 @@
 use crate::grid::builder::Builder;
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
 use terminal::Terminal;
 
+/// How long an alert is shown unless another one replaces it sooner.
+const DURATION: Duration = Duration::from_secs(5);
+
+/// Drawn in place of the end of a message that is too long for the terminal.
+const ELLIPSIS: char = '…';
+
 pub struct Alert {
     pub message: Cow<'static, str>,
-    pub clear_delay: usize,
+    /// When the alert is to be cleared, or `None` if it stays until it is replaced or cleared otherwise.
+    expiry: Option<Instant>,
 }
 
 impl Alert {
     pub fn new(message: Cow<'static, str>) -> Self {
+        Self::with_duration(message, Some(DURATION))
+    }
+
+    pub fn with_duration(message: Cow<'static, str>, duration: Option<Duration>) -> Self {
         Self {
             message,
-            clear_delay: 75,
+            expiry: duration.map(|duration| Instant::now() + duration),
         }
     }
 
+    /// Returns whether the alert has been shown for long enough to be cleared.
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.expiry.is_some_and(|expiry| now >= expiry)
+    }
+
+    /// Returns the message as it is drawn in the terminal, cut off if it is too long.
+    fn text(&self, terminal: &Terminal) -> Cow<'_, str> {
+        truncated(&self.message, terminal.size.width as usize)
+    }
+
     /// Clears the previous alert.
     pub fn clear(&mut self, terminal: &mut Terminal, builder: &mut Builder) {
-        crate::set_cursor_for_top_text(terminal, &builder, self.message.len(), 0, None);
-        for _ in 0..self.message.len() {
-            terminal.write(" ");
-        }
+        let len = self.text(terminal).chars().count();
+        crate::set_cursor_for_top_text(terminal, builder, len, 0, None);
+        terminal.write(&" ".repeat(len));
     }
 
     /// Draws an alert above the grid.
     pub fn draw(&self, terminal: &mut Terminal, builder: &Builder) {
-        crate::set_cursor_for_top_text(terminal, &builder, self.message.len(), 0, None);
-        terminal.write(&self.message);
+        let text = self.text(terminal).into_owned();
+        crate::set_cursor_for_top_text(terminal, builder, text.chars().count(), 0, None);
+        terminal.write(&text);
+    }
+}
+
+/// Returns the message cut off with an ellipsis if it has more than `width` characters.
+fn truncated(message: &str, width: usize) -> Cow<'_, str> {
+    if message.chars().count() <= width {
+        message.into()
+    } else {
+        let mut truncated: String = message.chars().take(width.saturating_sub(1)).collect();
+        if width != 0 {
+            truncated.push(ELLIPSIS);
+        }
+        truncated.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(truncated("Grid saved", 10), "Grid saved");
+        assert_eq!(truncated("Grid saved", 8), "Grid sa…");
+        assert_eq!(truncated("Grid saved", 1), "…");
+        assert_eq!(truncated("Grid saved", 0), "");
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let alert = Alert::new("Grid saved".into());
+        let now = Instant::now();
+        assert!(!alert.is_expired(now));
+        assert!(alert.is_expired(now + DURATION));
+
+        let alert = Alert::with_duration("Recap".into(), None);
+        assert!(!alert.is_expired(now + DURATION));
     }
 }
@@ -1,11 +1,22 @@
+mod checkpoint_menu;
+mod help;
+mod hint;
+mod history_panel;
+pub mod inspect;
+pub mod jump;
 pub mod key;
+mod mistakes;
 mod mouse;
+mod reveal;
+mod slot_menu;
+mod tagging;
 pub mod window;
 
 use super::{alert::Alert, State};
 use crate::{
     editor::Editor,
     grid::{builder::Builder, CellPlacement},
+    sandbox::Sandbox,
 };
 use terminal::{event::Event, Terminal};
 
@@ -17,6 +28,7 @@ pub fn handle(
     editor: &mut Editor,
     last_alert: &Option<Alert>,
     cell_placement: &mut CellPlacement,
+    sandbox: &mut Sandbox,
 ) -> State {
     match event {
         Event::Mouse(mouse_event) => mouse::handle_event(
@@ -25,10 +37,22 @@ pub fn handle(
             builder,
             editor.toggled,
             cell_placement,
+            sandbox.toggled,
+        ),
+        Event::Key(key_event) => key::handle_event(
+            terminal,
+            key_event,
+            builder,
+            editor,
+            cell_placement,
+            sandbox,
+        ),
+        Event::Resize => window::handle_resize(
+            terminal,
+            builder,
+            cell_placement,
+            last_alert,
+            sandbox.controls_help(),
         ),
-        Event::Key(key_event) => {
-            key::handle_event(terminal, key_event, builder, editor, cell_placement)
-        }
-        Event::Resize => window::handle_resize(terminal, builder, last_alert),
     }
 }
@@ -0,0 +1,151 @@
+//! Adding checkpoints to the undo/redo history with `P` and the menu for going back to them with `Shift+P`.
+
+use super::{key, State};
+use crate::{
+    grid::{self, builder::Builder, CellPlacement},
+    menu,
+    undo_redo_buffer::Checkpoint,
+};
+use std::borrow::Cow;
+use terminal::{event::Key, Terminal};
+
+const TITLE: &str = "Checkpoints";
+const CONTROLS_HELP: &str = "Enter: Go to, Backspace: Remove, Esc: Close";
+
+fn format_checkpoint(checkpoint: &Checkpoint) -> String {
+    match checkpoint.index {
+        Some(index) => format!(
+            "Checkpoint {:<3}  {:>5} operations done",
+            checkpoint.number, index
+        ),
+        None => format!(
+            "Checkpoint {:<3}  no longer in the history",
+            checkpoint.number
+        ),
+    }
+}
+
+/// Adds a checkpoint where the grid is in the history.
+pub fn add(builder: &mut Builder) -> State {
+    let number = builder.grid.undo_redo_buffer.add_checkpoint();
+    State::Alert(format!("Added checkpoint {}, go back to it with Shift+P", number).into())
+}
+
+/// Runs the menu until it is left, returning the checkpoint to go to if any.
+fn run(terminal: &mut Terminal, checkpoints: &mut Vec<Checkpoint>) -> Option<Checkpoint> {
+    let mut selected = checkpoints.len() - 1;
+    let mut bottom_text = Cow::from("");
+
+    loop {
+        let entries = checkpoints
+            .iter()
+            .map(|checkpoint| format_checkpoint(checkpoint).into());
+        menu::draw(
+            terminal,
+            TITLE,
+            entries,
+            selected,
+            &bottom_text,
+            CONTROLS_HELP,
+        );
+
+        match key::r#await(terminal) {
+            key if menu::move_selection(&key, &mut selected, checkpoints.len()) => {}
+            Key::Enter => {
+                let checkpoint = &checkpoints[selected];
+                if checkpoint.index.is_some() {
+                    return Some(checkpoint.clone());
+                }
+                // Going to where its index is now would be a different point of the history
+                bottom_text =
+                    format!("Checkpoint {} can't be gone to anymore", checkpoint.number).into();
+            }
+            Key::Backspace => {
+                checkpoints.remove(selected);
+                if checkpoints.is_empty() {
+                    return None;
+                }
+                selected = selected.min(checkpoints.len() - 1);
+                bottom_text = "".into();
+            }
+            Key::Esc => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Closes the menu by drawing the grid again.
+fn close(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &CellPlacement,
+    controls_help: &[&str],
+) {
+    terminal.clear();
+
+    // The terminal might have been resized in the meantime
+    builder.fit(terminal);
+
+    // Going back and forth in the history never solves the grid because it would've already been solved before
+    #[allow(unused_must_use)]
+    {
+        builder.draw_all(terminal);
+    }
+    crate::draw_controls_help(terminal, builder, controls_help);
+
+    if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+        grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+    }
+}
+
+/// Opens the menu and returns once it was closed.
+///
+/// `controls_help` is the one that is shown again once the menu is closed.
+pub fn open(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &CellPlacement,
+    controls_help: &[&str],
+) -> State {
+    if builder.grid.undo_redo_buffer.checkpoints.is_empty() {
+        return State::Alert("There are no checkpoints, add one with P".into());
+    }
+
+    let checkpoint = run(terminal, &mut builder.grid.undo_redo_buffer.checkpoints);
+    let state = match checkpoint {
+        Some(Checkpoint {
+            number,
+            index: Some(index),
+        }) => {
+            builder.grid.jump_in_history(index);
+            State::Alert(format!("Went to checkpoint {}", number).into())
+        }
+        _ => State::Continue,
+    };
+
+    close(terminal, builder, cell_placement, controls_help);
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_checkpoint() {
+        assert_eq!(
+            format_checkpoint(&Checkpoint {
+                number: 2,
+                index: Some(15)
+            }),
+            "Checkpoint 2       15 operations done"
+        );
+        assert_eq!(
+            format_checkpoint(&Checkpoint {
+                number: 12,
+                index: None
+            }),
+            "Checkpoint 12   no longer in the history"
+        );
+    }
+}
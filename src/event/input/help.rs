@@ -0,0 +1,137 @@
+//! The list of all controls that `?` and `F1` show in place of the grid.
+
+use super::{key, State};
+use crate::{
+    grid::{self, builder::Builder, CellPlacement},
+    menu,
+};
+use terminal::{
+    event::Key,
+    util::{Color, Point},
+    Terminal,
+};
+
+const TITLE: &str = "Controls";
+const CONTROLS_HELP: &str = "Up/Down: Previous/next page, any other key: Close";
+
+/// Returns the lines of the list, with the keys right-aligned in the left column and the actions in the right one.
+fn lines(controls: &[(&str, &str)]) -> Vec<String> {
+    let keys_width = controls
+        .iter()
+        .map(|(keys, _)| keys.len())
+        .max()
+        .unwrap_or(0);
+    controls
+        .iter()
+        .map(|(keys, action)| format!("{:>width$}  {}", keys, action, width = keys_width))
+        .collect()
+}
+
+/// Returns how many pages `line_count` lines take up if each page has `page_height` of them.
+fn page_count(line_count: usize, page_height: usize) -> usize {
+    line_count.div_ceil(page_height).max(1)
+}
+
+fn draw(terminal: &mut Terminal, lines: &[String], page: usize) {
+    terminal.clear();
+
+    let page_height = menu::visible_entry_count(terminal);
+    let page_count = page_count(lines.len(), page_height);
+    if page_count == 1 {
+        menu::draw_centered(terminal, TITLE, 0);
+    } else {
+        let title = format!("{} ({}/{})", TITLE, page + 1, page_count);
+        menu::draw_centered(terminal, &title, 0);
+    }
+
+    // The lines are aligned with each other, so they are centered as a whole
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as u16;
+    let x = (terminal.size.width / 2).saturating_sub(width / 2);
+    for (index, line) in lines
+        .iter()
+        .skip(page * page_height)
+        .take(page_height)
+        .enumerate()
+    {
+        terminal.set_cursor(Point {
+            x,
+            y: menu::ENTRIES_Y + index as u16,
+        });
+        terminal.write(line);
+    }
+
+    terminal.set_foreground_color(Color::DarkGray);
+    menu::draw_centered(terminal, CONTROLS_HELP, menu::bottom_text_y(terminal) + 1);
+    terminal.reset_colors();
+
+    terminal.flush();
+}
+
+/// Shows the list, going through its pages, until a key other than those for the pages is pressed.
+fn run(terminal: &mut Terminal) {
+    let lines = lines(key::CONTROLS);
+    let mut page = 0;
+
+    loop {
+        // The terminal might be resized while the list is shown
+        let page_count = page_count(lines.len(), menu::visible_entry_count(terminal));
+        page = page.min(page_count - 1);
+        draw(terminal, &lines, page);
+
+        match key::r#await(terminal) {
+            Key::Up | Key::Left => page = page.saturating_sub(1),
+            Key::Down | Key::Right if page + 1 < page_count => page += 1,
+            _ => return,
+        }
+    }
+}
+
+/// Shows the list and draws everything again once it was closed.
+///
+/// `controls_help` is the one that is shown again once the list is closed.
+pub fn open(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &CellPlacement,
+    controls_help: &[&str],
+) -> State {
+    run(terminal);
+
+    terminal.clear();
+    // The terminal might have been resized in the meantime
+    builder.fit(terminal);
+    // Nothing about the grid changed
+    #[allow(unused_must_use)]
+    {
+        builder.draw_all(terminal);
+    }
+    crate::draw_controls_help(terminal, builder, controls_help);
+    if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+        grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+    }
+
+    State::Continue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(
+            lines(&[("Q", "Fill the selected cell"), ("Shift+D", "Switch")]),
+            vec!["      Q  Fill the selected cell", "Shift+D  Switch"]
+        );
+        // All of them fit into a terminal that is 80 characters wide
+        assert!(lines(key::CONTROLS).iter().all(|line| line.len() <= 80));
+    }
+
+    #[test]
+    fn test_page_count() {
+        assert_eq!(page_count(40, 40), 1);
+        assert_eq!(page_count(41, 40), 2);
+        assert_eq!(page_count(40, 1), 40);
+        assert_eq!(page_count(0, 10), 1);
+    }
+}
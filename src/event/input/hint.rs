@@ -0,0 +1,132 @@
+//! Hints with `N`, which place one cell that is forced by its row or column,
+//! and softer ones with `U`, which only point out a row or column that forces a cell.
+
+use super::State;
+use crate::{
+    grid::{builder::Builder, tools::auto_cross, Cell, Grid},
+    solver,
+    undo_redo_buffer::Operation,
+};
+use terminal::Terminal;
+
+pub fn handle(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    auto_cross: bool,
+    sandbox_toggled: bool,
+) -> State {
+    let (point, cell) = match solver::hint(&builder.grid) {
+        Some(hint) => hint,
+        None => {
+            return State::Alert("No cell is forced by a single row or column".into());
+        }
+    };
+
+    builder.grid.apply(Operation::SetCell { point, cell });
+    if auto_cross {
+        auto_cross::cross_solved_lines_at(&mut builder.grid, point);
+    }
+
+    let all_clues_solved = builder.draw_changes(terminal);
+
+    // Solving the sandbox doesn't count
+    if all_clues_solved && !sandbox_toggled {
+        return State::Solved(builder.grid.playing_time);
+    }
+
+    // The hinted cell stays highlighted until the grid is drawn again
+    builder.draw_cell(terminal, point, |hinted_point| hinted_point == point);
+
+    let name = if cell == Cell::Filled {
+        "filled"
+    } else {
+        "crossed out"
+    };
+    State::Alert(format!("Hint: the highlighted cell must be {}", name).into())
+}
+
+/// Returns the rows and columns of the cells the player placed, from the most recent placement on,
+/// as the index into every row and then every column.
+fn touched_lines(grid: &Grid) -> Vec<usize> {
+    let buffer = &grid.undo_redo_buffer;
+    let points = buffer.buffer[..buffer.index]
+        .iter()
+        .rev()
+        .flat_map(|operation| match operation {
+            Operation::SetCell { point, .. } | Operation::Fill { point, .. } => vec![*point],
+            Operation::SetCells(cells) => cells.iter().map(|(point, _)| *point).collect(),
+            Operation::Measure(_)
+            | Operation::Clear
+            | Operation::Tag(_)
+            | Operation::Promote { .. }
+            | Operation::Snapshot { .. }
+            | Operation::Transform(_) => Vec::new(),
+        });
+
+    let mut lines = Vec::new();
+    for point in points {
+        for line in [
+            point.y as usize,
+            grid.size.height as usize + point.x as usize,
+        ] {
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+    }
+    lines
+}
+
+pub fn handle_line(terminal: &mut Terminal, builder: &mut Builder) -> State {
+    let line = match solver::hinted_line(&builder.grid, &touched_lines(&builder.grid)) {
+        Some(line) => line,
+        None => {
+            return State::Alert(
+                "No row or column forces a cell. Try N for a stronger hint".into(),
+            );
+        }
+    };
+
+    // The line stays highlighted until the grid is drawn again
+    builder.draw_hinted_line(terminal, line);
+
+    let height = builder.grid.size.height as usize;
+    let name = if line < height {
+        format!("row {}", line + 1)
+    } else {
+        format!("column {}", line - height + 1)
+    };
+    State::Alert(format!("Hint: the highlighted {} forces a cell", name).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminal::util::Point;
+
+    #[test]
+    fn test_touched_lines() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11 ",
+            " 1 ",
+        ]).unwrap();
+        assert!(touched_lines(&grid).is_empty());
+
+        grid.undo_redo_buffer.push(Operation::SetCell {
+            point: Point { x: 2, y: 1 },
+            cell: Cell::Crossed,
+        });
+        grid.undo_redo_buffer.push(Operation::Clear);
+        grid.undo_redo_buffer.push(Operation::SetCells(vec![
+            (Point { x: 0, y: 0 }, Cell::Filled),
+            (Point { x: 1, y: 1 }, Cell::Filled),
+        ]));
+        // The most recent ones first
+        assert_eq!(touched_lines(&grid), [0, 2, 1, 3, 4]);
+
+        // Undone placements don't count
+        grid.undo_last_cell();
+        assert_eq!(touched_lines(&grid), [1, 4]);
+    }
+}
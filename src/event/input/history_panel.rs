@@ -0,0 +1,293 @@
+//! The panel right of the grid that lists the undo/redo history, toggled with `Y`.
+//!
+//! Moving the selection up and down undoes and redoes the operations one by one, so jumping to any point of the history
+//! goes through the same changes as undoing and redoing with `A` and `D`.
+
+use super::{key, State};
+use crate::{
+    grid::{self, builder::Builder, Cell, CellPlacement},
+    undo_redo_buffer::Operation,
+};
+use std::borrow::Cow;
+use terminal::{
+    event::Key,
+    util::{Color, Point},
+    Terminal,
+};
+
+const TITLE: &str = "History";
+const CONTROLS_HELP: &[&str] = &["Up/Down: Undo/Redo", "Enter: Keep, Esc: Go back"];
+
+/// How many characters the panel takes up, including the space left of it.
+const PANEL_WIDTH: u16 = 26;
+/// The space between the grid and the panel, the same as for the solution picture.
+const MARGIN: u16 = 2;
+
+const SELECTED_BACKGROUND_COLOR: Color = Color::Byte(238);
+
+fn describe_point(point: Point) -> String {
+    format!("{},{}", point.x + 1, point.y + 1)
+}
+
+fn describe_cell_count(verb: &str, count: usize) -> String {
+    if count == 1 {
+        format!("{} 1 cell", verb)
+    } else {
+        format!("{} {} cells", verb, count)
+    }
+}
+
+const fn cell_verb(cell: Cell) -> &'static str {
+    match cell {
+        Cell::Empty => "empty",
+        Cell::Filled => "fill",
+        Cell::Maybed => "maybe",
+        Cell::Crossed => "cross out",
+        Cell::Measured(_) => "measure",
+    }
+}
+
+/// Describes what the operation does in a few words.
+fn describe(operation: &Operation) -> String {
+    match operation {
+        Operation::SetCell { point, cell } => {
+            format!("{} at {}", cell_verb(*cell), describe_point(*point))
+        }
+        Operation::SetCells(cells) => describe_cell_count("place", cells.len()),
+        Operation::Measure(measurement) => describe_cell_count("measure", measurement.len()),
+        Operation::Clear => "clear".into(),
+        Operation::Fill {
+            point, fill_cell, ..
+        } => format!(
+            "{} from {}",
+            match fill_cell {
+                Cell::Empty => "empty",
+                _ => "flood-fill",
+            },
+            describe_point(*point)
+        ),
+        Operation::Tag(tags) => describe_cell_count("tag", tags.len()),
+        Operation::Promote { tag, cell } => format!("{} tag {}", cell_verb(*cell), tag),
+        Operation::Transform(transform) => transform.name().replace('-', " "),
+        Operation::Snapshot { .. } => "older operations".into(),
+    }
+}
+
+/// Returns the entries of the panel, one for each point of the history that can be jumped to,
+/// along with the index of the first one.
+///
+/// The entry at `index` is for when `index` operations are done, so it describes the last of them.
+fn entries(buffer: &[Operation]) -> (usize, Vec<String>) {
+    let first_index = match buffer.first() {
+        Some(Operation::Snapshot { .. }) => 1,
+        _ => 0,
+    };
+    let entries = (first_index..=buffer.len())
+        .map(|index| match index.checked_sub(1) {
+            Some(last_index) => describe(&buffer[last_index]),
+            None => "start".into(),
+        })
+        .collect();
+    (first_index, entries)
+}
+
+/// Returns the top left point of the panel and how many lines it has, or `None` if it doesn't fit right of the grid.
+///
+/// It spans the clues above the grid, if they are there, and the grid, which leaves the texts above and below them untouched.
+fn area(terminal: &Terminal, builder: &Builder) -> Option<(Point, u16)> {
+    let clues_height = builder.leading_clues_size().height;
+    let point = Point {
+        x: builder.point.x + builder.screen_size().width + builder.width_right_of_grid() + MARGIN,
+        y: builder.point.y.saturating_sub(clues_height),
+    };
+    let height = clues_height + builder.screen_size().height;
+    // The title and at least one entry
+    if point.x + PANEL_WIDTH > terminal.size.width || height < 2 {
+        return None;
+    }
+    Some((point, height))
+}
+
+/// Returns the index of the first entry to draw so that the selected one is visible.
+fn first_visible_entry(selected: usize, visible_entry_count: usize) -> usize {
+    (selected + 1).saturating_sub(visible_entry_count)
+}
+
+fn draw(terminal: &mut Terminal, builder: &Builder, (point, height): (Point, u16)) {
+    let buffer = &builder.grid.undo_redo_buffer;
+    let (first_index, entries) = entries(&buffer.buffer);
+    let selected = buffer.index.saturating_sub(first_index);
+    let width = PANEL_WIDTH as usize - 1;
+
+    terminal.set_cursor(point);
+    terminal.write(&format!(" {:<width$}", TITLE, width = width));
+
+    let visible_entry_count = height as usize - 1;
+    let first_visible_entry = first_visible_entry(selected, visible_entry_count);
+    for line in 0..visible_entry_count {
+        terminal.set_cursor(Point {
+            y: point.y + 1 + line as u16,
+            ..point
+        });
+        let index = first_visible_entry + line;
+        let entry = match entries.get(index) {
+            Some(entry) => entry.as_str(),
+            None => "",
+        };
+
+        if index == selected {
+            terminal.set_background_color(SELECTED_BACKGROUND_COLOR);
+        }
+        // Undone operations can be redone, which is what sets them apart
+        terminal.set_foreground_color(if index > selected {
+            Color::DarkGray
+        } else {
+            Color::Gray
+        });
+        terminal.write(&format!(" {:<width$.width$}", entry, width = width));
+        terminal.reset_colors();
+    }
+}
+
+fn clear(terminal: &mut Terminal, (point, height): (Point, u16)) {
+    for line in 0..height {
+        terminal.set_cursor(Point {
+            y: point.y + line,
+            ..point
+        });
+        terminal.write(&" ".repeat(PANEL_WIDTH as usize));
+    }
+}
+
+/// Opens the panel and returns once it was closed, or only alerts where in the history the grid is if the panel doesn't fit.
+///
+/// `controls_help` is the one that is shown again once the panel is closed.
+pub fn open(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &CellPlacement,
+    controls_help: &[&str],
+) -> State {
+    let area = match area(terminal, builder) {
+        Some(area) => area,
+        None => {
+            let buffer = &builder.grid.undo_redo_buffer;
+            return State::Alert(
+                format!(
+                    "{} of {} operations done, too narrow for the history",
+                    buffer.index,
+                    buffer.buffer.len()
+                )
+                .into(),
+            );
+        }
+    };
+
+    crate::clear_controls_help(terminal, builder, controls_help);
+    crate::draw_controls_help(terminal, builder, CONTROLS_HELP);
+
+    let start_index = builder.grid.undo_redo_buffer.index;
+    let message: Option<Cow<'static, str>> = loop {
+        draw(terminal, builder, area);
+        terminal.flush();
+
+        let moved = match key::r#await(terminal) {
+            Key::Up => builder.grid.undo_last_cell(),
+            Key::Down => builder.grid.redo_last_cell(),
+            Key::Enter | Key::Char('y' | 'Y') => break None,
+            Key::Esc => {
+                if builder.grid.jump_in_history(start_index) {
+                    // Undoing and redoing never solves the grid because it would've already been solved before
+                    #[allow(unused_must_use)]
+                    {
+                        builder.draw_all(terminal);
+                    }
+                }
+                break Some("Went back in the history to where it was".into());
+            }
+            _ => false,
+        };
+        if moved {
+            // See above
+            #[allow(unused_must_use)]
+            {
+                builder.draw_all(terminal);
+            }
+        }
+    };
+
+    clear(terminal, area);
+    crate::clear_controls_help(terminal, builder, CONTROLS_HELP);
+    crate::draw_controls_help(terminal, builder, controls_help);
+    if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+        grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+    }
+
+    match message {
+        Some(message) => State::Alert(message),
+        None => State::Continue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::undo_redo_buffer::Measurement;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe(&Operation::SetCell {
+                point: Point { x: 3, y: 6 },
+                cell: Cell::Filled
+            }),
+            "fill at 4,7"
+        );
+        assert_eq!(
+            describe(&Operation::SetCells(vec![(
+                Point { x: 0, y: 0 },
+                Cell::Crossed
+            )])),
+            "place 1 cell"
+        );
+        let measurement = Measurement::new((0..9).map(|x| Point { x, y: 0 })).unwrap();
+        assert_eq!(
+            describe(&Operation::Measure(measurement)),
+            "measure 9 cells"
+        );
+        assert_eq!(describe(&Operation::Clear), "clear");
+        assert_eq!(
+            describe(&Operation::Promote {
+                tag: 2,
+                cell: Cell::Crossed
+            }),
+            "cross out tag 2"
+        );
+    }
+
+    #[test]
+    fn test_entries() {
+        assert_eq!(entries(&[]), (0, vec!["start".to_string()]));
+        assert_eq!(
+            entries(&[Operation::Clear]),
+            (0, vec!["start".to_string(), "clear".to_string()])
+        );
+
+        // The snapshot can't be undone so there's no way to get to before it
+        let snapshot = Operation::Snapshot {
+            cells: Vec::new(),
+            tags: Vec::new(),
+        };
+        assert_eq!(
+            entries(&[snapshot, Operation::Clear]),
+            (1, vec!["older operations".to_string(), "clear".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_first_visible_entry() {
+        assert_eq!(first_visible_entry(0, 5), 0);
+        assert_eq!(first_visible_entry(4, 5), 0);
+        assert_eq!(first_visible_entry(5, 5), 1);
+    }
+}
@@ -0,0 +1,331 @@
+//! Inspecting lets terminals without reliable mouse movement use everything that is otherwise shown on hover:
+//! while inspecting, a click acts like hovering instead of placing a cell.
+
+use super::State;
+use crate::grid::{self, builder::Builder, CellPlacement};
+use itertools::Itertools;
+use terminal::{util::Point, Terminal};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InspectMode {
+    /// Clicks place cells.
+    #[default]
+    Off,
+    /// The next click inspects, then clicks place cells again.
+    OneShot,
+    /// All clicks inspect until turned off.
+    Latched,
+}
+
+#[derive(Default)]
+pub struct Inspector {
+    pub mode: InspectMode,
+    /// Whether the mouse button that inspected is still being held.
+    /// While this is the case, dragging must not place any cells.
+    pub pressed: bool,
+}
+
+impl Inspector {
+    /// Handles a click, returning whether it inspects.
+    pub fn press(&mut self) -> bool {
+        match self.mode {
+            InspectMode::Off => false,
+            InspectMode::OneShot => {
+                self.mode = InspectMode::Off;
+                self.pressed = true;
+                true
+            }
+            InspectMode::Latched => {
+                self.pressed = true;
+                true
+            }
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.pressed = false;
+    }
+}
+
+/// What is at a point on the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InspectTarget {
+    /// A cell, given as the point that the cursor selects. See [`Builder::cursor_point`].
+    Cell(Point),
+    /// The clues of the column with the given X-coordinate on the grid.
+    ColumnClues(u16),
+    /// The clues of the row with the given Y-coordinate on the grid.
+    RowClues(u16),
+    Nothing,
+}
+
+impl InspectTarget {
+    /// Returns what is at the point on the screen, which hasn't been through [`Builder::cursor_point`] yet
+    /// because the clues below and right of a scrolled grid aren't where they would be if it wasn't scrolled.
+    pub fn at(builder: &Builder, point: Point) -> Self {
+        let grid = &builder.grid;
+        let screen_size = builder.screen_size();
+        let cursor_point = builder.cursor_point(point);
+
+        let within_grid_columns =
+            (builder.point.x..builder.point.x + screen_size.width).contains(&point.x);
+        let within_grid_rows =
+            (builder.point.y..builder.point.y + screen_size.height).contains(&point.y);
+        let clues_size = builder.screen_clues_size();
+        let column_clues_y = builder.column_clues_y();
+        let row_clues_x = builder.row_clues_x();
+        let within_column_clues =
+            (column_clues_y..column_clues_y + clues_size.height).contains(&point.y);
+        let within_row_clues = (row_clues_x..row_clues_x + clues_size.width).contains(&point.x);
+
+        match (within_grid_columns, within_grid_rows) {
+            (true, true) => InspectTarget::Cell(cursor_point),
+            (true, false) if within_column_clues => {
+                InspectTarget::ColumnClues((cursor_point.x - builder.point.x) / 2)
+            }
+            (false, true) if within_row_clues => {
+                let mut y = cursor_point.y - builder.point.y;
+                // The clues of the lower row are the ones next to the grid
+                let lower_row_clues_width = builder.viewport().clues_size.width;
+                let next_to_grid = if builder.settings.clue_layout.rows_right() {
+                    point.x < row_clues_x + lower_row_clues_width
+                } else {
+                    point.x >= row_clues_x + clues_size.width - lower_row_clues_width
+                };
+                if builder.settings.half_blocks && next_to_grid && y + 1 < grid.size.height {
+                    y += 1;
+                }
+                InspectTarget::RowClues(y)
+            }
+            _ => InspectTarget::Nothing,
+        }
+    }
+}
+
+/// Highlights the cells surrounding the hovered cell.
+pub fn hover(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    hovered_cell_point: Point,
+) {
+    cell_placement.selected_cell_point = Some(hovered_cell_point);
+    grid::draw_highlighted_cells(terminal, builder, hovered_cell_point);
+}
+
+/// Shows all clues of the column or row hovered at the point on the screen if some of them are cut off.
+pub fn hover_clues(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    point: Point,
+) -> State {
+    let target = InspectTarget::at(builder, point);
+    let clipped = match target {
+        InspectTarget::ColumnClues(x) => builder.are_column_clues_clipped(x),
+        InspectTarget::RowClues(y) => builder.are_row_clues_clipped(y),
+        _ => false,
+    };
+    if clipped {
+        inspect(terminal, builder, cell_placement, target)
+    } else {
+        State::Continue
+    }
+}
+
+#[allow(unstable_name_collisions)] // in the future `intersperse` will be in the std
+fn describe_clues(kind: &str, index: u16, clues: &[u16], solved: bool) -> String {
+    let clues: String = if clues.is_empty() {
+        "none".into()
+    } else {
+        clues
+            .iter()
+            .map(|clue| clue.to_string())
+            .intersperse(" ".into())
+            .collect()
+    };
+    let solved = if solved { " (solved)" } else { "" };
+
+    format!("{} {} clues: {}{}", kind, index + 1, clues, solved)
+}
+
+/// Shows everything that would otherwise be shown when hovering the target.
+pub fn inspect(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    target: InspectTarget,
+) -> State {
+    match target {
+        InspectTarget::Cell(point) => {
+            hover(terminal, builder, cell_placement, point);
+
+            let cell_point = grid::get_cell_point_from_cursor_point(point, builder);
+            State::Alert(format!("Row {}, column {}", cell_point.y + 1, cell_point.x + 1).into())
+        }
+        InspectTarget::ColumnClues(x) => {
+            let clues = &builder.grid.vertical_clues_solutions[x as usize];
+            let solved = builder.grid.get_vertical_clues(x).eq(clues.iter().copied());
+            State::Alert(describe_clues("Column", x, clues, solved).into())
+        }
+        InspectTarget::RowClues(y) => {
+            let clues = &builder.grid.horizontal_clues_solutions[y as usize];
+            let solved = builder
+                .grid
+                .get_horizontal_clues(y)
+                .eq(clues.iter().copied());
+            State::Alert(describe_clues("Row", y, clues, solved).into())
+        }
+        InspectTarget::Nothing => State::Continue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{
+        builder::{ClueLayout, Settings},
+        Cell, Grid,
+    };
+    use std::io;
+    use terminal::util::Size;
+
+    #[test]
+    fn test_one_shot() {
+        let mut inspector = Inspector {
+            mode: InspectMode::OneShot,
+            ..Default::default()
+        };
+
+        assert!(inspector.press());
+        assert!(inspector.pressed);
+        assert_eq!(inspector.mode, InspectMode::Off);
+        inspector.release();
+        assert!(!inspector.pressed);
+
+        assert!(!inspector.press());
+        assert!(!inspector.pressed);
+    }
+
+    #[test]
+    fn test_latched() {
+        let mut inspector = Inspector {
+            mode: InspectMode::Latched,
+            ..Default::default()
+        };
+
+        for _ in 0..3 {
+            assert!(inspector.press());
+            assert!(inspector.pressed);
+            inspector.release();
+        }
+        assert_eq!(inspector.mode, InspectMode::Latched);
+    }
+
+    #[test]
+    fn test_at_scrolled() {
+        let stdout = io::stdout();
+        let mut terminal = Terminal::new(stdout.lock()).unwrap();
+        terminal.size = Size {
+            width: 80,
+            height: 24,
+        };
+        let size = Size {
+            width: 40,
+            height: 40,
+        };
+        let grid = Grid::new(size.clone(), vec![Cell::Filled; size.product() as usize]);
+        let mut builder = Builder::new(&terminal, grid, Default::default());
+
+        for layout in [ClueLayout::BottomLeft, ClueLayout::TopRight] {
+            builder.settings.clue_layout = layout;
+            builder.fit(&terminal);
+            builder.scroll_to(&mut terminal, Point { x: 39, y: 39 });
+            let offset = builder.viewport().offset;
+            assert!(offset.x > 0 && offset.y > 0);
+
+            assert_eq!(
+                InspectTarget::at(
+                    &builder,
+                    Point {
+                        x: builder.point.x,
+                        y: builder.column_clues_y()
+                    }
+                ),
+                InspectTarget::ColumnClues(offset.x),
+            );
+            assert_eq!(
+                InspectTarget::at(
+                    &builder,
+                    Point {
+                        x: builder.row_clues_x(),
+                        y: builder.point.y
+                    }
+                ),
+                InspectTarget::RowClues(offset.y),
+            );
+            assert_eq!(
+                InspectTarget::at(&builder, builder.point),
+                InspectTarget::Cell(builder.cursor_point(builder.point)),
+            );
+        }
+    }
+
+    #[test]
+    fn test_at_half_blocks_and_border() {
+        let stdout = io::stdout();
+        let mut terminal = Terminal::new(stdout.lock()).unwrap();
+        terminal.size = Size {
+            width: 80,
+            height: 40,
+        };
+        let grid = Grid::from_lines(&["#.#", "##.", "#.#", "..#", "##."]).unwrap();
+        let settings = Settings {
+            half_blocks: true,
+            border: true,
+            ..Settings::default()
+        };
+        let mut builder = Builder::new(&terminal, grid, settings);
+
+        for layout in [ClueLayout::TopLeft, ClueLayout::BottomLeft] {
+            builder.settings.clue_layout = layout;
+            builder.fit(&terminal);
+            let (point, clues_width) = (builder.point, builder.screen_clues_size().width);
+            let at = |x, y| InspectTarget::at(&builder, Point { x, y });
+
+            // The lower row of the line has the clues next to the grid
+            let row_clues_x = builder.row_clues_x();
+            assert_eq!(at(row_clues_x, point.y + 1), InspectTarget::RowClues(2));
+            assert_eq!(
+                at(row_clues_x + clues_width - 1, point.y + 1),
+                InspectTarget::RowClues(3)
+            );
+            assert_eq!(
+                at(point.x + 2, builder.column_clues_y()),
+                InspectTarget::ColumnClues(1)
+            );
+            assert_eq!(
+                at(point.x + 2, point.y + 1),
+                InspectTarget::Cell(Point {
+                    x: point.x + 2,
+                    y: point.y + 2
+                })
+            );
+            // The border is neither the clues nor the cells
+            assert_eq!(at(point.x - 1, point.y), InspectTarget::Nothing);
+            assert_eq!(at(point.x, point.y - 1), InspectTarget::Nothing);
+        }
+    }
+
+    #[test]
+    fn test_describe_clues() {
+        assert_eq!(
+            describe_clues("Row", 2, &[1, 12], true),
+            "Row 3 clues: 1 12 (solved)"
+        );
+        assert_eq!(
+            describe_clues("Column", 0, &[], false),
+            "Column 1 clues: none"
+        );
+    }
+}
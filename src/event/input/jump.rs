@@ -0,0 +1,244 @@
+//! Jumping to the next or previous unsolved row or column with `]` and `[`.
+//!
+//! Jumps stay on the axis of the last jump, starting with rows. `}` and `{` switch to the other axis and jump on it.
+//! The selected cell is put on the first empty cell of the line, or on its center if it has none.
+
+use super::State;
+use crate::grid::{self, builder::Builder, CellPlacement, Grid};
+use terminal::{util::Point, Terminal};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Axis {
+    #[default]
+    Rows,
+    Columns,
+}
+
+impl Axis {
+    const fn other(self) -> Self {
+        match self {
+            Axis::Rows => Axis::Columns,
+            Axis::Columns => Axis::Rows,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Axis::Rows => "row",
+            Axis::Columns => "column",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Target {
+    pub line: usize,
+    /// Whether the jump went past the last line to the first one or the other way around.
+    pub wrapped: bool,
+}
+
+/// Returns the first unsolved line after the current line if `forward` or else before it,
+/// or `None` if all lines are solved. Without a current line, this starts at the first or last line.
+///
+/// Whether the current line is solved doesn't matter, so a line that became solved can't make a jump skip a line.
+pub fn target(current: Option<usize>, solved: &[bool], forward: bool) -> Option<Target> {
+    let len = solved.len();
+    let (lines, wrapped_lines): (Vec<usize>, Vec<usize>) = match (current, forward) {
+        (Some(current), true) => ((current + 1..len).collect(), (0..=current).collect()),
+        (Some(current), false) => ((0..current).rev().collect(), (current..len).rev().collect()),
+        (None, true) => ((0..len).collect(), Vec::new()),
+        (None, false) => ((0..len).rev().collect(), Vec::new()),
+    };
+
+    lines
+        .into_iter()
+        .map(|line| (line, false))
+        .chain(wrapped_lines.into_iter().map(|line| (line, true)))
+        .find(|(line, _)| !solved[*line])
+        .map(|(line, wrapped)| Target { line, wrapped })
+}
+
+/// Returns the point on the grid of the first empty cell of the line, or of its center if it has no empty cells.
+pub fn target_cell_point(grid: &Grid, axis: Axis, line: usize) -> Point {
+    let line = line as u16;
+    let (len, point): (u16, fn(u16, u16) -> Point) = match axis {
+        Axis::Rows => (grid.size.width, |line, index| Point { x: index, y: line }),
+        Axis::Columns => (grid.size.height, |line, index| Point { x: line, y: index }),
+    };
+
+    (0..len)
+        .map(|index| point(line, index))
+        .find(|point| grid.get_cell(*point) == grid::Cell::Empty)
+        .unwrap_or_else(|| point(line, len / 2))
+}
+
+pub fn handle(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    switch_axis: bool,
+    forward: bool,
+) -> State {
+    if switch_axis {
+        cell_placement.jump_axis = cell_placement.jump_axis.other();
+    }
+    let axis = cell_placement.jump_axis;
+
+    let current = cell_placement
+        .selected_cell_point
+        .map(|selected_cell_point| {
+            let point = grid::get_cell_point_from_cursor_point(selected_cell_point, builder);
+            match axis {
+                Axis::Rows => point.y as usize,
+                Axis::Columns => point.x as usize,
+            }
+        });
+    let solved = match axis {
+        Axis::Rows => builder.grid.solved_rows(),
+        Axis::Columns => builder.grid.solved_columns(),
+    };
+
+    let target = match target(current, &solved, forward) {
+        Some(target) => target,
+        None => return State::Alert(format!("All {}s are solved", axis.name()).into()),
+    };
+
+    let point = target_cell_point(&builder.grid, axis, target.line);
+    let selected_cell_point = Point {
+        x: builder.point.x + point.x * 2,
+        y: builder.point.y + point.y,
+    };
+    cell_placement.selected_cell_point = Some(selected_cell_point);
+
+    // We know that this point is hovered
+    grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+
+    if target.wrapped {
+        let end = if forward { "first" } else { "last" };
+        State::Alert(format!("Wrapped around to the {} unsolved {}", end, axis.name()).into())
+    } else {
+        State::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        let solved = [true, false, true, false, true];
+        let target_line = |current, forward| {
+            target(current, &solved, forward).map(|target| (target.line, target.wrapped))
+        };
+
+        assert_eq!(target_line(None, true), Some((1, false)));
+        assert_eq!(target_line(None, false), Some((3, false)));
+        assert_eq!(target_line(Some(1), true), Some((3, false)));
+        assert_eq!(target_line(Some(2), true), Some((3, false)));
+        assert_eq!(target_line(Some(3), true), Some((1, true)));
+        assert_eq!(target_line(Some(4), true), Some((1, true)));
+        assert_eq!(target_line(Some(3), false), Some((1, false)));
+        assert_eq!(target_line(Some(1), false), Some((3, true)));
+        assert_eq!(target_line(Some(0), false), Some((3, true)));
+    }
+
+    #[test]
+    fn test_single_and_no_unsolved_line() {
+        let solved = [true, false, true];
+        for current in [None, Some(0), Some(1), Some(2)] {
+            for forward in [true, false] {
+                assert_eq!(target(current, &solved, forward).unwrap().line, 1);
+            }
+        }
+        // Staying on the only unsolved line goes all the way around
+        assert!(target(Some(1), &solved, true).unwrap().wrapped);
+
+        assert_eq!(target(None, &[true, true], true), None);
+        assert_eq!(target(Some(1), &[true, true], false), None);
+        assert_eq!(target(None, &[], true), None);
+    }
+
+    #[test]
+    fn test_target_cell_point() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1", " 11 "]).unwrap();
+        grid.cells[0] = grid::Cell::Filled;
+        grid.cells[1] = grid::Cell::Crossed;
+        grid.cells[2] = grid::Cell::Filled;
+        grid.cells[4] = grid::Cell::Crossed;
+        // The first empty cell
+        assert_eq!(
+            target_cell_point(&grid, Axis::Rows, 0),
+            Point { x: 3, y: 0 }
+        );
+        assert_eq!(
+            target_cell_point(&grid, Axis::Columns, 0),
+            Point { x: 0, y: 2 }
+        );
+
+        for cell in &mut grid.cells[..4] {
+            *cell = grid::Cell::Maybed;
+        }
+        grid.cells[8] = grid::Cell::Crossed;
+        // The center if there is no empty cell
+        assert_eq!(
+            target_cell_point(&grid, Axis::Rows, 0),
+            Point { x: 2, y: 0 }
+        );
+        assert_eq!(
+            target_cell_point(&grid, Axis::Columns, 0),
+            Point { x: 0, y: 1 }
+        );
+    }
+
+    #[test]
+    fn test_jumping_while_solving() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11 1 ",
+            "1  11",
+            " 11 1",
+            "1 1 1",
+        ]).unwrap();
+        let mut axis = Axis::Rows;
+        let mut current = None;
+
+        // Alternate between the directions and axes, solving every line that is jumped to
+        for step in 0..100 {
+            let solved = match axis {
+                Axis::Rows => grid.solved_rows(),
+                Axis::Columns => grid.solved_columns(),
+            };
+            let target = match target(current, &solved, step % 2 == 0) {
+                Some(target) => target,
+                None if grid.solved_line_count() == 9 => return,
+                None => {
+                    axis = axis.other();
+                    current = None;
+                    continue;
+                }
+            };
+
+            let point = target_cell_point(&grid, axis, target.line);
+            for index in 0..grid.cells.len() {
+                let on_line = match axis {
+                    Axis::Rows => index / 5 == target.line,
+                    Axis::Columns => index % 5 == target.line,
+                };
+                if on_line && grid.solution[index] {
+                    grid.cells[index] = grid::Cell::Filled;
+                }
+            }
+            current = Some(match axis {
+                Axis::Rows => point.y as usize,
+                Axis::Columns => point.x as usize,
+            });
+            if step % 3 == 0 {
+                axis = axis.other();
+                current = None;
+            }
+        }
+
+        panic!("jumping didn't solve all lines");
+    }
+}
@@ -1,15 +1,72 @@
-use super::State;
+use super::{
+    checkpoint_menu, help, hint, history_panel, inspect::InspectMode, jump, mistakes, reveal,
+    slot_menu, tagging, window, State,
+};
 use crate::{
     editor::Editor,
+    export,
     grid::CellPlacement,
-    grid::{self, builder::Builder, Cell},
-    undo_redo_buffer,
+    grid::{
+        self, builder::Builder, colors, contradictions::Contradictions, heat_map::HeatMap,
+        tools::transform::Transform, Cell,
+    },
+    sandbox::Sandbox,
+    undo_redo_buffer, util,
 };
 use terminal::{
     event::{Event, Key},
     Terminal,
 };
 
+/// The keys that [`handle_event`] handles and what they do, as listed by the [help](super::help).
+/// Whenever a key is added or changed there, it has to be here too.
+pub const CONTROLS: &[(&str, &str)] = &[
+    ("Arrow keys", "Select a cell"),
+    ("Q", "Fill the selected cell"),
+    ("W", "Maybe the selected cell"),
+    ("E", "Cross out the selected cell"),
+    ("F", "Flood-fill from the next placed cell"),
+    ("X", "Set a measurement point"),
+    ("C", "Clear the grid"),
+    ("A", "Undo"),
+    ("D", "Redo"),
+    ("Shift+D", "Switch to what was undone and replaced"),
+    ("< >", "Undo or redo everything"),
+    ("Y", "Open the history"),
+    ("P", "Add a checkpoint"),
+    ("Shift+P", "List the checkpoints"),
+    ("N", "Place a cell that is forced, as a hint"),
+    ("U", "Highlight a line that forces a cell"),
+    ("O", "Toggle crossing out the rest of solved lines"),
+    ("V", "Toggle warning about impossible lines"),
+    ("J", "Toggle shading cells by how likely they are filled"),
+    ("#", "Toggle showing only the filled cells"),
+    (
+        "*",
+        "Switch to the next of the light, dark and high-contrast themes",
+    ),
+    ("M", "Mark the wrong filled cells"),
+    ("R", "Reveal the solution"),
+    ("] [", "Select the next or previous unsolved row"),
+    ("} {", "Switch between rows and columns for jumps"),
+    ("B", "Switch between the grid and the sandbox"),
+    ("Enter, Backspace", "Merge or discard the sandbox (sandbox)"),
+    ("G", "Arm or disarm the tag mode"),
+    ("I", "Inspect the next clicked cell or clue"),
+    ("Shift+I", "Keep inspecting until pressed again"),
+    ("L", "Open the save slots"),
+    ("T", "Save a text snapshot"),
+    ("! or F2", "Save a description of the grid in words"),
+    ("H", "Save a printable sheet"),
+    ("K", "Copy the puzzle code"),
+    ("Tab", "Toggle the editor"),
+    ("S", "Save the edited grid (editor)"),
+    ("M, Shift+M", "Mirror the grid (editor)"),
+    ("R", "Rotate the grid (editor)"),
+    ("? F1", "Show this list"),
+    ("Esc", "Exit"),
+];
+
 /// This handles all key input.
 pub fn handle_event(
     terminal: &mut Terminal,
@@ -17,23 +74,63 @@ pub fn handle_event(
     builder: &mut Builder,
     editor: &mut Editor,
     cell_placement: &mut CellPlacement,
+    sandbox: &mut Sandbox,
 ) -> State {
+    if let Some(state) = tagging::handle_key(
+        terminal,
+        &key_event,
+        builder,
+        cell_placement,
+        sandbox.toggled,
+    ) {
+        return state;
+    }
+
     match key_event {
         Key::Char('a' | 'A') => {
             if builder.grid.undo_last_cell() {
                 // An undo won't cause the grid to be solved at this point because otherwise it would've already been solved before when that operation was done.
                 #[allow(unused_must_use)]
                 {
-                    builder.draw_all(terminal);
+                    builder.draw_changes(terminal);
                 }
             }
 
             State::Continue
         }
-        Key::Char('d' | 'D') => {
+        Key::Char('D') => {
+            if builder.grid.switch_branch() {
+                builder.grid.redo_last_cell();
+                // This redo won't cause the grid to be solved either because that operation was done before as well.
+                #[allow(unused_must_use)]
+                {
+                    builder.draw_all(terminal);
+                }
+                State::Alert("Switched to the operations that were undone and replaced".into())
+            } else {
+                State::Alert("No undone operations were replaced".into())
+            }
+        }
+        Key::Char('d') => {
             if builder.grid.redo_last_cell() {
                 // A redo won't cause the grid to be solved at this point because otherwise it would've already been solved before when that operation was done.
                 #[allow(unused_must_use)]
+                {
+                    builder.draw_changes(terminal);
+                }
+            }
+
+            State::Continue
+        }
+        Key::Char(char @ ('<' | '>')) => {
+            let moved = if char == '<' {
+                builder.grid.undo_all()
+            } else {
+                builder.grid.redo_all()
+            };
+            if moved {
+                // Neither undoing nor redoing solves the grid, as above
+                #[allow(unused_must_use)]
                 {
                     builder.draw_all(terminal);
                 }
@@ -42,11 +139,7 @@ pub fn handle_event(
             State::Continue
         }
         Key::Char('c' | 'C') => {
-            builder.grid.clear();
-            builder
-                .grid
-                .undo_redo_buffer
-                .push(undo_redo_buffer::Operation::Clear);
+            builder.grid.apply(undo_redo_buffer::Operation::Clear);
 
             // A clear won't cause the grid to be solved at this point because otherwise it would've already been solved initially when the grid was empty.
             #[allow(unused_must_use)]
@@ -61,8 +154,279 @@ pub fn handle_event(
             State::Alert("Set place to fill".into())
         }
         Key::Char('x' | 'X') => cell_placement.place_measured_cells(terminal, builder),
+        Key::Char('k' | 'K') => {
+            let code = builder.grid.encode();
+            if util::copy_to_clipboard(terminal, &code) {
+                State::Alert("Puzzle code copied to clipboard".into())
+            } else {
+                State::Alert(format!("Puzzle code: {}", code).into())
+            }
+        }
+        Key::Char('t' | 'T') => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            match export::text::save(sandbox.real_grid(&builder.grid)) {
+                Ok(filename) => State::Alert(format!("Snapshot saved as {}", filename).into()),
+                Err(err) => State::Alert(err.into()),
+            }
+        }
+        Key::Char('!') | Key::F(2) => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            match export::description::save(sandbox.real_grid(&builder.grid)) {
+                Ok(filename) => State::Alert(format!("Description saved as {}", filename).into()),
+                Err(err) => State::Alert(err.into()),
+            }
+        }
+        Key::Char('h' | 'H') => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            match export::sheet::save(&builder.grid) {
+                Ok(filename) => State::Alert(format!("Sheet saved as {}", filename).into()),
+                Err(err) => State::Alert(err.into()),
+            }
+        }
+        Key::Char('g' | 'G') => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            tagging::toggle(cell_placement)
+        }
+        Key::Char('n' | 'N') => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            hint::handle(
+                terminal,
+                builder,
+                cell_placement.auto_cross,
+                sandbox.toggled,
+            )
+        }
+        Key::Char('u' | 'U') => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            hint::handle_line(terminal, builder)
+        }
+        Key::Char('o' | 'O') => {
+            cell_placement.auto_cross = !cell_placement.auto_cross;
+            if cell_placement.auto_cross {
+                State::Alert("Crossing out the rest of solved rows and columns".into())
+            } else {
+                State::Alert("Stopped crossing out solved rows and columns".into())
+            }
+        }
+        Key::Char('v' | 'V') => {
+            let message = if builder.contradictions.is_some() {
+                builder.contradictions = None;
+                "Stopped warning about impossible rows and columns"
+            } else {
+                builder.contradictions = Some(Contradictions::default());
+                "Warning about impossible rows and columns"
+            };
+            // Which clues are in the warning color changes but the grid stays unsolved
+            #[allow(unused_must_use)]
+            {
+                builder.draw_all(terminal);
+            }
+            State::Alert(message.into())
+        }
+        Key::Char('j' | 'J') => {
+            let message = if builder.heat_map.is_some() {
+                builder.heat_map = None;
+                "Stopped shading cells by how likely they are to be filled"
+            } else {
+                builder.heat_map = Some(HeatMap::default());
+                "Shading cells by how likely they are to be filled"
+            };
+            // The cells are drawn differently but the grid stays unsolved
+            #[allow(unused_must_use)]
+            {
+                builder.draw_all(terminal);
+            }
+            State::Alert(message.into())
+        }
+        Key::Char('#') => {
+            builder.clean_picture = !builder.clean_picture;
+            // Only how the cells are drawn changes
+            #[allow(unused_must_use)]
+            {
+                builder.draw_all(terminal);
+            }
+            if builder.clean_picture {
+                State::Alert("Showing only the filled cells".into())
+            } else {
+                State::Alert("Showing all cells".into())
+            }
+        }
+        Key::Char('*') => {
+            let theme = builder.settings.theme.next();
+            builder.set_theme(theme);
+            // It is switched to even if it couldn't be remembered
+            let saved = colors::save_theme(theme);
+            // Everything is drawn in other colors but the grid stays unsolved
+            #[allow(unused_must_use)]
+            {
+                builder.draw_all(terminal);
+            }
+            match saved {
+                Ok(()) => State::Alert(format!("Switched to the {} theme", theme.name()).into()),
+                Err(err) => State::Alert(err.into()),
+            }
+        }
+        Key::Char('?') | Key::F(1) => {
+            help::open(terminal, builder, cell_placement, sandbox.controls_help())
+        }
+        Key::Char('y' | 'Y') => {
+            history_panel::open(terminal, builder, cell_placement, sandbox.controls_help())
+        }
+        Key::Char('p') => checkpoint_menu::add(builder),
+        Key::Char('P') => {
+            checkpoint_menu::open(terminal, builder, cell_placement, sandbox.controls_help())
+        }
+        Key::Char('m') if editor.toggled => transform(
+            terminal,
+            builder,
+            cell_placement,
+            Transform::MirrorHorizontally,
+        ),
+        Key::Char('M') if editor.toggled => transform(
+            terminal,
+            builder,
+            cell_placement,
+            Transform::MirrorVertically,
+        ),
+        Key::Char('r' | 'R') if editor.toggled => {
+            transform(terminal, builder, cell_placement, Transform::Rotate)
+        }
+        Key::Char('m' | 'M') => mistakes::handle(terminal, builder),
+        Key::Char('r' | 'R') => {
+            if sandbox.toggled {
+                State::Alert("Leave the sandbox first".into())
+            } else {
+                reveal::handle(terminal, builder)
+            }
+        }
+        #[cfg(debug_assertions)]
+        Key::Char('z' | 'Z') => match grid::fixture::save(&builder.grid) {
+            Ok(filename) => State::Alert(format!("Fixture saved as {}", filename).into()),
+            Err(err) => State::Alert(err.into()),
+        },
+        Key::Char('i') => {
+            let inspector = &mut cell_placement.inspector;
+            if inspector.mode == InspectMode::Off {
+                inspector.mode = InspectMode::OneShot;
+                State::Alert("Inspecting: click a cell or clue".into())
+            } else {
+                inspector.mode = InspectMode::Off;
+                State::Alert("Stopped inspecting".into())
+            }
+        }
+        Key::Char('I') => {
+            let inspector = &mut cell_placement.inspector;
+            if inspector.mode == InspectMode::Latched {
+                inspector.mode = InspectMode::Off;
+                State::Alert("Stopped inspecting".into())
+            } else {
+                inspector.mode = InspectMode::Latched;
+                State::Alert("Inspecting until Shift+I is pressed again".into())
+            }
+        }
+        Key::Char(char @ ('[' | ']' | '{' | '}')) => jump::handle(
+            terminal,
+            builder,
+            cell_placement,
+            matches!(char, '{' | '}'),
+            matches!(char, ']' | '}'),
+        ),
+        Key::Char('l' | 'L') => {
+            if editor.toggled {
+                State::Alert("Disable the editor first".into())
+            } else if sandbox.toggled {
+                State::Alert("Leave the sandbox first".into())
+            } else if cell_placement.lives.is_some() {
+                // Loading would start over without the mistakes
+                State::Alert("Save slots are off in the lives mode".into())
+            } else {
+                slot_menu::open(terminal, builder, cell_placement)
+            }
+        }
+        Key::Char('b' | 'B') => {
+            if editor.toggled {
+                return State::Alert("Disable the editor first".into());
+            }
+
+            let previous_controls_help = sandbox.controls_help();
+            sandbox.toggle(&mut builder.grid);
+            draw_switched_grid(
+                terminal,
+                builder,
+                cell_placement,
+                sandbox,
+                previous_controls_help,
+            );
+
+            if sandbox.toggled {
+                State::Alert("Sandbox entered".into())
+            } else {
+                State::Alert("Sandbox left".into())
+            }
+        }
+        Key::Enter if sandbox.toggled => {
+            let previous_controls_help = sandbox.controls_help();
+            let merged_cell_count = sandbox.merge(&mut builder.grid);
+            let mistake_state = cell_placement
+                .lives
+                .as_mut()
+                .and_then(|lives| lives.reject_wrong_cells(&mut builder.grid));
+            let all_clues_solved = draw_switched_grid(
+                terminal,
+                builder,
+                cell_placement,
+                sandbox,
+                previous_controls_help,
+            );
+
+            if let Some(mistake_state) = mistake_state {
+                mistake_state
+            } else if all_clues_solved {
+                State::Solved(builder.grid.playing_time)
+            } else {
+                State::Alert(format!("Merged {} cells", merged_cell_count).into())
+            }
+        }
+        Key::Backspace if sandbox.toggled => {
+            let previous_controls_help = sandbox.controls_help();
+            sandbox.discard(&mut builder.grid);
+            draw_switched_grid(
+                terminal,
+                builder,
+                cell_placement,
+                sandbox,
+                previous_controls_help,
+            );
+
+            State::Alert("Sandbox discarded".into())
+        }
+        Key::Tab if sandbox.toggled => State::Alert("Leave the sandbox first".into()),
+        // Editing would change the solution that mistakes are told by
+        Key::Tab if cell_placement.lives.is_some() => {
+            State::Alert("The editor is off in the lives mode".into())
+        }
         Key::Tab => {
             editor.toggle();
+            // Tags are for solving, not for editing
+            cell_placement.tag_mode = None;
 
             if editor.toggled {
                 terminal.set_title("yayagram Editor");
@@ -73,7 +437,7 @@ pub fn handle_event(
             }
         }
         Key::Char('s' | 'S') if editor.toggled => {
-            if let Err(err) = editor.save_grid(&builder) {
+            if let Err(err) = editor.save_grid(builder) {
                 State::Alert(err.into())
             } else {
                 State::Alert(format!("Grid saved as {}", editor.filename).into())
@@ -94,6 +458,7 @@ pub fn handle_event(
                     selected_cell_point,
                     cell_to_place,
                     editor.toggled,
+                    sandbox.toggled,
                 );
 
                 cell_placement.cell = None;
@@ -156,10 +521,8 @@ pub fn handle_event(
                 grid_center
             };
 
-            builder.draw_grid(terminal);
-
             // We know that this point is hovered
-            grid::draw_highlighted_cells(terminal, &builder, selected_cell_point);
+            grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
 
             State::Continue
         }
@@ -168,11 +531,75 @@ pub fn handle_event(
     }
 }
 
-pub fn r#await(terminal: &mut Terminal) {
+/// Redraws everything after switching between the real grid and the sandbox,
+/// returning whether the grid that is now shown is solved.
+fn draw_switched_grid(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    sandbox: &Sandbox,
+    previous_controls_help: &[&str],
+) -> bool {
+    // A measurement or fill started on one grid shouldn't end on the other
+    cell_placement.measurement_point = None;
+    cell_placement.fill = false;
+
+    if sandbox.toggled {
+        terminal.set_title("yayagram Sandbox");
+    } else {
+        terminal.set_title("yayagram");
+    }
+
+    let all_clues_solved = builder.draw_all(terminal);
+
+    crate::clear_controls_help(terminal, builder, previous_controls_help);
+    crate::draw_controls_help(terminal, builder, sandbox.controls_help());
+
+    all_clues_solved
+}
+
+/// Transforms the grid in the editor, drawing it again because the clues might take up a different space now.
+fn transform(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    transform: Transform,
+) -> State {
+    if !transform.fits(&builder.grid.size) {
+        return State::Alert("Only square grids can be rotated".into());
+    }
+
+    builder
+        .grid
+        .apply(undo_redo_buffer::Operation::Transform(transform));
+
+    // The sandbox can't be entered in the editor
+    if let State::Exit = window::handle_resize(
+        terminal,
+        builder,
+        cell_placement,
+        &None,
+        crate::BASIC_CONTROLS_HELP,
+    ) {
+        return State::Exit;
+    }
+
+    State::Alert(
+        match transform {
+            Transform::MirrorHorizontally => "Mirrored the grid horizontally",
+            Transform::MirrorVertically => "Mirrored the grid vertically",
+            Transform::Rotate => "Rotated the grid",
+        }
+        .into(),
+    )
+}
+
+/// Waits for a key press and returns the pressed key.
+pub fn r#await(terminal: &mut Terminal) -> Key {
     loop {
         let event = terminal.read_event();
-        if let Some(Event::Key(_)) = event {
-            break;
+        if let Some(Event::Key(key)) = event {
+            break key;
         }
     }
 }
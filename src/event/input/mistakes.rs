@@ -0,0 +1,78 @@
+//! Checking for mistakes with `M`, which briefly shows the filled cells that are wrong.
+
+use super::State;
+use crate::{
+    grid::{builder::Builder, Grid},
+    solver,
+};
+use terminal::{
+    util::{Color, Point},
+    Terminal,
+};
+
+/// Returns the points of the filled cells that are empty in the solution.
+fn wrong_points(grid: &Grid, solution: &[bool]) -> Vec<Point> {
+    let width = grid.size.width as usize;
+
+    grid.cells
+        .iter()
+        .zip(solution)
+        .enumerate()
+        .filter(|(_, (cell, filled))| cell.is_filled() && !**filled)
+        .map(|(index, _)| Point {
+            x: (index % width) as u16,
+            y: (index / width) as u16,
+        })
+        .collect()
+}
+
+pub fn handle(terminal: &mut Terminal, builder: &mut Builder) -> State {
+    let grid = &builder.grid;
+
+    let solution = match solver::known_solution(grid) {
+        Some(solution) => solution,
+        None => return State::Alert("The solution of this puzzle is unknown".into()),
+    };
+
+    let points = wrong_points(grid, &solution);
+
+    // The cells stay marked until the grid is drawn again
+    for point in points.iter().filter(|point| builder.is_visible(**point)) {
+        terminal.set_cursor(builder.screen_point(*point));
+        terminal.set_background_color(Color::Yellow);
+        terminal.set_foreground_color(Color::Black);
+        terminal.write("!!");
+    }
+    terminal.reset_colors();
+
+    match points.len() {
+        0 => State::Alert("No filled cells are wrong".into()),
+        1 => State::Alert("1 filled cell is wrong".into()),
+        count => State::Alert(format!("{} filled cells are wrong", count).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+
+    #[test]
+    fn test_wrong_points() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11 ",
+            " 1 ",
+        ]).unwrap();
+        let solution = grid.solution.clone();
+        assert!(wrong_points(&grid, &solution).is_empty());
+
+        grid.cells[0] = Cell::Filled;
+        grid.cells[2] = Cell::Filled;
+        // Only filled cells count, even if the others are wrong too
+        grid.cells[3] = Cell::Crossed;
+        grid.cells[4] = Cell::Crossed;
+        grid.cells[5] = Cell::Maybed;
+        assert_eq!(wrong_points(&grid, &solution), [Point { x: 2, y: 0 }]);
+    }
+}
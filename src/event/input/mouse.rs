@@ -1,5 +1,9 @@
-use super::State;
-use crate::grid::{self, builder::Builder, Cell, CellPlacement};
+use super::{
+    inspect::{self, InspectTarget},
+    State,
+};
+use crate::event::latency::Quality;
+use crate::grid::{builder::Builder, Cell, CellPlacement};
 use terminal::{
     event::{MouseButton, MouseEvent, MouseEventKind},
     Terminal,
@@ -12,8 +16,27 @@ pub fn handle_event(
     builder: &mut Builder,
     editor_toggled: bool,
     cell_placement: &mut CellPlacement,
+    sandbox_toggled: bool,
 ) -> State {
+    // The clues are hit-tested where they are on the screen
+    let screen_point = event.point;
+    let event = MouseEvent {
+        point: builder.cursor_point(event.point),
+        ..event
+    };
+
     match event {
+        MouseEvent {
+            kind: MouseEventKind::Press(_),
+            ..
+        } if cell_placement.inspector.press() => {
+            let target = InspectTarget::at(builder, screen_point);
+            inspect::inspect(terminal, builder, cell_placement, target)
+        }
+        MouseEvent {
+            kind: MouseEventKind::Drag(_),
+            ..
+        } if cell_placement.inspector.pressed => State::Continue,
         MouseEvent {
             kind: MouseEventKind::Drag(mouse_button) | MouseEventKind::Press(mouse_button),
             point: selected_cell_point,
@@ -33,6 +56,7 @@ pub fn handle_event(
                     selected_cell_point,
                     cell_to_place,
                     editor_toggled,
+                    sandbox_toggled,
                 )
             } else {
                 State::Continue
@@ -41,19 +65,27 @@ pub fn handle_event(
         MouseEvent {
             kind: MouseEventKind::Move,
             point,
-        } => {
-            builder.draw_grid(terminal);
-
+        } if cell_placement.quality == Quality::Reduced => {
+            // Highlighting the hovered cell's row and column redraws both of them and their clues
             if builder.contains(point) {
                 cell_placement.selected_cell_point = Some(point);
-                let some_selected_cell_point = point;
-
-                // We know that this point is hovered
-                grid::draw_highlighted_cells(terminal, &builder, some_selected_cell_point);
             }
             State::Continue
         }
+        MouseEvent {
+            kind: MouseEventKind::Move,
+            point,
+        } => {
+            if builder.contains(point) {
+                inspect::hover(terminal, builder, cell_placement, point);
+                State::Continue
+            } else {
+                builder.draw_crosshair(terminal, None);
+                inspect::hover_clues(terminal, builder, cell_placement, screen_point)
+            }
+        }
         _ => {
+            cell_placement.inspector.release();
             cell_placement.cell = None;
             State::Continue
         }
@@ -0,0 +1,80 @@
+//! Giving up with `R`, which reveals the solution after asking for confirmation.
+
+use super::State;
+use crate::{
+    grid::{builder::Builder, Cell, Grid},
+    solver,
+};
+use terminal::Terminal;
+
+/// Fills in the solution, leaving nothing that could be undone or redone to get back to the player's progress.
+fn reveal(grid: &mut Grid, solution: &[bool]) {
+    for (cell, filled) in grid.cells.iter_mut().zip(solution) {
+        *cell = if *filled { Cell::Filled } else { Cell::Empty };
+    }
+    grid.tags.fill(None);
+    grid.restart_undo_redo_buffer();
+}
+
+pub fn handle(terminal: &mut Terminal, builder: &mut Builder) -> State {
+    let solution = match solver::known_solution(&builder.grid) {
+        Some(solution) => solution.into_owned(),
+        None => return State::Alert("The solution of this puzzle is unknown".into()),
+    };
+
+    if !crate::confirm_prompt(terminal, builder, "Give up and reveal the solution? (Y/N)") {
+        return State::Continue;
+    }
+
+    reveal(&mut builder.grid, &solution);
+
+    // Revealing always solves the grid
+    #[allow(unused_must_use)]
+    {
+        builder.draw_all(terminal);
+    }
+
+    State::Revealed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::undo_redo_buffer::Operation;
+    use terminal::util::Point;
+
+    #[test]
+    fn test_reveal() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "1 1",
+            " 1 ",
+        ]).unwrap();
+        grid.cells[1] = Cell::Filled;
+        grid.cells[3] = Cell::Crossed;
+        grid.tags[0] = Some(2);
+        grid.undo_redo_buffer.push(Operation::SetCell {
+            point: Point { x: 1, y: 0 },
+            cell: Cell::Filled,
+        });
+
+        let solution = grid.solution.clone();
+        reveal(&mut grid, &solution);
+        assert_eq!(
+            grid.cells,
+            [
+                Cell::Filled,
+                Cell::Empty,
+                Cell::Filled,
+                Cell::Empty,
+                Cell::Filled,
+                Cell::Empty
+            ]
+        );
+        assert!(grid.tags.iter().all(Option::is_none));
+
+        // There is no way back to the progress from before
+        assert!(!grid.undo_last_cell());
+        assert!(!grid.redo_last_cell());
+    }
+}
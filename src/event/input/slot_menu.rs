@@ -0,0 +1,195 @@
+//! The menu for saving the progress to slots and loading it from them.
+
+use super::{key, window, State};
+use crate::{
+    grid::{self, builder::Builder, CellPlacement, Grid},
+    menu,
+    save::slot::{self, Slot},
+    util,
+};
+use std::borrow::Cow;
+use terminal::{event::Key, Terminal};
+
+const TITLE: &str = "Save slots";
+const CONTROLS_HELP: &str = "Enter: Load or save new, S: Save, Backspace: Delete, Esc: Close";
+const NEW_SLOT: &str = "New slot";
+
+fn format_slot(slot: &Slot) -> String {
+    if let Some(summary) = &slot.summary {
+        format!(
+            "{:<name_len$}  {:>5}  {:>4}  {}",
+            slot.name,
+            format!("{}x{}", summary.size.width, summary.size.height),
+            format!("{}%", summary.completion),
+            util::format_timestamp(summary.saved),
+            name_len = slot::MAX_NAME_LEN
+        )
+    } else {
+        format!(
+            "{:<name_len$}  broken",
+            slot.name,
+            name_len = slot::MAX_NAME_LEN
+        )
+    }
+}
+
+/// Draws the menu. The first entry is for saving to a new slot.
+fn draw(terminal: &mut Terminal, slots: &[Slot], selected: usize, bottom_text: &str) {
+    let entries = std::iter::once(Cow::from(NEW_SLOT))
+        .chain(slots.iter().map(|slot| format_slot(slot).into()));
+    menu::draw(
+        terminal,
+        TITLE,
+        entries,
+        selected,
+        bottom_text,
+        CONTROLS_HELP,
+    );
+}
+
+/// Lets the player type in the name of a new slot, returning `None` if cancelled.
+fn prompt_name(terminal: &mut Terminal, slots: &[Slot]) -> Option<String> {
+    let mut name = String::new();
+    let bottom_y = menu::bottom_text_y(terminal);
+
+    loop {
+        let text = format!(
+            "Slot name: {:<name_len$}",
+            name,
+            name_len = slot::MAX_NAME_LEN
+        );
+        menu::draw_centered(terminal, &text, bottom_y);
+        terminal.flush();
+
+        match key::r#await(terminal) {
+            Key::Char(char) if slot::is_name_char(char) && name.len() < slot::MAX_NAME_LEN => {
+                name.push(char);
+            }
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Enter if !name.is_empty() && slots.iter().all(|slot| slot.name != name) => {
+                break Some(name);
+            }
+            Key::Esc => break None,
+            _ => {}
+        }
+    }
+}
+
+/// Closes the menu by drawing the grid again.
+fn close(terminal: &mut Terminal, builder: &mut Builder, cell_placement: &CellPlacement) {
+    terminal.clear();
+
+    // The terminal might have been resized in the meantime
+    builder.fit(terminal);
+
+    // Grids are never saved solved so a loaded grid can't be solved either
+    #[allow(unused_must_use)]
+    {
+        builder.draw_all(terminal);
+    }
+    crate::draw_controls_help(terminal, builder, crate::BASIC_CONTROLS_HELP);
+
+    if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+        grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+    }
+}
+
+/// What the menu was left with.
+enum Action {
+    Close,
+    Alert(Cow<'static, str>),
+    Load { grid: Box<Grid>, name: String },
+}
+
+/// Runs the menu until it is left.
+///
+/// The menu only gets to read the grid so that it can't change while the menu covers it.
+/// Whatever the menu does to the grid is returned instead and only done once the menu is closed.
+fn run(terminal: &mut Terminal, grid: &Grid) -> Action {
+    let mut slots = slot::list();
+    let mut selected = 0;
+    let mut bottom_text = Cow::from("");
+
+    loop {
+        draw(terminal, &slots, selected, &bottom_text);
+
+        // `selected` is the index of the slot plus one because of the entry for a new slot
+        let selected_slot = selected.checked_sub(1).map(|index| &slots[index]);
+
+        match (key::r#await(terminal), selected_slot) {
+            (key, _) if menu::move_selection(&key, &mut selected, slots.len() + 1) => {}
+            (Key::Enter, None) => {
+                if let Some(name) = prompt_name(terminal, &slots) {
+                    return Action::Alert(match slot::save(&name, grid) {
+                        Ok(()) => format!("Saved to slot {}", name).into(),
+                        Err(err) => err.into(),
+                    });
+                }
+            }
+            (Key::Enter, Some(selected_slot)) => match slot::load(&selected_slot.name) {
+                Ok(grid) => {
+                    return Action::Load {
+                        grid: Box::new(grid),
+                        name: selected_slot.name.clone(),
+                    }
+                }
+                Err(err) => bottom_text = err.into(),
+            },
+            (Key::Char('s' | 'S'), Some(selected_slot)) => {
+                let question = format!("Overwrite slot {}? (Y/N)", selected_slot.name);
+                if menu::confirm(terminal, &question) {
+                    return Action::Alert(match slot::save(&selected_slot.name, grid) {
+                        Ok(()) => format!("Saved to slot {}", selected_slot.name).into(),
+                        Err(err) => err.into(),
+                    });
+                }
+            }
+            (Key::Backspace, Some(selected_slot)) => {
+                let question = format!("Delete slot {}? (Y/N)", selected_slot.name);
+                if menu::confirm(terminal, &question) {
+                    bottom_text = match slot::delete(&selected_slot.name) {
+                        Ok(()) => format!("Deleted slot {}", selected_slot.name).into(),
+                        Err(err) => err.into(),
+                    };
+                    slots = slot::list();
+                    selected = selected.min(slots.len());
+                }
+            }
+            (Key::Esc, _) => return Action::Close,
+            _ => {}
+        }
+    }
+}
+
+/// Opens the menu and returns once it was closed.
+pub fn open(
+    terminal: &mut Terminal,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+) -> State {
+    let state = match run(terminal, &builder.grid) {
+        Action::Close => State::Continue,
+        Action::Alert(message) => State::Alert(message),
+        Action::Load { grid, name } => {
+            terminal.clear();
+            if let State::Exit = window::await_fitting_size(terminal, &grid, builder.settings) {
+                return State::Exit;
+            }
+
+            builder.grid = *grid;
+            // The selected cell might not even be on the loaded grid
+            *cell_placement = CellPlacement {
+                auto_cross: cell_placement.auto_cross,
+                quality: cell_placement.quality,
+                ..CellPlacement::default()
+            };
+
+            State::Alert(format!("Loaded slot {}", name).into())
+        }
+    };
+
+    close(terminal, builder, cell_placement);
+    state
+}
@@ -0,0 +1,263 @@
+//! Tagging cells with the keyboard while the tag mode is armed with `G`.
+//!
+//! The number keys toggle their tag on the selected cell, `Backspace` removes the last used tag from all cells
+//! and `Q` and `E` promote all cells with the last used tag to filled or crossed out cells.
+
+use super::State;
+use crate::{
+    grid::{
+        self,
+        builder::Builder,
+        tag::{self, TAG_COUNT},
+        Cell, CellPlacement,
+    },
+    undo_redo_buffer::Operation,
+};
+use terminal::{event::Key, Terminal};
+
+pub fn toggle(cell_placement: &mut CellPlacement) -> State {
+    if cell_placement.tag_mode.take().is_some() {
+        State::Alert("Stopped tagging".into())
+    } else {
+        cell_placement.tag_mode = Some(1);
+        State::Alert(
+            format!(
+                "Tagging: 1-{} to tag, Backspace to clear, Q/E to promote",
+                TAG_COUNT
+            )
+            .into(),
+        )
+    }
+}
+
+/// Redraws the grid, keeping the selected cell highlighted.
+fn draw_grid(terminal: &mut Terminal, builder: &mut Builder, cell_placement: &CellPlacement) {
+    builder.draw_grid(terminal);
+
+    if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+        // We know that this point is hovered
+        grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+    }
+}
+
+/// Handles the key if it does something in the tag mode, which must be armed.
+pub fn handle_key(
+    terminal: &mut Terminal,
+    key: &Key,
+    builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
+    sandbox_toggled: bool,
+) -> Option<State> {
+    let current_tag = cell_placement.tag_mode?;
+
+    let state = match key {
+        Key::Char(char @ '1'..='9') if char.to_digit(10).unwrap() <= TAG_COUNT as u32 => {
+            let tag = char.to_digit(10).unwrap() as u8;
+            cell_placement.tag_mode = Some(tag);
+
+            let selected_cell_point = match cell_placement.selected_cell_point {
+                Some(selected_cell_point) => selected_cell_point,
+                None => return Some(State::Alert(format!("Tagging with tag {}", tag).into())),
+            };
+            let point = grid::get_cell_point_from_cursor_point(selected_cell_point, builder);
+            let cell_tag = if builder.grid.get_tag(point) == Some(tag) {
+                None
+            } else {
+                Some(tag)
+            };
+            builder.grid.apply(Operation::Tag(vec![(point, cell_tag)]));
+
+            draw_grid(terminal, builder, cell_placement);
+
+            State::Continue
+        }
+        Key::Backspace => {
+            let points = tag::tagged_points(&builder.grid, current_tag);
+            if points.is_empty() {
+                return Some(State::Alert(
+                    format!("No cells have tag {}", current_tag).into(),
+                ));
+            }
+
+            let point_count = points.len();
+            let operation = Operation::Tag(points.into_iter().map(|point| (point, None)).collect());
+            builder.grid.apply(operation);
+
+            draw_grid(terminal, builder, cell_placement);
+
+            State::Alert(format!("Removed tag {} from {} cells", current_tag, point_count).into())
+        }
+        Key::Char(char @ ('q' | 'Q' | 'e' | 'E')) => {
+            let (cell, name) = if matches!(char, 'q' | 'Q') {
+                (Cell::Filled, "filled")
+            } else {
+                (Cell::Crossed, "crossed out")
+            };
+
+            let point_count = tag::tagged_points(&builder.grid, current_tag).len();
+            if point_count == 0 {
+                return Some(State::Alert(
+                    format!("No cells have tag {}", current_tag).into(),
+                ));
+            }
+
+            builder.grid.apply(Operation::Promote {
+                tag: current_tag,
+                cell,
+            });
+
+            let mistake_state = match (&mut cell_placement.lives, sandbox_toggled) {
+                (Some(lives), false) => lives.reject_wrong_cells(&mut builder.grid),
+                _ => None,
+            };
+
+            let all_clues_solved = builder.draw_all(terminal);
+
+            if let Some(mistake_state) = mistake_state {
+                return Some(mistake_state);
+            }
+
+            // Solving the sandbox doesn't count
+            if all_clues_solved && !sandbox_toggled {
+                return Some(State::Solved(builder.grid.playing_time));
+            }
+
+            if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+                // We know that this point is hovered
+                grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+            }
+
+            State::Alert(format!("Promoted {} cells to {} cells", point_count, name).into())
+        }
+        Key::Char('g' | 'G') | Key::Esc => toggle(cell_placement),
+        _ => return None,
+    };
+
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{colors::Palette, Grid, DEFAULT_SEPARATION_INTERVAL};
+    use terminal::util::{Color, Point};
+
+    #[test]
+    fn test_overlay() {
+        let point = Point { x: 0, y: 0 };
+        let palette = Palette::default();
+        let tag_segment = |tag| {
+            (
+                Some(Color::Black),
+                tag::appearance(tag).1,
+                tag::appearance(tag).0.into(),
+            )
+        };
+
+        // Without a tag, the cell is drawn as usual
+        assert_eq!(
+            Cell::Filled.segments(
+                point,
+                DEFAULT_SEPARATION_INTERVAL,
+                false,
+                None,
+                &palette,
+                false
+            ),
+            vec![(None, Color::White, "  ".into())]
+        );
+
+        // With one, the tag takes up the left half and the cell still shows through on the right half
+        for tag in 1..=TAG_COUNT {
+            assert_eq!(
+                Cell::Filled.segments(
+                    point,
+                    DEFAULT_SEPARATION_INTERVAL,
+                    false,
+                    Some(tag),
+                    &palette,
+                    false
+                ),
+                vec![tag_segment(tag), (None, Color::White, " ".into())]
+            );
+            assert_eq!(
+                Cell::Empty.segments(
+                    point,
+                    DEFAULT_SEPARATION_INTERVAL,
+                    true,
+                    Some(tag),
+                    &palette,
+                    false
+                ),
+                vec![tag_segment(tag), (None, Color::Byte(237), " ".into())]
+            );
+        }
+        assert_eq!(
+            Cell::Measured(Some(7)).segments(
+                point,
+                DEFAULT_SEPARATION_INTERVAL,
+                false,
+                Some(2),
+                &palette,
+                false
+            ),
+            vec![
+                tag_segment(2),
+                (Some(Color::Black), Color::Green, "7".into())
+            ]
+        );
+
+        // The tags can be told apart without colors
+        let glyphs: Vec<&str> = (1..=TAG_COUNT).map(|tag| tag::appearance(tag).0).collect();
+        for (index, glyph) in glyphs.iter().enumerate() {
+            assert!(!glyphs[index + 1..].contains(glyph));
+        }
+    }
+
+    #[test]
+    fn test_promote_replay() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        let operations = [
+            Operation::SetCell {
+                point: Point { x: 2, y: 0 },
+                cell: Cell::Crossed,
+            },
+            Operation::Tag(vec![
+                (Point { x: 0, y: 0 }, Some(1)),
+                (Point { x: 1, y: 0 }, Some(1)),
+                (Point { x: 2, y: 0 }, Some(1)),
+                (Point { x: 3, y: 1 }, Some(2)),
+            ]),
+            Operation::Tag(vec![(Point { x: 2, y: 0 }, None)]),
+            Operation::Promote {
+                tag: 1,
+                cell: Cell::Filled,
+            },
+        ];
+        for operation in operations {
+            grid.undo_redo_buffer.push(operation);
+        }
+        grid.rebuild();
+
+        assert_eq!(
+            &grid.cells[..4],
+            [Cell::Filled, Cell::Filled, Cell::Crossed, Cell::Empty]
+        );
+        assert_eq!(grid.tags[..3], [None, None, None]);
+        assert_eq!(grid.get_tag(Point { x: 3, y: 1 }), Some(2));
+
+        // Undoing the promotion brings the tags back instead of the cells
+        assert!(grid.undo_last_cell());
+        assert_eq!(&grid.cells[..2], [Cell::Empty, Cell::Empty]);
+        assert_eq!(tag::tagged_points(&grid, 1).len(), 2);
+
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.get_tag(Point { x: 2, y: 0 }), Some(1));
+        assert_eq!(grid.get_cell(Point { x: 2, y: 0 }), Cell::Crossed);
+
+        // Clearing removes the tags too
+        grid.undo_redo_buffer.push(Operation::Clear);
+        grid.rebuild();
+        assert!(grid.tags.iter().all(Option::is_none));
+    }
+}
@@ -1,21 +1,35 @@
 use super::{Alert, State};
-use crate::grid::{self, builder::Builder, Grid};
+use crate::grid::{
+    self,
+    builder::{Builder, Settings},
+    CellPlacement, Grid,
+};
 use terminal::{
     event::{Event, Key},
-    util::Point,
+    util::{Point, Size},
     Terminal,
 };
 
+/// Redraws everything centered in the resized terminal, once it is big enough for the grid.
+///
+/// The selected cell stays the same, even though the grid moves on the screen.
 pub fn handle_resize(
     terminal: &mut Terminal,
     builder: &mut Builder,
+    cell_placement: &mut CellPlacement,
     last_alert: &Option<Alert>,
+    controls_help: &[&str],
 ) -> State {
     terminal.clear();
 
-    let state = await_fitting_size(terminal, &builder.grid);
+    let state = await_fitting_size(terminal, &builder.grid, builder.settings);
 
-    builder.point = grid::builder::centered_point(terminal, &builder.grid);
+    let previous_point = builder.point;
+    builder.fit(terminal);
+    if let Some(selected_cell_point) = &mut cell_placement.selected_cell_point {
+        selected_cell_point.x = selected_cell_point.x + builder.point.x - previous_point.x;
+        selected_cell_point.y = selected_cell_point.y + builder.point.y - previous_point.y;
+    }
 
     // No grid mutation happened
     #[allow(unused_must_use)]
@@ -23,69 +37,58 @@ pub fn handle_resize(
         builder.draw_all(terminal);
     }
 
-    crate::draw_basic_controls_help(terminal, &builder);
+    crate::draw_controls_help(terminal, builder, controls_help);
     if let Some(alert) = last_alert {
         alert.draw(terminal, builder);
     }
+    if let Some(selected_cell_point) = cell_placement.selected_cell_point {
+        grid::draw_highlighted_cells(terminal, builder, selected_cell_point);
+    }
 
     state
 }
 
-pub fn await_fitting_size(terminal: &mut Terminal, grid: &Grid) -> State {
-    const fn terminal_width_is_within_grid_width(grid: &Grid, terminal: &Terminal) -> bool {
-        terminal.size.width >= grid.size.width * 2 + grid.max_clues_size.width
-    }
+fn too_small_message(required_size: &Size, size: &Size) -> String {
+    let length = if size.width < required_size.width {
+        "width"
+    } else {
+        "height"
+    };
+    format!(
+        "Terminal too small: need {}x{}. Please increase window {} or decrease text size (Ctrl and -)",
+        required_size.width, required_size.height, length
+    )
+}
 
-    fn terminal_height_is_within_grid_height(grid: &Grid, terminal: &Terminal) -> bool {
-        terminal.size.height > crate::total_height(grid)
-    }
+/// Shows how big the terminal has to be until it is big enough for the grid, which is scrolled if it doesn't fit as a whole.
+pub fn await_fitting_size(terminal: &mut Terminal, grid: &Grid, settings: Settings) -> State {
+    let required_size = grid::builder::required_size(grid, settings);
+    let fits = |terminal: &Terminal| grid::builder::fits(grid, &terminal.size, settings);
 
-    let mut state = State::Continue;
-
-    match (
-        terminal_width_is_within_grid_width(&grid, terminal),
-        terminal_height_is_within_grid_height(&grid, terminal),
-    ) {
-        (true, true) => state,
-        (within_width, within_height) => {
-            terminal.set_cursor(Point::default());
-            let length = if !within_width {
-                "width"
-            } else if !within_height {
-                "height"
-            } else {
-                unreachable!()
-            };
-            let message = format!(
-                "Please increase window {} or decrease text size (Ctrl and -)",
-                length
-            );
-            terminal.write(&message);
-            terminal.flush();
-
-            let state = loop {
-                match (
-                    terminal_width_is_within_grid_width(&grid, terminal),
-                    terminal_height_is_within_grid_height(&grid, terminal),
-                ) {
-                    (true, true) => break state,
-                    _ => {
-                        state = await_resize(terminal);
-                        if let State::Exit = state {
-                            break state;
-                        }
-                    }
-                }
-            };
+    let mut message_len = 0;
+    let state = loop {
+        if fits(terminal) {
+            break State::Continue;
+        }
 
-            terminal.set_cursor(Point::default());
-            for _ in 0..message.len() {
-                terminal.write(" ");
-            }
+        // The message changes as the size does
+        terminal.set_cursor(Point::default());
+        terminal.write(&" ".repeat(message_len));
+        let message = too_small_message(&required_size, &terminal.size);
+        terminal.set_cursor(Point::default());
+        terminal.write(&message);
+        terminal.flush();
+        message_len = message.len();
 
-            state
+        if let State::Exit = await_resize(terminal) {
+            break State::Exit;
         }
-    }
+    };
+
+    terminal.set_cursor(Point::default());
+    terminal.write(&" ".repeat(message_len));
+
+    state
 }
 
 pub fn await_resize(terminal: &mut Terminal) -> State {
@@ -99,3 +102,34 @@ pub fn await_resize(terminal: &mut Terminal) -> State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_too_small_message() {
+        let required_size = Size {
+            width: 40,
+            height: 30,
+        };
+        assert_eq!(
+            too_small_message(
+                &required_size,
+                &Size {
+                    width: 20,
+                    height: 30
+                }
+            ),
+            "Terminal too small: need 40x30. Please increase window width or decrease text size (Ctrl and -)"
+        );
+        assert!(too_small_message(
+            &required_size,
+            &Size {
+                width: 40,
+                height: 10
+            }
+        )
+        .contains("window height"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Detecting slow terminals, e.g. over SSH, from how long it takes to write to them.
+//!
+//! Writing blocks once the terminal can't keep up, so while flushes take long on average,
+//! redraws that are only cosmetic are skipped so that they don't hold up the ones that matter.
+
+use std::time::Duration;
+
+/// How much each new flush duration counts towards the average.
+const SMOOTHING: f64 = 0.2;
+
+/// Above this average flush duration, the quality is reduced.
+const REDUCE_THRESHOLD: Duration = Duration::from_millis(40);
+
+/// Below this average flush duration, the full quality is restored.
+/// It's lower than `REDUCE_THRESHOLD` so that the quality doesn't keep changing with a latency in between.
+const RESTORE_THRESHOLD: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Quality {
+    #[default]
+    Full,
+    /// Hovering doesn't highlight cells and the playing time is only updated every few seconds.
+    Reduced,
+}
+
+impl Quality {
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Quality::Full => "Terminal is fast again, showing everything",
+            Quality::Reduced => "Terminal is slow, showing less while hovering",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LatencyMonitor {
+    /// The exponentially weighted moving average of the flush durations, in seconds.
+    average: f64,
+    quality: Quality,
+}
+
+impl LatencyMonitor {
+    pub const fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Records how long a flush took, returning the new quality if it changed.
+    pub fn record(&mut self, flush_duration: Duration) -> Option<Quality> {
+        self.average += SMOOTHING * (flush_duration.as_secs_f64() - self.average);
+
+        let quality = match self.quality {
+            Quality::Full if self.average > REDUCE_THRESHOLD.as_secs_f64() => Quality::Reduced,
+            Quality::Reduced if self.average < RESTORE_THRESHOLD.as_secs_f64() => Quality::Full,
+            quality => quality,
+        };
+
+        if quality == self.quality {
+            None
+        } else {
+            self.quality = quality;
+            Some(quality)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(monitor: &mut LatencyMonitor, milliseconds: u64, count: usize) -> Vec<Quality> {
+        (0..count)
+            .filter_map(|_| monitor.record(Duration::from_millis(milliseconds)))
+            .collect()
+    }
+
+    #[test]
+    fn test_latency_monitor() {
+        let mut monitor = LatencyMonitor::default();
+        assert_eq!(record(&mut monitor, 1, 50), []);
+
+        // A single slow flush is not enough
+        assert_eq!(record(&mut monitor, 150, 1), []);
+        assert_eq!(record(&mut monitor, 1, 10), []);
+
+        // But sustained latency is
+        assert_eq!(record(&mut monitor, 300, 3), [Quality::Reduced]);
+        assert_eq!(monitor.quality(), Quality::Reduced);
+
+        // A latency between the thresholds doesn't change anything either way
+        assert_eq!(record(&mut monitor, 20, 100), []);
+        assert_eq!(monitor.quality(), Quality::Reduced);
+
+        assert_eq!(record(&mut monitor, 1, 50), [Quality::Full]);
+        assert_eq!(record(&mut monitor, 20, 100), []);
+        assert_eq!(monitor.quality(), Quality::Full);
+    }
+}
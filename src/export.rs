@@ -0,0 +1,7 @@
+//! Exports grids to formats usable outside of the terminal.
+
+pub mod art;
+pub mod description;
+pub mod png;
+pub mod sheet;
+pub mod text;
@@ -0,0 +1,87 @@
+//! Block art of the picture that can be pasted as plain text, e.g. into a chat.
+//!
+//! Every character holds two cells on top of each other using half blocks,
+//! which keeps the cells about as wide as they are high.
+
+use crate::{grid::Grid, util};
+use std::io::Write;
+
+fn block(top: bool, bottom: bool) -> char {
+    match (top, bottom) {
+        (true, true) => '█',
+        (true, false) => '▀',
+        (false, true) => '▄',
+        (false, false) => ' ',
+    }
+}
+
+/// Renders the filled cells of the grid. Every line has the width of the grid, even if it is empty.
+pub fn render(grid: &Grid) -> String {
+    let width = grid.size.width as usize;
+    let mut string = String::new();
+
+    for rows in grid.cells.chunks(width * 2) {
+        let (top_row, bottom_row) = rows.split_at(width.min(rows.len()));
+        for (x, top_cell) in top_row.iter().enumerate() {
+            // The bottom row is missing in the last line if the height is uneven
+            let bottom_filled = bottom_row.get(x).is_some_and(|cell| cell.is_filled());
+            string.push(block(top_cell.is_filled(), bottom_filled));
+        }
+        string.push('\n');
+    }
+
+    string
+}
+
+/// Copies the block art to the clipboard or saves it as a new file if that is not possible.
+///
+/// Returns the message to show.
+pub fn copy(terminal: &mut terminal::Terminal, grid: &Grid) -> Result<String, &'static str> {
+    let art = render(grid);
+
+    if util::copy_to_clipboard(terminal, &art) {
+        return Ok("Picture copied to clipboard".into());
+    }
+
+    let (filename, mut file) = util::create_numbered_file("txt")?;
+    if file.write_all(art.as_bytes()).is_err() {
+        return Err("Picture saving error");
+    }
+
+    Ok(format!("Picture saved as {}", filename))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+
+    #[test]
+    fn test_render() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11 1",
+            "1  1",
+            "    ",
+            " 11 ",
+            "1111",
+        ]).unwrap();
+        grid.cells = grid
+            .solution
+            .iter()
+            .map(|filled| if *filled { Cell::Filled } else { Cell::Empty })
+            .collect();
+        // Crossed cells are empty in the picture
+        grid.cells[2] = Cell::Crossed;
+
+        assert_eq!(render(&grid), "█▀ █\n ▄▄ \n▀▀▀▀\n");
+    }
+
+    #[test]
+    fn test_render_empty_rows() {
+        let mut grid = Grid::from_lines(&["     ", "     ", "    1"]).unwrap();
+        grid.cells[14] = Cell::Filled;
+
+        assert_eq!(render(&grid), "     \n    ▀\n");
+    }
+}
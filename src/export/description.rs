@@ -0,0 +1,181 @@
+//! Descriptions of the player's progress in words, for screen readers:
+//!
+//! ```text
+//! 4 by 2 grid, 2 of 6 rows and columns solved.
+//!
+//! Rows:
+//! Row 1, clues 2 1, not solved, currently 2: 2 filled, 1 crossed out, 1 empty.
+//! Row 2, clues 1 1, not solved, currently none: 2 empty, 1 measured 3, 1 maybed.
+//!
+//! Columns:
+//! Column 1, clues 2, not solved, currently 1: 1 filled, 1 empty.
+//! ```
+//!
+//! Each row and column lists its clues, whether they are solved and, if they aren't, the clues of the cells as they are.
+//! Its cells follow as runs of the same kind of cell, from the left to the right and from the top to the bottom.
+
+use crate::{
+    grid::{Cell, Clue, Grid},
+    util,
+};
+use itertools::Itertools;
+use std::{borrow::Cow, fmt::Write as _, io::Write as _};
+use terminal::util::Point;
+
+pub fn cell_words(cell: Cell) -> Cow<'static, str> {
+    match cell {
+        Cell::Empty => "empty".into(),
+        Cell::Filled => "filled".into(),
+        Cell::Crossed => "crossed out".into(),
+        Cell::Maybed => "maybed".into(),
+        Cell::Measured(Some(index)) => format!("measured {}", index).into(),
+        Cell::Measured(None) => "measured".into(),
+    }
+}
+
+fn clues_words(clues: impl Iterator<Item = Clue>) -> String {
+    let clues = clues.map(|clue| clue.to_string()).join(" ");
+    if clues.is_empty() {
+        "none".into()
+    } else {
+        clues
+    }
+}
+
+/// Returns the sentence describing a row or column.
+fn line_sentence(
+    name: &str,
+    clues_solution: &[Clue],
+    clues: impl Iterator<Item = Clue>,
+    cells: impl Iterator<Item = Cell>,
+) -> String {
+    let clues: Vec<Clue> = clues.collect();
+    let mut sentence = format!(
+        "{}, clues {}",
+        name,
+        clues_words(clues_solution.iter().copied())
+    );
+    if clues == clues_solution {
+        sentence.push_str(", solved");
+    } else {
+        write!(
+            sentence,
+            ", not solved, currently {}",
+            clues_words(clues.into_iter())
+        )
+        .unwrap();
+    }
+
+    let runs = cells
+        .dedup_with_count()
+        .map(|(count, cell)| format!("{} {}", count, cell_words(cell)))
+        .join(", ");
+    write!(sentence, ": {}.", runs).unwrap();
+    sentence
+}
+
+pub fn describe(grid: &Grid) -> String {
+    let height = grid.size.height;
+    let width = grid.size.width;
+    let solved_rows = (0..height)
+        .filter(|y| {
+            grid.get_horizontal_clues(*y)
+                .eq(grid.horizontal_clues_solutions[*y as usize].iter().copied())
+        })
+        .count();
+    let solved_columns = (0..width)
+        .filter(|x| {
+            grid.get_vertical_clues(*x)
+                .eq(grid.vertical_clues_solutions[*x as usize].iter().copied())
+        })
+        .count();
+
+    let mut string = String::new();
+    writeln!(
+        string,
+        "{} by {} grid, {} of {} rows and columns solved.",
+        width,
+        height,
+        solved_rows + solved_columns,
+        width + height
+    )
+    .unwrap();
+
+    string.push_str("\nRows:\n");
+    for y in 0..height {
+        let cells = (0..width).map(|x| grid.get_cell(Point { x, y }));
+        let sentence = line_sentence(
+            &format!("Row {}", y + 1),
+            &grid.horizontal_clues_solutions[y as usize],
+            grid.get_horizontal_clues(y),
+            cells,
+        );
+        writeln!(string, "{}", sentence).unwrap();
+    }
+
+    string.push_str("\nColumns:\n");
+    for x in 0..width {
+        let cells = (0..height).map(|y| grid.get_cell(Point { x, y }));
+        let sentence = line_sentence(
+            &format!("Column {}", x + 1),
+            &grid.vertical_clues_solutions[x as usize],
+            grid.get_vertical_clues(x),
+            cells,
+        );
+        writeln!(string, "{}", sentence).unwrap();
+    }
+
+    string
+}
+
+/// Saves a description of the grid as a new file, returning its filename.
+pub fn save(grid: &Grid) -> Result<String, &'static str> {
+    let (filename, mut file) = util::create_numbered_file("txt")?;
+
+    if file.write_all(describe(grid).as_bytes()).is_err() {
+        return Err("Description saving error");
+    }
+
+    Ok(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::fixture;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe(&fixture::example()),
+            "4 by 2 grid, 2 of 6 rows and columns solved.\n\
+             \n\
+             Rows:\n\
+             Row 1, clues 2 1, not solved, currently 2: 2 filled, 1 crossed out, 1 empty.\n\
+             Row 2, clues 1 1, not solved, currently none: 2 empty, 1 measured 3, 1 maybed.\n\
+             \n\
+             Columns:\n\
+             Column 1, clues 2, not solved, currently 1: 1 filled, 1 empty.\n\
+             Column 2, clues 1, solved: 1 filled, 1 empty.\n\
+             Column 3, clues none, solved: 1 crossed out, 1 measured 3.\n\
+             Column 4, clues 2, not solved, currently none: 1 empty, 1 maybed.\n"
+        );
+    }
+
+    #[test]
+    fn test_describe_solved() {
+        let mut grid = Grid::from_lines(&["#."]).unwrap();
+        grid.cells[0] = Cell::Filled;
+        assert_eq!(
+            describe(&grid),
+            "2 by 1 grid, 3 of 3 rows and columns solved.\n\
+             \n\
+             Rows:\n\
+             Row 1, clues 1, solved: 1 filled, 1 empty.\n\
+             \n\
+             Columns:\n\
+             Column 1, clues 1, solved: 1 filled.\n\
+             Column 2, clues none, solved: 1 empty.\n"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! A minimal PNG encoder for exporting the solved picture.
+//!
+//! The image data is stored uncompressed (using deflate's "stored" blocks) which keeps the encoder tiny
+//! and is perfectly fine for pictures of at most 99x99 cells.
+
+use crate::{grid::Grid, util};
+use std::io::Write;
+
+/// Every cell becomes a square of this many pixels so that the picture is not tiny.
+const SCALE: u32 = 10;
+
+const SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+const BLACK: u8 = 0x00;
+const WHITE: u8 = 0xff;
+
+/// The maximum amount of bytes a single stored deflate block can hold.
+const MAX_STORED_BLOCK_SIZE: usize = u16::MAX as usize;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for byte in bytes {
+        a = (a + *byte as u32) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let crc_start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[crc_start..]);
+
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream without compressing it.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let block_count = data.len() / MAX_STORED_BLOCK_SIZE + 1;
+    let mut zlib = Vec::<u8>::with_capacity(2 + data.len() + block_count * 5 + 4);
+
+    // Deflate with a 32K window and no preset dictionary, using the fastest "compression".
+    zlib.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK_SIZE).peekable();
+    if chunks.peek().is_none() {
+        // An empty final block.
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last_block = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+
+        zlib.push(last_block as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(chunk);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+
+    zlib
+}
+
+/// Encodes the grid's filled cells as black and everything else as white, scaled up by `scale`.
+///
+/// Cells like maybed, crossed and measured cells are only aids for the player and not part of the picture.
+fn encode(grid: &Grid, scale: u32) -> Vec<u8> {
+    let width = grid.size.width as u32 * scale;
+    let height = grid.size.height as u32 * scale;
+
+    let mut pixels = Vec::<u8>::with_capacity(((width + 1) * height) as usize);
+    for row in grid.cells.chunks(grid.size.width as usize) {
+        // Every scanline starts with a filter type byte. We use none.
+        let mut scanline = vec![0];
+        for cell in row {
+            let pixel = if cell.is_filled() { BLACK } else { WHITE };
+            scanline.extend(std::iter::repeat_n(pixel, scale as usize));
+        }
+        for _ in 0..scale {
+            pixels.extend_from_slice(&scanline);
+        }
+    }
+
+    let mut header = Vec::<u8>::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.push(8); // Bit depth
+    header.push(0); // Color type: grayscale
+    header.push(0); // Compression method: deflate
+    header.push(0); // Filter method: adaptive
+    header.push(0); // Interlace method: none
+
+    let mut png = Vec::<u8>::new();
+    png.extend_from_slice(SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+/// Saves the grid's picture as a new PNG file, returning the filename.
+pub fn save(grid: &Grid) -> Result<String, &'static str> {
+    let (filename, mut file) = util::create_numbered_file("png")?;
+
+    if file.write_all(&encode(grid, SCALE)).is_err() {
+        return Err("PNG saving error");
+    }
+
+    Ok(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+    use terminal::util::Size;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn test_zlib_stored() {
+        let data = vec![7; MAX_STORED_BLOCK_SIZE + 1];
+        let zlib = zlib_stored(&data);
+
+        // Two blocks with a header of 5 bytes each
+        assert_eq!(zlib.len(), 2 + 5 + MAX_STORED_BLOCK_SIZE + 5 + 1 + 4);
+        assert_eq!(zlib[2], 0);
+        assert_eq!(zlib[2 + 5 + MAX_STORED_BLOCK_SIZE], 1);
+    }
+
+    #[test]
+    fn test_encode() {
+        let size = Size {
+            width: 3,
+            height: 2,
+        };
+        let mut grid = Grid::new(size.clone(), vec![Cell::Empty; size.product() as usize]);
+        grid.cells[0] = Cell::Filled;
+        grid.cells[1] = Cell::Maybed;
+
+        let png = encode(&grid, 2);
+
+        assert!(png.starts_with(SIGNATURE));
+        // The width and height are the first fields of the header
+        assert_eq!(&png[16..20], &6_u32.to_be_bytes());
+        assert_eq!(&png[20..24], &4_u32.to_be_bytes());
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        // The first scanline's pixels follow the zlib and the block header
+        let idat_data = 8 + 25 + 8;
+        let first_scanline = &png[idat_data + 2 + 5..][..1 + 6];
+        assert_eq!(
+            first_scanline,
+            &[0, BLACK, BLACK, WHITE, WHITE, WHITE, WHITE]
+        );
+    }
+}
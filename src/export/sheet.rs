@@ -0,0 +1,79 @@
+//! Printable puzzle sheets for solving a grid on paper:
+//!
+//! ```text
+//!      2 1   2
+//!  2 1 · · · ·
+//!  1 1 · · · ·
+//! ```
+//!
+//! The clues are laid out just like on the screen: every clue and cell takes up two characters,
+//! the top clues are aligned to the bottom and left-aligned within their column
+//! and the left clues are aligned to the right.
+//! Unlike on the screen, a space separates the left clues from the cells as there are no colors to tell them apart.
+
+use crate::{grid::Grid, util};
+use std::io::Write;
+
+const EMPTY_CELL: &str = "· ";
+
+pub fn render(grid: &Grid) -> String {
+    let left_clues_width = grid.max_clues_size.width as usize;
+    let mut string = String::new();
+
+    for level in (0..grid.max_clues_size.height as usize).rev() {
+        let mut line = " ".repeat(left_clues_width + 1);
+        for clues in &grid.vertical_clues_solutions {
+            match clues.len().checked_sub(level + 1) {
+                Some(index) => line += &format!("{:<2}", clues[index]),
+                None => line.push_str("  "),
+            }
+        }
+        string.push_str(line.trim_end());
+        string.push('\n');
+    }
+
+    for clues in &grid.horizontal_clues_solutions {
+        let left_clues: String = clues.iter().map(|clue| format!("{:>2}", clue)).collect();
+        let line = format!(
+            "{:>width$} {}",
+            left_clues,
+            EMPTY_CELL.repeat(grid.size.width as usize),
+            width = left_clues_width
+        );
+        string.push_str(line.trim_end());
+        string.push('\n');
+    }
+
+    string
+}
+
+/// Saves a sheet of the grid as a new file, returning its filename.
+pub fn save(grid: &Grid) -> Result<String, &'static str> {
+    let (filename, mut file) = util::create_numbered_file("sheet.txt")?;
+
+    if file.write_all(render(grid).as_bytes()).is_err() {
+        return Err("Sheet saving error");
+    }
+
+    Ok(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::fixture;
+    use std::fs;
+
+    #[test]
+    fn test_render() {
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "111111111111",
+            "1 1 1 1 1 1 ",
+            "11  11 1111 ",
+        ]).unwrap();
+
+        let golden = fs::read_to_string(fixture::path("wide.sheet.txt")).unwrap();
+        assert_eq!(render(&grid), golden);
+    }
+}
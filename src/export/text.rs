@@ -0,0 +1,232 @@
+//! Text snapshots of the player's progress, e.g. for asking others for help:
+//!
+//! ```text
+//!          2  1     2
+//! -------+------------
+//!   2  1 |  #  #  x  .
+//!   1  1 |  .  .  3  ?
+//! code AQQCrQ==
+//! ```
+//!
+//! `#` is a filled cell, `x` a crossed out cell, `?` a maybed cell and `.` an empty cell.
+//! Measured cells are shown as their index or as `R` if they have none.
+//!
+//! The clues are only there to be read by humans. Importing a snapshot uses the puzzle code on the last line.
+
+use crate::{
+    grid::{Cell, Grid},
+    undo_redo_buffer::Operation,
+    util,
+};
+use std::{fmt::Write as _, io::Write as _};
+use terminal::util::Point;
+
+/// Every clue and cell takes up this many characters, including the space before it.
+const COLUMN_WIDTH: usize = 3;
+
+pub fn cell_str(cell: Cell) -> String {
+    match cell {
+        Cell::Empty => ".".into(),
+        Cell::Filled => "#".into(),
+        Cell::Crossed => "x".into(),
+        Cell::Maybed => "?".into(),
+        Cell::Measured(Some(index)) => index.to_string(),
+        Cell::Measured(None) => "R".into(),
+    }
+}
+
+fn str_cell(str: &str) -> Option<Cell> {
+    Some(match str {
+        // Blank cells are accepted too in case the empty cells at the end of a row were trimmed away
+        "." | "" => Cell::Empty,
+        "#" => Cell::Filled,
+        "x" => Cell::Crossed,
+        "?" => Cell::Maybed,
+        "R" => Cell::Measured(None),
+        index => {
+            Cell::Measured(Some(index.parse::<usize>().ok().filter(|index| {
+                (1..=crate::grid::MAX_MEASURED_CELLS).contains(index)
+            })?))
+        }
+    })
+}
+
+pub fn snapshot(grid: &Grid) -> String {
+    let left_clues_width = grid.max_clues_size.width as usize / 2 * COLUMN_WIDTH;
+    let mut string = String::new();
+
+    // The top clues are aligned to the bottom, just like on the screen
+    for level in (0..grid.max_clues_size.height as usize).rev() {
+        let mut line = " ".repeat(left_clues_width + 2);
+        for clues in &grid.vertical_clues_solutions {
+            match clues.len().checked_sub(level + 1) {
+                Some(index) => write!(line, " {:>2}", clues[index]).unwrap(),
+                None => line.push_str("   "),
+            }
+        }
+        string.push_str(line.trim_end());
+        string.push('\n');
+    }
+
+    writeln!(
+        string,
+        "{}+{}",
+        "-".repeat(left_clues_width + 1),
+        "-".repeat(grid.size.width as usize * COLUMN_WIDTH)
+    )
+    .unwrap();
+
+    for (clues, cells) in grid
+        .horizontal_clues_solutions
+        .iter()
+        .zip(grid.cells.chunks(grid.size.width as usize))
+    {
+        let mut left_clues = String::new();
+        for clue in clues {
+            write!(left_clues, " {:>2}", clue).unwrap();
+        }
+        write!(string, "{:>width$} |", left_clues, width = left_clues_width).unwrap();
+
+        for cell in cells {
+            write!(string, " {:>2}", cell_str(*cell)).unwrap();
+        }
+        string.push('\n');
+    }
+
+    writeln!(string, "code {}", grid.encode()).unwrap();
+
+    string
+}
+
+/// Saves a snapshot of the grid as a new file, returning its filename.
+pub fn save(grid: &Grid) -> Result<String, &'static str> {
+    let (filename, mut file) = util::create_numbered_file("txt")?;
+
+    if file.write_all(snapshot(grid).as_bytes()).is_err() {
+        return Err("Snapshot saving error");
+    }
+
+    Ok(filename)
+}
+
+/// Restores the progress of a snapshot on the puzzle it was taken of.
+pub fn import(str: &str) -> Result<Grid, &'static str> {
+    let lines: Vec<&str> = str.lines().collect();
+
+    let code = lines
+        .iter()
+        .rev()
+        .find_map(|line| line.strip_prefix("code "))
+        .ok_or("Snapshot has no puzzle code")?;
+    let mut grid = Grid::decode(code.trim())?;
+
+    let (separator_index, separator_position) = lines
+        .iter()
+        .enumerate()
+        .find_map(|(index, line)| {
+            let position = line.find('+')?;
+            line.chars()
+                .all(|char| char == '-' || char == '+')
+                .then_some((index, position))
+        })
+        .ok_or("Snapshot has no line above the cells")?;
+
+    let rows = &lines[separator_index + 1..];
+    if rows.len() <= grid.size.height as usize {
+        return Err("Snapshot has too few rows");
+    }
+
+    let mut cells = Vec::<(Point, Cell)>::new();
+    for (y, row) in rows[..grid.size.height as usize].iter().enumerate() {
+        let row = row
+            .get(separator_position + 1..)
+            .ok_or("Row is too short")?;
+        let mut row = row.to_string();
+        let width = grid.size.width as usize * COLUMN_WIDTH;
+        if row.len() > width {
+            return Err("Row is too long");
+        }
+        row.extend(std::iter::repeat_n(' ', width - row.len()));
+
+        for x in 0..grid.size.width as usize {
+            let cell = row
+                .get(x * COLUMN_WIDTH..(x + 1) * COLUMN_WIDTH)
+                .and_then(|column| str_cell(column.trim()))
+                .ok_or("Row has an invalid cell")?;
+
+            if cell != Cell::Empty {
+                let point = Point {
+                    x: x as u16,
+                    y: y as u16,
+                };
+                cells.push((point, cell));
+            }
+        }
+    }
+
+    // Importing is a single step that can be undone
+    grid.apply(Operation::SetCells(cells));
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::fixture;
+
+    #[test]
+    fn test_snapshot() {
+        let grid = fixture::example();
+
+        assert_eq!(
+            snapshot(&grid),
+            format!(
+                "          2  1     2\n\
+                 -------+------------\n  \
+                 2  1 |  #  #  x  .\n  \
+                 1  1 |  .  .  3  ?\n\
+                 code {}\n",
+                grid.encode()
+            )
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let grid = fixture::example();
+
+        let mut imported_grid = import(&snapshot(&grid)).unwrap();
+        assert_eq!(imported_grid.cells, grid.cells);
+        assert_eq!(
+            imported_grid.horizontal_clues_solutions,
+            grid.horizontal_clues_solutions
+        );
+
+        assert!(imported_grid.undo_last_cell());
+        assert!(imported_grid.cells.iter().all(|cell| *cell == Cell::Empty));
+
+        // The empty cells at the end of a row may have been lost while sharing the snapshot
+        let empty_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        let trimmed_snapshot = snapshot(&empty_grid).replace("  .  .  .  .", "");
+        assert_eq!(import(&trimmed_snapshot).unwrap().cells, empty_grid.cells);
+    }
+
+    #[test]
+    fn test_invalid_import() {
+        let snapshot = snapshot(&fixture::example());
+
+        assert!(import(&snapshot.replace("code", "puzzle")).is_err());
+        assert!(import(&snapshot.replace("-+-", "---")).is_err());
+        assert!(import(&snapshot.replace("  3  ?", "  3  !")).is_err());
+        assert!(import(&snapshot.replace("  3  ?", " 100  ?")).is_err());
+        assert!(import(&snapshot.replace("  3  ?", "  3  ?  .")).is_err());
+
+        let missing_row: String = snapshot
+            .lines()
+            .filter(|line| !line.starts_with("  1  1"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert!(import(&missing_row).is_err());
+    }
+}
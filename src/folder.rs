@@ -0,0 +1,182 @@
+//! A folder of grid files whose puzzles can be picked from within the game.
+//!
+//! Every `.yaya` and `.g` file in the folder is a puzzle. Files that can't be loaded are still listed, as invalid.
+//!
+//! Which puzzles the player solved is recorded in the `solved` file in the data directory,
+//! one fingerprint per line, so that it is remembered no matter where the folder is:
+//!
+//! ```text
+//! 5b630af62f6e7569
+//! ```
+
+pub mod picker;
+
+use crate::{
+    editor::LoadError,
+    grid::{formats, Grid},
+    paths,
+};
+use std::{
+    collections::HashSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+pub struct Entry {
+    pub name: String,
+    /// `None` if the file couldn't be read.
+    content: Option<String>,
+}
+
+impl Entry {
+    /// Loads the puzzle. Only this reveals whether an entry is invalid.
+    pub fn grid(&self) -> Result<Grid, LoadError> {
+        let content = self.content.as_ref().ok_or(LoadError {
+            message: "file reading error",
+            line_number: None,
+        })?;
+        formats::load(&self.name, content)
+    }
+}
+
+pub struct Folder {
+    /// The entries sorted by their filename.
+    pub entries: Vec<Entry>,
+    record_path: Option<PathBuf>,
+    /// The fingerprints of the solved puzzles.
+    solved: HashSet<u64>,
+}
+
+fn parse_record(content: &str) -> HashSet<u64> {
+    // Corrupted lines are ignored
+    content
+        .lines()
+        .filter_map(|line| u64::from_str_radix(line.trim(), 16).ok())
+        .collect()
+}
+
+impl Folder {
+    pub fn open(path: &Path) -> Result<Self, &'static str> {
+        Self::open_with_record(
+            path,
+            paths::dir(paths::Kind::Data).map(|dir| dir.join("solved")),
+        )
+    }
+
+    fn open_with_record(path: &Path, record_path: Option<PathBuf>) -> Result<Self, &'static str> {
+        let mut entries = Vec::<Entry>::new();
+        for dir_entry in fs::read_dir(path).map_err(|_| "Folder opening error")? {
+            let path = dir_entry.map_err(|_| "Folder reading error")?.path();
+            if !path.is_file() || !formats::is_grid_file(&path) {
+                continue;
+            }
+
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            entries.push(Entry {
+                name,
+                content: fs::read_to_string(&path).ok(),
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // The player might not have solved any puzzle yet
+        let solved = record_path
+            .as_ref()
+            .and_then(|record_path| fs::read_to_string(record_path).ok())
+            .map(|content| parse_record(&content))
+            .unwrap_or_default();
+
+        Ok(Self {
+            entries,
+            record_path,
+            solved,
+        })
+    }
+
+    pub fn is_solved(&self, grid: &Grid) -> bool {
+        self.solved.contains(&grid.fingerprint())
+    }
+
+    /// Records that the grid's puzzle was solved.
+    pub fn set_solved(&mut self, grid: &Grid) -> Result<(), &'static str> {
+        if !self.solved.insert(grid.fingerprint()) {
+            return Ok(());
+        }
+
+        let record_path = self
+            .record_path
+            .as_ref()
+            .ok_or("Data directory not found")?;
+        if let Some(dir) = record_path.parent() {
+            paths::create_dir(dir).map_err(|_| "Data directory creation failed")?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(record_path)
+            .map_err(|_| "Completion record opening error")?;
+        writeln!(file, "{:016x}", grid.fingerprint()).map_err(|_| "Completion record writing error")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("yayagram-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_parse_record() {
+        assert_eq!(
+            parse_record("00000000000000ff\ngarbage\n\n5b630af62f6e7569\n"),
+            HashSet::from([0xff, 0x5b630af62f6e7569])
+        );
+    }
+
+    #[test]
+    fn test_open() {
+        let path = temporary_path("folder");
+        let record_path = temporary_path("folder-solved");
+        let _ = fs::remove_dir_all(&path);
+        let _ = fs::remove_file(&record_path);
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("b.yaya"), "+--+\n|11|\n|1 |\n+--+\n").unwrap();
+        fs::write(path.join("a.g"), ": rows\n1\n: columns\n1\n").unwrap();
+        fs::write(path.join("broken.yaya"), "not a grid").unwrap();
+        fs::write(path.join("notes.txt"), "not a grid either").unwrap();
+        fs::create_dir(path.join("directory.yaya")).unwrap();
+
+        let mut folder = Folder::open_with_record(&path, Some(record_path.clone())).unwrap();
+        let names: Vec<&str> = folder
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["a.g", "b.yaya", "broken.yaya"]);
+
+        // An invalid file doesn't affect the others
+        let grid = folder.entries[0].grid().unwrap();
+        assert!(folder.entries[1].grid().is_ok());
+        assert!(folder.entries[2].grid().is_err());
+
+        assert!(!folder.is_solved(&grid));
+        folder.set_solved(&grid).unwrap();
+        folder.set_solved(&grid).unwrap();
+        assert!(folder.is_solved(&grid));
+
+        let folder = Folder::open_with_record(&path, Some(record_path.clone())).unwrap();
+        assert!(folder.is_solved(&grid));
+        assert_eq!(fs::read_to_string(&record_path).unwrap().lines().count(), 1);
+
+        fs::remove_dir_all(&path).unwrap();
+        fs::remove_file(&record_path).unwrap();
+    }
+}
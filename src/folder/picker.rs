@@ -0,0 +1,80 @@
+//! The menu for picking a puzzle of a folder.
+
+use super::Folder;
+use crate::{event::input::key, grid::Grid, menu};
+use std::borrow::Cow;
+use terminal::{event::Key, Terminal};
+
+const CONTROLS_HELP: &str = "Enter: Play, Esc: Exit";
+
+fn format_entry(folder: &Folder, index: usize) -> String {
+    let entry = &folder.entries[index];
+
+    match entry.grid() {
+        Ok(grid) => {
+            let status = if folder.is_solved(&grid) { "✓" } else { " " };
+
+            format!(
+                "{} {:<24} {:>5}",
+                status,
+                entry.name,
+                format!("{}x{}", grid.size.width, grid.size.height)
+            )
+        }
+        Err(_) => format!("  {:<24} invalid", entry.name),
+    }
+}
+
+/// Lets the player pick a puzzle, returning its grid, or `None` if the player wants to exit.
+///
+/// `selected` is the index of the entry that is selected initially and then the picked one.
+pub fn pick(
+    terminal: &mut Terminal,
+    title: &str,
+    folder: &Folder,
+    selected: &mut usize,
+) -> Option<Grid> {
+    if folder.entries.is_empty() {
+        menu::draw(
+            terminal,
+            title,
+            std::iter::empty(),
+            0,
+            "This folder has no .yaya or .g files",
+            CONTROLS_HELP,
+        );
+        key::r#await(terminal);
+        return None;
+    }
+
+    let mut bottom_text = Cow::from("");
+    loop {
+        let entries = (0..folder.entries.len()).map(|index| Cow::from(format_entry(folder, index)));
+        menu::draw(
+            terminal,
+            title,
+            entries,
+            *selected,
+            &bottom_text,
+            CONTROLS_HELP,
+        );
+
+        match key::r#await(terminal) {
+            key if menu::move_selection(&key, selected, folder.entries.len()) => {
+                bottom_text = "".into();
+            }
+            Key::Enter => match folder.entries[*selected].grid() {
+                Ok(grid) => break Some(grid),
+                Err(err) => {
+                    bottom_text = if let Some(line_number) = err.line_number {
+                        format!("Invalid on line {}: {}", line_number, err.message).into()
+                    } else {
+                        format!("Invalid: {}", err.message).into()
+                    };
+                }
+            },
+            Key::Esc => break None,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Formatting of the numbers shown to the player, so that they look the same everywhere.
+//!
+//! Only what is shown is formatted. Files like pack progress keep raw numbers.
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// Formats the duration by its magnitude like `1h 23m 45s`, `23m 45s` or `45s`.
+///
+/// If that is wider than `max_width`, it is written more compactly like `1:23:45` or `23:45`,
+/// and if that is still too wide, it is rounded down to its largest unit like `1h` or `23m`.
+/// Returns `None` if no format fits.
+pub fn duration(total_seconds: u64, max_width: usize) -> Option<String> {
+    let hours = total_seconds / HOUR;
+    let minutes = total_seconds / MINUTE % 60;
+    let seconds = total_seconds % 60;
+
+    let candidates = if hours != 0 {
+        [
+            format!("{}h {}m {}s", hours, minutes, seconds),
+            format!("{}:{:02}:{:02}", hours, minutes, seconds),
+            format!("{}h", hours),
+        ]
+    } else if minutes != 0 {
+        [
+            format!("{}m {}s", minutes, seconds),
+            format!("{}:{:02}", minutes, seconds),
+            format!("{}m", minutes),
+        ]
+    } else {
+        [
+            format!("{}s", seconds),
+            format!("{}s", seconds),
+            format!("{}s", seconds),
+        ]
+    };
+
+    candidates
+        .iter()
+        .find(|candidate| candidate.len() <= max_width)
+        .cloned()
+}
+
+/// Returns how many percent `part` is of `whole`, rounded half up.
+///
+/// Only the whole is 100% and only nothing is 0%, so that e.g. 199 of 200 is 99% and 1 of 200 is 1%.
+pub fn percentage(part: usize, whole: usize) -> u8 {
+    if whole == 0 || part >= whole {
+        return 100;
+    }
+    if part == 0 {
+        return 0;
+    }
+
+    let percentage = (part * 200 + whole) / (whole * 2);
+    percentage.clamp(1, 99) as u8
+}
+
+/// Formats how long ago something was by its largest unit like `just now`, `1 minute ago`, `5 hours ago` or `6 days ago`.
+pub fn ago(total_seconds: u64) -> String {
+    let (count, unit) = if total_seconds < MINUTE {
+        return "just now".into();
+    } else if total_seconds < HOUR {
+        (total_seconds / MINUTE, "minute")
+    } else if total_seconds < DAY {
+        (total_seconds / HOUR, "hour")
+    } else {
+        (total_seconds / DAY, "day")
+    };
+
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration() {
+        let duration = |seconds| duration(seconds, usize::MAX).unwrap();
+
+        assert_eq!(duration(0), "0s");
+        assert_eq!(duration(45), "45s");
+        assert_eq!(duration(60), "1m 0s");
+        assert_eq!(duration(23 * MINUTE + 45), "23m 45s");
+        assert_eq!(duration(HOUR), "1h 0m 0s");
+        assert_eq!(duration(HOUR + 23 * MINUTE + 45), "1h 23m 45s");
+        assert_eq!(duration(99 * HOUR + 5), "99h 0m 5s");
+    }
+
+    #[test]
+    fn test_duration_width() {
+        let seconds = HOUR + 23 * MINUTE + 45;
+        assert_eq!(duration(seconds, 10).unwrap(), "1h 23m 45s");
+        assert_eq!(duration(seconds, 9).unwrap(), "1:23:45");
+        assert_eq!(duration(seconds, 6).unwrap(), "1h");
+        assert_eq!(duration(seconds, 1), None);
+        assert_eq!(duration(23 * MINUTE + 45, 6).unwrap(), "23:45");
+        assert_eq!(duration(45, 2), None);
+
+        for seconds in (0..100 * HOUR).step_by(997) {
+            for max_width in 0..12 {
+                if let Some(string) = duration(seconds, max_width) {
+                    assert!(string.len() <= max_width, "{}", string);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ago() {
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(MINUTE), "1 minute ago");
+        assert_eq!(ago(59 * MINUTE + 59), "59 minutes ago");
+        assert_eq!(ago(HOUR), "1 hour ago");
+        assert_eq!(ago(23 * HOUR), "23 hours ago");
+        assert_eq!(ago(DAY + HOUR), "1 day ago");
+        assert_eq!(ago(6 * DAY), "6 days ago");
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(0, 6), 0);
+        assert_eq!(percentage(2, 6), 33);
+        assert_eq!(percentage(4, 6), 67);
+        assert_eq!(percentage(1, 8), 13);
+        assert_eq!(percentage(6, 6), 100);
+        assert_eq!(percentage(1, 200), 1);
+        assert_eq!(percentage(199, 200), 99);
+        assert_eq!(percentage(0, 0), 100);
+    }
+}
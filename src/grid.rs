@@ -1,24 +1,36 @@
 pub mod builder;
 mod cell;
-#[cfg(debug_assertions)]
+mod code;
+pub mod colors;
+pub mod contradictions;
+#[cfg(any(test, debug_assertions))]
 pub mod debug;
-mod random;
+#[cfg(any(test, debug_assertions))]
+pub mod fixture;
+pub mod formats;
+pub mod heat_map;
+mod matching;
+pub mod random;
+mod satisfied;
+pub mod tag;
 pub mod tools;
 
 use crate::undo_redo_buffer::UndoRedoBuffer;
 pub use cell::*;
 use itertools::Itertools;
+use std::time::Duration;
 use terminal::util::{Point, Size};
 
 /// A single clue specifying how many cells there are in a row at some point.
-type Clue = u16;
+pub type Clue = u16;
 /// A complete set of clues.
 type Clues = Vec<Clue>;
 
-#[derive(Debug)]
-pub struct SolvedClues {
-    pub horizontal_clues: Vec<Cell>,
-    pub vertical_clues: Vec<Cell>,
+#[derive(Debug, PartialEq)]
+pub struct LinesError {
+    pub message: &'static str,
+    pub line_number: Option<usize>,
+    pub column_number: Option<usize>,
 }
 
 pub struct Grid {
@@ -29,8 +41,19 @@ pub struct Grid {
     pub horizontal_clues_solutions: Vec<Clues>,
     /// The vertical clue solutions generated out of the initial input.
     pub vertical_clues_solutions: Vec<Clues>,
+    /// The filled cells of the initial input that the clue solutions were generated out of.
+    ///
+    /// Note that the clues might have more than this one solution.
+    /// This is empty if only the clues are known, e.g. for grids loaded from clue files.
+    pub solution: Vec<bool>,
     pub max_clues_size: Size,
     pub undo_redo_buffer: UndoRedoBuffer,
+    /// How long the grid has been played, including earlier sessions.
+    pub playing_time: Duration,
+    /// The tag of every cell that has one, from 1 to `TAG_COUNT`.
+    ///
+    /// Tags are marks on top of the cells for telling apart hypotheses and never affect the clues.
+    pub tags: Vec<Option<u8>>,
 }
 
 fn get_index(width: u16, point: Point) -> usize {
@@ -39,7 +62,7 @@ fn get_index(width: u16, point: Point) -> usize {
 
 fn get_horizontal_clues(cells: &[Cell], width: u16, y: u16) -> impl Iterator<Item = Clue> + '_ {
     (0..width)
-        .map(move |x| cells[get_index(width, Point { x, y })] == Cell::Filled)
+        .map(move |x| cells[get_index(width, Point { x, y })].is_filled())
         .dedup_with_count()
         .filter(|(_, filled)| *filled)
         .map(|(count, _)| count as Clue)
@@ -52,12 +75,33 @@ fn get_vertical_clues(
     x: u16,
 ) -> impl Iterator<Item = Clue> + '_ {
     (0..height)
-        .map(move |y| cells[get_index(width, Point { x, y })] == Cell::Filled)
+        .map(move |y| cells[get_index(width, Point { x, y })].is_filled())
         .dedup_with_count()
         .filter(|(_, filled)| *filled)
         .map(|(count, _)| count as Clue)
 }
 
+fn get_max_clues_size(
+    horizontal_clues_solutions: &[Clues],
+    vertical_clues_solutions: &[Clues],
+) -> Size {
+    let max_clues_width = horizontal_clues_solutions
+        .iter()
+        .map(|horizontal_clues_solution| horizontal_clues_solution.len() * 2)
+        .max()
+        .unwrap() as u16;
+    let max_clues_height = vertical_clues_solutions
+        .iter()
+        .map(|vertical_clues_solution| vertical_clues_solution.len())
+        .max()
+        .unwrap() as u16;
+
+    Size {
+        width: max_clues_width,
+        height: max_clues_height,
+    }
+}
+
 impl Grid {
     /// Creates a new grid. `cells` must have a length of `size.width * size.height`.
     pub fn new(size: Size, mut cells: Vec<Cell>) -> Self {
@@ -69,45 +113,180 @@ impl Grid {
                 get_horizontal_clues(&cells, size.width, y).collect();
             horizontal_clues_solutions.push(horizontal_clues_solution);
         }
-        let max_clues_width = horizontal_clues_solutions
-            .iter()
-            .map(|horizontal_clues_solution| horizontal_clues_solution.len() * 2)
-            .max()
-            .unwrap() as u16;
-
         let mut vertical_clues_solutions = Vec::<Clues>::new();
         for x in 0..size.width {
             let vertical_clues_solution: Clues =
                 get_vertical_clues(&cells, size.width, size.height, x).collect();
             vertical_clues_solutions.push(vertical_clues_solution);
         }
-        let max_clues_height = vertical_clues_solutions
-            .iter()
-            .map(|vertical_clues_solution| vertical_clues_solution.len())
-            .max()
-            .unwrap() as u16;
+        let solution = cells.iter().map(|cell| cell.is_filled()).collect();
 
         for cell in &mut cells {
-            if *cell == Cell::Filled {
+            if cell.is_filled() {
                 *cell = Cell::Empty;
             }
         }
 
-        let max_clues_size = Size {
-            width: max_clues_width,
-            height: max_clues_height,
-        };
+        let max_clues_size =
+            get_max_clues_size(&horizontal_clues_solutions, &vertical_clues_solutions);
 
         let undo_redo_buffer = UndoRedoBuffer::default();
+        let tags = vec![None; cells.len()];
 
         Self {
             size,
             cells,
             horizontal_clues_solutions,
             vertical_clues_solutions,
+            solution,
             max_clues_size,
             undo_redo_buffer,
+            playing_time: Duration::ZERO,
+            tags,
+        }
+    }
+
+    /// Creates a new grid with the clues of the picture, which must have a length of `size.width * size.height`.
+    pub fn from_picture(size: Size, picture: &[PictureCell]) -> Self {
+        Self::new(size, picture.iter().copied().map(Cell::from).collect())
+    }
+
+    /// Returns the lines of the picture with `#` for filled cells and `.` for empty ones, as accepted by [`Grid::from_lines`].
+    pub fn picture_lines(size: &Size, picture: &[bool]) -> Vec<String> {
+        picture
+            .chunks(size.width as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|filled| if *filled { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Creates a new grid of which only the clues are known. Lines without clues are empty.
+    pub fn from_clues(
+        horizontal_clues_solutions: Vec<Clues>,
+        vertical_clues_solutions: Vec<Clues>,
+    ) -> Result<Self, &'static str> {
+        let max_grid_size = crate::args::MAX_GRID_SIZE as usize;
+        let (width, height) = (
+            vertical_clues_solutions.len(),
+            horizontal_clues_solutions.len(),
+        );
+        if !(1..=max_grid_size).contains(&width) || !(1..=max_grid_size).contains(&height) {
+            return Err("Grid has an invalid size");
+        }
+        let size = Size {
+            width: width as u16,
+            height: height as u16,
+        };
+
+        fn fit(clues_solutions: &[Clues], length: u16) -> bool {
+            clues_solutions.iter().all(|clues| {
+                let filled_cell_count: usize = clues.iter().map(|clue| *clue as usize).sum();
+                let gap_count = clues.len().saturating_sub(1);
+
+                !clues.contains(&0) && filled_cell_count + gap_count <= length as usize
+            })
+        }
+        if !fit(&horizontal_clues_solutions, size.width)
+            || !fit(&vertical_clues_solutions, size.height)
+        {
+            return Err("Clues don't fit into their lines");
+        }
+
+        let filled_cell_count = |clues_solutions: &[Clues]| -> usize {
+            clues_solutions
+                .iter()
+                .flatten()
+                .map(|clue| *clue as usize)
+                .sum()
+        };
+        if filled_cell_count(&horizontal_clues_solutions)
+            != filled_cell_count(&vertical_clues_solutions)
+        {
+            return Err("Rows and columns have different amounts of filled cells");
+        }
+
+        let max_clues_size =
+            get_max_clues_size(&horizontal_clues_solutions, &vertical_clues_solutions);
+
+        Ok(Self {
+            cells: vec![Cell::Empty; size.product() as usize],
+            tags: vec![None; size.product() as usize],
+            size,
+            horizontal_clues_solutions,
+            vertical_clues_solutions,
+            solution: Vec::new(),
+            max_clues_size,
+            undo_redo_buffer: UndoRedoBuffer::default(),
+            playing_time: Duration::ZERO,
+        })
+    }
+
+    /// Creates a new grid from a picture made of lines of text.
+    /// A `1` or `#` represents a filled cell, a ` ` or `.` represents an empty cell.
+    /// Lines shorter than the longest one are padded with empty cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lines = [
+    ///    "111 1",
+    ///    "11111",
+    ///    "1 1",
+    /// ];
+    /// let grid = Grid::from_lines(&lines).unwrap();
+    /// ```
+    pub fn from_lines(lines: &[&str]) -> Result<Self, LinesError> {
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = lines.len();
+
+        if width == 0 {
+            return Err(LinesError {
+                message: "the picture is empty",
+                line_number: None,
+                column_number: None,
+            });
+        }
+        if width > crate::args::MAX_GRID_SIZE as usize
+            || height > crate::args::MAX_GRID_SIZE as usize
+        {
+            return Err(LinesError {
+                message: "the picture is too big",
+                line_number: None,
+                column_number: None,
+            });
         }
+
+        let size = Size {
+            width: width as u16,
+            height: height as u16,
+        };
+        let mut picture = Vec::<PictureCell>::with_capacity(size.product() as usize);
+        for (line_index, line) in lines.iter().enumerate() {
+            let cell_count = picture.len();
+            for (char_index, char) in line.chars().enumerate() {
+                picture.push(match char {
+                    '1' | '#' => PictureCell::Filled,
+                    ' ' | '.' => PictureCell::Empty,
+                    _ => {
+                        return Err(LinesError {
+                            message: "expected '1', '#', ' ' or '.'",
+                            line_number: Some(line_index + 1),
+                            column_number: Some(char_index + 1),
+                        })
+                    }
+                });
+            }
+            picture.resize(cell_count + width, PictureCell::Empty);
+        }
+
+        Ok(Grid::from_picture(size, &picture))
     }
 
     fn cell_panic(point: Point, index: usize) -> ! {
@@ -125,6 +304,21 @@ impl Grid {
             .unwrap_or_else(|| Self::cell_panic(point, index))
     }
 
+    pub fn get_tag(&self, point: Point) -> Option<u8> {
+        let index = get_index(self.size.width, point);
+        *self
+            .tags
+            .get(index)
+            .unwrap_or_else(|| Self::cell_panic(point, index))
+    }
+
+    pub fn get_mut_tag(&mut self, point: Point) -> &mut Option<u8> {
+        let index = get_index(self.size.width, point);
+        self.tags
+            .get_mut(index)
+            .unwrap_or_else(|| Self::cell_panic(point, index))
+    }
+
     pub fn get_mut_cell(&mut self, point: Point) -> &mut Cell {
         let index = get_index(self.size.width, point);
         self.cells
@@ -140,8 +334,106 @@ impl Grid {
         get_vertical_clues(&self.cells, self.size.width, self.size.height, x)
     }
 
+    /// Returns for every row whether its clues are solved.
+    pub fn solved_rows(&self) -> Vec<bool> {
+        (0..self.size.height)
+            .map(|y| {
+                self.get_horizontal_clues(y)
+                    .eq(self.horizontal_clues_solutions[y as usize].iter().copied())
+            })
+            .collect()
+    }
+
+    /// Returns for every column whether its clues are solved.
+    pub fn solved_columns(&self) -> Vec<bool> {
+        (0..self.size.width)
+            .map(|x| {
+                self.get_vertical_clues(x)
+                    .eq(self.vertical_clues_solutions[x as usize].iter().copied())
+            })
+            .collect()
+    }
+
+    /// Returns the amount of rows and columns whose clues are solved.
+    pub fn solved_line_count(&self) -> usize {
+        self.solved_rows()
+            .into_iter()
+            .chain(self.solved_columns())
+            .filter(|solved| *solved)
+            .count()
+    }
+
+    /// Returns whether the filled cells of the solved grid differ from the known solution.
+    ///
+    /// This can only be the case for clues with more than one solution.
+    pub fn is_alternate_solution(&self) -> bool {
+        !self.solution.is_empty()
+            && self
+                .cells
+                .iter()
+                .map(|cell| cell.is_filled())
+                .ne(self.solution.iter().copied())
+    }
+
+    /// Makes the solution and the clue solutions out of the filled cells, like the editor does for every edited cell.
+    pub fn remake_clues_solutions(&mut self) {
+        self.solution = self.cells.iter().map(|cell| cell.is_filled()).collect();
+        self.horizontal_clues_solutions = (0..self.size.height)
+            .map(|y| self.get_horizontal_clues(y).collect())
+            .collect();
+        self.vertical_clues_solutions = (0..self.size.width)
+            .map(|x| self.get_vertical_clues(x).collect())
+            .collect();
+        self.max_clues_size = get_max_clues_size(
+            &self.horizontal_clues_solutions,
+            &self.vertical_clues_solutions,
+        );
+    }
+
+    /// Clears the cells and their tags.
     pub fn clear(&mut self) {
         self.cells.fill_with(Default::default);
+        self.tags.fill(None);
+    }
+
+    /// Returns whether the clues can only be solved in exactly one way.
+    ///
+    /// This is also false for clues that are too hard to find that out for with a limited amount of guessing.
+    pub fn has_unique_solution(&self) -> bool {
+        crate::solver::solutions(self) == crate::solver::Solutions::Unique
+    }
+
+    /// Returns a hash identifying this grid's puzzle, i.e. its size and clue solutions.
+    ///
+    /// Unlike `std`'s hashers, this is guaranteed to stay the same across versions and platforms.
+    pub fn fingerprint(&self) -> u64 {
+        // This is FNV-1a.
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut hash = OFFSET_BASIS;
+        let mut hash_value = |value: u16| {
+            for byte in value.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(PRIME);
+            }
+        };
+
+        hash_value(self.size.width);
+        hash_value(self.size.height);
+        for clues in self
+            .horizontal_clues_solutions
+            .iter()
+            .chain(&self.vertical_clues_solutions)
+        {
+            for clue in clues {
+                hash_value(*clue);
+            }
+            // Separates the clues of different lines
+            hash_value(0);
+        }
+
+        hash
     }
 }
 
@@ -149,108 +441,190 @@ impl Grid {
 mod tests {
     use super::*;
 
-    impl Grid {
-        /// Creates a new grid from the given line of strings.
-        /// A `1` represents a filled cell, a ` ` represents an empty cell.
-        ///
-        /// # Examples
-        ///
-        /// ```
-        /// let lines = [
-        ///    "111 1",
-        ///    "11111",
-        ///    "1 111"
-        /// ]
-        /// let grid = Grid::from_lines(lines);
-        /// ```
-        fn from_lines(lines: &[&str]) -> Grid {
-            let width = lines.iter().map(|line| line.len()).max().unwrap();
-            let height = lines.len();
-            let size = Size {
-                width: width as u16,
-                height: height as u16,
-            };
-            let mut cells = Vec::<Cell>::with_capacity(size.product() as usize);
-            for line in lines {
-                for char in line.chars() {
-                    cells.push(match char {
-                        '1' => Cell::Filled,
-                        ' ' => Cell::Empty,
-                        _ => panic!("the strings must only contain '1' or ' '"),
-                    });
-                }
-            }
-            Grid::new(size, cells)
-        }
+    fn assert_fixture_clues(name: &str) {
+        let fixture = fixture::load(&fixture::path(name)).unwrap();
+
+        assert_eq!(
+            fixture.grid.horizontal_clues_solutions,
+            fixture.horizontal_clues_solutions
+        );
+        assert_eq!(
+            fixture.grid.vertical_clues_solutions,
+            fixture.vertical_clues_solutions
+        );
     }
 
     #[test]
     fn test_squared_grid() {
-        #[rustfmt::skip]
-        let grid = Grid::from_lines(&[
-            "1 1 111 1 ",
-            " 1 11 111 ",
-            "1111 11  1",
-            "1 11 1  11",
-            "1  111  11",
-        ]);
+        assert_fixture_clues("squared.fixture");
+    }
+
+    #[test]
+    fn test_non_squared_grid() {
+        assert_fixture_clues("non_squared.fixture");
+    }
 
+    #[test]
+    fn test_from_picture() {
+        assert_eq!(Cell::from(PictureCell::Empty), Cell::Empty);
+        assert_eq!(Cell::from(PictureCell::Filled), Cell::Filled);
+
+        let size = Size {
+            width: 2,
+            height: 2,
+        };
+        let grid = Grid::from_picture(
+            size,
+            &[
+                PictureCell::Filled,
+                PictureCell::Empty,
+                PictureCell::Filled,
+                PictureCell::Filled,
+            ],
+        );
+        // Only the solution is made from the picture
+        assert_eq!(grid.solution, [true, false, true, true]);
+        assert!(grid.cells.iter().all(|cell| *cell == Cell::Empty));
+        let lines_grid = Grid::from_lines(&["1 ", "11"]).unwrap();
         assert_eq!(
             grid.horizontal_clues_solutions,
-            [
-                vec![1, 1, 3, 1],
-                vec![1, 2, 3],
-                vec![4, 2, 1],
-                vec![1, 2, 1, 2],
-                vec![1, 3, 2],
-            ]
+            lines_grid.horizontal_clues_solutions
         );
+    }
 
-        assert_eq!(
-            grid.vertical_clues_solutions,
-            [
-                vec![1, 3],
-                vec![2],
-                vec![1, 2],
-                vec![4],
-                vec![2, 1],
-                vec![1, 3],
-                vec![3],
-                vec![1],
-                vec![2, 2],
-                vec![3]
-            ]
-        );
+    #[test]
+    fn test_picture_lines() {
+        let lines = ["#.#", ".##"];
+        let grid = Grid::from_lines(&lines).unwrap();
+        assert_eq!(Grid::picture_lines(&grid.size, &grid.solution), lines);
     }
 
     #[test]
-    fn test_non_squared_grid() {
+    fn test_from_lines_aliases() {
         #[rustfmt::skip]
-        let grid = Grid::from_lines(&[
-            " 111",
-            " 1 1",
-            "11 1",
-            "1 1 ",
-            "1  1",
-            "  1 ",
-        ]);
+        let grids = [
+            (
+                Grid::from_lines(&[
+                    "1 1 111 1 ",
+                    " 1 11 111 ",
+                    "1111 11  1",
+                    "1 11 1  11",
+                    "1  111  11",
+                ]).unwrap(),
+                Grid::from_lines(&[
+                    "#.#.###.#",
+                    " # ##.###",
+                    "####.##..#",
+                    "1.##.#..##",
+                    "#  ###  ##",
+                ]).unwrap(),
+            ),
+            (
+                Grid::from_lines(&[
+                    " 111",
+                    " 1 1",
+                    "11 1",
+                    "1 1 ",
+                    "1  1",
+                    "  1 ",
+                ]).unwrap(),
+                Grid::from_lines(&[
+                    ".###",
+                    ".#.#",
+                    "##.#",
+                    "#.#",
+                    "#..#",
+                    "..#",
+                ]).unwrap(),
+            ),
+        ];
+
+        for (grid, aliased_grid) in grids.iter() {
+            assert_eq!(aliased_grid.size, grid.size);
+            assert_eq!(
+                aliased_grid.horizontal_clues_solutions,
+                grid.horizontal_clues_solutions
+            );
+            assert_eq!(
+                aliased_grid.vertical_clues_solutions,
+                grid.vertical_clues_solutions
+            );
+        }
+    }
 
+    #[test]
+    fn test_from_lines_errors() {
         assert_eq!(
-            grid.horizontal_clues_solutions,
-            [
-                vec![3],
-                vec![1, 1],
-                vec![2, 1],
-                vec![1, 1],
-                vec![1, 1],
-                vec![1],
-            ]
+            Grid::from_lines(&["11", "1x1"]).err(),
+            Some(LinesError {
+                message: "expected '1', '#', ' ' or '.'",
+                line_number: Some(2),
+                column_number: Some(2),
+            })
         );
+        assert!(Grid::from_lines(&[]).is_err());
+        assert!(Grid::from_lines(&["", ""]).is_err());
+        assert!(Grid::from_lines(&[&"1".repeat(100)]).is_err());
+        assert!(Grid::from_lines(&["1"; 100]).is_err());
+    }
 
+    #[test]
+    fn test_from_clues() {
+        let grid = Grid::from_lines(&[" 111", " 1 1", "11 1", "1 1 "]).unwrap();
+        let clues_grid = Grid::from_clues(
+            grid.horizontal_clues_solutions.clone(),
+            grid.vertical_clues_solutions.clone(),
+        )
+        .unwrap();
+
+        assert_eq!(clues_grid.size, grid.size);
+        assert_eq!(clues_grid.max_clues_size, grid.max_clues_size);
+        assert_eq!(clues_grid.fingerprint(), grid.fingerprint());
+        assert!(clues_grid.solution.is_empty());
+
+        let error = |horizontal_clues_solutions, vertical_clues_solutions| {
+            Grid::from_clues(horizontal_clues_solutions, vertical_clues_solutions).err()
+        };
         assert_eq!(
-            grid.vertical_clues_solutions,
-            [vec![3], vec![3], vec![1, 1, 1], vec![3, 1]]
+            error(vec![], vec![vec![1]]),
+            Some("Grid has an invalid size")
         );
+        assert_eq!(
+            error(vec![vec![1, 1]], vec![vec![1], vec![1]]),
+            Some("Clues don't fit into their lines")
+        );
+        assert_eq!(
+            error(vec![vec![0]], vec![vec![0]]),
+            Some("Clues don't fit into their lines")
+        );
+        assert_eq!(
+            error(vec![vec![1], vec![]], vec![vec![1], vec![1]]),
+            Some("Rows and columns have different amounts of filled cells")
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "11 1",
+            "1  1",
+        ]).unwrap();
+        #[rustfmt::skip]
+        let same_clues_grid = Grid::from_lines(&[
+            "11 1",
+            "1  1",
+        ]).unwrap();
+        #[rustfmt::skip]
+        let moved_clue_grid = Grid::from_lines(&[
+            "11 1",
+            " 1 1",
+        ]).unwrap();
+
+        assert_eq!(grid.fingerprint(), same_clues_grid.fingerprint());
+        assert_ne!(grid.fingerprint(), moved_clue_grid.fingerprint());
+        // This must never change because autosaves are identified by it
+        assert_eq!(grid.fingerprint(), 0x5b63_0af6_2f6e_7569);
     }
 
     #[test]
@@ -260,10 +634,29 @@ mod tests {
             "1111",
             "1111",
             "1111",
-        ]);
+        ]).unwrap();
 
         grid.clear();
 
         assert!(grid.cells.iter().all(|cell| *cell == Cell::Empty));
     }
+
+    #[test]
+    fn test_is_alternate_solution() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "1 ",
+            " 1",
+        ]).unwrap();
+        grid.cells = vec![Cell::Filled, Cell::Crossed, Cell::Empty, Cell::Filled];
+        assert!(!grid.is_alternate_solution());
+
+        grid.cells = vec![Cell::Empty, Cell::Filled, Cell::Filled, Cell::Empty];
+        assert_eq!(grid.solved_line_count(), 4);
+        assert!(grid.is_alternate_solution());
+
+        // Without a solution there is nothing to differ from
+        grid.solution.clear();
+        assert!(!grid.is_alternate_solution());
+    }
 }
@@ -1,37 +1,642 @@
-use super::Grid;
+use super::{
+    cell::{self, Segment},
+    colors::{Palette, Theme},
+    contradictions::Contradictions,
+    heat_map::{self, HeatMap},
+    tag, Cell, Clue, Grid, PictureCell, RenderMode, DEFAULT_SEPARATION_INTERVAL,
+};
+use crate::{format, undo_redo_buffer};
 use itertools::Itertools;
+use std::time::Duration;
 use terminal::{
-    util::{Color, Point},
+    util::{Color, Point, Size},
     Terminal,
 };
 
-/// Gets a point to the first cell of the grid which is together with its clues centered on the screen.
-pub const fn centered_point(terminal: &Terminal, grid: &Grid) -> Point {
-    let grid_width_half = grid.size.width; // No division because blocks are 2 characters
-    let grid_height_half = grid.size.height / 2;
+/// How every grid is drawn and played, which is set on the command line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// Whether two rows of cells are drawn on each line of the terminal, as halves of the line.
+    ///
+    /// Points on the grid that the cursor selects still have one line per row, with the rows below the first one of a line
+    /// on the lines below it, so that moving between rows works the same. Only drawing and the mouse go from one to the other.
+    pub half_blocks: bool,
+    /// Every how many cells the color of empty cells alternates, or 0 if it doesn't.
+    pub separation_interval: u16,
+    /// Whether the numbers of the rows are drawn right of the grid and those of the columns below it.
+    ///
+    /// In the half-block mode, each line is numbered with its upper row.
+    pub coordinates: bool,
+    /// Whether a border is drawn around the cells.
+    pub border: bool,
+    /// Whether the grid is drawn with colors or with plain characters.
+    pub render_mode: RenderMode,
+    /// Which sides of the grid the clues are drawn on.
+    pub clue_layout: ClueLayout,
+    /// Whether colors are drawn as 24-bit colors rather than as the closest of the 256 colors of the terminal.
+    pub true_color: bool,
+    /// The palette of the light theme, which the palettes of the other themes are made out of.
+    pub palette: Palette,
+    /// The theme that is drawn in, which is switched while playing.
+    pub theme: Theme,
+    /// How many operations the undo redo buffer of the grid keeps.
+    pub undo_limit: usize,
+    /// Within how long cell placements are merged into one operation, if they are.
+    pub merge_window: Option<Duration>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            half_blocks: false,
+            separation_interval: DEFAULT_SEPARATION_INTERVAL,
+            coordinates: false,
+            border: false,
+            render_mode: RenderMode::default(),
+            clue_layout: ClueLayout::default(),
+            true_color: false,
+            palette: Palette::default(),
+            theme: Theme::default(),
+            undo_limit: undo_redo_buffer::DEFAULT_LIMIT,
+            merge_window: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Returns how many characters the row numbers right of the grid and how many lines the column numbers below it take up.
+    pub fn coordinates_size(self) -> Size {
+        if self.coordinates {
+            // The row numbers are set apart from the grid by a space
+            Size {
+                width: 3,
+                height: 1,
+            }
+        } else {
+            Size {
+                width: 0,
+                height: 0,
+            }
+        }
+    }
+
+    /// Returns how many characters and lines the border takes up on each side of the cells, which is none if it is off.
+    pub fn border_width(self) -> u16 {
+        u16::from(self.border)
+    }
+
+    /// Returns how many characters and lines the border and the row and column numbers take up in addition to the cells and the clues.
+    pub fn frame_size(self) -> Size {
+        let coordinates_size = self.coordinates_size();
+        Size {
+            width: coordinates_size.width + self.border_width() * 2,
+            height: coordinates_size.height + self.border_width() * 2,
+        }
+    }
+
+    /// Returns how many lines of the terminal the rows of the grid take up.
+    pub fn screen_height(self, grid: &Grid) -> u16 {
+        self.lines(grid.size.height)
+    }
+
+    /// Returns how many characters the clues left of the grid take up.
+    ///
+    /// In the half-block mode, the clues of the upper and the lower row of a line are drawn next to each other.
+    fn left_clues_width(self, grid: &Grid) -> u16 {
+        self.clues_width_on_screen(grid.max_clues_size.width)
+    }
+
+    /// Returns how many lines the rows take up.
+    fn lines(self, rows: u16) -> u16 {
+        if self.half_blocks {
+            rows.div_ceil(2)
+        } else {
+            rows
+        }
+    }
+
+    /// Returns how many characters the clues left of the rows take up if `clues_width` characters fit left of each row.
+    fn clues_width_on_screen(self, clues_width: u16) -> u16 {
+        if self.half_blocks {
+            clues_width * 2 + HALF_BLOCK_CLUES_GAP
+        } else {
+            clues_width
+        }
+    }
+}
+
+/// Returns the number of the row or column with the index as it is drawn, which takes up two characters like a cell.
+///
+/// Numbers above 99 are drawn modulo 100, with a tick in place of the leading space that tells them apart from the others.
+fn coordinate_label(index: u16) -> String {
+    let number = index + 1;
+    if number < 100 {
+        format!("{:>2}", number)
+    } else if number % 100 < 10 {
+        format!("'{}", number % 10)
+    } else {
+        format!("{:02}", number % 100)
+    }
+}
+
+/// Which sides of the grid the clues of its columns and the clues of its rows are drawn on.
+///
+/// The picture is drawn in the corner between them, and the row and column numbers stay next to the grid,
+/// between it and the clues if they are on the same side.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ClueLayout {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl ClueLayout {
+    pub const ALL: [Self; 4] = [
+        ClueLayout::TopLeft,
+        ClueLayout::TopRight,
+        ClueLayout::BottomLeft,
+        ClueLayout::BottomRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClueLayout::TopLeft => "top-left",
+            ClueLayout::TopRight => "top-right",
+            ClueLayout::BottomLeft => "bottom-left",
+            ClueLayout::BottomRight => "bottom-right",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|layout| layout.name() == name)
+    }
+
+    /// Returns whether the clues of the columns are drawn below the grid instead of above it.
+    pub fn columns_below(self) -> bool {
+        matches!(self, ClueLayout::BottomLeft | ClueLayout::BottomRight)
+    }
+
+    /// Returns whether the clues of the rows are drawn right of the grid instead of left of it.
+    pub fn rows_right(self) -> bool {
+        matches!(self, ClueLayout::TopRight | ClueLayout::BottomRight)
+    }
+}
+
+/// The space between the clues of the upper rows and those of the lower rows in the half-block mode.
+const HALF_BLOCK_CLUES_GAP: u16 = 2;
+
+/// The fewest columns and rows of cells that are drawn when the grid has to be scrolled, unless it has fewer.
+const MIN_VIEWPORT_CELLS: u16 = 5;
+
+/// The part of the grid and its clues that is drawn, which is all of it if it fits the terminal.
+///
+/// The cursor still selects points as if the whole grid was drawn, with the columns and rows that are scrolled out of view
+/// before and after the drawn ones. Only drawing and the mouse go from one to the other, like for [`Settings::half_blocks`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Viewport {
+    /// The first column and row of cells that are drawn.
+    pub offset: Point,
+    /// How many columns and rows of cells are drawn.
+    pub size: Size,
+    /// How many characters the clues left of each row and how many lines the clues above each column take up at most,
+    /// like `Grid::max_clues_size`. Clues that don't fit are summarized by a marker.
+    pub clues_size: Size,
+    /// Whether there is space for the picture, which there is only if the whole grid fits.
+    pub picture: bool,
+}
+
+/// The marker drawn in place of the outermost clues of a row or column that don't fit.
+const CLIPPED_CLUES_MARKER: &str = "..";
+
+/// Returns how much of the grid fits the terminal of the size, or `None` if not even the smallest part of it does.
+///
+/// If the whole grid doesn't fit, the cells and the clues share the space, with the cells getting at least half of it.
+fn layout(grid: &Grid, terminal_size: &Size, settings: Settings) -> Option<Viewport> {
+    let frame_size = settings.frame_size();
+    if terminal_size.width
+        >= grid.size.width * 2 + settings.left_clues_width(grid) + frame_size.width
+        && terminal_size.height > crate::total_height(grid, settings)
+    {
+        return Some(Viewport {
+            offset: Point::default(),
+            size: grid.size.clone(),
+            clues_size: grid.max_clues_size.clone(),
+            picture: true,
+        });
+    }
+
+    let min_size = Size {
+        width: grid.size.width.min(MIN_VIEWPORT_CELLS),
+        height: grid.size.height.min(MIN_VIEWPORT_CELLS),
+    };
+
+    // Below the grid is the progress bar, which is left out by the last line
+    let available_lines = terminal_size
+        .height
+        .checked_sub(1 + crate::TEXT_HEIGHT + frame_size.height)?;
+    let grid_lines = settings
+        .lines(grid.size.height)
+        .min(settings.lines(min_size.height).max(available_lines / 2));
+    let clues_height = grid
+        .max_clues_size
+        .height
+        .min(available_lines.saturating_sub(grid_lines));
+    let grid_lines = settings
+        .lines(grid.size.height)
+        .min(available_lines - clues_height);
+    let height = if settings.half_blocks {
+        (grid_lines * 2).min(grid.size.height)
+    } else {
+        grid_lines
+    };
+
+    let terminal_width = terminal_size.width.saturating_sub(frame_size.width);
+    let grid_width = (grid.size.width * 2).min((min_size.width * 2).max(terminal_width / 2));
+    let available_clues_width = terminal_width.saturating_sub(grid_width);
+    let clues_width = if settings.half_blocks {
+        available_clues_width.saturating_sub(HALF_BLOCK_CLUES_GAP) / 2
+    } else {
+        available_clues_width
+    };
+    // Each clue takes up two characters
+    let clues_width = (clues_width - clues_width % 2).min(grid.max_clues_size.width);
+    let width = grid
+        .size
+        .width
+        .min(terminal_width.saturating_sub(settings.clues_width_on_screen(clues_width)) / 2);
+
+    let fits = height >= min_size.height
+        && width >= min_size.width
+        && clues_height >= grid.max_clues_size.height.min(1)
+        && clues_width >= grid.max_clues_size.width.min(2);
+    fits.then(|| Viewport {
+        offset: Point::default(),
+        size: Size { width, height },
+        clues_size: Size {
+            width: clues_width,
+            height: clues_height,
+        },
+        picture: false,
+    })
+}
 
-    let max_clues_width_half = grid.max_clues_size.width / 2;
-    let max_clues_height_half = grid.max_clues_size.height / 2;
+/// Returns the smallest size of the terminal that the grid can be played in, scrolling it if needed.
+pub fn required_size(grid: &Grid, settings: Settings) -> Size {
+    let frame_size = settings.frame_size();
+    let width = grid.size.width.min(MIN_VIEWPORT_CELLS) * 2
+        + settings.clues_width_on_screen(grid.max_clues_size.width.min(2))
+        + frame_size.width;
+    let height = 1
+        + crate::TEXT_HEIGHT
+        + frame_size.height
+        + settings.lines(grid.size.height.min(MIN_VIEWPORT_CELLS))
+        + grid.max_clues_size.height.min(1);
+    Size { width, height }
+}
 
-    Point {
-        x: terminal.size.width / 2 - grid_width_half + max_clues_width_half,
-        y: terminal.size.height / 2 - grid_height_half + max_clues_height_half,
+/// Returns whether the grid can be played in the terminal, scrolling it if needed.
+pub fn fits(grid: &Grid, terminal_size: &Size, settings: Settings) -> bool {
+    layout(grid, terminal_size, settings).is_some()
+}
+
+/// Returns how many of the clues are drawn if `count` of them fit and whether the marker is drawn after them,
+/// which takes the place of the last one that would fit if not all of them do. Without room for any, nothing is drawn.
+fn clipped(clues: usize, count: u16) -> (usize, bool) {
+    let count = count as usize;
+    if clues <= count {
+        (clues, false)
+    } else {
+        (count.saturating_sub(1), count != 0)
     }
 }
 
-const HIGHLIGHTED_CLUE_BACKGROUND_COLOR: Color = Color::Byte(238);
+/// How a clue is drawn: grayed out once it is satisfied and standing out if its line contradicts it or is hinted at,
+/// or if the block of filled cells of the selected cell belongs to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ClueStyle {
+    Unsatisfied,
+    Satisfied,
+    Contradicted,
+    Hinted,
+    Matched,
+}
+
+impl ClueStyle {
+    fn new(satisfied: bool, contradicted: bool) -> Self {
+        if contradicted {
+            ClueStyle::Contradicted
+        } else if satisfied {
+            ClueStyle::Satisfied
+        } else {
+            ClueStyle::Unsatisfied
+        }
+    }
+
+    fn color(self, palette: &Palette, true_color: bool) -> Color {
+        match self {
+            ClueStyle::Unsatisfied => palette.clue.get(true_color),
+            ClueStyle::Satisfied => palette.satisfied_clue.get(true_color),
+            ClueStyle::Contradicted => palette.contradicted_clue.get(true_color),
+            ClueStyle::Hinted => palette.hinted_clue.get(true_color),
+            ClueStyle::Matched => palette.matched_clue.get(true_color),
+        }
+    }
+
+    /// Returns the text attribute that the clue is drawn with in the ASCII mode, where it can't have a color.
+    fn ascii_attribute(self) -> &'static str {
+        match self {
+            ClueStyle::Unsatisfied => NORMAL_INTENSITY,
+            ClueStyle::Satisfied => FAINT,
+            ClueStyle::Contradicted | ClueStyle::Hinted | ClueStyle::Matched => BOLD,
+        }
+    }
+}
+
+const STRIKETHROUGH: &str = "\u{1b}[9m";
+const NOT_STRIKETHROUGH: &str = "\u{1b}[29m";
+const UNDERLINE: &str = "\u{1b}[4m";
+const NOT_UNDERLINE: &str = "\u{1b}[24m";
+const BOLD: &str = "\u{1b}[1m";
+const FAINT: &str = "\u{1b}[2m";
+const NORMAL_INTENSITY: &str = "\u{1b}[22m";
+
+/// How many characters the completion percentage right of the progress bar takes up, including the space before it.
+pub const COMPLETION_WIDTH: u16 = " 100%".len() as u16;
+
+/// Returns the completion percentage drawn right of the progress bar, padded to `COMPLETION_WIDTH`.
+///
+/// It is the percentage of solved rows and columns so that it is only 100% once the grid is solved.
+fn completion_text(solved_rows: usize, rows: usize) -> String {
+    format!(
+        "{:>width$}",
+        format!("{}%", format::percentage(solved_rows, rows)),
+        width = COMPLETION_WIDTH as usize
+    )
+}
+
+/// What the grid looked like when it was last drawn as a whole, so that only what changed since has to be drawn again.
+struct Drawn {
+    point: Point,
+    viewport: Viewport,
+    cells: Vec<Cell>,
+    tags: Vec<Option<u8>>,
+    horizontal_clues_solutions: Vec<Vec<Clue>>,
+    vertical_clues_solutions: Vec<Vec<Clue>>,
+    /// Whether every row and then every column is solved.
+    solved_lines: Vec<bool>,
+}
 
 /// Builds and draws the grid to the screen.
 pub struct Builder {
     pub grid: Grid,
     pub point: Point,
+    /// The check for contradicted rows and columns if it is on.
+    pub contradictions: Option<Contradictions>,
+    /// The overlay of how likely the cells are to be filled if it is on.
+    pub heat_map: Option<HeatMap>,
+    /// `None` until the grid is drawn as a whole and once something was drawn over it.
+    drawn: Option<Drawn>,
+    /// The cell whose row and column are highlighted, along with their clues, if any.
+    crosshair: Option<Point>,
+    /// The size of the terminal that the grid was last laid out for.
+    terminal_size: Size,
+    /// The first column and row of cells that are drawn if the grid doesn't fit the terminal.
+    /// Each is moved back if fewer columns or rows follow it than are drawn.
+    scroll: Point,
+    pub settings: Settings,
+    /// The palette of the settings in the current theme, which is made once when the theme changes rather than for every cell.
+    palette: Palette,
+    /// Whether only the filled cells are drawn, with all others drawn as empty cells, to see the picture on its own.
+    /// The cells themselves stay the same.
+    pub clean_picture: bool,
 }
 
 impl Builder {
-    pub fn new(terminal: &Terminal, grid: Grid) -> Self {
-        let point = centered_point(terminal, &grid);
+    pub fn new(terminal: &Terminal, grid: Grid, settings: Settings) -> Self {
+        let mut builder = Self {
+            grid,
+            point: Point::default(),
+            contradictions: None,
+            heat_map: None,
+            drawn: None,
+            crosshair: None,
+            terminal_size: terminal.size.clone(),
+            scroll: Point::default(),
+            settings,
+            palette: settings.palette.themed(settings.theme),
+            clean_picture: false,
+        };
+        builder.fit(terminal);
+        builder
+    }
+
+    /// Switches to the theme, making the palette to draw with in it.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.settings.theme = theme;
+        self.palette = self.settings.palette.themed(theme);
+    }
+
+    /// Lays out the grid for the size of the terminal, centering it along with its clues and its row and column numbers.
+    pub fn fit(&mut self, terminal: &Terminal) {
+        self.terminal_size = terminal.size.clone();
+        let mut screen_size = self.screen_size();
+        let mut clues_size = self.leading_clues_size();
+        clues_size.width += self.settings.border_width();
+        clues_size.height += self.settings.border_width();
+        screen_size.width += self.width_right_of_grid();
+        screen_size.height += self.height_below_grid();
+
+        // The grid is kept right of and below its clues and its border even if the terminal is too small, e.g. while it's being resized
+        self.point = Point {
+            x: (terminal.size.width / 2 + clues_size.width / 2)
+                .saturating_sub(screen_size.width / 2)
+                .max(clues_size.width),
+            y: (terminal.size.height / 2 + clues_size.height / 2)
+                .saturating_sub(screen_size.height / 2)
+                .max(clues_size.height),
+        };
+    }
+
+    /// Returns the part of the grid that is drawn.
+    ///
+    /// If the grid doesn't fit at all, e.g. while the terminal is being resized, it would be drawn as a whole without the picture.
+    pub fn viewport(&self) -> Viewport {
+        let mut viewport =
+            layout(&self.grid, &self.terminal_size, self.settings).unwrap_or_else(|| Viewport {
+                offset: Point::default(),
+                size: self.grid.size.clone(),
+                clues_size: self.grid.max_clues_size.clone(),
+                picture: false,
+            });
+        viewport.offset = Point {
+            x: self
+                .scroll
+                .x
+                .min(self.grid.size.width - viewport.size.width),
+            y: self
+                .scroll
+                .y
+                .min(self.grid.size.height - viewport.size.height),
+        };
+        // The picture is drawn in colors
+        viewport.picture &= self.settings.render_mode == RenderMode::Colors;
+        // Each line has an upper and a lower row
+        if self.settings.half_blocks && viewport.offset.y % 2 == 1 {
+            viewport.offset.y += 1;
+        }
+        viewport
+    }
 
-        Self { grid, point }
+    /// Returns how many characters and lines the drawn cells take up.
+    pub fn screen_size(&self) -> Size {
+        let size = self.viewport().size;
+        Size {
+            width: size.width * 2,
+            height: self.settings.lines(size.height),
+        }
+    }
+
+    /// Returns the line of the progress bar, which is below the grid and everything else drawn below it.
+    pub fn progress_bar_y(&self) -> u16 {
+        self.point.y + self.screen_size().height + self.height_below_grid()
+    }
+
+    /// Returns how many characters the clues of the rows and how many lines the clues of the columns take up.
+    pub fn screen_clues_size(&self) -> Size {
+        let clues_size = self.viewport().clues_size;
+        Size {
+            width: self.settings.clues_width_on_screen(clues_size.width),
+            height: clues_size.height,
+        }
+    }
+
+    /// Returns how many characters the clues left of the grid and how many lines the clues above it take up,
+    /// which are none for the clues drawn on the other side.
+    pub fn leading_clues_size(&self) -> Size {
+        let clues_size = self.screen_clues_size();
+        Size {
+            width: if self.settings.clue_layout.rows_right() {
+                0
+            } else {
+                clues_size.width
+            },
+            height: if self.settings.clue_layout.columns_below() {
+                0
+            } else {
+                clues_size.height
+            },
+        }
+    }
+
+    /// Returns the first column right of the cells and their border.
+    fn grid_right_x(&self) -> u16 {
+        self.point.x + self.screen_size().width + self.settings.border_width()
+    }
+
+    /// Returns the first line below the cells and their border.
+    fn grid_bottom_y(&self) -> u16 {
+        self.point.y + self.screen_size().height + self.settings.border_width()
+    }
+
+    /// Returns how many characters the border, the row numbers, the clues and the picture take up right of the grid,
+    /// if they are there.
+    pub fn width_right_of_grid(&self) -> u16 {
+        if self.settings.clue_layout.rows_right() {
+            let picture_width = if self.viewport().picture {
+                self.grid.size.width
+            } else {
+                0
+            };
+            self.row_clues_x() - self.point.x - self.screen_size().width
+                + self.screen_clues_size().width.max(picture_width)
+        } else {
+            self.settings.border_width() + self.settings.coordinates_size().width
+        }
+    }
+
+    /// Returns the first column of the clues of the rows, and of the picture if it is right of the grid.
+    ///
+    /// Right of the grid, the clues are set apart from the row numbers by a space.
+    pub fn row_clues_x(&self) -> u16 {
+        if self.settings.clue_layout.rows_right() {
+            self.grid_right_x()
+                + self.settings.coordinates_size().width
+                + u16::from(self.settings.coordinates)
+        } else {
+            self.point
+                .x
+                .saturating_sub(self.screen_clues_size().width + self.settings.border_width())
+        }
+    }
+
+    /// Returns the first line of the clues of the columns.
+    pub fn column_clues_y(&self) -> u16 {
+        if self.settings.clue_layout.columns_below() {
+            self.grid_bottom_y() + self.settings.coordinates_size().height
+        } else {
+            self.point
+                .y
+                .saturating_sub(self.screen_clues_size().height + self.settings.border_width())
+        }
+    }
+
+    /// Returns how many lines the border, the column numbers, the clues and the picture take up below the grid,
+    /// if they are there.
+    fn height_below_grid(&self) -> u16 {
+        let mut height = self.settings.border_width() + self.settings.coordinates_size().height;
+        if self.settings.clue_layout.columns_below() {
+            let picture_height = if self.viewport().picture {
+                crate::get_picture_height(&self.grid)
+            } else {
+                0
+            };
+            height += self.screen_clues_size().height.max(picture_height);
+        }
+        height
+    }
+
+    /// Returns whether the cell at the point is drawn, i.e. not scrolled out of view.
+    pub fn is_visible(&self, cell_point: Point) -> bool {
+        let viewport = self.viewport();
+        (viewport.offset.x..viewport.offset.x + viewport.size.width).contains(&cell_point.x)
+            && (viewport.offset.y..viewport.offset.y + viewport.size.height).contains(&cell_point.y)
+            && cell_point.y < self.grid.size.height
+    }
+
+    /// Scrolls the grid so that the cell at the point is drawn, drawing everything again if it had to be scrolled.
+    pub fn scroll_to(&mut self, terminal: &mut Terminal, cell_point: Point) {
+        if self.is_visible(cell_point) {
+            return;
+        }
+        let viewport = self.viewport();
+        let scroll = |offset: u16, size: u16, index: u16| {
+            if index < offset {
+                index
+            } else if index >= offset + size {
+                index + 1 - size
+            } else {
+                offset
+            }
+        };
+        self.clear_clues(terminal);
+        self.scroll = Point {
+            x: scroll(viewport.offset.x, viewport.size.width, cell_point.x),
+            y: scroll(viewport.offset.y, viewport.size.height, cell_point.y),
+        };
+        // The row has to be the upper one of its line to be the first one that is drawn
+        if self.settings.half_blocks && cell_point.y < viewport.offset.y {
+            self.scroll.y -= self.scroll.y % 2;
+        }
+        // Scrolling doesn't change the grid
+        #[allow(unused_must_use)]
+        {
+            self.draw_all(terminal);
+        }
     }
 
     /// Checks whether the point is within the grid on the screen.
@@ -53,233 +658,1065 @@ impl Builder {
         }
     }
 
+    /// Returns where on the screen the cell at the point is drawn, which it must be.
+    pub fn screen_point(&self, cell_point: Point) -> Point {
+        let offset = self.viewport().offset;
+        let row = cell_point.y - offset.y;
+        let line = if self.settings.half_blocks {
+            row / 2
+        } else {
+            row
+        };
+        Point {
+            x: self.point.x + (cell_point.x - offset.x) * 2,
+            y: self.point.y + line,
+        }
+    }
+
+    /// Returns the point that the cursor selects at the point on the screen, which is that of a cell scrolled into view
+    /// and in the half-block mode the upper row of a line of the grid. See [`Viewport`] and [`Settings::half_blocks`].
+    pub fn cursor_point(&self, screen_point: Point) -> Point {
+        let viewport = self.viewport();
+        let screen_size = self.screen_size();
+
+        let mut point = screen_point;
+        if let Some(column) = screen_point.x.checked_sub(self.point.x) {
+            point.x = self.point.x
+                + if column < screen_size.width {
+                    viewport.offset.x * 2 + column
+                } else {
+                    // Right of the grid, e.g. the completion percentage
+                    column - screen_size.width + self.grid.size.width * 2
+                };
+        }
+        if let Some(line) = screen_point.y.checked_sub(self.point.y) {
+            let row_height = if self.settings.half_blocks { 2 } else { 1 };
+            point.y = self.point.y
+                + if line < screen_size.height {
+                    viewport.offset.y + line * row_height
+                } else {
+                    // Below the grid, e.g. the progress bar
+                    line - screen_size.height + self.grid.size.height
+                };
+        }
+        point
+    }
+
+    /// Draws the cell at the point, highlighted if it is one of the highlighted ones and shaded by the heat map otherwise.
+    ///
+    /// In the half-block mode, the other cell of its line is drawn along with it.
+    pub fn draw_cell(
+        &self,
+        terminal: &mut Terminal,
+        point: Point,
+        highlighted: impl Fn(Point) -> bool,
+    ) {
+        if !self.is_visible(point) {
+            return;
+        }
+        if self.settings.half_blocks {
+            self.draw_half_blocks(terminal, point, highlighted);
+        } else {
+            terminal.set_cursor(self.screen_point(point));
+            cell::write_segments(
+                terminal,
+                self.cell_segments(point, highlighted(point)),
+                self.settings.render_mode,
+            );
+            terminal.reset_colors();
+        }
+    }
+
+    /// Returns what the cell at the point is drawn as, shaded by the heat map unless it is highlighted or tagged.
+    /// In the ASCII mode, there is no shading.
+    fn cell_segments(&self, point: Point, highlight: bool) -> Vec<Segment> {
+        let tag = self.grid.get_tag(point);
+        if self.settings.render_mode == RenderMode::Ascii {
+            return self.displayed_cell(point).ascii_segments(
+                point,
+                self.settings.separation_interval,
+                highlight,
+                tag,
+            );
+        }
+        let filled_share = self
+            .heat_map
+            .as_ref()
+            .filter(|_| !highlight && tag.is_none())
+            .and_then(|heat_map| heat_map.filled_share(&self.grid, point));
+        if let Some(filled_share) = filled_share {
+            vec![(None, heat_map::color(filled_share), "  ".into())]
+        } else {
+            self.displayed_cell(point).segments(
+                point,
+                self.settings.separation_interval,
+                highlight,
+                tag,
+                &self.palette,
+                self.settings.true_color,
+            )
+        }
+    }
+
+    /// Returns the cell at the point as it is drawn, which is empty if it isn't filled and only the picture is drawn.
+    fn displayed_cell(&self, point: Point) -> Cell {
+        let cell = self.grid.get_cell(point);
+        if self.clean_picture && !cell.is_filled() {
+            Cell::Empty
+        } else {
+            cell
+        }
+    }
+
+    /// Returns the color that the cell at the point is drawn in when it only has half of a line.
+    ///
+    /// There's no space for the index of a measured cell or for the glyph of a tag, so a tagged cell has the color of its tag.
+    fn half_block_color(&self, point: Point, highlight: bool) -> Color {
+        if let Some(tag) = self.grid.get_tag(point) {
+            return tag::appearance(tag).1;
+        }
+        let filled_share = self
+            .heat_map
+            .as_ref()
+            .filter(|_| !highlight)
+            .and_then(|heat_map| heat_map.filled_share(&self.grid, point));
+        if let Some(filled_share) = filled_share {
+            return heat_map::color(filled_share);
+        }
+        self.displayed_cell(point).segments(
+            point,
+            self.settings.separation_interval,
+            highlight,
+            None,
+            &self.palette,
+            self.settings.true_color,
+        )[0]
+        .1
+    }
+
+    /// Draws the upper cell of the line of the point as the upper half of the line and the lower one as the lower half.
+    fn draw_half_blocks(
+        &self,
+        terminal: &mut Terminal,
+        point: Point,
+        highlighted: impl Fn(Point) -> bool,
+    ) {
+        let upper_point = Point {
+            y: point.y - point.y % 2,
+            ..point
+        };
+        let lower_point = Point {
+            y: upper_point.y + 1,
+            ..point
+        };
+
+        terminal.set_cursor(self.screen_point(upper_point));
+        terminal.set_foreground_color(self.half_block_color(upper_point, highlighted(upper_point)));
+        // The last line of a grid of an uneven height only has an upper row
+        if lower_point.y < self.grid.size.height {
+            terminal
+                .set_background_color(self.half_block_color(lower_point, highlighted(lower_point)));
+        }
+        terminal.write("▀▀");
+        terminal.reset_colors();
+    }
+
     /// Reconstructs the clues associated with the given `cell_point`.
     pub fn rebuild_clues(&mut self, terminal: &mut Terminal, cell_point: Point) {
         self.clear_clues(terminal);
+        let index = cell_point.y as usize * self.grid.size.width as usize + cell_point.x as usize;
+        if self.grid.solution.is_empty() {
+            // Only the clues were known so far. From now on the edited cells are the solution.
+            self.grid.solution = self
+                .grid
+                .cells
+                .iter()
+                .map(|cell| cell.is_filled())
+                .collect();
+        }
+        self.grid.solution[index] = self.grid.cells[index].is_filled();
         self.grid.horizontal_clues_solutions[cell_point.y as usize] =
             self.grid.get_horizontal_clues(cell_point.y).collect();
         self.grid.vertical_clues_solutions[cell_point.x as usize] =
             self.grid.get_vertical_clues(cell_point.x).collect();
     }
 
-    /// Draws the top clues while also returning the amount of solved clue rows.
-    fn draw_top_clues(&mut self, terminal: &mut Terminal) -> usize {
-        let previous_point = self.point;
+    /// Returns whether the column is drawn, i.e. not scrolled out of view.
+    fn is_column_visible(&self, x: u16) -> bool {
+        let offset = self.viewport().offset;
+        self.is_visible(Point { x, y: offset.y })
+    }
 
-        let mut highlighted = true;
-        let mut solved_rows = 0;
-        for (x, vertical_clues_solution) in self.grid.vertical_clues_solutions.iter().enumerate() {
-            let vertical_clues = self.grid.get_vertical_clues(x as u16);
-            let solved = vertical_clues.eq(vertical_clues_solution.iter().copied());
+    /// Returns whether the row is drawn, i.e. not scrolled out of view.
+    fn is_row_visible(&self, y: u16) -> bool {
+        let offset = self.viewport().offset;
+        self.is_visible(Point { x: offset.x, y })
+    }
+
+    /// Returns whether some of the clues of the column are cut off.
+    pub fn are_column_clues_clipped(&self, x: u16) -> bool {
+        let clues = &self.grid.vertical_clues_solutions[x as usize];
+        clues.len() > self.viewport().clues_size.height as usize
+    }
+
+    /// Returns whether some of the clues of the row are cut off.
+    pub fn are_row_clues_clipped(&self, y: u16) -> bool {
+        let clues = &self.grid.horizontal_clues_solutions[y as usize];
+        clues.len() > (self.viewport().clues_size.width / 2) as usize
+    }
 
-            if highlighted {
-                terminal.set_background_color(HIGHLIGHTED_CLUE_BACKGROUND_COLOR);
+    /// Draws the clues above the column in their colors, from the one next to the grid outwards,
+    /// with the marker in place of the outermost ones if they don't all fit.
+    fn draw_top_clues_in(
+        &self,
+        terminal: &mut Terminal,
+        x: u16,
+        clues: impl DoubleEndedIterator<Item = (Clue, ClueStyle)> + ExactSizeIterator,
+    ) {
+        let (shown, clipped) = clipped(clues.len(), self.viewport().clues_size.height);
+        let mut point = Point {
+            y: self.point.y.saturating_sub(self.settings.border_width()),
+            ..self.screen_point(Point {
+                x,
+                y: self.viewport().offset.y,
+            })
+        };
+        let mut clues = clues.rev();
+        for (clue, style) in clues.by_ref().take(shown) {
+            // The clues that don't fit above the grid are skipped
+            point.y = match point.y.checked_sub(1) {
+                Some(y) => y,
+                None => return,
+            };
+            terminal.set_cursor(point);
+            self.set_clue_style(terminal, style);
+            terminal.write(&format!("{:<2}", clue));
+        }
+        if let (true, Some(y)) = (clipped, point.y.checked_sub(1)) {
+            point.y = y;
+            terminal.set_cursor(point);
+            if let Some((_, style)) = clues.next() {
+                self.set_clue_style(terminal, style);
             }
-            if solved {
-                terminal.set_foreground_color(Color::DarkGray);
-                solved_rows += 1;
+            terminal.write(CLIPPED_CLUES_MARKER);
+        }
+    }
+
+    /// Returns where the first of the clues below the column is drawn, which is below the column numbers,
+    /// with the others further down.
+    fn bottom_clues_point(&self, x: u16) -> Point {
+        Point {
+            y: self.column_clues_y(),
+            ..self.screen_point(Point {
+                x,
+                y: self.viewport().offset.y,
+            })
+        }
+    }
+
+    /// Draws the clues below the column like [`Self::draw_top_clues_in`], from the first one next to the grid downwards.
+    fn draw_bottom_clues_in(
+        &self,
+        terminal: &mut Terminal,
+        x: u16,
+        mut clues: impl ExactSizeIterator<Item = (Clue, ClueStyle)>,
+    ) {
+        let (shown, clipped) = clipped(clues.len(), self.viewport().clues_size.height);
+        let mut point = self.bottom_clues_point(x);
+        for (clue, style) in clues.by_ref().take(shown) {
+            terminal.set_cursor(point);
+            self.set_clue_style(terminal, style);
+            terminal.write(&format!("{:<2}", clue));
+            point.y += 1;
+        }
+        if clipped {
+            terminal.set_cursor(point);
+            if let Some((_, style)) = clues.next() {
+                self.set_clue_style(terminal, style);
+            }
+            terminal.write(CLIPPED_CLUES_MARKER);
+        }
+    }
+
+    /// Draws the clues of the column above or below it, depending on the layout.
+    fn draw_column_clues_in(
+        &self,
+        terminal: &mut Terminal,
+        x: u16,
+        clues: impl DoubleEndedIterator<Item = (Clue, ClueStyle)> + ExactSizeIterator,
+    ) {
+        if self.settings.clue_layout.columns_below() {
+            self.draw_bottom_clues_in(terminal, x, clues);
+        } else {
+            self.draw_top_clues_in(terminal, x, clues);
+        }
+    }
+
+    /// Draws the clues left of the row in their colors like [`Self::draw_top_clues_in`].
+    fn draw_left_clues_in(
+        &self,
+        terminal: &mut Terminal,
+        y: u16,
+        clues: impl DoubleEndedIterator<Item = (Clue, ClueStyle)> + ExactSizeIterator,
+    ) {
+        // Each clue takes up two characters
+        let (shown, clipped) = clipped(clues.len(), self.viewport().clues_size.width / 2);
+        terminal.set_cursor(self.left_clues_point(y));
+        let mut clues = clues.rev();
+        for (clue, style) in clues.by_ref().take(shown) {
+            self.set_clue_style(terminal, style);
+            terminal.write(&format!("{:>2}", clue));
+            terminal.move_cursor_left_by(4);
+        }
+        if clipped {
+            if let Some((_, style)) = clues.next() {
+                self.set_clue_style(terminal, style);
             }
+            terminal.write(CLIPPED_CLUES_MARKER);
+        }
+    }
 
-            let previous_point_y = self.point.y;
-            for clue in vertical_clues_solution.iter().rev() {
-                self.point.y -= 1;
-                terminal.set_cursor(self.point);
-                terminal.write(&format!("{:<2}", clue));
+    /// Draws the clues right of the row like [`Self::draw_left_clues_in`], from the first one next to the grid rightwards.
+    fn draw_right_clues_in(
+        &self,
+        terminal: &mut Terminal,
+        y: u16,
+        mut clues: impl ExactSizeIterator<Item = (Clue, ClueStyle)>,
+    ) {
+        // Each clue takes up two characters
+        let (shown, clipped) = clipped(clues.len(), self.viewport().clues_size.width / 2);
+        terminal.set_cursor(self.right_clues_point(y));
+        for (clue, style) in clues.by_ref().take(shown) {
+            self.set_clue_style(terminal, style);
+            terminal.write(&format!("{:>2}", clue));
+        }
+        if clipped {
+            if let Some((_, style)) = clues.next() {
+                self.set_clue_style(terminal, style);
             }
-            self.point.y = previous_point_y;
+            terminal.write(CLIPPED_CLUES_MARKER);
+        }
+    }
 
-            // We need to reset the colors because we don't always set both the background and foreground color
-            terminal.reset_colors();
-            highlighted = !highlighted;
-            self.point.x += 2;
+    /// Draws the clues of the row left or right of it, depending on the layout.
+    fn draw_row_clues_in(
+        &self,
+        terminal: &mut Terminal,
+        y: u16,
+        clues: impl DoubleEndedIterator<Item = (Clue, ClueStyle)> + ExactSizeIterator,
+    ) {
+        if self.settings.clue_layout.rows_right() {
+            self.draw_right_clues_in(terminal, y, clues);
+        } else {
+            self.draw_left_clues_in(terminal, y, clues);
+        }
+    }
+
+    /// Sets the background of the clues of the row or column with the index. Every other one has a highlighted background,
+    /// starting with the first one, and so do those of the crosshair. In the ASCII mode, there are no backgrounds.
+    fn set_clues_background(&self, terminal: &mut Terminal, index: u16, crosshair: bool) {
+        if self.settings.render_mode == RenderMode::Ascii {
+            return;
+        }
+        if crosshair {
+            terminal.set_background_color(
+                self.palette
+                    .crosshair_clue_background
+                    .get(self.settings.true_color),
+            );
+        } else if index.is_multiple_of(2) {
+            terminal
+                .set_background_color(self.palette.clue_background.get(self.settings.true_color));
         }
+    }
 
-        self.point = previous_point;
+    /// Returns how a clue is drawn. In the ASCII mode, all clues of the crosshair stand out, as they have no background.
+    fn clue_style(
+        &self,
+        satisfied: bool,
+        contradicted: bool,
+        crosshair: bool,
+        matched: bool,
+    ) -> ClueStyle {
+        if matched && !contradicted {
+            ClueStyle::Matched
+        } else if crosshair && self.settings.render_mode == RenderMode::Ascii {
+            ClueStyle::Hinted
+        } else {
+            ClueStyle::new(satisfied, contradicted)
+        }
+    }
 
-        solved_rows
+    fn set_clue_style(&self, terminal: &mut Terminal, style: ClueStyle) {
+        match self.settings.render_mode {
+            RenderMode::Colors => {
+                terminal.set_foreground_color(style.color(&self.palette, self.settings.true_color));
+                if self.palette.high_contrast {
+                    terminal.write(if style == ClueStyle::Satisfied {
+                        STRIKETHROUGH
+                    } else {
+                        NOT_STRIKETHROUGH
+                    });
+                }
+            }
+            RenderMode::Ascii => {
+                terminal.write(style.ascii_attribute());
+                // Otherwise it would be as bold as the other clues of the crosshair
+                terminal.write(if style == ClueStyle::Matched {
+                    UNDERLINE
+                } else {
+                    NOT_UNDERLINE
+                });
+            }
+        }
+    }
+
+    /// Draws the clues of the column, returning whether they are solved.
+    fn draw_column_clues_of(&self, terminal: &mut Terminal, x: u16) -> bool {
+        let vertical_clues_solution = &self.grid.vertical_clues_solutions[x as usize];
+        let vertical_clues = self.grid.get_vertical_clues(x);
+        let solved = vertical_clues.eq(vertical_clues_solution.iter().copied());
+        if !self.is_column_visible(x) {
+            return solved;
+        }
+
+        let crosshair = self.crosshair.filter(|crosshair| crosshair.x == x);
+        let matched_clue =
+            crosshair.and_then(|crosshair| self.grid.matched_vertical_clue(crosshair));
+        let crosshair = crosshair.is_some();
+        self.set_clues_background(terminal, x, crosshair);
+        let satisfied_clues = if solved {
+            vec![true; vertical_clues_solution.len()]
+        } else {
+            self.grid.satisfied_vertical_clues(x)
+        };
+        let contradicted = self
+            .contradictions
+            .as_ref()
+            .is_some_and(|contradictions| contradictions.is_column_contradicted(&self.grid, x));
+
+        let clues = vertical_clues_solution
+            .iter()
+            .zip(satisfied_clues)
+            .enumerate()
+            .map(|(index, (clue, satisfied))| {
+                let matched = matched_clue == Some(index);
+                (
+                    *clue,
+                    self.clue_style(satisfied, contradicted, crosshair, matched),
+                )
+            });
+        self.draw_column_clues_in(terminal, x, clues);
+
+        // We need to reset the colors because we don't always set both the background and foreground color
+        terminal.reset_colors();
+
+        solved
+    }
+    /// Draws the clues of the columns while also returning whether each column is solved.
+    fn draw_column_clues(&self, terminal: &mut Terminal) -> Vec<bool> {
+        (0..self.grid.size.width)
+            .map(|x| self.draw_column_clues_of(terminal, x))
+            .collect()
     }
     /// Clears the top clues, only graphically.
     fn clear_top_clues(&mut self, terminal: &mut Terminal) {
-        let previous_point = self.point;
-
-        let mut highlighted = true;
-        for vertical_clues_solution in self.grid.vertical_clues_solutions.iter() {
-            let previous_point_y = self.point.y;
-            for _ in vertical_clues_solution.iter().rev() {
-                self.point.y -= 1;
-                terminal.set_cursor(self.point);
+        let clues_height = self.viewport().clues_size.height as usize;
+        for (x, vertical_clues_solution) in self.grid.vertical_clues_solutions.iter().enumerate() {
+            let x = x as u16;
+            if !self.is_column_visible(x) {
+                continue;
+            }
+            let mut point = Point {
+                y: self.point.y - self.settings.border_width(),
+                ..self.screen_point(Point {
+                    x,
+                    y: self.viewport().offset.y,
+                })
+            };
+            for _ in 0..vertical_clues_solution.len().min(clues_height) {
+                point.y -= 1;
+                terminal.set_cursor(point);
                 terminal.write("  ");
             }
-            self.point.y = previous_point_y;
+        }
+    }
+    /// Clears the bottom clues, only graphically.
+    fn clear_bottom_clues(&mut self, terminal: &mut Terminal) {
+        let clues_height = self.viewport().clues_size.height as usize;
+        for (x, vertical_clues_solution) in self.grid.vertical_clues_solutions.iter().enumerate() {
+            let x = x as u16;
+            if !self.is_column_visible(x) {
+                continue;
+            }
+            let mut point = self.bottom_clues_point(x);
+            for _ in 0..vertical_clues_solution.len().min(clues_height) {
+                terminal.set_cursor(point);
+                terminal.write("  ");
+                point.y += 1;
+            }
+        }
+    }
 
-            highlighted = !highlighted;
-            self.point.x += 2;
+    /// Returns where the last of the clues left of the row is drawn, with the others further to the left.
+    fn left_clues_point(&self, y: u16) -> Point {
+        let viewport = self.viewport();
+        let mut point = self.screen_point(Point {
+            x: viewport.offset.x,
+            y,
+        });
+        point.x = point.x.saturating_sub(2 + self.settings.border_width());
+        if self.settings.half_blocks && y.is_multiple_of(2) {
+            // Left of the clues of the lower row
+            point.x = point
+                .x
+                .saturating_sub(viewport.clues_size.width + HALF_BLOCK_CLUES_GAP);
         }
+        point
+    }
 
-        self.point = previous_point;
+    /// Returns where the first of the clues right of the row is drawn, which is right of the row numbers,
+    /// with the others further to the right.
+    fn right_clues_point(&self, y: u16) -> Point {
+        let viewport = self.viewport();
+        let mut point = Point {
+            x: self.row_clues_x(),
+            ..self.screen_point(Point {
+                x: viewport.offset.x,
+                y,
+            })
+        };
+        if self.settings.half_blocks && y.is_multiple_of(2) {
+            // Right of the clues of the lower row
+            point.x += viewport.clues_size.width + HALF_BLOCK_CLUES_GAP;
+        }
+        point
     }
 
-    /// Draws the left clues while also returning the amount of solved clue rows.
-    fn draw_left_clues(&mut self, terminal: &mut Terminal) -> usize {
-        let previous_point = self.point;
+    /// Draws the clues of the row, returning whether they are solved.
+    fn draw_row_clues_of(&self, terminal: &mut Terminal, y: u16) -> bool {
+        let horizontal_clues_solution = &self.grid.horizontal_clues_solutions[y as usize];
+        let horizontal_clues = self.grid.get_horizontal_clues(y);
+        let solved = horizontal_clues.eq(horizontal_clues_solution.iter().copied());
+        if !self.is_row_visible(y) {
+            return solved;
+        }
 
-        self.point.x -= 2;
-        let mut highlighted = true;
-        let mut solved_rows = 0;
+        let crosshair = self.crosshair.filter(|crosshair| crosshair.y == y);
+        let matched_clue =
+            crosshair.and_then(|crosshair| self.grid.matched_horizontal_clue(crosshair));
+        let crosshair = crosshair.is_some();
+        self.set_clues_background(terminal, y, crosshair);
+        let satisfied_clues = if solved {
+            vec![true; horizontal_clues_solution.len()]
+        } else {
+            self.grid.satisfied_horizontal_clues(y)
+        };
+        let contradicted = self
+            .contradictions
+            .as_ref()
+            .is_some_and(|contradictions| contradictions.is_row_contradicted(y));
+
+        let clues = horizontal_clues_solution
+            .iter()
+            .zip(satisfied_clues)
+            .enumerate()
+            .map(|(index, (clue, satisfied))| {
+                let matched = matched_clue == Some(index);
+                (
+                    *clue,
+                    self.clue_style(satisfied, contradicted, crosshair, matched),
+                )
+            });
+        self.draw_row_clues_in(terminal, y, clues);
+        // We need to reset the colors because we don't always set both the background and foreground color
+        terminal.reset_colors();
+
+        solved
+    }
+    /// Draws the clues of the rows while also returning whether each row is solved.
+    fn draw_row_clues(&self, terminal: &mut Terminal) -> Vec<bool> {
+        (0..self.grid.size.height)
+            .map(|y| self.draw_row_clues_of(terminal, y))
+            .collect()
+    }
+    /// Clears the left clues, only graphically.
+    fn clear_left_clues(&mut self, terminal: &mut Terminal) {
+        let clues_count = self.viewport().clues_size.width as usize / 2;
         for (y, horizontal_clues_solution) in
             self.grid.horizontal_clues_solutions.iter().enumerate()
         {
-            terminal.set_cursor(self.point);
-            let horizontal_clues = self.grid.get_horizontal_clues(y as u16);
-            let solved = horizontal_clues.eq(horizontal_clues_solution.iter().copied());
-
-            if highlighted {
-                terminal.set_background_color(HIGHLIGHTED_CLUE_BACKGROUND_COLOR);
-            }
-            if solved {
-                terminal.set_foreground_color(Color::DarkGray);
-                solved_rows += 1;
+            let y = y as u16;
+            if !self.is_row_visible(y) {
+                continue;
             }
-
-            for clue in horizontal_clues_solution.iter().rev() {
-                terminal.write(&format!("{:>2}", clue));
+            terminal.set_cursor(self.left_clues_point(y));
+            for _ in 0..horizontal_clues_solution.len().min(clues_count) {
+                terminal.write("  ");
                 terminal.move_cursor_left_by(4);
             }
-            // We need to reset the colors because we don't always set both the background and foreground color
-            terminal.reset_colors();
-            highlighted = !highlighted;
-            self.point.y += 1;
         }
+    }
+    /// Clears the right clues, only graphically.
+    fn clear_right_clues(&mut self, terminal: &mut Terminal) {
+        let clues_count = self.viewport().clues_size.width as usize / 2;
+        for (y, horizontal_clues_solution) in
+            self.grid.horizontal_clues_solutions.iter().enumerate()
+        {
+            let y = y as u16;
+            if !self.is_row_visible(y) {
+                continue;
+            }
+            terminal.set_cursor(self.right_clues_point(y));
+            terminal.write(&"  ".repeat(horizontal_clues_solution.len().min(clues_count)));
+        }
+    }
+
+    /// Highlights the cells and the clues of the line given as the index into every row and then every column.
+    pub fn draw_hinted_line(&mut self, terminal: &mut Terminal, line: usize) {
+        // The line has to be drawn as usual again once anything else changes
+        self.drawn = None;
+
+        let height = self.grid.size.height as usize;
+        let (points, clues): (Vec<Point>, _) = if line < height {
+            let y = line as u16;
+            (
+                (0..self.grid.size.width).map(|x| Point { x, y }).collect(),
+                &self.grid.horizontal_clues_solutions[line],
+            )
+        } else {
+            let x = (line - height) as u16;
+            (
+                (0..self.grid.size.height).map(|y| Point { x, y }).collect(),
+                &self.grid.vertical_clues_solutions[line - height],
+            )
+        };
 
-        self.point = previous_point;
+        for point in &points {
+            self.draw_cell(terminal, *point, |point| points.contains(&point));
+        }
 
-        solved_rows
+        // Every other line has a highlighted background, starting with the first one
+        let line_number = if line < height { line } else { line - height };
+        let line_number = line_number as u16;
+        let visible = if line < height {
+            self.is_row_visible(line_number)
+        } else {
+            self.is_column_visible(line_number)
+        };
+        if !visible {
+            return;
+        }
+        self.set_clues_background(terminal, line_number, false);
+        let clues = clues.iter().map(|clue| (*clue, ClueStyle::Hinted));
+        if line < height {
+            self.draw_row_clues_in(terminal, line_number, clues);
+        } else {
+            self.draw_column_clues_in(terminal, line_number, clues);
+        }
+        terminal.reset_colors();
     }
-    /// Clears the left clues, only graphically.
-    fn clear_left_clues(&mut self, terminal: &mut Terminal) {
-        let previous_point = self.point;
 
-        self.point.x -= 2;
-        let mut highlighted = true;
-        for horizontal_clues_solution in self.grid.horizontal_clues_solutions.iter() {
-            terminal.set_cursor(self.point);
-            for _ in horizontal_clues_solution.iter().rev() {
-                terminal.write("  ");
-                terminal.move_cursor_left_by(4);
+    /// Highlights the row and the column of the cell at the point and their clues, with the cell itself standing out
+    /// by not being highlighted, or nothing if the point is `None`.
+    ///
+    /// Only the lines that were highlighted before and the ones that are highlighted now are drawn again.
+    pub fn draw_crosshair(&mut self, terminal: &mut Terminal, point: Option<Point>) {
+        let size = &self.grid.size;
+        let point = point.filter(|point| point.x < size.width && point.y < size.height);
+        let previous_point = std::mem::replace(&mut self.crosshair, point)
+            .filter(|point| point.x < size.width && point.y < size.height);
+
+        let highlighted = |cell_point: Point| {
+            point.is_some_and(|point| {
+                cell_point != point && (cell_point.x == point.x || cell_point.y == point.y)
+            })
+        };
+        for crosshair in previous_point.iter().chain(point.iter()) {
+            for x in 0..size.width {
+                self.draw_cell(terminal, Point { x, ..*crosshair }, highlighted);
+            }
+            for y in 0..size.height {
+                self.draw_cell(terminal, Point { y, ..*crosshair }, highlighted);
             }
-            terminal.reset_colors();
-            highlighted = !highlighted;
-            self.point.y += 1;
         }
 
-        self.point = previous_point;
+        for crosshair in previous_point.iter().chain(point.iter()) {
+            self.draw_column_clues_of(terminal, crosshair.x);
+            self.draw_row_clues_of(terminal, crosshair.y);
+        }
     }
 
-    /// Draws the top clues and the left clues while also returning the amount of solved clue rows.
-    fn draw_clues(&mut self, terminal: &mut Terminal) -> usize {
-        let solved_top_rows = self.draw_top_clues(terminal);
+    /// Draws the clues of the columns and the rows while also returning whether every row and then every column is solved.
+    fn draw_clues(&mut self, terminal: &mut Terminal) -> Vec<bool> {
+        if let Some(contradictions) = &mut self.contradictions {
+            contradictions.update(&self.grid);
+        }
 
-        let solved_left_rows = self.draw_left_clues(terminal);
+        let solved_columns = self.draw_column_clues(terminal);
 
-        solved_top_rows + solved_left_rows
+        let mut solved_lines = self.draw_row_clues(terminal);
+        solved_lines.extend(solved_columns);
+        solved_lines
     }
     /// Clears all clues, only graphically.
     pub fn clear_clues(&mut self, terminal: &mut Terminal) {
-        self.clear_top_clues(terminal);
+        if self.settings.clue_layout.columns_below() {
+            self.clear_bottom_clues(terminal);
+        } else {
+            self.clear_top_clues(terminal);
+        }
 
-        self.clear_left_clues(terminal);
+        if self.settings.clue_layout.rows_right() {
+            self.clear_right_clues(terminal);
+        } else {
+            self.clear_left_clues(terminal);
+        }
     }
 
-    /// Draws the grid.
+    /// Draws the grid, with the unknown cells shaded by how likely they are to be filled if the heat map is on.
     pub fn draw_grid(&mut self, terminal: &mut Terminal) {
-        let previous_point_y = self.point.y;
-        for (y, row) in self
-            .grid
-            .cells
-            .chunks(self.grid.size.width as usize)
-            .enumerate()
-        {
-            terminal.set_cursor(self.point);
-            let previous_point_x = self.point.x;
-            for (x, cell) in row.iter().enumerate() {
-                let point = Point {
-                    x: x as u16,
-                    y: y as u16,
-                };
-                cell.draw(terminal, point, false);
+        // None of the cells are highlighted anymore
+        if let Some(crosshair) = self.crosshair.take() {
+            if crosshair.x < self.grid.size.width && crosshair.y < self.grid.size.height {
+                self.draw_column_clues_of(terminal, crosshair.x);
+                self.draw_row_clues_of(terminal, crosshair.y);
+            }
+        }
+
+        if let Some(heat_map) = &mut self.heat_map {
+            heat_map.update(&self.grid);
+        }
+
+        let viewport = self.viewport();
+        let columns = viewport.offset.x..viewport.offset.x + viewport.size.width;
+        for y in self.drawn_rows() {
+            if self.settings.half_blocks {
+                for x in columns.clone() {
+                    self.draw_cell(terminal, Point { x, y }, |_| false);
+                }
+            } else {
+                // The whole row is written at once, with the colors only set where they change
+                terminal.set_cursor(self.screen_point(Point {
+                    x: viewport.offset.x,
+                    y,
+                }));
+                let segments = columns
+                    .clone()
+                    .flat_map(|x| self.cell_segments(Point { x, y }, false));
+                cell::write_segments(terminal, segments, self.settings.render_mode);
                 terminal.reset_colors();
-                self.point.x += 2;
             }
-            self.point.x = previous_point_x;
-            self.point.y += 1;
         }
-        self.point.y = previous_point_y;
+    }
+
+    /// Returns the rows of the lines of cells that are drawn from the top to the bottom,
+    /// which in the half-block mode are the upper rows of the lines.
+    pub fn drawn_rows(&self) -> impl Iterator<Item = u16> {
+        let viewport = self.viewport();
+        // Each line of the half-block mode is drawn at once
+        let row_step = if self.settings.half_blocks { 2 } else { 1 };
+        (viewport.offset.y..viewport.offset.y + viewport.size.height).step_by(row_step)
+    }
+
+    /// Draws the numbers of the drawn rows right of the grid and those of the drawn columns below it, if they are on.
+    fn draw_coordinates(&self, terminal: &mut Terminal) {
+        if !self.settings.coordinates {
+            return;
+        }
+        let viewport = self.viewport();
+
+        terminal.set_foreground_color(Color::DarkGray);
+        for y in self.drawn_rows() {
+            let point = self.screen_point(Point {
+                x: viewport.offset.x,
+                y,
+            });
+            terminal.set_cursor(Point {
+                x: self.grid_right_x() + 1,
+                ..point
+            });
+            terminal.write(&coordinate_label(y));
+        }
+        terminal.set_cursor(Point {
+            y: self.grid_bottom_y(),
+            ..self.point
+        });
+        for x in viewport.offset.x..viewport.offset.x + viewport.size.width {
+            terminal.write(&coordinate_label(x));
+        }
+        terminal.reset_colors();
+    }
+
+    /// Draws the border around the drawn cells if it is on, which turns green once the grid is solved.
+    /// In the ASCII mode, it is drawn with plain characters and turns bold instead.
+    fn draw_border(&self, terminal: &mut Terminal, solved: bool) {
+        if self.settings.border_width() == 0 {
+            return;
+        }
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] =
+            match self.settings.render_mode {
+                RenderMode::Colors => ["┌", "┐", "└", "┘", "─", "│"],
+                RenderMode::Ascii => ["+", "+", "+", "+", "-", "|"],
+            };
+        match (self.settings.render_mode, solved) {
+            (RenderMode::Colors, false) => terminal.set_foreground_color(Color::DarkGray),
+            (RenderMode::Colors, true) => terminal.set_foreground_color(Color::Green),
+            (RenderMode::Ascii, false) => {}
+            (RenderMode::Ascii, true) => terminal.write(BOLD),
+        }
+
+        let left = self.point.x - 1;
+        let right = self.grid_right_x() - 1;
+        let horizontal_line = horizontal.repeat(self.screen_size().width as usize);
+        terminal.set_cursor(Point {
+            x: left,
+            y: self.point.y - 1,
+        });
+        terminal.write(&format!("{}{}{}", top_left, horizontal_line, top_right));
+        for y in self.point.y..self.grid_bottom_y() - 1 {
+            terminal.set_cursor(Point { x: left, y });
+            terminal.write(vertical);
+            terminal.set_cursor(Point { x: right, y });
+            terminal.write(vertical);
+        }
+        terminal.set_cursor(Point {
+            x: left,
+            y: self.grid_bottom_y() - 1,
+        });
+        terminal.write(&format!(
+            "{}{}{}",
+            bottom_left, horizontal_line, bottom_right
+        ));
+        terminal.reset_colors();
+    }
+
+    /// Draws the line of cells of the row with only the filled cells, without tags or shading,
+    /// as part of the picture that solving the grid reveals.
+    pub fn draw_revealed_row(&mut self, terminal: &mut Terminal, y: u16) {
+        let clean_picture = std::mem::replace(&mut self.clean_picture, true);
+        let tags = std::mem::replace(&mut self.grid.tags, vec![None; self.grid.cells.len()]);
+        let heat_map = self.heat_map.take();
+
+        let viewport = self.viewport();
+        for x in viewport.offset.x..viewport.offset.x + viewport.size.width {
+            self.draw_cell(terminal, Point { x, y }, |_| false);
+        }
+
+        self.clean_picture = clean_picture;
+        self.grid.tags = tags;
+        self.heat_map = heat_map;
     }
 
     fn draw_half_block(terminal: &mut Terminal) {
         terminal.write("▄");
     }
 
-    /// Draws the grid in smaller form on the top left, making it easier to see the whole picture.
+    /// Draws the grid in smaller form in the corner between the clues, making it easier to see the whole picture.
     ///
     /// NOTE: Perhaps at some point in the future [sixel](https://en.wikipedia.org/wiki/Sixel) can be supported.
     ///       Maybe exclusively for cases where the window size does not suffice.
     ///
     /// NOTE: Perhaps at some point, if stabilized, `array_chunks` can be used to implement this.
     pub fn draw_picture(&mut self, terminal: &mut Terminal) {
-        let previous_point = self.point;
+        if !self.viewport().picture {
+            return;
+        }
+        if self.clean_picture {
+            let cells: Vec<Cell> = (0..self.grid.size.height)
+                .flat_map(|y| (0..self.grid.size.width).map(move |x| Point { x, y }))
+                .map(|point| self.displayed_cell(point))
+                .collect();
+            self.draw_cells_picture(terminal, &cells, self.picture_point());
+        } else {
+            self.draw_cells_picture(terminal, &self.grid.cells, self.picture_point());
+        }
+    }
 
-        self.point.x -= self.grid.size.width;
-        self.point.y -= self.grid.size.height / 2;
-        self.point.y -= 1;
+    /// Returns the point of the picture, below which its pairs of rows are drawn and on which only a first row of an uneven height is.
+    fn picture_point(&self) -> Point {
+        Point {
+            x: if self.settings.clue_layout.rows_right() {
+                self.row_clues_x()
+            } else {
+                self.point
+                    .x
+                    .saturating_sub(self.grid.size.width + self.settings.border_width())
+            },
+            y: if self.settings.clue_layout.columns_below() {
+                (self.grid_bottom_y()
+                    + self.settings.coordinates_size().height
+                    + self.grid.size.height % 2)
+                    .saturating_sub(1)
+            } else {
+                self.point
+                    .y
+                    .saturating_sub(self.grid.size.height / 2 + 1 + self.settings.border_width())
+            },
+        }
+    }
 
-        let mut chunks = self.grid.cells.chunks(self.grid.size.width as usize);
+    /// Draws the half block of [`Self::draw_picture`] that the cell at the point is drawn in.
+    fn draw_picture_cell(&self, terminal: &mut Terminal, point: Point) {
+        if !self.viewport().picture {
+            return;
+        }
+        let mut picture_point = self.picture_point();
+        picture_point.x += point.x;
+        let get_color = |y: u16| {
+            self.displayed_cell(Point { y, ..point })
+                .get_color(&self.palette, self.settings.true_color)
+        };
+
+        // See `draw_cells_picture` for how the rows are paired
+        let uneven = self.grid.size.height % 2;
+        if uneven == 1 && point.y == 0 {
+            terminal.set_cursor(picture_point);
+            terminal.set_foreground_color(get_color(0));
+        } else {
+            let upper_y = point.y - (point.y - uneven) % 2;
+            picture_point.y += 1 + (upper_y - uneven) / 2;
+            terminal.set_cursor(picture_point);
+            terminal.set_background_color(get_color(upper_y));
+            terminal.set_foreground_color(get_color(upper_y + 1));
+        }
+        Self::draw_half_block(terminal);
+        terminal.reset_colors();
+    }
+
+    /// Draws the cells, which are as many as the grid has, in the half block form of [`Self::draw_picture`] at the point.
+    fn draw_cells_picture(&self, terminal: &mut Terminal, cells: &[Cell], mut point: Point) {
+        let mut chunks = cells.chunks(self.grid.size.width as usize);
 
         if self.grid.size.height % 2 == 1 {
             let uneven_chunk = chunks.next().unwrap();
 
-            terminal.set_cursor(self.point);
+            terminal.set_cursor(point);
             for cell in uneven_chunk {
-                terminal.set_foreground_color(cell.get_color());
+                terminal
+                    .set_foreground_color(cell.get_color(&self.palette, self.settings.true_color));
                 Self::draw_half_block(terminal);
             }
         }
 
         for (first_row, second_row) in chunks.tuples() {
-            self.point.y += 1;
-            terminal.set_cursor(self.point);
+            point.y += 1;
+            terminal.set_cursor(point);
             for (upper_cell, lower_cell) in first_row.iter().zip(second_row) {
-                terminal.set_background_color(upper_cell.get_color());
-                terminal.set_foreground_color(lower_cell.get_color());
+                terminal.set_background_color(
+                    upper_cell.get_color(&self.palette, self.settings.true_color),
+                );
+                terminal.set_foreground_color(
+                    lower_cell.get_color(&self.palette, self.settings.true_color),
+                );
                 Self::draw_half_block(terminal);
             }
         }
+    }
 
-        self.point = previous_point;
+    /// Draws the solution as a picture right of the grid, next to the player's own one, returning whether it fit there.
+    /// In the ASCII mode, it has a character for each cell.
+    pub fn draw_solution_picture(&self, terminal: &mut Terminal, solution: &[bool]) -> bool {
+        let point = Point {
+            x: self.point.x + self.screen_size().width + self.width_right_of_grid() + 2,
+            // Pairs of rows are drawn below the point and only a first row of an uneven height is drawn on it
+            y: (self.point.y + self.grid.size.height % 2).saturating_sub(1),
+        };
+        if point.x + self.grid.size.width > terminal.size.width {
+            return false;
+        }
+        if self.settings.render_mode == RenderMode::Ascii {
+            // A character for each cell, with the rows next to those of the grid
+            for (y, row) in solution.chunks(self.grid.size.width as usize).enumerate() {
+                terminal.set_cursor(Point {
+                    y: self.point.y + y as u16,
+                    ..point
+                });
+                let row: String = row
+                    .iter()
+                    .map(|filled| if *filled { '#' } else { '.' })
+                    .collect();
+                terminal.write(&row);
+            }
+            return true;
+        }
+
+        let cells: Vec<Cell> = solution
+            .iter()
+            .map(|filled| {
+                if *filled {
+                    PictureCell::Filled
+                } else {
+                    PictureCell::Empty
+                }
+                .into()
+            })
+            .collect();
+        self.draw_cells_picture(terminal, &cells, point);
+        terminal.reset_colors();
+
+        true
     }
 
-    /// Draws the progress of solved clue rows as a bar at the bottom.
+    /// Draws the progress of solved clue rows as a bar at the bottom, followed by the completion percentage if it fits.
     fn draw_progress_bar(&mut self, terminal: &mut Terminal, solved_rows: usize) {
+        let screen_size = self.screen_size();
         terminal.set_cursor(Point {
-            y: self.point.y + self.grid.size.height,
+            y: self.progress_bar_y(),
             ..self.point
         });
 
-        let grid_width = self.grid.size.width * 2;
-        let width = ((solved_rows as f64 / (self.grid.size.width + self.grid.size.height) as f64)
-            * grid_width as f64) as u16;
-
-        terminal.set_foreground_color(Color::Gray);
-        for _ in 0..width {
-            Self::draw_half_block(terminal);
-        }
+        let grid_width = screen_size.width;
+        let rows = (self.grid.size.width + self.grid.size.height) as usize;
+        let width = ((solved_rows as f64 / rows as f64) * grid_width as f64) as u16;
 
         let rest = grid_width - width;
-        if rest > 0 {
-            terminal.set_foreground_color(Color::DarkGray);
-            for _ in 0..rest {
+        if self.settings.render_mode == RenderMode::Ascii {
+            terminal.write(&format!(
+                "{}{}",
+                "=".repeat(width as usize),
+                "-".repeat(rest as usize)
+            ));
+        } else {
+            terminal.set_foreground_color(Color::Gray);
+            for _ in 0..width {
                 Self::draw_half_block(terminal);
             }
+
+            if rest > 0 {
+                terminal.set_foreground_color(Color::DarkGray);
+                for _ in 0..rest {
+                    Self::draw_half_block(terminal);
+                }
+            }
+        }
+
+        if self.point.x + grid_width + COMPLETION_WIDTH <= terminal.size.width {
+            terminal.set_foreground_color(Color::Gray);
+            terminal.write(&completion_text(solved_rows, rows));
+        }
+        terminal.reset_colors();
+    }
+
+    /// Draws how long the grid has been played left of the progress bar, which is the corner below the left clues
+    /// unless they are right of the grid, if it fits there.
+    pub fn draw_playing_time(&self, terminal: &mut Terminal) {
+        let width = self.screen_clues_size().width as usize;
+
+        if let (Some(x), Some(playing_time)) = (
+            self.point
+                .x
+                .checked_sub(width as u16 + self.settings.border_width()),
+            format::duration(self.grid.playing_time.as_secs(), width),
+        ) {
+            terminal.set_cursor(Point {
+                x,
+                y: self.progress_bar_y(),
+            });
+            terminal.set_foreground_color(Color::DarkGray);
+            // The padding clears a previous, longer time
+            terminal.write(&format!("{:>width$}", playing_time, width = width));
+            terminal.reset_colors();
         }
     }
 
@@ -290,18 +1727,115 @@ impl Builder {
 
         self.draw_grid(terminal);
 
-        let solved_rows = self.draw_clues(terminal);
+        self.draw_coordinates(terminal);
+
+        let solved_lines = self.draw_clues(terminal);
+        let solved_rows = solved_lines.iter().filter(|solved| **solved).count();
+        let solved = solved_rows == (self.grid.size.width + self.grid.size.height) as usize;
+
+        self.draw_border(terminal, solved);
 
         self.draw_progress_bar(terminal, solved_rows);
 
-        solved_rows == (self.grid.size.width + self.grid.size.height) as usize
+        self.draw_playing_time(terminal);
+
+        self.drawn = Some(Drawn {
+            point: self.point,
+            viewport: self.viewport(),
+            cells: self.grid.cells.clone(),
+            tags: self.grid.tags.clone(),
+            horizontal_clues_solutions: self.grid.horizontal_clues_solutions.clone(),
+            vertical_clues_solutions: self.grid.vertical_clues_solutions.clone(),
+            solved_lines,
+        });
+
+        solved
+    }
+
+    /// Returns the points of the cells and tags that changed since the grid was last drawn as a whole,
+    /// or `None` if it has to be drawn as a whole again, e.g. because it moved, its clues changed or too many cells changed.
+    fn changed_points(&self) -> Option<Vec<Point>> {
+        let drawn = self.drawn.as_ref()?;
+        if drawn.point != self.point
+            || drawn.viewport != self.viewport()
+            || drawn.cells.len() != self.grid.cells.len()
+            || drawn.horizontal_clues_solutions != self.grid.horizontal_clues_solutions
+            || drawn.vertical_clues_solutions != self.grid.vertical_clues_solutions
+        {
+            return None;
+        }
+
+        let width = self.grid.size.width as usize;
+        let points: Vec<Point> = (0..self.grid.cells.len())
+            .filter(|index| {
+                drawn.cells[*index] != self.grid.cells[*index]
+                    || drawn.tags[*index] != self.grid.tags[*index]
+            })
+            .map(|index| Point {
+                x: (index % width) as u16,
+                y: (index / width) as u16,
+            })
+            .collect();
+        // Beyond a line's worth of cells, e.g. after a clear or undoing a large group, drawing everything is just as fast
+        if points.len() > self.grid.size.width.max(self.grid.size.height) as usize {
+            return None;
+        }
+        Some(points)
+    }
+
+    /// Draws only the cells that changed since the grid was last drawn as a whole and the clues of their rows and columns,
+    /// or everything like [`Self::draw_all`] if that is needed. Also returns whether the grid was solved.
+    #[must_use]
+    pub fn draw_changes(&mut self, terminal: &mut Terminal) -> bool {
+        let points = match self.changed_points() {
+            Some(points) => points,
+            None => return self.draw_all(terminal),
+        };
+
+        if self.heat_map.is_some() {
+            // The shades of all cells of the rows and columns change
+            self.draw_grid(terminal);
+        } else {
+            for point in &points {
+                self.draw_cell(terminal, *point, |_| false);
+            }
+        }
+        for point in &points {
+            self.draw_picture_cell(terminal, *point);
+        }
+
+        if let Some(contradictions) = &mut self.contradictions {
+            contradictions.update(&self.grid);
+        }
+        let mut drawn = self.drawn.take().unwrap();
+        let was_solved = drawn.solved_lines.iter().all(|solved| *solved);
+        let height = self.grid.size.height as usize;
+        for y in points.iter().map(|point| point.y).unique() {
+            drawn.solved_lines[y as usize] = self.draw_row_clues_of(terminal, y);
+        }
+        for x in points.iter().map(|point| point.x).unique() {
+            drawn.solved_lines[height + x as usize] = self.draw_column_clues_of(terminal, x);
+        }
+
+        let solved_rows = drawn.solved_lines.iter().filter(|solved| **solved).count();
+        self.draw_progress_bar(terminal, solved_rows);
+        let solved = solved_rows == (self.grid.size.width + self.grid.size.height) as usize;
+        if solved != was_solved {
+            self.draw_border(terminal, solved);
+        }
+
+        drawn.cells.clone_from(&self.grid.cells);
+        drawn.tags.clone_from(&self.grid.tags);
+        self.drawn = Some(drawn);
+
+        solved
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::grid::Cell;
+    use crate::grid::{colors::BuiltinPalette, Cell};
     use std::io;
     use terminal::util::Size;
 
@@ -312,7 +1846,7 @@ mod tests {
         };
         let grid = Grid::new(size.clone(), vec![Cell::Empty; size.product() as usize]);
         let terminal = Terminal::new(stdout).unwrap();
-        let builder = Builder::new(&terminal, grid);
+        let builder = Builder::new(&terminal, grid, Settings::default());
         (terminal, builder)
     }
 
@@ -321,10 +1855,13 @@ mod tests {
         let stdout = io::stdout();
         let (_, builder) = get_terminal_and_builder(stdout.lock());
 
-        assert!(!builder.contains(Point {
-            x: builder.point.x - 1,
-            y: builder.point.y - 1
-        }));
+        // The grid might be at the edge of a tiny terminal
+        if let (Some(x), Some(y)) = (
+            builder.point.x.checked_sub(1),
+            builder.point.y.checked_sub(1),
+        ) {
+            assert!(!builder.contains(Point { x, y }));
+        }
         assert!(builder.contains(builder.point));
         assert!(!builder.contains(Point {
             x: builder.point.x + builder.grid.size.width,
@@ -374,4 +1911,557 @@ mod tests {
         }
         assert_eq!(previous_point, builder.point);
     }
+
+    #[test]
+    fn test_draw_changes() {
+        let stdout = io::stdout();
+        let (mut terminal, mut builder) = get_terminal_and_builder(stdout.lock());
+        // Nothing was drawn yet
+        assert_eq!(builder.changed_points(), None);
+
+        // The grid has no clues, so every line is solved
+        assert!(builder.draw_all(&mut terminal));
+        assert_eq!(builder.changed_points(), Some(Vec::new()));
+
+        let point = Point { x: 3, y: 2 };
+        *builder.grid.get_mut_cell(point) = Cell::Crossed;
+        *builder.grid.get_mut_tag(Point { x: 0, y: 0 }) = Some(1);
+        assert_eq!(
+            builder.changed_points(),
+            Some(vec![Point { x: 0, y: 0 }, point])
+        );
+        assert!(builder.draw_changes(&mut terminal));
+        assert_eq!(builder.changed_points(), Some(Vec::new()));
+
+        // Two rows are more than a line's worth
+        for x in 0..builder.grid.size.width {
+            for y in 0..2 {
+                *builder.grid.get_mut_cell(Point { x, y }) = Cell::Maybed;
+            }
+        }
+        assert_eq!(builder.changed_points(), None);
+        assert!(builder.draw_changes(&mut terminal));
+        assert_eq!(builder.changed_points(), Some(Vec::new()));
+
+        builder.point.x += 1;
+        assert_eq!(builder.changed_points(), None);
+
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "1 ",
+            " 1",
+        ]).unwrap();
+        builder.grid = grid;
+        assert!(!builder.draw_changes(&mut terminal));
+        *builder.grid.get_mut_cell(Point { x: 0, y: 0 }) = Cell::Filled;
+        assert!(!builder.draw_changes(&mut terminal));
+        // Only the solved lines of the last change are checked again
+        *builder.grid.get_mut_cell(Point { x: 1, y: 1 }) = Cell::Filled;
+        assert!(builder.draw_changes(&mut terminal));
+    }
+
+    #[test]
+    fn test_draw_crosshair() {
+        let stdout = io::stdout();
+        let (mut terminal, mut builder) = get_terminal_and_builder(stdout.lock());
+        let point = Point { x: 1, y: 2 };
+        builder.draw_crosshair(&mut terminal, Some(point));
+        assert_eq!(builder.crosshair, Some(point));
+
+        // Points outside of the grid highlight nothing
+        builder.draw_crosshair(&mut terminal, Some(Point { x: 99, y: 0 }));
+        assert_eq!(builder.crosshair, None);
+
+        builder.draw_crosshair(&mut terminal, Some(point));
+        builder.draw_grid(&mut terminal);
+        assert_eq!(builder.crosshair, None);
+    }
+
+    #[test]
+    fn test_layout() {
+        let size = Size {
+            width: 60,
+            height: 60,
+        };
+        let grid = Grid::new(size.clone(), vec![Cell::Filled; size.product() as usize]);
+        let terminal_size = Size {
+            width: 80,
+            height: 24,
+        };
+
+        for settings in [
+            Settings::default(),
+            Settings {
+                half_blocks: true,
+                ..Settings::default()
+            },
+            Settings {
+                coordinates: true,
+                border: true,
+                ..Settings::default()
+            },
+        ] {
+            let viewport = layout(&grid, &terminal_size, settings).unwrap();
+            assert!(!viewport.picture);
+            assert!(viewport.size.width < grid.size.width);
+            assert!(viewport.size.height < grid.size.height);
+            assert!(
+                viewport.size.width * 2
+                    + settings.clues_width_on_screen(viewport.clues_size.width)
+                    + settings.frame_size().width
+                    <= 80
+            );
+            assert!(fits(&grid, &terminal_size, settings));
+
+            let required_size = required_size(&grid, settings);
+            assert!(fits(&grid, &required_size, settings));
+            assert!(!fits(
+                &grid,
+                &Size {
+                    width: required_size.width - 1,
+                    height: required_size.height,
+                },
+                settings
+            ));
+            assert!(!fits(
+                &grid,
+                &Size {
+                    width: required_size.width,
+                    height: required_size.height - 1,
+                },
+                settings
+            ));
+        }
+
+        // Two rows are drawn on each line, so the lines can show twice as many of them
+        let half_blocks = Settings {
+            half_blocks: true,
+            ..Settings::default()
+        };
+        assert!(
+            layout(&grid, &terminal_size, half_blocks)
+                .unwrap()
+                .size
+                .height
+                > layout(&grid, &terminal_size, Settings::default())
+                    .unwrap()
+                    .size
+                    .height
+        );
+    }
+
+    #[test]
+    fn test_clue_layout() {
+        let stdout = io::stdout();
+        let mut terminal = Terminal::new(stdout.lock()).unwrap();
+        terminal.size = Size {
+            width: 80,
+            height: 40,
+        };
+        let grid = Grid::from_lines(&["#.#", "##.", "#.#", "..#"]).unwrap();
+        let mut builder = Builder::new(&terminal, grid, Settings::default());
+        let top_left_point = builder.point;
+        let clues_size = builder.screen_clues_size();
+        assert_eq!(builder.leading_clues_size(), clues_size);
+
+        builder.settings.clue_layout = ClueLayout::BottomRight;
+        builder.fit(&terminal);
+        let screen_size = builder.screen_size();
+        assert_eq!(
+            builder.leading_clues_size(),
+            Size {
+                width: 0,
+                height: 0
+            }
+        );
+        // The grid moves by the clues so that everything stays centered
+        assert!(builder.point.x < top_left_point.x);
+        assert!(builder.point.y < top_left_point.y);
+        assert!(builder.contains(builder.point));
+        assert_eq!(
+            builder.bottom_clues_point(0),
+            Point {
+                x: builder.point.x,
+                y: builder.point.y + screen_size.height
+            }
+        );
+        assert_eq!(
+            builder.right_clues_point(0),
+            Point {
+                x: builder.point.x + screen_size.width,
+                y: builder.point.y
+            }
+        );
+        // The clues below the grid are as tall as the picture next to them
+        assert_eq!(
+            builder.progress_bar_y(),
+            builder.point.y + screen_size.height + 2
+        );
+
+        for layout in ClueLayout::ALL {
+            assert_eq!(ClueLayout::from_name(layout.name()), Some(layout));
+        }
+    }
+
+    #[test]
+    fn test_half_blocks() {
+        let stdout = io::stdout();
+        let mut terminal = Terminal::new(stdout.lock()).unwrap();
+        terminal.size = Size {
+            width: 80,
+            height: 40,
+        };
+        let grid = Grid::from_lines(&["#.#", "##.", "#.#", "..#", "##."]).unwrap();
+        let settings = Settings {
+            half_blocks: true,
+            ..Settings::default()
+        };
+        let mut builder = Builder::new(&terminal, grid, settings);
+
+        assert_eq!(builder.screen_size().height, 3);
+        assert_eq!(builder.drawn_rows().collect::<Vec<_>>(), [0, 2, 4]);
+        // The lower row is drawn on the line of the upper one, but selected on the line below it
+        let lower_row = Point { x: 1, y: 3 };
+        let screen_point = builder.screen_point(lower_row);
+        assert_eq!(
+            screen_point,
+            Point {
+                x: builder.point.x + 2,
+                y: builder.point.y + 1
+            }
+        );
+        assert_eq!(
+            builder.cursor_point(screen_point),
+            Point {
+                x: builder.point.x + 2,
+                y: builder.point.y + 2
+            }
+        );
+        // The clues of the upper row are left of those of the lower one
+        assert_eq!(
+            builder.screen_clues_size().width,
+            builder.grid.max_clues_size.width * 2 + HALF_BLOCK_CLUES_GAP
+        );
+        assert_eq!(builder.left_clues_point(3).x, builder.point.x - 2);
+        assert_eq!(
+            builder.left_clues_point(2).x,
+            builder.point.x - 2 - builder.grid.max_clues_size.width - HALF_BLOCK_CLUES_GAP
+        );
+
+        let previous_point = builder.point;
+        assert!(!builder.draw_all(&mut terminal));
+        assert_eq!(previous_point, builder.point);
+    }
+
+    #[test]
+    fn test_border_and_coordinates() {
+        let stdout = io::stdout();
+        let mut terminal = Terminal::new(stdout.lock()).unwrap();
+        terminal.size = Size {
+            width: 80,
+            height: 40,
+        };
+        let lines = ["#.#", "##.", "#.#", "..#"];
+        let plain = Builder::new(
+            &terminal,
+            Grid::from_lines(&lines).unwrap(),
+            Settings::default(),
+        );
+        let settings = Settings {
+            coordinates: true,
+            border: true,
+            ..Settings::default()
+        };
+        let mut builder = Builder::new(&terminal, Grid::from_lines(&lines).unwrap(), settings);
+
+        assert_eq!(
+            settings.frame_size(),
+            Size {
+                width: 5,
+                height: 3
+            }
+        );
+        // The border is between the cells and everything around them
+        let clues_size = builder.screen_clues_size();
+        assert_eq!(
+            builder.row_clues_x(),
+            builder.point.x - clues_size.width - 1
+        );
+        assert_eq!(
+            builder.column_clues_y(),
+            builder.point.y - clues_size.height - 1
+        );
+        assert_eq!(
+            builder.grid_right_x(),
+            builder.point.x + builder.screen_size().width + 1
+        );
+        assert_eq!(
+            builder.width_right_of_grid(),
+            plain.width_right_of_grid() + 1 + 3
+        );
+        assert_eq!(builder.progress_bar_y(), builder.grid_bottom_y() + 1);
+        assert!(builder.contains(builder.point));
+
+        // The clues right of the grid are set apart from the row numbers
+        builder.settings.clue_layout = ClueLayout::TopRight;
+        builder.fit(&terminal);
+        assert_eq!(builder.row_clues_x(), builder.grid_right_x() + 3 + 1);
+
+        let previous_point = builder.point;
+        assert!(!builder.draw_all(&mut terminal));
+        assert_eq!(previous_point, builder.point);
+    }
+
+    #[test]
+    fn test_render_mode_and_separation_interval() {
+        let stdout = io::stdout();
+        let (_, mut builder) = get_terminal_and_builder(stdout.lock());
+        let (first, last) = (Point { x: 4, y: 0 }, Point { x: 5, y: 0 });
+        let content = |builder: &Builder, point| {
+            builder
+                .cell_segments(point, false)
+                .into_iter()
+                .map(|(_, _, content)| content)
+                .collect::<String>()
+        };
+        let background = |builder: &Builder, point| builder.cell_segments(point, false)[0].1;
+
+        // The empty cells of the next interval have the other color
+        assert_ne!(background(&builder, first), background(&builder, last));
+        builder.settings.separation_interval = 0;
+        assert_eq!(background(&builder, first), background(&builder, last));
+
+        // Without colors, a guide ends the interval instead
+        builder.settings.render_mode = RenderMode::Ascii;
+        assert_eq!(content(&builder, first), content(&builder, last));
+        builder.settings.separation_interval = 5;
+        assert_ne!(content(&builder, first), content(&builder, last));
+        assert!(content(&builder, first).ends_with('|'));
+        // There is no picture without colors
+        assert!(!builder.viewport().picture);
+    }
+
+    #[test]
+    fn test_palette() {
+        let stdout = io::stdout();
+        let (terminal, mut builder) = get_terminal_and_builder(stdout.lock());
+        let settings = Settings {
+            palette: BuiltinPalette::HighContrast.palette(),
+            ..Settings::default()
+        };
+        let grid = Grid::new(builder.grid.size.clone(), builder.grid.cells.clone());
+        let high_contrast_builder = Builder::new(&terminal, grid, settings);
+        let background = |builder: &Builder| builder.cell_segments(Point::default(), false)[0].1;
+
+        // Each builder draws with its own palette
+        assert_eq!(background(&builder), Color::Byte(240));
+        assert_eq!(background(&high_contrast_builder), Color::Byte(232));
+
+        // And with its own theme
+        builder.set_theme(Theme::Dark);
+        assert_eq!(
+            background(&builder),
+            Palette::default().themed(Theme::Dark).empty[0]
+                .normal
+                .get(false)
+        );
+        assert_eq!(background(&high_contrast_builder), Color::Byte(232));
+    }
+
+    #[test]
+    fn test_fit_tiny_terminal() {
+        let stdout = io::stdout();
+        let (mut terminal, _) = get_terminal_and_builder(stdout.lock());
+        terminal.size = Size {
+            width: 3,
+            height: 2,
+        };
+        let size = Size {
+            width: 60,
+            height: 60,
+        };
+        let grid = Grid::new(size.clone(), vec![Cell::Filled; size.product() as usize]);
+        assert!(!fits(&grid, &terminal.size, Settings::default()));
+        let mut builder = Builder::new(&terminal, grid, Settings::default());
+
+        // The grid stays right of and below its clues instead of wrapping around
+        let clues_size = builder.screen_clues_size();
+        assert!(builder.point.x >= clues_size.width);
+        assert!(builder.point.y >= clues_size.height);
+        assert!(builder.contains(builder.get_center()));
+        assert!(!builder.contains(Point { x: 0, y: 0 }));
+
+        #[allow(unused_must_use)]
+        {
+            builder.draw_all(&mut terminal);
+        }
+        let point = builder.point;
+        assert_eq!(builder.cursor_point(point), point);
+
+        // Without any clues, nothing keeps the grid off the edges of the terminal
+        let all = Settings {
+            half_blocks: true,
+            coordinates: true,
+            border: true,
+            ..Settings::default()
+        };
+        for settings in [Settings::default(), all] {
+            for (width, height) in [(4, 3), (1, 1), (0, 0)] {
+                terminal.size = Size { width, height };
+                let size = Size {
+                    width: 7,
+                    height: 5,
+                };
+                let grid = Grid::new(size.clone(), vec![Cell::Empty; size.product() as usize]);
+                let mut builder = Builder::new(&terminal, grid, settings);
+                #[allow(unused_must_use)]
+                {
+                    builder.draw_all(&mut terminal);
+                }
+            }
+        }
+
+        // Nor for clues that come up after the grid was laid out, e.g. in the editor, which are left out
+        for clue_layout in ClueLayout::ALL.iter().copied() {
+            for (width, height) in [(4, 3), (1, 1), (0, 0)] {
+                terminal.size = Size { width, height };
+                let size = Size {
+                    width: 5,
+                    height: 3,
+                };
+                let grid = Grid::new(size.clone(), vec![Cell::Empty; size.product() as usize]);
+                let settings = Settings {
+                    clue_layout,
+                    ..Settings::default()
+                };
+                let mut builder = Builder::new(&terminal, grid, settings);
+                #[rustfmt::skip]
+                let grid = Grid::from_lines(&[
+                    "1 1 1",
+                    " 1 1 ",
+                    "1 1 1",
+                ]).unwrap();
+                builder.grid = grid;
+                assert!(!builder.draw_all(&mut terminal));
+                *builder.grid.get_mut_cell(Point { x: 0, y: 0 }) = Cell::Filled;
+                assert!(!builder.draw_changes(&mut terminal));
+            }
+        }
+    }
+
+    #[test]
+    fn test_clipped() {
+        assert_eq!(clipped(3, 5), (3, false));
+        assert_eq!(clipped(5, 5), (5, false));
+        assert_eq!(clipped(6, 5), (4, true));
+        assert_eq!(clipped(1, 0), (0, false));
+    }
+
+    #[test]
+    fn test_scroll_to() {
+        let stdout = io::stdout();
+        let (mut terminal, _) = get_terminal_and_builder(stdout.lock());
+        let size = Size {
+            width: 60,
+            height: 60,
+        };
+        // Every other cell is filled, which gives every line more clues than fit
+        let cells = (0..size.product())
+            .map(|index| {
+                if (index + index / size.width as u32).is_multiple_of(2) {
+                    Cell::Filled
+                } else {
+                    Cell::Empty
+                }
+            })
+            .collect();
+        let grid = Grid::new(size.clone(), cells);
+        let mut builder = Builder::new(&terminal, grid, Settings::default());
+        terminal.size = Size {
+            width: 80,
+            height: 24,
+        };
+        builder.fit(&terminal);
+        assert_eq!(builder.viewport().offset, Point::default());
+
+        let point = Point { x: 59, y: 40 };
+        assert!(!builder.is_visible(point));
+        builder.scroll_to(&mut terminal, point);
+        assert!(builder.is_visible(point));
+        let viewport = builder.viewport();
+        assert_eq!(viewport.offset.x + viewport.size.width, 60);
+        assert_eq!(viewport.offset.y + viewport.size.height, 41);
+
+        // The point on the screen selects the same cell again
+        let screen_point = builder.screen_point(point);
+        let cursor_point = builder.cursor_point(screen_point);
+        assert_eq!(
+            cursor_point,
+            Point {
+                x: builder.point.x + point.x * 2,
+                y: builder.point.y + point.y,
+            }
+        );
+
+        assert!((0..60).any(|x| builder.are_column_clues_clipped(x)));
+        assert!((0..60).any(|y| builder.are_row_clues_clipped(y)));
+
+        // Only the axis that the cell is out of view on is scrolled
+        builder.scroll_to(&mut terminal, Point { x: 0, y: 40 });
+        assert_eq!(builder.viewport().offset.x, 0);
+        assert_eq!(builder.viewport().offset.y, viewport.offset.y);
+    }
+
+    #[test]
+    fn test_draw_revealed_row() {
+        let stdout = io::stdout();
+        let (mut terminal, mut builder) = get_terminal_and_builder(stdout.lock());
+        *builder.grid.get_mut_cell(Point { x: 1, y: 0 }) = Cell::Crossed;
+        *builder.grid.get_mut_tag(Point { x: 2, y: 0 }) = Some(1);
+        let cells = builder.grid.cells.clone();
+
+        assert_eq!(builder.drawn_rows().count(), 5);
+        for y in builder.drawn_rows().collect::<Vec<_>>() {
+            builder.draw_revealed_row(&mut terminal, y);
+        }
+        // Only the drawing leaves out the crossed out cell and the tag
+        assert_eq!(builder.grid.cells, cells);
+        assert_eq!(builder.grid.get_tag(Point { x: 2, y: 0 }), Some(1));
+    }
+
+    #[test]
+    fn test_coordinate_label() {
+        assert_eq!(coordinate_label(0), " 1");
+        assert_eq!(coordinate_label(98), "99");
+        assert_eq!(coordinate_label(99), "'0");
+        assert_eq!(coordinate_label(104), "'5");
+        assert_eq!(coordinate_label(111), "12");
+    }
+
+    #[test]
+    fn test_displayed_cell() {
+        let stdout = io::stdout();
+        let (_, mut builder) = get_terminal_and_builder(stdout.lock());
+        let (filled, measured) = (Point { x: 0, y: 0 }, Point { x: 1, y: 0 });
+        *builder.grid.get_mut_cell(filled) = Cell::Filled;
+        *builder.grid.get_mut_cell(measured) = Cell::Measured(Some(3));
+
+        builder.clean_picture = true;
+        assert_eq!(builder.displayed_cell(filled), Cell::Filled);
+        assert_eq!(builder.displayed_cell(measured), Cell::Empty);
+        // Measured cells keep their index for when they are drawn again
+        builder.clean_picture = false;
+        assert_eq!(builder.displayed_cell(measured), Cell::Measured(Some(3)));
+    }
+
+    #[test]
+    fn test_completion_text() {
+        assert_eq!(completion_text(0, 30), "   0%");
+        assert_eq!(completion_text(22, 30), "  73%");
+        // Only a solved grid is complete
+        assert_eq!(completion_text(29, 30), "  97%");
+        assert_eq!(completion_text(299, 300), "  99%");
+        assert_eq!(completion_text(30, 30), " 100%");
+        assert_eq!(completion_text(30, 30).len(), COMPLETION_WIDTH as usize);
+    }
 }
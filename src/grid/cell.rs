@@ -1,13 +1,27 @@
-use crate::{grid::builder::Builder, undo_redo_buffer, util, Grid, State};
-use std::{borrow::Cow, time::Instant};
+use crate::{
+    event::{
+        input::{inspect::Inspector, jump::Axis},
+        latency::Quality,
+    },
+    grid::{
+        builder::Builder,
+        colors::{self, Palette},
+        tag,
+    },
+    lives::Lives,
+    undo_redo_buffer::{self, Measurement},
+    util, Grid, State,
+};
+use std::borrow::Cow;
 use terminal::{
     util::{Color, Point},
     Terminal,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum Cell {
     /// An umarked cell.
+    #[default]
     Empty,
     /// Used to mark filled cells.
     Filled,
@@ -17,87 +31,218 @@ pub enum Cell {
     Crossed,
     /// Used for indicating cells that were measured using the measurement tool.
     ///
-    /// When this cell is saved, the index is not preserved.
+    /// The index is preserved in saves of the progress but not in `.yaya` grid files.
     Measured(Option<usize>),
 }
 
-impl Default for Cell {
-    fn default() -> Self {
-        Cell::Empty
-    }
+/// The kinds of cells, without the data some of them have.
+///
+/// Code that treats some kinds alike matches on this exhaustively instead of using catch-all patterns,
+/// so that adding a kind is a compile error wherever it would need handling (see the `audit` module).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellKind {
+    Empty,
+    Filled,
+    Maybed,
+    Crossed,
+    Measured,
 }
 
-impl From<bool> for Cell {
-    fn from(filled: bool) -> Self {
-        filled.then(|| Cell::Filled).unwrap_or_default()
+impl CellKind {
+    pub const ALL: [CellKind; 5] = [
+        CellKind::Empty,
+        CellKind::Filled,
+        CellKind::Maybed,
+        CellKind::Crossed,
+        CellKind::Measured,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            CellKind::Empty => "empty",
+            CellKind::Filled => "filled",
+            CellKind::Maybed => "maybed",
+            CellKind::Crossed => "crossed",
+            CellKind::Measured => "measured",
+        }
+    }
+
+    /// Returns a cell of this kind.
+    pub const fn example(self) -> Cell {
+        match self {
+            CellKind::Empty => Cell::Empty,
+            CellKind::Filled => Cell::Filled,
+            CellKind::Maybed => Cell::Maybed,
+            CellKind::Crossed => Cell::Crossed,
+            CellKind::Measured => Cell::Measured(None),
+        }
     }
 }
 
 impl Cell {
-    pub fn get_color(&self) -> Color {
+    pub const fn kind(self) -> CellKind {
         match self {
-            Cell::Empty => Color::default(),
-            Cell::Filled => Color::White,
-            Cell::Maybed => Color::Blue,
-            Cell::Crossed => Color::Red,
-            Cell::Measured(_) => Color::Green,
+            Cell::Empty => CellKind::Empty,
+            Cell::Filled => CellKind::Filled,
+            Cell::Maybed => CellKind::Maybed,
+            Cell::Crossed => CellKind::Crossed,
+            Cell::Measured(_) => CellKind::Measured,
         }
     }
 
-    pub fn get_highlighted_color(&self) -> Color {
-        match self {
-            Cell::Empty => Color::DarkGray,
-            Cell::Filled => Color::Gray,
-            Cell::Maybed => Color::DarkBlue,
-            Cell::Crossed => Color::DarkRed,
-            Cell::Measured(_) => Color::DarkGreen,
+    /// Returns whether this cell makes up the clues, the solution and the pictures, as opposed to being empty.
+    pub const fn is_filled(self) -> bool {
+        match self.kind() {
+            CellKind::Filled => true,
+            CellKind::Empty | CellKind::Maybed | CellKind::Crossed | CellKind::Measured => false,
         }
     }
+}
 
-    pub fn draw(&self, terminal: &mut Terminal, point: Point, highlight: bool) {
-        /// Every 5 cells, the color changes to make the grid and its cells easier to look at and distinguish.
-        const SEPARATION_POINT: u16 = 5;
-
-        fn draw(
-            terminal: &mut Terminal,
-            foreground_color: Option<Color>,
-            background_color: Color,
-            content: Cow<'static, str>,
-        ) {
-            terminal.set_background_color(background_color);
-            if let Some(foreground_color) = foreground_color {
-                terminal.set_foreground_color(foreground_color);
-            }
-            terminal.write(&content);
+/// Returns whether measuring over a cell of the kind replaces it with a measured cell.
+pub const fn is_measurable(kind: CellKind) -> bool {
+    match kind {
+        CellKind::Empty | CellKind::Measured => true,
+        CellKind::Filled | CellKind::Maybed | CellKind::Crossed => false,
+    }
+}
+
+/// A cell of a picture that a grid is made from.
+///
+/// Pictures only say which cells are filled, unlike the cells the player marks,
+/// so every way of making a grid from a picture goes through this.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PictureCell {
+    Empty,
+    Filled,
+}
+
+impl From<PictureCell> for Cell {
+    fn from(picture_cell: PictureCell) -> Self {
+        match picture_cell {
+            PictureCell::Empty => Cell::Empty,
+            PictureCell::Filled => Cell::Filled,
         }
+    }
+}
 
-        let mut background_color = if highlight {
-            self.get_highlighted_color()
+impl Cell {
+    /// Returns the colors of the cell in the palette, or `None` for empty cells, which have no color of their own.
+    fn palette_color(&self, palette: &Palette) -> Option<colors::CellColor> {
+        match self {
+            Cell::Empty => None,
+            Cell::Filled => Some(palette.filled),
+            Cell::Maybed => Some(palette.maybed),
+            Cell::Crossed => Some(palette.crossed),
+            Cell::Measured(_) => Some(palette.measured),
+        }
+    }
+
+    pub fn get_color(&self, palette: &Palette, true_color: bool) -> Color {
+        self.palette_color(palette)
+            .map_or(Color::default(), |color| color.normal.get(true_color))
+    }
+
+    pub fn get_highlighted_color(&self, palette: &Palette, true_color: bool) -> Color {
+        self.palette_color(palette)
+            .map_or(Color::DarkGray, |color| color.highlighted.get(true_color))
+    }
+
+    /// Returns what the cell is drawn as: the colors and content of each part of it.
+    ///
+    /// A tag takes up the left half of the cell, leaving the right half to the cell itself.
+    pub fn segments(
+        &self,
+        point: Point,
+        separation_interval: u16,
+        highlight: bool,
+        tag: Option<u8>,
+        palette: &Palette,
+        true_color: bool,
+    ) -> Vec<Segment> {
+        let (foreground_color, background_color, content) =
+            self.segment(point, separation_interval, highlight, palette, true_color);
+
+        if let Some(tag) = tag {
+            let (glyph, tag_color) = tag::appearance(tag);
+            let right_half = content.chars().nth(1).unwrap_or(' ').to_string();
+            vec![
+                (Some(Color::Black), tag_color, glyph.into()),
+                (foreground_color, background_color, right_half.into()),
+            ]
         } else {
-            self.get_color()
-        };
+            vec![(foreground_color, background_color, content)]
+        }
+    }
 
-        let (foreground_color, background_color, content) = match self {
+    /// Returns what the cell is drawn as without colors, like [`Self::segments`]: a character for each kind of cell,
+    /// or the index of a measured cell, with guides in place of the alternating colors of empty cells.
+    ///
+    /// The last cell of each interval of columns ends with `|` and the empty cells of the last row of each interval of rows
+    /// are drawn as `-`, except for highlighted empty cells, which are drawn as `+`. A tag is drawn as its letter.
+    pub fn ascii_segments(
+        &self,
+        point: Point,
+        separation_interval: u16,
+        highlight: bool,
+        tag: Option<u8>,
+    ) -> Vec<Segment> {
+        let guide = |index: u16| {
+            separation_interval != 0 && (index + 1).is_multiple_of(separation_interval)
+        };
+        let right_half = if guide(point.x) { '|' } else { ' ' };
+        let content = match self {
             Cell::Empty => {
-                let x_reached_point = point.x / SEPARATION_POINT % 2 == 0;
-                let y_reached_point = point.y / SEPARATION_POINT % 2 == 0;
-                let mut background_color_byte = if x_reached_point ^ y_reached_point {
-                    238
+                let left_half = if highlight {
+                    '+'
+                } else if guide(point.y) {
+                    '-'
                 } else {
-                    240
+                    '.'
                 };
+                format!("{}{}", left_half, right_half)
+            }
+            Cell::Filled => "##".into(),
+            Cell::Maybed => format!("?{}", right_half),
+            Cell::Crossed => format!("X{}", right_half),
+            Cell::Measured(Some(index)) => measured_index_label(*index).into_owned(),
+            Cell::Measured(None) => format!("={}", right_half),
+        };
+        let content = match tag {
+            Some(tag) => format!("{}{}", tag::ascii_glyph(tag), &content[1..]),
+            None => content,
+        };
+        vec![(None, Color::default(), content.into())]
+    }
 
-                if highlight {
-                    background_color_byte -= 3;
-                }
+    fn segment(
+        &self,
+        point: Point,
+        separation_interval: u16,
+        highlight: bool,
+        palette: &Palette,
+        true_color: bool,
+    ) -> Segment {
+        let mut background_color = if highlight {
+            self.get_highlighted_color(palette, true_color)
+        } else {
+            self.get_color(palette, true_color)
+        };
 
-                background_color = Color::Byte(background_color_byte);
+        match self {
+            Cell::Empty => {
+                // With no interval, all empty cells have the same color
+                let alternate = separation_interval != 0
+                    && !(point.x / separation_interval + point.y / separation_interval)
+                        .is_multiple_of(2);
+                let empty = palette.empty[alternate as usize];
+                background_color = empty.get(highlight, true_color);
 
                 (None, background_color, "  ".into())
             }
             Cell::Measured(index) => {
                 let (foreground_color, content) = if let Some(index) = index {
-                    (Some(Color::Black), format!("{:>2}", index).into())
+                    (Some(Color::Black), measured_index_label(*index))
                 } else {
                     (None, "  ".into())
                 };
@@ -105,21 +250,103 @@ impl Cell {
                 (foreground_color, background_color, content)
             }
             _ => (None, background_color, "  ".into()),
-        };
+        }
+    }
+}
+
+/// Every 5 cells by default, the color of empty cells changes to make the grid and its cells easier to look at and distinguish.
+pub const DEFAULT_SEPARATION_INTERVAL: u16 = 5;
+
+/// How the grid is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RenderMode {
+    #[default]
+    Colors,
+    /// With plain characters and without any colors, for terminals that can't show them.
+    Ascii,
+}
+
+/// The foreground color, if any, the background color and the content of a part of a drawn cell.
+pub type Segment = (Option<Color>, Color, Cow<'static, str>);
+
+/// Returns the segments with each run of adjacent ones of the same colors merged into one, which is drawn the same.
+fn coalesced(segments: impl IntoIterator<Item = Segment>) -> Vec<Segment> {
+    let mut coalesced: Vec<Segment> = Vec::new();
+    for (foreground_color, background_color, content) in segments {
+        match coalesced.last_mut() {
+            Some((last_foreground_color, last_background_color, last_content))
+                if *last_foreground_color == foreground_color
+                    && *last_background_color == background_color =>
+            {
+                last_content.to_mut().push_str(&content);
+            }
+            _ => coalesced.push((foreground_color, background_color, content)),
+        }
+    }
+    coalesced
+}
+
+/// Draws the segments one after another, setting the colors only once for each run of segments of the same colors,
+/// or not at all in the ASCII mode.
+pub fn write_segments(
+    terminal: &mut Terminal,
+    segments: impl IntoIterator<Item = Segment>,
+    render_mode: RenderMode,
+) {
+    match render_mode {
+        RenderMode::Colors => {
+            for (foreground_color, background_color, content) in coalesced(segments) {
+                terminal.set_background_color(background_color);
+                if let Some(foreground_color) = foreground_color {
+                    terminal.set_foreground_color(foreground_color);
+                }
+                terminal.write(&content);
+            }
+        }
+        RenderMode::Ascii => {
+            let content: String = segments
+                .into_iter()
+                .map(|(_, _, content)| content)
+                .collect();
+            terminal.write(&content);
+        }
+    }
+}
+
+/// Measured cells show their index using two digits so we can't measure more cells than this.
+pub const MAX_MEASURED_CELLS: usize = 99;
+
+/// Drawn in place of the index of a measured cell that has more than two digits, which can't be measured but might still
+/// come from elsewhere, so that the cell stays two characters wide like all others.
+const OVERFLOWING_INDEX_LABEL: &str = "++";
 
-        draw(terminal, foreground_color, background_color, content);
+/// Returns the index of a measured cell as it is drawn, which takes up two characters.
+fn measured_index_label(index: usize) -> Cow<'static, str> {
+    if index <= MAX_MEASURED_CELLS {
+        format!("{:>2}", index).into()
+    } else {
+        OVERFLOWING_INDEX_LABEL.into()
     }
 }
 
 #[derive(Default)]
 pub struct CellPlacement {
     pub cell: Option<Cell>,
-    /// The time of when the first cell was placed.
-    pub starting_time: Option<Instant>,
     pub selected_cell_point: Option<Point>,
     pub measurement_point: Option<Point>,
     /// Whether the next cell placement will flood-fill.
     pub fill: bool,
+    pub inspector: Inspector,
+    /// The axis that jumps to unsolved lines are on.
+    pub jump_axis: Axis,
+    /// The last used tag if the tag mode is armed.
+    pub tag_mode: Option<u8>,
+    /// Whether the empty cells of rows and columns are crossed out once their clues are solved by a placement.
+    pub auto_cross: bool,
+    /// Whether hovering highlights cells, which depends on how fast the terminal is.
+    pub quality: Quality,
+    /// The mistakes of the lives mode if it is on.
+    pub lives: Option<Lives>,
 }
 
 pub const fn get_cell_point_from_cursor_point(cursor_point: Point, builder: &Builder) -> Point {
@@ -129,11 +356,11 @@ pub const fn get_cell_point_from_cursor_point(cursor_point: Point, builder: &Bui
     }
 }
 
-pub fn set_measured_cells(grid: &mut Grid, line_points: &[Point]) {
-    for (index, point) in line_points.iter().enumerate() {
-        let cell = grid.get_mut_cell(*point);
+pub fn set_measured_cells(grid: &mut Grid, line_points: impl Iterator<Item = Point>) {
+    for (index, point) in line_points.enumerate() {
+        let cell = grid.get_mut_cell(point);
 
-        if let Cell::Empty | Cell::Measured(_) = cell {
+        if is_measurable(cell.kind()) {
             *cell = Cell::Measured(Some(index + 1));
         }
     }
@@ -141,53 +368,12 @@ pub fn set_measured_cells(grid: &mut Grid, line_points: &[Point]) {
 
 pub fn draw_highlighted_cells(
     terminal: &mut Terminal,
-    builder: &Builder,
+    builder: &mut Builder,
     hovered_cell_point: Point,
 ) {
-    fn highlight_cell(terminal: &mut Terminal, mut cursor_point: Point, builder: &Builder) {
-        if (cursor_point.x - builder.point.x) % 2 != 0 {
-            cursor_point.x -= 1;
-        }
-        terminal.set_cursor(cursor_point);
-        let cell_point = get_cell_point_from_cursor_point(cursor_point, builder);
-        let cell = builder.grid.get_cell(cell_point);
-        cell.draw(terminal, cell_point, true);
-    }
-
-    // From the left of the grid to the pointer
-    for x in builder.point.x..=hovered_cell_point.x - 2 {
-        let point = Point {
-            x,
-            ..hovered_cell_point
-        };
-        highlight_cell(terminal, point, builder);
-    }
-    // From the pointer to the right of the grid
-    for x in hovered_cell_point.x + 2..builder.point.x + builder.grid.size.width * 2 {
-        let point = Point {
-            x,
-            ..hovered_cell_point
-        };
-        highlight_cell(terminal, point, builder);
-    }
-    // From the top of the grid to the pointer
-    for y in builder.point.y..hovered_cell_point.y {
-        let point = Point {
-            y,
-            ..hovered_cell_point
-        };
-        highlight_cell(terminal, point, builder);
-    }
-    // From the pointer to the bottom of the grid
-    for y in hovered_cell_point.y + 1..builder.point.y + builder.grid.size.height {
-        let point = Point {
-            y,
-            ..hovered_cell_point
-        };
-        highlight_cell(terminal, point, builder);
-    }
-
-    terminal.reset_colors();
+    let hovered = get_cell_point_from_cursor_point(hovered_cell_point, builder);
+    builder.scroll_to(terminal, hovered);
+    builder.draw_crosshair(terminal, Some(hovered));
 }
 
 impl CellPlacement {
@@ -198,50 +384,68 @@ impl CellPlacement {
         selected_cell_point: Point,
         mut cell_to_place: Cell,
         editor_toggled: bool,
+        sandbox_toggled: bool,
     ) -> State {
-        let starting_time = self.starting_time.get_or_insert(Instant::now());
-
         let cell_point = get_cell_point_from_cursor_point(selected_cell_point, builder);
 
-        let grid_cell = builder.grid.get_mut_cell(cell_point);
+        // The cells placed by a drag of the mouse are undone at once, while a single click stays on its own
+        let continues_stroke = self.cell.is_some();
+        if !continues_stroke {
+            builder.grid.undo_redo_buffer.start_group();
+        }
+
+        let grid_cell = builder.grid.get_cell(cell_point);
 
-        *grid_cell = if let Some(cell) = self.cell {
-            if *grid_cell == cell {
+        let mut cell = if let Some(cell) = self.cell {
+            if grid_cell == cell {
                 builder.draw_grid(terminal);
 
                 // We know that this point is hovered
-                draw_highlighted_cells(terminal, &builder, selected_cell_point);
+                draw_highlighted_cells(terminal, builder, selected_cell_point);
 
                 return State::Continue;
             }
 
             cell
         } else {
-            if *grid_cell == cell_to_place {
+            if grid_cell == cell_to_place {
                 cell_to_place = Cell::default();
             }
             self.cell = Some(cell_to_place);
 
             if self.fill {
-                let cell = *grid_cell;
+                builder.grid.apply(undo_redo_buffer::Operation::Fill {
+                    point: cell_point,
+                    first_cell: grid_cell,
+                    fill_cell: cell_to_place,
+                });
 
-                super::tools::fill::fill(&mut builder.grid, cell_point, cell, cell_to_place);
+                self.fill = false;
+                // The fill is undone on its own
+                builder.grid.undo_redo_buffer.start_group();
 
-                builder
-                    .grid
-                    .undo_redo_buffer
-                    .push(undo_redo_buffer::Operation::Fill {
-                        point: cell_point,
-                        first_cell: cell,
-                        fill_cell: cell_to_place,
-                    });
+                // A fill can't be rejected part by part so its wrong cells are crossed out afterwards
+                let mistake_state = match (&mut self.lives, sandbox_toggled) {
+                    (Some(lives), false) => lives.reject_wrong_cells(&mut builder.grid),
+                    _ => None,
+                };
 
-                self.fill = false;
+                if self.auto_cross && !editor_toggled {
+                    // The fill can reach any line
+                    let size = &builder.grid.size;
+                    let (rows, columns) = (0..size.height, 0..size.width);
+                    super::tools::auto_cross::cross_solved_lines(&mut builder.grid, rows, columns);
+                }
 
                 let all_clues_solved = builder.draw_all(terminal);
 
-                if all_clues_solved {
-                    return State::Solved(starting_time.elapsed());
+                if let Some(mistake_state) = mistake_state {
+                    return mistake_state;
+                }
+
+                // Solving the sandbox doesn't count
+                if all_clues_solved && !sandbox_toggled {
+                    return State::Solved(builder.grid.playing_time);
                 } else {
                     return State::ClearAlert;
                 }
@@ -249,15 +453,28 @@ impl CellPlacement {
 
             cell_to_place
         };
-        let cell = *grid_cell;
 
-        builder
-            .grid
-            .undo_redo_buffer
-            .push(undo_redo_buffer::Operation::SetCell {
-                point: cell_point,
-                cell,
-            });
+        // In the lives mode, wrong cells are crossed out instead of being filled in
+        let mut mistake_state = None;
+        if let (Some(lives), false) = (&mut self.lives, sandbox_toggled) {
+            if cell == Cell::Filled && lives.is_wrong(&builder.grid, cell_point) {
+                cell = Cell::Crossed;
+                mistake_state = Some(lives.add_mistake());
+            }
+        }
+
+        builder.grid.apply(undo_redo_buffer::Operation::SetCell {
+            point: cell_point,
+            cell,
+        });
+
+        if self.auto_cross && !editor_toggled {
+            super::tools::auto_cross::cross_solved_lines_at(&mut builder.grid, cell_point);
+        }
+
+        if continues_stroke {
+            builder.grid.undo_redo_buffer.group();
+        }
 
         if editor_toggled {
             builder.rebuild_clues(terminal, cell_point);
@@ -268,17 +485,18 @@ impl CellPlacement {
                 builder.draw_all(terminal);
             }
         } else {
-            let all_clues_solved = builder.draw_all(terminal);
+            let all_clues_solved = builder.draw_changes(terminal);
 
-            if all_clues_solved {
-                return State::Solved(starting_time.elapsed());
+            // Solving the sandbox doesn't count
+            if all_clues_solved && !sandbox_toggled {
+                return State::Solved(builder.grid.playing_time);
             }
         }
 
         // We know that this point is hovered
-        draw_highlighted_cells(terminal, &builder, selected_cell_point);
+        draw_highlighted_cells(terminal, builder, selected_cell_point);
 
-        State::Continue
+        mistake_state.unwrap_or(State::Continue)
     }
 
     pub fn place_measured_cells(
@@ -295,21 +513,30 @@ impl CellPlacement {
                 let end_point =
                     super::get_cell_point_from_cursor_point(selected_cell_point, builder);
 
-                let line_points: Vec<Point> =
-                    util::get_line_points(start_point, end_point).collect();
-
-                set_measured_cells(&mut builder.grid, &line_points);
+                // A line is always contiguous
+                let measurement =
+                    Measurement::new(util::get_line_points(start_point, end_point)).unwrap();
+
+                if measurement.len() > MAX_MEASURED_CELLS {
+                    self.measurement_point = None;
+                    return State::Alert(
+                        format!(
+                            "Measurements can be at most {} cells long",
+                            MAX_MEASURED_CELLS
+                        )
+                        .into(),
+                    );
+                }
 
                 builder
                     .grid
-                    .undo_redo_buffer
-                    .push(undo_redo_buffer::Operation::Measure(line_points));
+                    .apply(undo_redo_buffer::Operation::Measure(measurement));
 
                 builder.draw_picture(terminal);
                 builder.draw_grid(terminal);
 
                 // We know that this point is hovered
-                super::draw_highlighted_cells(terminal, &builder, selected_cell_point);
+                super::draw_highlighted_cells(terminal, builder, selected_cell_point);
 
                 self.measurement_point = None;
 
@@ -324,3 +551,129 @@ impl CellPlacement {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the colors that each character of the segments is drawn with, in order.
+    fn drawn_characters(segments: &[Segment]) -> Vec<(Option<Color>, Color, char)> {
+        segments
+            .iter()
+            .flat_map(|(foreground_color, background_color, content)| {
+                content
+                    .chars()
+                    .map(move |char| (*foreground_color, *background_color, char))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_coalesced() {
+        let cells = [
+            Cell::Empty,
+            Cell::Empty,
+            Cell::Filled,
+            Cell::Filled,
+            Cell::Measured(Some(4)),
+            Cell::Crossed,
+            Cell::Empty,
+        ];
+        let segments: Vec<Segment> = cells
+            .iter()
+            .enumerate()
+            .flat_map(|(x, cell)| {
+                let tag = (x == 1).then_some(1);
+                cell.segments(
+                    Point { x: x as u16, y: 0 },
+                    5,
+                    false,
+                    tag,
+                    &Palette::default(),
+                    false,
+                )
+            })
+            .collect();
+
+        let coalesced = coalesced(segments.clone());
+        assert_eq!(drawn_characters(&coalesced), drawn_characters(&segments));
+        assert!(coalesced.len() < segments.len());
+        assert!(coalesced
+            .windows(2)
+            .all(|pair| (pair[0].0, pair[0].1) != (pair[1].0, pair[1].1)));
+    }
+
+    #[test]
+    fn test_ascii_segments() {
+        let content = |cell: Cell, x, y, highlight, tag| {
+            cell.ascii_segments(Point { x, y }, 5, highlight, tag)
+                .into_iter()
+                .map(|(_, _, content)| content)
+                .collect::<String>()
+        };
+
+        assert_eq!(content(Cell::Empty, 0, 0, false, None), ". ");
+        assert_eq!(content(Cell::Empty, 4, 0, false, None), ".|");
+        assert_eq!(content(Cell::Empty, 0, 4, false, None), "- ");
+        assert_eq!(content(Cell::Empty, 0, 4, true, None), "+ ");
+        assert_eq!(content(Cell::Filled, 4, 4, false, None), "##");
+        assert_eq!(content(Cell::Crossed, 9, 0, false, None), "X|");
+        assert_eq!(content(Cell::Maybed, 1, 0, false, None), "? ");
+        assert_eq!(content(Cell::Measured(Some(12)), 4, 0, false, None), "12");
+        assert_eq!(content(Cell::Filled, 0, 0, false, Some(2)), "b#");
+        // Without an interval there are no guides
+        assert_eq!(
+            Cell::Empty.ascii_segments(Point { x: 4, y: 4 }, 0, false, None)[0].2,
+            ". "
+        );
+    }
+
+    #[test]
+    fn test_measured_index_label() {
+        // A measurement of 120 cells in a row, which is longer than any that can be made
+        let row: Vec<Segment> = (1..=120)
+            .flat_map(|index| {
+                let point = Point {
+                    x: index as u16 - 1,
+                    y: 0,
+                };
+                let cell = Cell::Measured(Some(index));
+                let mut segments = cell.segments(point, 5, false, None, &Palette::default(), false);
+                segments.extend(cell.ascii_segments(point, 5, false, None));
+                segments
+            })
+            .collect();
+        assert_eq!(drawn_characters(&row).len(), 120 * 2 * 2);
+
+        assert_eq!(measured_index_label(7), " 7");
+        assert_eq!(measured_index_label(99), "99");
+        assert_eq!(measured_index_label(100), OVERFLOWING_INDEX_LABEL);
+    }
+
+    #[test]
+    fn test_separation_interval() {
+        let background = |x, y, separation_interval| {
+            Cell::Empty.segments(
+                Point { x, y },
+                separation_interval,
+                false,
+                None,
+                &Palette::default(),
+                false,
+            )[0]
+            .1
+        };
+        let [empty, alternate_empty] = Palette::default()
+            .empty
+            .map(|color| color.get(false, false));
+
+        assert_eq!(background(0, 0, 5), empty);
+        assert_eq!(background(5, 0, 5), alternate_empty);
+        assert_eq!(background(5, 5, 5), empty);
+        assert_eq!(background(4, 0, 4), alternate_empty);
+        assert_eq!(background(3, 0, 4), empty);
+        for x in 0..10 {
+            assert_eq!(background(x, 7, 0), empty);
+        }
+    }
+}
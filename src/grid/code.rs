@@ -0,0 +1,239 @@
+//! Puzzle codes are short strings containing a whole grid that can be shared easily, e.g. in a chat.
+//!
+//! A code is the base64 encoding of the following bytes:
+//!
+//! 1. The code format version.
+//! 2. The grid width.
+//! 3. The grid height.
+//! 4. The solution bitmap with one bit per cell, row by row, most significant bit first.
+//!    The last byte is padded with zero bits.
+//!
+//! Grids of which only the clues are known use version 2 of the format instead:
+//! 1. to 3. are the same, followed by
+//! 4. the clues of each row and then each column, each line as the amount of its clues followed by the clues.
+
+use super::{Grid, PictureCell};
+use crate::util;
+use terminal::util::Size;
+
+/// This must be incremented whenever the format changes so that old codes stay loadable.
+const VERSION: u8 = 2;
+
+/// The version of codes containing the solution, which are still created whenever the solution is known.
+const SOLUTION_VERSION: u8 = 1;
+
+impl Grid {
+    /// Encodes the solution of this grid into a code, or the clues if the solution is unknown.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::<u8>::with_capacity(3 + self.solution.len().div_ceil(8));
+        let version = if self.solution.is_empty() {
+            VERSION
+        } else {
+            SOLUTION_VERSION
+        };
+        bytes.push(version);
+        bytes.push(self.size.width as u8);
+        bytes.push(self.size.height as u8);
+
+        if self.solution.is_empty() {
+            for clues in self
+                .horizontal_clues_solutions
+                .iter()
+                .chain(&self.vertical_clues_solutions)
+            {
+                bytes.push(clues.len() as u8);
+                bytes.extend(clues.iter().map(|clue| *clue as u8));
+            }
+        }
+
+        for chunk in self.solution.chunks(8) {
+            let mut byte = 0;
+            for (index, filled) in chunk.iter().enumerate() {
+                if *filled {
+                    byte |= 0b1000_0000 >> index;
+                }
+            }
+            bytes.push(byte);
+        }
+
+        util::base64_encode(&bytes)
+    }
+
+    /// Decodes a code created by [`Grid::encode`] into a new grid.
+    pub fn decode(code: &str) -> Result<Grid, &'static str> {
+        let bytes = util::base64_decode(code.trim()).ok_or("Code is not valid")?;
+
+        let (version, width, height, bitmap) = match bytes.as_slice() {
+            [version, width, height, bitmap @ ..] => (*version, *width, *height, bitmap),
+            _ => return Err("Code is too short"),
+        };
+
+        if version > VERSION {
+            return Err("Code was created by a newer version of yayagram");
+        } else if version == 0 {
+            return Err("Code is not valid");
+        }
+
+        let size = Size {
+            width: width as u16,
+            height: height as u16,
+        };
+        if size.product() == 0
+            || width as u16 > crate::args::MAX_GRID_SIZE
+            || height as u16 > crate::args::MAX_GRID_SIZE
+        {
+            return Err("Code has an invalid grid size");
+        }
+
+        if version == VERSION {
+            return decode_clues(size, bitmap);
+        }
+
+        let cell_count = size.product() as usize;
+        if bitmap.len() != cell_count.div_ceil(8) {
+            return Err("Code is corrupted");
+        }
+
+        let picture: Vec<PictureCell> = (0..cell_count)
+            .map(|index| {
+                if bitmap[index / 8] & (0b1000_0000 >> (index % 8)) != 0 {
+                    PictureCell::Filled
+                } else {
+                    PictureCell::Empty
+                }
+            })
+            .collect();
+
+        Ok(Grid::from_picture(size, &picture))
+    }
+}
+
+fn decode_clues(size: Size, mut bytes: &[u8]) -> Result<Grid, &'static str> {
+    let mut next_clues = || -> Result<Vec<u16>, &'static str> {
+        let (clue_count, rest) = bytes.split_first().ok_or("Code is corrupted")?;
+        let clues = rest
+            .get(..*clue_count as usize)
+            .ok_or("Code is corrupted")?;
+        bytes = &rest[clues.len()..];
+        Ok(clues.iter().map(|clue| *clue as u16).collect())
+    };
+
+    let horizontal_clues_solutions = (0..size.height)
+        .map(|_| next_clues())
+        .collect::<Result<Vec<Vec<u16>>, &'static str>>()?;
+    let vertical_clues_solutions = (0..size.width)
+        .map(|_| next_clues())
+        .collect::<Result<Vec<Vec<u16>>, &'static str>>()?;
+    if !bytes.is_empty() {
+        return Err("Code is corrupted");
+    }
+
+    Grid::from_clues(horizontal_clues_solutions, vertical_clues_solutions)
+        .map_err(|_| "Code is corrupted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(grid: &Grid) {
+        let decoded_grid = Grid::decode(&grid.encode()).unwrap();
+
+        assert_eq!(decoded_grid.size, grid.size);
+        assert_eq!(decoded_grid.solution, grid.solution);
+        assert_eq!(
+            decoded_grid.horizontal_clues_solutions,
+            grid.horizontal_clues_solutions
+        );
+        assert_eq!(
+            decoded_grid.vertical_clues_solutions,
+            grid.vertical_clues_solutions
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        #[rustfmt::skip]
+        assert_round_trip(&Grid::from_lines(&[
+            "1 1 111 1 ",
+            " 1 11 111 ",
+            "1111 11  1",
+            "1 11 1  11",
+            "1  111  11",
+        ]).unwrap());
+
+        #[rustfmt::skip]
+        assert_round_trip(&Grid::from_lines(&[
+            " 111",
+            " 1 1",
+            "11 1",
+            "1 1 ",
+            "1  1",
+            "  1 ",
+        ]).unwrap());
+
+        assert_round_trip(
+            &Grid::random(
+                Size {
+                    width: 99,
+                    height: 99,
+                },
+                &mut crate::rng::Rng::new(0),
+                false,
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_clues_round_trip() {
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            " 111",
+            " 1 1",
+            "11 1",
+            "1 1 ",
+        ]).unwrap();
+        let clues_grid = Grid::from_clues(
+            grid.horizontal_clues_solutions.clone(),
+            grid.vertical_clues_solutions.clone(),
+        )
+        .unwrap();
+
+        let code = clues_grid.encode();
+        assert_ne!(code, grid.encode());
+        assert_round_trip(&clues_grid);
+
+        let bytes = util::base64_decode(&code).unwrap();
+        let truncated_bytes = &bytes[..bytes.len() - 1];
+        assert!(Grid::decode(&util::base64_encode(truncated_bytes)).is_err());
+        let extended_bytes = [bytes.as_slice(), &[0]].concat();
+        assert!(Grid::decode(&util::base64_encode(&extended_bytes)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_codes() {
+        #[rustfmt::skip]
+        let code = Grid::from_lines(&[
+            "1 1 111 1 ",
+            " 1 11 111 ",
+        ]).unwrap()
+        .encode();
+
+        assert!(Grid::decode("").is_err());
+        assert!(Grid::decode("not a code!").is_err());
+        assert!(Grid::decode(&code[..code.len() - 4]).is_err());
+        assert!(Grid::decode(&format!("{}AAAA", code)).is_err());
+
+        let mut bytes = util::base64_decode(&code).unwrap();
+        bytes[0] = VERSION + 1;
+        assert_eq!(
+            Grid::decode(&util::base64_encode(&bytes)).err(),
+            Some("Code was created by a newer version of yayagram")
+        );
+
+        let mut bytes = util::base64_decode(&code).unwrap();
+        bytes[1] = 0;
+        assert!(Grid::decode(&util::base64_encode(&bytes)).is_err());
+    }
+}
@@ -0,0 +1,621 @@
+//! The colors of the cells and the clues, drawn as 24-bit colors on terminals that support them
+//! and as the colors of the 256 color palette otherwise.
+//!
+//! The 256 colors depend on the theme of the terminal and some themes make the cells hard to tell apart,
+//! while 24-bit colors look the same everywhere.
+//!
+//! Besides the default palette there are built-in ones chosen with `--palette`, and a palette file given with
+//! `--palette-file` overrides single colors of it. Each line of the file sets a color, like `crossed = #e69f00`
+//! or `crossed = 214`. Empty lines and lines starting with `#` are skipped.
+//!
+//! The theme is switched while playing and remembered in the data directory. The dark theme dims the empty cells and the clues,
+//! and the high-contrast theme draws with the high-contrast palette regardless of the chosen one, for low-vision players.
+
+use crate::paths;
+use std::{borrow::Cow, env, fs};
+use terminal::util::Color;
+
+/// The file in the data directory that the theme is remembered in.
+const THEME_FILE_NAME: &str = "theme";
+
+/// Returns whether the `COLORTERM` environment variable says that the terminal supports 24-bit colors.
+fn advertises_true_color(colorterm: Option<&str>) -> bool {
+    matches!(colorterm, Some("truecolor" | "24bit"))
+}
+
+/// Returns whether 24-bit colors are drawn, which they are if the terminal says it supports them
+/// unless `no_true_color` overrides that.
+pub fn true_color(no_true_color: bool) -> bool {
+    !no_true_color && advertises_true_color(env::var("COLORTERM").ok().as_deref())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    Light,
+    /// Dims the empty cells and the clues, for playing in the dark.
+    Dark,
+    /// Draws with [`BuiltinPalette::HighContrast`], for low-vision players.
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|theme| theme.name() == name)
+    }
+
+    /// Returns the theme that is switched to after this one, going back to the first one after the last one.
+    pub const fn next(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::HighContrast,
+            Theme::HighContrast => Theme::Light,
+        }
+    }
+}
+
+/// Returns the theme that was last switched to, if any.
+pub fn load_theme() -> Option<Theme> {
+    paths::dir(paths::Kind::Data)
+        .and_then(|dir| fs::read_to_string(dir.join(THEME_FILE_NAME)).ok())
+        .and_then(|content| Theme::from_name(content.trim()))
+}
+
+/// Remembers the theme for the next time.
+pub fn save_theme(theme: Theme) -> Result<(), &'static str> {
+    let dir = paths::dir(paths::Kind::Data).ok_or("Data directory is not accessible")?;
+    paths::create_dir(dir).map_err(|_| "Data directory creation failed")?;
+    fs::write(dir.join(THEME_FILE_NAME), theme.name()).map_err(|_| "Theme couldn't be saved")
+}
+
+/// The 16 colors that come before the color cube in the 256 color palette, as xterm draws them.
+const SYSTEM_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The levels of each component of the 6×6×6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Returns the 24-bit color of the color of the 256 color palette, as xterm draws it.
+fn byte_to_rgb(byte: u8) -> (u8, u8, u8) {
+    match byte {
+        0..=15 => SYSTEM_COLORS[byte as usize],
+        16..=231 => {
+            let index = byte - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let level = 8 + (byte - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Returns the color of the color cube or the gray ramp of the 256 color palette that is closest to the 24-bit color.
+fn rgb_to_byte(rgb: (u8, u8, u8)) -> u8 {
+    let distance = |other: (u8, u8, u8)| {
+        let component = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+        component(rgb.0, other.0) + component(rgb.1, other.1) + component(rgb.2, other.2)
+    };
+    (16..=255)
+        .min_by_key(|byte| distance(byte_to_rgb(*byte)))
+        .unwrap()
+}
+
+/// A color of the palette, with the 24-bit color and the color it falls back to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaletteColor {
+    rgb: (u8, u8, u8),
+    fallback: Color,
+}
+
+impl PaletteColor {
+    const fn new(rgb: (u8, u8, u8), fallback: Color) -> Self {
+        Self { rgb, fallback }
+    }
+
+    /// Makes a color out of a 24-bit color, falling back to the closest color of the 256 color palette.
+    fn from_rgb(rgb: (u8, u8, u8)) -> Self {
+        Self::new(rgb, Color::Byte(rgb_to_byte(rgb)))
+    }
+
+    /// Makes a color out of a color of the 256 color palette, which is also what it looks like as a 24-bit color.
+    fn from_byte(byte: u8) -> Self {
+        Self::new(byte_to_rgb(byte), Color::Byte(byte))
+    }
+
+    /// Parses a color like `#e69f00` as a 24-bit color or like `214` as a color of the 256 color palette.
+    fn parse(string: &str) -> Option<Self> {
+        match string.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 && hex.is_ascii() => {
+                let component = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+                Some(Self::from_rgb((
+                    component(0)?,
+                    component(2)?,
+                    component(4)?,
+                )))
+            }
+            Some(_) => None,
+            None => string.parse::<u8>().ok().map(Self::from_byte),
+        }
+    }
+
+    /// Returns a darker color for when the cell is highlighted.
+    fn darkened(self) -> Self {
+        let darken = |component: u8| (u16::from(component) * 5 / 8) as u8;
+        Self::from_rgb((darken(self.rgb.0), darken(self.rgb.1), darken(self.rgb.2)))
+    }
+
+    /// Returns a slightly dimmer color for the dark theme.
+    ///
+    /// Grays of the 256 color palette become the gray two steps darker, which looks the same in every terminal theme.
+    fn dimmed(self) -> Self {
+        let dim = |component: u8| component.saturating_sub(20);
+        let rgb = (dim(self.rgb.0), dim(self.rgb.1), dim(self.rgb.2));
+        match self.fallback {
+            Color::Byte(byte @ 234..=255) => Self::new(rgb, Color::Byte(byte - 2)),
+            _ => Self::from_rgb(rgb),
+        }
+    }
+
+    /// Returns the color to draw, as a 24-bit color or as the color it falls back to.
+    pub const fn get(self, true_color: bool) -> Color {
+        if true_color {
+            let (r, g, b) = self.rgb;
+            Color::Rgb { r, g, b }
+        } else {
+            self.fallback
+        }
+    }
+}
+
+/// The color of a kind of cell, and its color in the row and the column of the selected cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellColor {
+    pub normal: PaletteColor,
+    pub highlighted: PaletteColor,
+}
+
+impl CellColor {
+    const fn new(normal: PaletteColor, highlighted: PaletteColor) -> Self {
+        Self {
+            normal,
+            highlighted,
+        }
+    }
+
+    /// Makes a color that is darkened when highlighted.
+    fn darkening(normal: PaletteColor) -> Self {
+        Self::new(normal, normal.darkened())
+    }
+
+    pub fn get(self, highlight: bool, true_color: bool) -> Color {
+        if highlight {
+            self.highlighted.get(true_color)
+        } else {
+            self.normal.get(true_color)
+        }
+    }
+
+    fn dimmed(self) -> Self {
+        Self::new(self.normal.dimmed(), self.highlighted.dimmed())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    pub filled: CellColor,
+    pub maybed: CellColor,
+    pub crossed: CellColor,
+    pub measured: CellColor,
+    /// Every 5 cells, empty cells alternate between these to make the grid easier to count.
+    pub empty: [CellColor; 2],
+    pub clue: PaletteColor,
+    /// The color of the clues that are satisfied.
+    pub satisfied_clue: PaletteColor,
+    /// The color of the clues of rows and columns that contradict them.
+    pub contradicted_clue: PaletteColor,
+    /// The color of the clues of a row or column pointed out by a hint.
+    pub hinted_clue: PaletteColor,
+    /// The color of the clue that the block of filled cells of the selected cell belongs to, if it's certain.
+    pub matched_clue: PaletteColor,
+    /// The background of every other row and column of clues.
+    pub clue_background: PaletteColor,
+    /// The background of the clues of the row and the column of the selected cell.
+    pub crosshair_clue_background: PaletteColor,
+    /// Whether satisfied clues are also struck through, so that they don't only differ by their color,
+    /// and the dark theme only dims the empty cells, keeping the contrast of the clues.
+    pub high_contrast: bool,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            filled: CellColor::new(
+                PaletteColor::new((236, 236, 236), Color::White),
+                PaletteColor::new((172, 172, 172), Color::Gray),
+            ),
+            maybed: CellColor::new(
+                PaletteColor::new((74, 132, 232), Color::Blue),
+                PaletteColor::new((42, 82, 162), Color::DarkBlue),
+            ),
+            crossed: CellColor::new(
+                PaletteColor::new((222, 72, 72), Color::Red),
+                PaletteColor::new((150, 42, 42), Color::DarkRed),
+            ),
+            measured: CellColor::new(
+                PaletteColor::new((86, 194, 104), Color::Green),
+                PaletteColor::new((44, 122, 62), Color::DarkGreen),
+            ),
+            empty: [
+                CellColor::new(
+                    PaletteColor::new((88, 90, 96), Color::Byte(240)),
+                    PaletteColor::new((56, 58, 64), Color::Byte(237)),
+                ),
+                CellColor::new(
+                    PaletteColor::new((66, 68, 74), Color::Byte(238)),
+                    PaletteColor::new((38, 40, 46), Color::Byte(235)),
+                ),
+            ],
+            clue: PaletteColor::new((192, 192, 192), Color::Gray),
+            satisfied_clue: PaletteColor::new((110, 110, 110), Color::DarkGray),
+            contradicted_clue: PaletteColor::new((222, 72, 72), Color::Red),
+            hinted_clue: PaletteColor::new((232, 200, 64), Color::Yellow),
+            matched_clue: PaletteColor::new((96, 206, 232), Color::Cyan),
+            clue_background: PaletteColor::new((60, 62, 68), Color::Byte(238)),
+            crosshair_clue_background: PaletteColor::new((36, 56, 88), Color::Byte(17)),
+            high_contrast: false,
+        }
+    }
+}
+
+impl Palette {
+    /// Returns the palette to draw with in the theme.
+    pub fn themed(&self, theme: Theme) -> Self {
+        match theme {
+            Theme::Light => *self,
+            Theme::Dark => self.dark(),
+            Theme::HighContrast => BuiltinPalette::HighContrast.palette(),
+        }
+    }
+
+    /// Returns the palette of the dark theme, which has dimmer empty cells, clues and clue backgrounds.
+    /// The colors of the other cells stay the same so that they still stand out.
+    fn dark(&self) -> Self {
+        if self.high_contrast {
+            return Self {
+                empty: [self.empty[0].dimmed(), self.empty[1].dimmed()],
+                ..*self
+            };
+        }
+        Self {
+            empty: [self.empty[0].dimmed(), self.empty[1].dimmed()],
+            clue: self.clue.dimmed(),
+            satisfied_clue: self.satisfied_clue.dimmed(),
+            clue_background: self.clue_background.dimmed(),
+            crosshair_clue_background: self.crosshair_clue_background.dimmed(),
+            ..*self
+        }
+    }
+
+    /// Sets colors as the lines of a palette file say, returning the error and the number of the line if one is invalid.
+    pub fn apply_file(&mut self, content: &str) -> Result<(), (usize, Cow<'static, str>)> {
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            self.apply_line(line).map_err(|err| (index + 1, err))?;
+        }
+        Ok(())
+    }
+
+    fn cell_color_mut(&mut self, name: &str) -> Option<&mut CellColor> {
+        match name {
+            "filled" => Some(&mut self.filled),
+            "maybed" => Some(&mut self.maybed),
+            "crossed" => Some(&mut self.crossed),
+            "measured" => Some(&mut self.measured),
+            "empty" => Some(&mut self.empty[0]),
+            "alternate-empty" => Some(&mut self.empty[1]),
+            _ => None,
+        }
+    }
+
+    fn apply_line(&mut self, line: &str) -> Result<(), Cow<'static, str>> {
+        let (name, color) = line
+            .split_once('=')
+            .ok_or("Expected a name and a color like \"crossed = 214\"")?;
+        let (name, color) = (name.trim(), color.trim());
+        let color = PaletteColor::parse(color).ok_or_else(|| {
+            format!(
+                "\"{}\" is not a color like #e69f00 or a number up to 255",
+                color
+            )
+        })?;
+
+        if let Some(highlighted_name) = name.strip_suffix("-highlighted") {
+            if let Some(cell_color) = self.cell_color_mut(highlighted_name) {
+                cell_color.highlighted = color;
+                return Ok(());
+            }
+        } else if let Some(cell_color) = self.cell_color_mut(name) {
+            // The highlighted color can be set on its own after this one
+            *cell_color = CellColor::darkening(color);
+            return Ok(());
+        }
+
+        let color_mut = match name {
+            "clue" => &mut self.clue,
+            "satisfied-clue" => &mut self.satisfied_clue,
+            "contradicted-clue" => &mut self.contradicted_clue,
+            "hinted-clue" => &mut self.hinted_clue,
+            "matched-clue" => &mut self.matched_clue,
+            "clue-background" => &mut self.clue_background,
+            "crosshair-clue-background" => &mut self.crosshair_clue_background,
+            _ => return Err(format!("\"{}\" is not a color of the palette", name).into()),
+        };
+        *color_mut = color;
+        Ok(())
+    }
+}
+
+/// The palettes that can be chosen with `--palette`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BuiltinPalette {
+    Default,
+    /// Tells crossed out and measured cells apart without relying on red and green.
+    Deuteranopia,
+    /// Sets the cells apart from each other and the background as much as possible.
+    HighContrast,
+}
+
+impl BuiltinPalette {
+    pub const ALL: [BuiltinPalette; 3] = [
+        BuiltinPalette::Default,
+        BuiltinPalette::Deuteranopia,
+        BuiltinPalette::HighContrast,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            BuiltinPalette::Default => "default",
+            BuiltinPalette::Deuteranopia => "deuteranopia",
+            BuiltinPalette::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|palette| palette.name() == name)
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            BuiltinPalette::Default => Palette::default(),
+            // Blue, orange and yellow from the Okabe-Ito palette remain distinct with any kind of red-green color blindness
+            BuiltinPalette::Deuteranopia => Palette {
+                maybed: CellColor::darkening(PaletteColor::from_rgb((0, 114, 178))),
+                crossed: CellColor::darkening(PaletteColor::from_rgb((230, 159, 0))),
+                measured: CellColor::darkening(PaletteColor::from_rgb((240, 228, 66))),
+                contradicted_clue: PaletteColor::from_rgb((230, 159, 0)),
+                hinted_clue: PaletteColor::from_rgb((86, 180, 233)),
+                matched_clue: PaletteColor::from_rgb((204, 121, 167)),
+                ..Palette::default()
+            },
+            // The empty cells alternate between black and a mid gray, and highlighted empty cells turn blue and teal instead of darker,
+            // which black can't get. The clues are white on black, and satisfied ones are cyan and struck through.
+            BuiltinPalette::HighContrast => Palette {
+                filled: CellColor::new(PaletteColor::from_byte(231), PaletteColor::from_byte(250)),
+                maybed: CellColor::new(PaletteColor::from_byte(27), PaletteColor::from_byte(25)),
+                crossed: CellColor::new(PaletteColor::from_byte(196), PaletteColor::from_byte(124)),
+                measured: CellColor::new(
+                    PaletteColor::from_byte(226),
+                    PaletteColor::from_byte(142),
+                ),
+                empty: [
+                    CellColor::new(PaletteColor::from_byte(232), PaletteColor::from_byte(19)),
+                    CellColor::new(PaletteColor::from_byte(242), PaletteColor::from_byte(30)),
+                ],
+                clue: PaletteColor::from_byte(231),
+                satisfied_clue: PaletteColor::from_byte(51),
+                contradicted_clue: PaletteColor::from_byte(196),
+                hinted_clue: PaletteColor::from_byte(226),
+                matched_clue: PaletteColor::from_byte(201),
+                clue_background: PaletteColor::from_byte(16),
+                crosshair_clue_background: PaletteColor::from_byte(19),
+                high_contrast: true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advertises_true_color() {
+        assert!(advertises_true_color(Some("truecolor")));
+        assert!(advertises_true_color(Some("24bit")));
+        assert!(!advertises_true_color(Some("yes")));
+        assert!(!advertises_true_color(None));
+    }
+
+    #[test]
+    fn test_color() {
+        let filled = Palette::default().filled.normal;
+        assert_eq!(filled.get(false), Color::White);
+        assert_eq!(
+            filled.get(true),
+            Color::Rgb {
+                r: 236,
+                g: 236,
+                b: 236
+            }
+        );
+    }
+
+    #[test]
+    fn test_byte_to_rgb() {
+        assert_eq!(byte_to_rgb(9), (255, 0, 0));
+        assert_eq!(byte_to_rgb(16), (0, 0, 0));
+        assert_eq!(byte_to_rgb(214), (255, 175, 0));
+        assert_eq!(byte_to_rgb(238), (68, 68, 68));
+        for byte in 16..=255 {
+            assert_eq!(rgb_to_byte(byte_to_rgb(byte)), byte);
+        }
+        assert_eq!(rgb_to_byte((230, 159, 0)), 178);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            PaletteColor::parse("#e69f00"),
+            Some(PaletteColor::new((230, 159, 0), Color::Byte(178)))
+        );
+        assert_eq!(
+            PaletteColor::parse("214"),
+            Some(PaletteColor::new((255, 175, 0), Color::Byte(214)))
+        );
+        assert_eq!(PaletteColor::parse("#e69f0"), None);
+        assert_eq!(PaletteColor::parse("256"), None);
+        assert_eq!(PaletteColor::parse("orange"), None);
+    }
+
+    #[test]
+    fn test_apply_file() {
+        let mut palette = Palette::default();
+        palette
+            .apply_file(
+                "# Crossed out cells in orange\n\
+                 crossed = #e69f00\n\
+                 \n\
+                 measured = 33\n\
+                 measured-highlighted = 17\n\
+                 clue-background=236\n",
+            )
+            .unwrap();
+        assert_eq!(palette.crossed.normal.get(false), Color::Byte(178));
+        // The highlighted color is darker unless it is set too
+        assert_eq!(
+            palette.crossed.highlighted,
+            PaletteColor::from_rgb((143, 99, 0))
+        );
+        assert_eq!(palette.measured.highlighted.get(false), Color::Byte(17));
+        assert_eq!(palette.clue_background.get(false), Color::Byte(236));
+        assert_eq!(palette.filled, Palette::default().filled);
+
+        assert_eq!(
+            Palette::default()
+                .apply_file("filled = 1\nfiled = 2")
+                .unwrap_err()
+                .0,
+            2
+        );
+        assert!(Palette::default().apply_file("filled 1").is_err());
+        assert!(Palette::default()
+            .apply_file("clue-highlighted = 1")
+            .is_err());
+    }
+
+    #[test]
+    fn test_dark() {
+        let palette = Palette::default();
+        let dark = palette.dark();
+        assert_eq!(dark.empty[0].normal.get(false), Color::Byte(238));
+        assert_eq!(dark.empty[1].highlighted.get(false), Color::Byte(233));
+        assert_eq!(
+            dark.clue_background.get(true),
+            Color::Rgb {
+                r: 40,
+                g: 42,
+                b: 48
+            }
+        );
+        assert_eq!(dark.satisfied_clue, PaletteColor::from_rgb((90, 90, 90)));
+        assert_eq!(dark.filled, palette.filled);
+    }
+
+    #[test]
+    fn test_theme() {
+        for theme in Theme::ALL.iter().copied() {
+            assert_eq!(Theme::from_name(theme.name()), Some(theme));
+            assert_eq!(theme.next().next().next(), theme);
+        }
+        assert_eq!(Theme::from_name("Dark"), None);
+
+        let palette = BuiltinPalette::Deuteranopia.palette();
+        assert_eq!(palette.themed(Theme::Light), palette);
+        assert_eq!(palette.themed(Theme::Dark), palette.dark());
+        assert_eq!(
+            palette.themed(Theme::HighContrast),
+            BuiltinPalette::HighContrast.palette()
+        );
+    }
+
+    #[test]
+    fn test_builtin_palette() {
+        for palette in BuiltinPalette::ALL.iter().copied() {
+            assert_eq!(BuiltinPalette::from_name(palette.name()), Some(palette));
+        }
+        assert_eq!(BuiltinPalette::Default.palette(), Palette::default());
+
+        // Crossed out and measured cells are neither red nor green anymore
+        let palette = BuiltinPalette::Deuteranopia.palette();
+        assert_ne!(palette.crossed, Palette::default().crossed);
+        assert_ne!(palette.measured, Palette::default().measured);
+    }
+
+    #[test]
+    fn test_high_contrast() {
+        // How far apart two colors are, from 0 to 765
+        let contrast = |a: PaletteColor, b: PaletteColor| {
+            let (a, b) = (a.rgb, b.rgb);
+            a.0.abs_diff(b.0) as u16 + a.1.abs_diff(b.1) as u16 + a.2.abs_diff(b.2) as u16
+        };
+
+        let palette = BuiltinPalette::HighContrast.palette();
+        for palette in [&palette, &palette.dark()] {
+            let [empty, alternate_empty] = palette.empty;
+            assert!(contrast(empty.normal, alternate_empty.normal) >= 200);
+            assert!(contrast(empty.normal, empty.highlighted) >= 120);
+            assert!(contrast(alternate_empty.normal, alternate_empty.highlighted) >= 120);
+            assert!(contrast(palette.satisfied_clue, palette.clue_background) >= 400);
+            assert!(contrast(palette.clue, palette.crosshair_clue_background) >= 400);
+        }
+        assert!(palette.high_contrast);
+        assert!(!Palette::default().high_contrast);
+    }
+}
@@ -0,0 +1,94 @@
+//! The optional check for rows and columns that the filled and crossed out cells made impossible to solve, toggled with `V`.
+//!
+//! The clues of such lines are drawn in a warning color so that a wrongly crossed out cell doesn't leave the player stuck.
+//! Lines are only solved again once their clues or known cells changed, which are usually just the row and the column
+//! of the last placement, so that the check stays cheap.
+
+use super::{Clue, Grid};
+use crate::solver::{self, LineDeduction};
+
+#[derive(Default)]
+pub struct Contradictions {
+    /// The clues and known cells of every row and then every column as of the last check, with whether they contradict.
+    lines: Vec<(Vec<Clue>, Vec<LineDeduction>, bool)>,
+}
+
+impl Contradictions {
+    /// Checks the lines that changed since the last check.
+    pub fn update(&mut self, grid: &Grid) {
+        let line_count = (grid.size.width + grid.size.height) as usize;
+        if self.lines.len() != line_count {
+            // The grid was switched for one of another size
+            self.lines = vec![(Vec::new(), Vec::new(), false); line_count];
+        }
+
+        for ((clues, points), (checked_clues, checked_cells, contradicted)) in
+            solver::lines(grid).zip(&mut self.lines)
+        {
+            let line: Vec<_> = points.iter().map(|point| grid.get_cell(*point)).collect();
+            let known_cells: Vec<LineDeduction> =
+                line.iter().map(|cell| solver::input(cell.kind())).collect();
+            if *checked_clues == clues && *checked_cells == known_cells {
+                continue;
+            }
+
+            *contradicted = solver::solve_line(clues, &line).is_none();
+            *checked_clues = clues.to_vec();
+            *checked_cells = known_cells;
+        }
+    }
+
+    /// Returns whether the row contradicts its clues as of the last check.
+    pub fn is_row_contradicted(&self, y: u16) -> bool {
+        self.lines.get(y as usize).is_some_and(|line| line.2)
+    }
+
+    /// Returns whether the column contradicts its clues as of the last check.
+    pub fn is_column_contradicted(&self, grid: &Grid, x: u16) -> bool {
+        self.lines
+            .get((grid.size.height + x) as usize)
+            .is_some_and(|line| line.2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+
+    #[test]
+    fn test_update() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11 ",
+            " 1 ",
+        ]).unwrap();
+        let mut contradictions = Contradictions::default();
+        contradictions.update(&grid);
+        assert!(!contradictions.is_row_contradicted(0));
+
+        // The row of 2 in 3 cells must have its middle filled
+        grid.cells[1] = Cell::Crossed;
+        contradictions.update(&grid);
+        assert!(contradictions.is_row_contradicted(0));
+        assert!(!contradictions.is_row_contradicted(1));
+        // The column of 2 in 2 cells can't have any crossed out
+        assert!(contradictions.is_column_contradicted(&grid, 1));
+        assert!(!contradictions.is_column_contradicted(&grid, 0));
+
+        // Maybed cells are no constraint
+        grid.cells[1] = Cell::Maybed;
+        contradictions.update(&grid);
+        assert!(!contradictions.is_row_contradicted(0));
+        assert!(!contradictions.is_column_contradicted(&grid, 1));
+
+        // Too many filled cells
+        grid.cells[3] = Cell::Filled;
+        grid.cells[5] = Cell::Filled;
+        contradictions.update(&grid);
+        assert!(contradictions.is_row_contradicted(1));
+        grid.cells[5] = Cell::Empty;
+        contradictions.update(&grid);
+        assert!(!contradictions.is_row_contradicted(1));
+    }
+}
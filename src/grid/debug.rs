@@ -1,6 +1,10 @@
-use super::{builder::Builder, Cell, Grid};
+use super::{Cell, Grid};
 use std::fmt;
-use terminal::{util::Point, Terminal};
+#[cfg(debug_assertions)]
+use {
+    super::builder::Builder,
+    terminal::{util::Point, Terminal},
+};
 
 impl fmt::Debug for Grid {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -38,14 +42,21 @@ impl fmt::Debug for Grid {
 }
 
 /// NOTE: A hack to allow keeping this variable out of release builds.
+#[cfg(debug_assertions)]
 static mut LAST_DEBUG_GRID_DISPLAY_LEN: usize = 0;
 
+#[cfg(debug_assertions)]
 pub fn display(terminal: &mut Terminal, builder: &mut Builder) {
     terminal.save_cursor_point();
 
     // This length ensures that the text does not touch the grid.
     // `builder.point.x` will be the point of the first cell.
-    let max_length = (builder.point.x - builder.grid.max_clues_size.width) as usize;
+    let max_length = (builder.point.x
+        - builder.leading_clues_size().width
+        - builder.settings.border_width()) as usize;
+    if max_length == 0 {
+        return;
+    }
 
     let clear_spaces = &" ".repeat(unsafe { LAST_DEBUG_GRID_DISPLAY_LEN });
     draw_chunks(terminal, clear_spaces, max_length);
@@ -60,6 +71,7 @@ pub fn display(terminal: &mut Terminal, builder: &mut Builder) {
     terminal.restore_cursor_point();
 }
 
+#[cfg(debug_assertions)]
 fn draw_chunks(terminal: &mut Terminal, string: &str, max_length: usize) {
     for (index, line) in string.as_bytes().chunks(max_length).enumerate() {
         terminal.set_cursor(Point {
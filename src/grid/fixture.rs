@@ -0,0 +1,379 @@
+//! Test fixtures that can be captured from within the game to reproduce a state of the grid:
+//!
+//! ```text
+//! // A yayagram test fixture
+//! solution = [
+//!     "11 1",
+//!     "1  1",
+//! ];
+//! cells = [
+//!     "11X.",
+//!     "..R?",
+//! ];
+//! rows = [
+//!     "2 1",
+//!     "1 1",
+//! ];
+//! columns = [
+//!     "2",
+//!     "1",
+//!     "",
+//!     "2",
+//! ];
+//! history = [
+//!     "history 1",
+//!     "set 2 0 X",
+//! ];
+//! ```
+//!
+//! Every section is an array of string literals so that it can also be pasted into a test as is.
+//! The solution is written like for `Grid::from_lines` and is empty if only the clues are known.
+//! The cells are written like in a save and the history like in a save too.
+//! The rows and columns are the expected clue solutions of each line.
+//!
+//! In debug builds, `Z` captures the grid to a file that can be put into `tests/fixtures` and loaded with `load`.
+
+use super::Grid;
+use crate::save::{self, history};
+use std::fmt::Write as _;
+#[cfg(test)]
+use {
+    super::Cell,
+    crate::editor::LoadError,
+    std::{fs, path::Path},
+};
+#[cfg(debug_assertions)]
+use {crate::util, std::io::Write as _};
+
+/// At most this many operations of the history are captured.
+const MAX_CAPTURED_OPERATIONS: usize = 50;
+
+#[cfg(test)]
+pub struct Fixture {
+    pub grid: Grid,
+    pub horizontal_clues_solutions: Vec<Vec<u16>>,
+    pub vertical_clues_solutions: Vec<Vec<u16>>,
+}
+
+fn write_section(string: &mut String, name: &str, lines: impl Iterator<Item = String>) {
+    writeln!(string, "{} = [", name).unwrap();
+    for line in lines {
+        writeln!(string, "    \"{}\",", line).unwrap();
+    }
+    string.push_str("];\n");
+}
+
+fn clues_line(clues: &[u16]) -> String {
+    clues
+        .iter()
+        .map(|clue| clue.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Captures the grid as it is, no matter what state it is in.
+pub fn capture(grid: &Grid) -> String {
+    let mut string = String::from("// A yayagram test fixture\n");
+    let width = grid.size.width as usize;
+
+    write_section(
+        &mut string,
+        "solution",
+        grid.solution.chunks(width).map(|row| {
+            row.iter()
+                .map(|filled| if *filled { '1' } else { ' ' })
+                .collect()
+        }),
+    );
+    write_section(
+        &mut string,
+        "cells",
+        grid.cells
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| save::cell_char(*cell)).collect()),
+    );
+    write_section(
+        &mut string,
+        "rows",
+        grid.horizontal_clues_solutions
+            .iter()
+            .map(|clues| clues_line(clues)),
+    );
+    write_section(
+        &mut string,
+        "columns",
+        grid.vertical_clues_solutions
+            .iter()
+            .map(|clues| clues_line(clues)),
+    );
+
+    let mut history = String::new();
+    history::write_limited(&mut history, grid, MAX_CAPTURED_OPERATIONS);
+    write_section(
+        &mut string,
+        "history",
+        history.lines().map(|line| line.to_string()),
+    );
+
+    string
+}
+
+/// Saves a capture of the grid as a new file, returning its filename.
+#[cfg(debug_assertions)]
+pub fn save(grid: &Grid) -> Result<String, &'static str> {
+    let (filename, mut file) = util::create_numbered_file("fixture")?;
+
+    if file.write_all(capture(grid).as_bytes()).is_err() {
+        return Err("Fixture saving error");
+    }
+
+    Ok(filename)
+}
+
+#[cfg(test)]
+fn error(message: &'static str, line_number: usize) -> LoadError {
+    LoadError {
+        message,
+        line_number: Some(line_number),
+    }
+}
+
+/// A section's lines together with the line number of the first one.
+#[cfg(test)]
+struct Section<'a> {
+    lines: Vec<&'a str>,
+    first_line_number: usize,
+}
+
+#[cfg(test)]
+fn parse_section<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    name: &str,
+) -> Result<Section<'a>, LoadError> {
+    let (line_index, line) = lines
+        .find(|(_, line)| !line.trim().is_empty() && !line.starts_with("//"))
+        .ok_or(LoadError {
+            message: "expected section",
+            line_number: None,
+        })?;
+    if line.strip_prefix(name) != Some(" = [") {
+        return Err(error("expected section", line_index + 1));
+    }
+
+    let first_line_number = line_index + 2;
+    let mut section_lines = Vec::<&str>::new();
+    for (line_index, line) in lines {
+        let line = line.trim();
+        if line == "];" {
+            return Ok(Section {
+                lines: section_lines,
+                first_line_number,
+            });
+        }
+
+        let string = line
+            .strip_suffix(',')
+            .unwrap_or(line)
+            .strip_prefix('"')
+            .and_then(|line| line.strip_suffix('"'))
+            .ok_or_else(|| error("expected string", line_index + 1))?;
+        section_lines.push(string);
+    }
+
+    Err(LoadError {
+        message: "expected end of section",
+        line_number: None,
+    })
+}
+
+#[cfg(test)]
+fn parse_clues(section: &Section) -> Result<Vec<Vec<u16>>, LoadError> {
+    section
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            line.split_whitespace()
+                .map(|clue| clue.parse::<u16>())
+                .collect::<Result<Vec<u16>, _>>()
+                .map_err(|_| error("invalid clue", section.first_line_number + index))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub fn load(path: &Path) -> Result<Fixture, LoadError> {
+    let content = fs::read_to_string(path).map_err(|_| LoadError {
+        message: "fixture reading error",
+        line_number: None,
+    })?;
+    let mut lines = content.lines().enumerate();
+
+    let solution = parse_section(&mut lines, "solution")?;
+    let cells = parse_section(&mut lines, "cells")?;
+    let horizontal_clues_solutions = parse_clues(&parse_section(&mut lines, "rows")?)?;
+    let vertical_clues_solutions = parse_clues(&parse_section(&mut lines, "columns")?)?;
+
+    let mut grid = if solution.lines.is_empty() {
+        // Only the clues are known
+        Grid::from_clues(
+            horizontal_clues_solutions.clone(),
+            vertical_clues_solutions.clone(),
+        )
+        .map_err(|message| LoadError {
+            message,
+            line_number: None,
+        })?
+    } else {
+        Grid::from_lines(&solution.lines).map_err(|err| LoadError {
+            message: err.message,
+            line_number: Some(solution.first_line_number + err.line_number.unwrap_or(1) - 1),
+        })?
+    };
+
+    if cells.lines.len() != grid.size.height as usize {
+        return Err(error("wrong amount of cells", cells.first_line_number));
+    }
+    for (y, line) in cells.lines.iter().enumerate() {
+        let line_number = cells.first_line_number + y;
+
+        let row = line
+            .chars()
+            .map(save::char_cell)
+            .collect::<Option<Vec<Cell>>>()
+            .ok_or_else(|| error("expected '.', '1', 'X', '?' or 'R'", line_number))?;
+        if row.len() != grid.size.width as usize {
+            return Err(error("wrong amount of cells", line_number));
+        }
+
+        let start = y * grid.size.width as usize;
+        grid.cells[start..start + row.len()].copy_from_slice(&row);
+    }
+
+    let history = parse_section(&mut lines, "history")?;
+    grid.undo_redo_buffer = history::parse(
+        history.lines.iter().copied(),
+        &grid.size,
+        history.first_line_number,
+    )?;
+
+    Ok(Fixture {
+        grid,
+        horizontal_clues_solutions,
+        vertical_clues_solutions,
+    })
+}
+
+/// Returns the path of a fixture in the fixtures directory of the tests.
+#[cfg(test)]
+pub fn path(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Returns the grid of the example above, which is shared by the tests of what is written for a grid.
+#[cfg(test)]
+#[rustfmt::skip]
+pub fn example() -> Grid {
+    let mut grid = Grid::from_lines(&[
+        "11 1",
+        "1  1",
+    ]).unwrap();
+    grid.cells[0] = Cell::Filled;
+    grid.cells[1] = Cell::Filled;
+    grid.cells[2] = Cell::Crossed;
+    grid.cells[6] = Cell::Measured(Some(3));
+    grid.cells[7] = Cell::Maybed;
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::undo_redo_buffer::Operation;
+    use terminal::util::Point;
+
+    #[test]
+    fn test_round_trip() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        for index in 0..60 {
+            grid.undo_redo_buffer.push(Operation::SetCell {
+                point: Point {
+                    x: index % 4,
+                    y: index / 4 % 2,
+                },
+                cell: if index % 2 == 0 {
+                    Cell::Filled
+                } else {
+                    Cell::Crossed
+                },
+            });
+        }
+        grid.rebuild();
+        // A cell that is not part of the history, like in the middle of placing cells
+        grid.cells[3] = Cell::Maybed;
+
+        let path =
+            std::env::temp_dir().join(format!("yayagram-fixture-{}.fixture", std::process::id()));
+        fs::write(&path, capture(&grid)).unwrap();
+        let fixture = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut loaded_grid = fixture.grid;
+        assert_eq!(loaded_grid.solution, grid.solution);
+        assert_eq!(loaded_grid.cells, grid.cells);
+        assert_eq!(
+            fixture.horizontal_clues_solutions,
+            grid.horizontal_clues_solutions
+        );
+        assert_eq!(
+            fixture.vertical_clues_solutions,
+            grid.vertical_clues_solutions
+        );
+
+        // The operation tail leads to the same cells as the whole history, apart from the maybed cell
+        assert_eq!(
+            loaded_grid.undo_redo_buffer.buffer.len(),
+            MAX_CAPTURED_OPERATIONS
+        );
+        grid.rebuild();
+        loaded_grid.rebuild();
+        assert_eq!(loaded_grid.cells, grid.cells);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let path = std::env::temp_dir().join(format!(
+            "yayagram-invalid-fixture-{}.fixture",
+            std::process::id()
+        ));
+        let fixture = capture(&Grid::from_lines(&["11 1", "1  1"]).unwrap());
+
+        let line_number = |content: &str| {
+            fs::write(&path, content).unwrap();
+            load(&path).err().unwrap().line_number
+        };
+        assert_eq!(
+            line_number(&fixture.replace("\"11 1\"", "\"1x 1\"")),
+            Some(3)
+        );
+        assert_eq!(
+            line_number(&fixture.replace("\"....\"", "\"...\"")),
+            Some(7)
+        );
+        assert_eq!(line_number(&fixture.replace("rows", "lines")), Some(10));
+        assert_eq!(line_number(&fixture.replace("\"2 1\"", "2 1")), Some(11));
+        assert_eq!(
+            line_number(&fixture.replace("\"2 1\"", "\"2 a\"")),
+            Some(11)
+        );
+        assert_eq!(
+            line_number(&fixture.replace("history 0", "history 1")),
+            Some(21)
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+}
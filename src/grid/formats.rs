@@ -0,0 +1,79 @@
+//! Loads grids from the file formats of other nonogram programs.
+
+pub mod olsak;
+
+use super::Grid;
+use crate::{
+    editor::{self, LoadError},
+    solver::Solutions,
+};
+use std::path::Path;
+
+/// Whether the file is a grid file that can be loaded with [`load`], going by its extension.
+pub fn is_grid_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("yaya" | "g")
+    )
+}
+
+/// Loads the content of a `.yaya` file or of a file in one of the other formats, going by the filename's extension.
+pub fn load(filename: &str, content: &str) -> Result<Grid, LoadError> {
+    if Path::new(filename).extension() == Some("g".as_ref()) {
+        olsak::parse(content)
+    } else {
+        editor::load_grid(content)
+    }
+}
+
+/// A problem with the clues of an imported puzzle that still lets it be played.
+///
+/// Clues that can't be played at all, e.g. because they don't fit into their lines, aren't loaded in the first place.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Problem {
+    NoSolution,
+    MultipleSolutions,
+}
+
+impl Problem {
+    pub const fn message(self) -> &'static str {
+        match self {
+            Self::NoSolution => "This puzzle appears to have no solution",
+            Self::MultipleSolutions => "This puzzle appears to have multiple solutions",
+        }
+    }
+}
+
+/// Checks whether the clues of an imported puzzle have exactly one solution, going by [`crate::solver::solutions`].
+///
+/// Clues that are too hard to find that out for pass as they are most likely fine.
+pub fn check(solutions: Solutions) -> Option<Problem> {
+    match solutions {
+        Solutions::None => Some(Problem::NoSolution),
+        Solutions::Multiple => Some(Problem::MultipleSolutions),
+        Solutions::Unique | Solutions::Unknown => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    #[test]
+    fn test_check() {
+        let grid = load("unique.g", ": rows\n2\n1\n: columns\n2\n1\n").unwrap();
+        assert_eq!(check(solver::solutions(&grid)), None);
+
+        // Both diagonals fit
+        let grid = load("diagonal.g", ": rows\n1\n1\n: columns\n1\n1\n").unwrap();
+        assert_eq!(
+            check(solver::solutions(&grid)),
+            Some(Problem::MultipleSolutions)
+        );
+
+        // The clues add up and fit, but the first row can only be placed across the empty column
+        let grid = load("none.g", ": rows\n1 1\n1\n: columns\n2\n1\n0\n").unwrap();
+        assert_eq!(check(solver::solutions(&grid)), Some(Problem::NoSolution));
+    }
+}
@@ -0,0 +1,146 @@
+//! The clue files of Mirek Olšák's nonogram solver, usually ending with `.g`:
+//!
+//! ```text
+//! # A comment
+//! : rows
+//! 2 1
+//! 0
+//! : columns
+//! 1
+//! 1, 1
+//! ```
+//!
+//! Every line after a section header holds the clues of one row or column, separated by spaces or commas.
+//! A line without any filled cells is written as `0`. Comments and blank lines are ignored.
+
+use crate::{editor::LoadError, grid::Grid};
+
+fn error(message: &'static str, line_number: usize) -> LoadError {
+    LoadError {
+        message,
+        line_number: Some(line_number),
+    }
+}
+
+fn parse_clues(line: &str, line_number: usize) -> Result<Vec<u16>, LoadError> {
+    let clues = line
+        .split(|char: char| char.is_whitespace() || char == ',')
+        .filter(|clue| !clue.is_empty())
+        .map(|clue| clue.parse::<u16>())
+        .collect::<Result<Vec<u16>, _>>()
+        .map_err(|_| error("invalid clue", line_number))?;
+
+    match clues.as_slice() {
+        [0] => Ok(Vec::new()),
+        clues if clues.contains(&0) => Err(error("invalid clue", line_number)),
+        _ => Ok(clues),
+    }
+}
+
+/// Parses the clues of a grid. The solution is unknown.
+pub fn parse(str: &str) -> Result<Grid, LoadError> {
+    // The rows and the columns
+    let mut sections: [Option<Vec<Vec<u16>>>; 2] = [None, None];
+    let mut section_index: Option<usize> = None;
+
+    for (index, line) in str.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix(':') {
+            let index = match name.trim() {
+                "rows" => 0,
+                "columns" => 1,
+                _ => return Err(error("expected \": rows\" or \": columns\"", line_number)),
+            };
+            if sections[index].is_some() {
+                return Err(error("section appears twice", line_number));
+            }
+            sections[index] = Some(Vec::new());
+            section_index = Some(index);
+            continue;
+        }
+
+        let index = section_index.ok_or_else(|| error("expected section", line_number))?;
+        if let Some(clues_solutions) = &mut sections[index] {
+            clues_solutions.push(parse_clues(line, line_number)?);
+        }
+    }
+
+    match sections {
+        [Some(horizontal_clues_solutions), Some(vertical_clues_solutions)] => {
+            Grid::from_clues(horizontal_clues_solutions, vertical_clues_solutions).map_err(
+                |message| LoadError {
+                    message,
+                    line_number: None,
+                },
+            )
+        }
+        _ => Err(LoadError {
+            message: "expected \": rows\" and \": columns\"",
+            line_number: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::fixture;
+    use std::fs;
+
+    fn assert_same_clues(name: &str, lines: &[&str]) {
+        let grid = parse(&fs::read_to_string(fixture::path(name)).unwrap()).unwrap();
+        let expected_grid = Grid::from_lines(lines).unwrap();
+
+        assert_eq!(grid.size, expected_grid.size);
+        assert_eq!(
+            grid.horizontal_clues_solutions,
+            expected_grid.horizontal_clues_solutions
+        );
+        assert_eq!(
+            grid.vertical_clues_solutions,
+            expected_grid.vertical_clues_solutions
+        );
+        assert_eq!(grid.max_clues_size, expected_grid.max_clues_size);
+    }
+
+    #[test]
+    fn test_empty_row() {
+        #[rustfmt::skip]
+        assert_same_clues("empty_row.g", &[
+            "11 1",
+            "    ",
+            "1 11",
+        ]);
+    }
+
+    #[test]
+    fn test_wide_clues() {
+        #[rustfmt::skip]
+        assert_same_clues("wide_clues.g", &[
+            "111111111111",
+            "11111111111 ",
+            "1 1 1 1 1 1 ",
+            "1          1",
+        ]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let line_number = |str: &str| parse(str).unwrap_err().line_number;
+
+        assert_eq!(line_number("1\n: rows\n1\n: columns\n1"), Some(1));
+        assert_eq!(line_number(": rows\n1\n: lines\n1"), Some(3));
+        assert_eq!(line_number(": rows\n1\n# 1 a\n: columns\n1 a"), Some(5));
+        assert_eq!(line_number(": rows\n1 0\n: columns\n1"), Some(2));
+        assert_eq!(line_number(": rows\n1\n: rows\n1"), Some(3));
+        assert_eq!(line_number(": rows\n1"), None);
+        assert_eq!(line_number(": rows\n2\n: columns\n1"), None);
+        assert!(parse(": rows\n1\n: columns\n1").is_ok());
+    }
+}
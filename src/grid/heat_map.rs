@@ -0,0 +1,123 @@
+//! The optional overlay toggled with `J` that shades every unknown cell by how likely it is to be filled.
+//!
+//! How likely a cell is to be filled is the share of the placements of the clues of its row that fill it,
+//! averaged with the same share for its column. Counting placements is heavier than solving a line,
+//! so the counts of a line are kept until its clues or known cells change.
+
+use super::{Clue, Grid};
+use crate::solver::{self, LineDeduction};
+use terminal::util::{Color, Point};
+
+/// The first of the 24 grays at the end of the 256 colors, which are ordered from dark to bright.
+const DARKEST_GRAY: u8 = 232;
+const BRIGHTEST_GRAY: u8 = 255;
+
+/// The clues and known cells of a line as of the last count,
+/// with the share of placements that fill each cell or `None` if there are no placements.
+type CountedLine = (Vec<Clue>, Vec<LineDeduction>, Option<Vec<f64>>);
+
+#[derive(Default)]
+pub struct HeatMap {
+    /// Every row and then every column.
+    lines: Vec<CountedLine>,
+}
+
+impl HeatMap {
+    /// Counts the placements of the lines that changed since the last count.
+    pub fn update(&mut self, grid: &Grid) {
+        let line_count = (grid.size.width + grid.size.height) as usize;
+        if self.lines.len() != line_count {
+            // The grid was switched for one of another size
+            self.lines = vec![(Vec::new(), Vec::new(), None); line_count];
+        }
+
+        for ((clues, points), (counted_clues, counted_cells, filled_shares)) in
+            solver::lines(grid).zip(&mut self.lines)
+        {
+            let line: Vec<_> = points.iter().map(|point| grid.get_cell(*point)).collect();
+            let known_cells: Vec<LineDeduction> =
+                line.iter().map(|cell| solver::input(cell.kind())).collect();
+            if *counted_clues == clues && *counted_cells == known_cells {
+                continue;
+            }
+
+            *filled_shares = solver::count_placements(clues, &line).map(|placements| {
+                (0..line.len())
+                    .map(|index| placements.filled_share(index))
+                    .collect()
+            });
+            *counted_clues = clues.to_vec();
+            *counted_cells = known_cells;
+        }
+    }
+
+    /// Returns how likely the cell is to be filled as of the last count,
+    /// or `None` if the cell is known or its row or column can't be solved.
+    pub fn filled_share(&self, grid: &Grid, point: Point) -> Option<f64> {
+        if solver::input(grid.get_cell(point).kind()) != LineDeduction::Unknown {
+            return None;
+        }
+
+        let row = self.lines.get(point.y as usize)?.2.as_ref()?;
+        let column = self
+            .lines
+            .get((grid.size.height + point.x) as usize)?
+            .2
+            .as_ref()?;
+        Some((row[point.x as usize] + column[point.y as usize]) / 2.0)
+    }
+}
+
+/// Returns the color of a cell that is as likely to be filled as the share, from dark for never to bright for always.
+pub fn color(filled_share: f64) -> Color {
+    let range = (BRIGHTEST_GRAY - DARKEST_GRAY) as f64;
+    Color::Byte(DARKEST_GRAY + (filled_share.clamp(0.0, 1.0) * range).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+
+    #[test]
+    fn test_filled_share() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "111",
+            "1  ",
+        ]).unwrap();
+        let mut heat_map = HeatMap::default();
+        heat_map.update(&grid);
+        // Always filled by its column of 2 but only by one of the three placements of its row
+        assert_eq!(
+            heat_map.filled_share(&grid, Point { x: 0, y: 1 }),
+            Some((1.0 / 3.0 + 1.0) / 2.0)
+        );
+        // The 1 of the row has three places and the 1 of the column two
+        assert_eq!(
+            heat_map.filled_share(&grid, Point { x: 2, y: 1 }),
+            Some((1.0 / 3.0 + 1.0 / 2.0) / 2.0)
+        );
+
+        // Known cells keep their own colors
+        grid.cells[5] = Cell::Crossed;
+        heat_map.update(&grid);
+        assert_eq!(heat_map.filled_share(&grid, Point { x: 2, y: 1 }), None);
+        assert_eq!(
+            heat_map.filled_share(&grid, Point { x: 1, y: 1 }),
+            Some((1.0 / 2.0 + 1.0 / 2.0) / 2.0)
+        );
+
+        // A row that can't be solved has no placements to count
+        grid.cells[0] = Cell::Crossed;
+        heat_map.update(&grid);
+        assert_eq!(heat_map.filled_share(&grid, Point { x: 1, y: 0 }), None);
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(color(0.0), Color::Byte(DARKEST_GRAY));
+        assert_eq!(color(1.0), Color::Byte(BRIGHTEST_GRAY));
+        assert_eq!(color(0.5), Color::Byte(244));
+    }
+}
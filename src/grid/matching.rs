@@ -0,0 +1,185 @@
+//! Which clue the block of filled cells under the cursor belongs to, so that it can be pointed out in lines with many clues.
+//!
+//! The blocks of the line are aligned against its clues in every way they might be: in order, with each clue covering
+//! one or more neighboring blocks that fit into it without a cross in between, and with room for the clues before and
+//! after it. A block's clue is only pointed out if every alignment gives it the same one. Alignments that can't
+//! actually be solved are allowed through, which only ever makes a block ambiguous instead of matching the wrong clue.
+
+use super::{Cell, Clue, Grid};
+use crate::solver::{self, LineDeduction};
+use std::ops::Range;
+use terminal::util::Point;
+
+fn is_crossed(cell: &Cell) -> bool {
+    solver::input(cell.kind()) == LineDeduction::Crossed
+}
+
+/// Returns the cells of each block of filled cells of the line, through which it has the clues of
+/// [`Grid::get_horizontal_clues`] or [`Grid::get_vertical_clues`].
+fn blocks(line: &[Cell]) -> Vec<Range<usize>> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+    for (index, cell) in line.iter().enumerate() {
+        match (cell.is_filled(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(block_start)) => {
+                blocks.push(block_start..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = start {
+        blocks.push(start..line.len());
+    }
+    blocks
+}
+
+/// Returns whether the clue with the index can cover the cells, which go from the start of a block to the end of one.
+fn fits(line: &[Cell], clues: &[Clue], index: usize, cells: &Range<usize>) -> bool {
+    let clue = clues[index] as usize;
+    if cells.len() > clue || line[cells.clone()].iter().any(is_crossed) {
+        return false;
+    }
+
+    // Each clue before and after it takes up its cells and the one between it and the next clue
+    let before: usize = clues[..index].iter().map(|clue| *clue as usize + 1).sum();
+    let after: usize = clues[index + 1..]
+        .iter()
+        .map(|clue| *clue as usize + 1)
+        .sum();
+    // It can extend to both sides up to the closest crosses
+    let space_start = line[..cells.start]
+        .iter()
+        .rposition(is_crossed)
+        .map_or(0, |position| position + 1);
+    let space_end = line[cells.end..]
+        .iter()
+        .position(is_crossed)
+        .map_or(line.len(), |position| cells.end + position);
+
+    let lowest_start = space_start.max(before).max(cells.end.saturating_sub(clue));
+    let highest_start = (line.len().checked_sub(after))
+        .and_then(|end| end.min(space_end).checked_sub(clue))
+        .map(|start| start.min(cells.start));
+    highest_start.is_some_and(|highest_start| lowest_start <= highest_start)
+}
+
+/// Returns the index of the clue that the block of filled cells with the cell at the index belongs to,
+/// or `None` if the cell isn't filled or its block might belong to more than one clue or to none.
+pub fn matched_clue(line: &[Cell], clues: &[Clue], cell_index: usize) -> Option<usize> {
+    let blocks = blocks(line);
+    let block = blocks
+        .iter()
+        .position(|block| block.contains(&cell_index))?;
+    let (block_count, clue_count) = (blocks.len(), clues.len());
+    let group_fits = |first: usize, last: usize, clue: usize| {
+        fits(line, clues, clue, &(blocks[first].start..blocks[last].end))
+    };
+
+    // Whether the blocks up to the one with the first index can be aligned with the clues up to the second one,
+    // which covers that block last
+    let mut ends = vec![vec![false; clue_count]; block_count];
+    for last in 0..block_count {
+        for clue in 0..clue_count {
+            ends[last][clue] = (0..=last).any(|first| {
+                group_fits(first, last, clue)
+                    && (first == 0 || (0..clue).any(|previous| ends[first - 1][previous]))
+            });
+        }
+    }
+    // Whether the blocks from the one with the first index on can be aligned with the clues from the second one on,
+    // which covers that block first
+    let mut starts = vec![vec![false; clue_count]; block_count];
+    for first in (0..block_count).rev() {
+        for clue in 0..clue_count {
+            starts[first][clue] = (first..block_count).any(|last| {
+                group_fits(first, last, clue)
+                    && (last + 1 == block_count
+                        || (clue + 1..clue_count).any(|next| starts[last + 1][next]))
+            });
+        }
+    }
+
+    let mut clues = (0..clue_count).filter(|clue| {
+        (0..=block).any(|first| {
+            (block..block_count).any(|last| {
+                group_fits(first, last, *clue)
+                    && (first == 0 || (0..*clue).any(|previous| ends[first - 1][previous]))
+                    && (last + 1 == block_count
+                        || (*clue + 1..clue_count).any(|next| starts[last + 1][next]))
+            })
+        })
+    });
+    let clue = clues.next()?;
+    clues.next().is_none().then_some(clue)
+}
+
+impl Grid {
+    /// Returns the index of the clue of the row that the block with the filled cell at the point belongs to, if it's certain.
+    pub fn matched_horizontal_clue(&self, point: Point) -> Option<usize> {
+        let width = self.size.width as usize;
+        let start = point.y as usize * width;
+        matched_clue(
+            &self.cells[start..start + width],
+            &self.horizontal_clues_solutions[point.y as usize],
+            point.x as usize,
+        )
+    }
+
+    /// Returns the index of the clue of the column that the block with the filled cell at the point belongs to, if it's certain.
+    pub fn matched_vertical_clue(&self, point: Point) -> Option<usize> {
+        let column: Vec<Cell> = self
+            .cells
+            .iter()
+            .skip(point.x as usize)
+            .step_by(self.size.width as usize)
+            .copied()
+            .collect();
+        matched_clue(
+            &column,
+            &self.vertical_clues_solutions[point.x as usize],
+            point.y as usize,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a line like the grid lines of the tests, with `X` for crossed cells.
+    fn line(str: &str) -> Vec<Cell> {
+        str.chars()
+            .map(|char| match char {
+                '1' => Cell::Filled,
+                'X' => Cell::Crossed,
+                _ => Cell::Empty,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matched_clue() {
+        // Only the first clue has room before it
+        assert_eq!(matched_clue(&line("1         "), &[1, 1, 1], 0), Some(0));
+        // Only the last clue is long enough
+        assert_eq!(matched_clue(&line("    111   "), &[1, 1, 3], 5), Some(2));
+        // The blocks can only be aligned one by one
+        assert_eq!(matched_clue(&line("1 1 1"), &[1, 1, 1], 2), Some(1));
+        // The cross leaves room for the first clue only
+        assert_eq!(matched_clue(&line(" 1X      "), &[2, 2], 1), Some(0));
+    }
+
+    #[test]
+    fn test_ambiguous_clue() {
+        // Not filled
+        assert_eq!(matched_clue(&line("1    "), &[1, 1], 1), None);
+        // Either clue has room around it
+        assert_eq!(matched_clue(&line("   1      "), &[1, 1], 3), None);
+        // The two blocks might also be the first clue together
+        assert_eq!(matched_clue(&line("  1 1        "), &[3, 1, 1], 4), None);
+        // No clue is long enough
+        assert_eq!(matched_clue(&line("1111  "), &[2, 1], 0), None);
+    }
+}
@@ -1,26 +1,109 @@
-use rand::distributions::{Bernoulli, Distribution};
-use rand::rngs::SmallRng;
-use rand::SeedableRng;
-
-use super::{Cell, Grid};
+use super::{Grid, PictureCell};
+use crate::{rng::Rng, solver};
 use terminal::util::Size;
 
-fn random_cells(size: u32) -> Vec<Cell> {
-    let mut cells = Vec::<Cell>::with_capacity(size as usize);
-    let mut small_rng = SmallRng::from_entropy();
-    let distribution = Bernoulli::new(0.75).unwrap();
+/// How likely it is for a cell to be filled.
+const FILL_PROBABILITY: f64 = 0.75;
 
-    for _ in 0..size {
-        let filled = distribution.sample(&mut small_rng);
+fn random_picture(size: u32, rng: &mut Rng) -> Vec<PictureCell> {
+    let mut picture = Vec::<PictureCell>::with_capacity(size as usize);
 
-        cells.push(Cell::from(filled));
+    for _ in 0..size {
+        picture.push(if rng.chance(FILL_PROBABILITY) {
+            PictureCell::Filled
+        } else {
+            PictureCell::Empty
+        });
     }
 
-    cells
+    picture
 }
 
+/// At most this many grids are generated before giving up, so that large grids don't take forever.
+pub const MAX_ATTEMPTS: usize = 100;
+
 impl Grid {
-    pub fn random(size: Size) -> Grid {
-        Self::new(size.clone(), random_cells(size.product()))
+    /// Generates a grid whose clues have exactly one solution or, if `logic_only` is true,
+    /// can be solved by solving one row or column at a time.
+    ///
+    /// Returns `None` if none of the grids generated within [`MAX_ATTEMPTS`] attempts is like that.
+    pub fn random(size: Size, rng: &mut Rng, logic_only: bool) -> Option<Grid> {
+        Self::random_within(size, rng, logic_only, MAX_ATTEMPTS)
+    }
+
+    fn random_within(
+        size: Size,
+        rng: &mut Rng,
+        logic_only: bool,
+        max_attempts: usize,
+    ) -> Option<Grid> {
+        (0..max_attempts).find_map(|_| {
+            let grid = Self::from_picture(size.clone(), &random_picture(size.product(), rng));
+
+            let solvable = if logic_only {
+                solver::is_line_solvable(&grid)
+            } else {
+                grid.has_unique_solution()
+            };
+            solvable.then_some(grid)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let grid = Grid::random(size.clone(), &mut Rng::new(42), false).unwrap();
+
+        assert_eq!(
+            grid.solution,
+            Grid::random(size.clone(), &mut Rng::new(42), false)
+                .unwrap()
+                .solution
+        );
+        assert_ne!(
+            grid.solution,
+            Grid::random(size, &mut Rng::new(43), false)
+                .unwrap()
+                .solution
+        );
+        // The same as `--seed 42 10`, which must not change between versions
+        assert_eq!(grid.fingerprint(), 7037415802899701557);
+    }
+
+    #[test]
+    fn test_gives_up() {
+        let size = Size {
+            width: 3,
+            height: 3,
+        };
+        // The first grid of the seed has more than one solution, so it can't be solved one line at a time either
+        for logic_only in [false, true] {
+            assert!(Grid::random_within(size.clone(), &mut Rng::new(8), logic_only, 1).is_none());
+            assert!(Grid::random(size.clone(), &mut Rng::new(8), logic_only).is_some());
+        }
+    }
+
+    #[test]
+    fn test_solvable() {
+        for seed in 0..20 {
+            let size = Size {
+                width: 20,
+                height: 15,
+            };
+            assert!(Grid::random(size.clone(), &mut Rng::new(seed), false)
+                .unwrap()
+                .has_unique_solution());
+            assert!(solver::is_line_solvable(
+                &Grid::random(size, &mut Rng::new(seed), true).unwrap()
+            ));
+        }
     }
 }
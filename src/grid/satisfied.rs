@@ -0,0 +1,182 @@
+//! Which individual clues of a row or column are satisfied before the whole line is, so that they can be grayed out.
+//!
+//! A clue is only satisfied if it's certain which block of the line it belongs to: its block must be sealed off with
+//! crosses or the edge of the grid and there may only be other sealed blocks and crosses between it and the edge.
+//! Counting blocks from either edge then tells which clue each of them is. Anything else is left alone because graying
+//! out a clue that isn't satisfied would mislead the player.
+
+use super::{Cell, Clue, Grid};
+use crate::solver::{self, LineDeduction};
+
+/// Returns the lengths of the sealed blocks from the start of the line up to the first cell that isn't known,
+/// along with whether the whole line was known.
+fn sealed_blocks(line: impl Iterator<Item = Cell>) -> (Vec<Clue>, bool) {
+    let mut blocks = Vec::new();
+    let mut block = 0;
+    for cell in line {
+        match solver::input(cell.kind()) {
+            LineDeduction::Filled => block += 1,
+            LineDeduction::Crossed => {
+                if block != 0 {
+                    blocks.push(block);
+                    block = 0;
+                }
+            }
+            // A block up to here might continue
+            LineDeduction::Unknown => return (blocks, false),
+        }
+    }
+    if block != 0 {
+        blocks.push(block);
+    }
+    (blocks, true)
+}
+
+/// Returns for each of the clues of the line whether it is satisfied.
+pub fn satisfied_clues(line: &[Cell], clues: &[Clue]) -> Vec<bool> {
+    let mut satisfied = vec![false; clues.len()];
+
+    let (front_blocks, whole_line) = sealed_blocks(line.iter().copied());
+    if whole_line {
+        // Without anything left to place, blocks that don't match can't be told apart from missing ones
+        if front_blocks == clues {
+            satisfied.fill(true);
+        }
+        return satisfied;
+    }
+    // Both ends stop at a cell that isn't known so they can't reach the same block
+    let (back_blocks, _) = sealed_blocks(line.iter().rev().copied());
+
+    if front_blocks.len() + back_blocks.len() > clues.len() {
+        // There are too many blocks to tell which clues they are
+        return satisfied;
+    }
+
+    for ((block, clue), satisfied) in front_blocks.iter().zip(clues).zip(&mut satisfied) {
+        if block != clue {
+            break;
+        }
+        *satisfied = true;
+    }
+    for ((block, clue), satisfied) in back_blocks
+        .iter()
+        .zip(clues.iter().rev())
+        .zip(satisfied.iter_mut().rev())
+    {
+        if block != clue {
+            break;
+        }
+        *satisfied = true;
+    }
+
+    satisfied
+}
+
+impl Grid {
+    /// Returns for each clue of the row whether it is satisfied.
+    pub fn satisfied_horizontal_clues(&self, y: u16) -> Vec<bool> {
+        let width = self.size.width as usize;
+        let start = y as usize * width;
+        satisfied_clues(
+            &self.cells[start..start + width],
+            &self.horizontal_clues_solutions[y as usize],
+        )
+    }
+
+    /// Returns for each clue of the column whether it is satisfied.
+    pub fn satisfied_vertical_clues(&self, x: u16) -> Vec<bool> {
+        let column: Vec<Cell> = self
+            .cells
+            .iter()
+            .skip(x as usize)
+            .step_by(self.size.width as usize)
+            .copied()
+            .collect();
+        satisfied_clues(&column, &self.vertical_clues_solutions[x as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a line like the grid lines of the tests, with `X` for crossed and `?` for maybed cells.
+    fn line(str: &str) -> Vec<Cell> {
+        str.chars()
+            .map(|char| match char {
+                '1' => Cell::Filled,
+                'X' => Cell::Crossed,
+                '?' => Cell::Maybed,
+                _ => Cell::Empty,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_satisfied_clues() {
+        // Sealed against the edge and with a cross
+        assert_eq!(
+            satisfied_clues(&line("111X      "), &[3, 1, 2]),
+            [true, false, false]
+        );
+        assert_eq!(
+            satisfied_clues(&line("XX111X     "), &[3, 1, 2]),
+            [true, false, false]
+        );
+        assert_eq!(
+            satisfied_clues(&line("111X1X  X11"), &[3, 1, 1, 2]),
+            [true, true, false, true]
+        );
+        // Maybed cells are no crosses
+        assert_eq!(satisfied_clues(&line("111?  X11"), &[3, 2]), [false, true]);
+    }
+
+    #[test]
+    fn test_ambiguous_clues() {
+        // Not sealed
+        assert_eq!(
+            satisfied_clues(&line("111       "), &[3, 1]),
+            [false, false]
+        );
+        assert_eq!(
+            satisfied_clues(&line(" X111X    "), &[3, 1]),
+            [false, false]
+        );
+        // A block of the same length somewhere in the middle
+        assert_eq!(satisfied_clues(&line("  X1X  "), &[1, 1]), [false, false]);
+        // The block doesn't match its clue, so neither do the ones after it
+        assert_eq!(
+            satisfied_clues(&line("11X1X   "), &[3, 1, 1]),
+            [false, false, false]
+        );
+        // More blocks than clues
+        assert_eq!(satisfied_clues(&line("1X1X 1X1"), &[1, 2]), [false; 2]);
+    }
+
+    #[test]
+    fn test_whole_line() {
+        assert_eq!(satisfied_clues(&line("111X1X"), &[3, 1]), [true, true]);
+        // It is unknown which clue is missing
+        assert_eq!(satisfied_clues(&line("XX1XXX"), &[1, 1]), [false, false]);
+        assert_eq!(satisfied_clues(&line("XXXXXX"), &[]), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "1 11",
+            "1  1",
+            "   1",
+        ]).unwrap();
+        grid.cells[0] = Cell::Filled;
+        grid.cells[1] = Cell::Crossed;
+        assert_eq!(grid.satisfied_horizontal_clues(0), [true, false]);
+        assert_eq!(grid.satisfied_vertical_clues(0), [false]);
+
+        grid.cells[4] = Cell::Filled;
+        grid.cells[8] = Cell::Crossed;
+        assert_eq!(grid.satisfied_vertical_clues(0), [true]);
+        assert_eq!(grid.satisfied_horizontal_clues(2), [false]);
+    }
+}
@@ -0,0 +1,50 @@
+//! Tags are marks on top of the cells for multi-pass solving, e.g. to tell apart the cells placed under two hypotheses.
+//!
+//! A cell can have one of `TAG_COUNT` tags. Once a hypothesis is confirmed or refuted,
+//! all cells with its tag can be promoted to filled or crossed out cells at once.
+
+use super::{Cell, Grid};
+use terminal::util::{Color, Point};
+
+pub const TAG_COUNT: u8 = 4;
+
+/// The glyph and background color each tag is shown with. The glyphs still tell the tags apart without colors.
+const APPEARANCES: [(&str, Color); TAG_COUNT as usize] = [
+    ("◆", Color::Cyan),
+    ("●", Color::Byte(208)),
+    ("▲", Color::Magenta),
+    ("■", Color::Yellow),
+];
+
+/// Returns the glyph and background color of the tag.
+pub fn appearance(tag: u8) -> (&'static str, Color) {
+    APPEARANCES[tag as usize - 1]
+}
+
+/// Returns the letter the tag is shown as in the ASCII mode, where there are no colors and only ASCII characters.
+pub fn ascii_glyph(tag: u8) -> char {
+    (b'a' + tag - 1) as char
+}
+
+/// Returns the points of all cells with the tag.
+pub fn tagged_points(grid: &Grid, tag: u8) -> Vec<Point> {
+    grid.tags
+        .iter()
+        .enumerate()
+        .filter(|(_, cell_tag)| **cell_tag == Some(tag))
+        .map(|(index, _)| Point {
+            x: (index % grid.size.width as usize) as u16,
+            y: (index / grid.size.width as usize) as u16,
+        })
+        .collect()
+}
+
+/// Sets all cells with the tag to the cell and removes the tag from them.
+pub fn promote(grid: &mut Grid, tag: u8, cell: Cell) {
+    for (grid_cell, cell_tag) in grid.cells.iter_mut().zip(&mut grid.tags) {
+        if *cell_tag == Some(tag) {
+            *grid_cell = cell;
+            *cell_tag = None;
+        }
+    }
+}
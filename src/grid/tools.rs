@@ -1 +1,4 @@
+pub mod auto_cross;
 pub mod fill;
+pub mod prefill;
+pub mod transform;
@@ -0,0 +1,156 @@
+use crate::grid::{Cell, CellKind, Clue, Grid};
+use terminal::util::Point;
+
+/// Returns whether crossing out a line crosses out cells of the kind. Marked cells are kept.
+pub const fn is_crossable(kind: CellKind) -> bool {
+    match kind {
+        CellKind::Empty => true,
+        CellKind::Filled | CellKind::Maybed | CellKind::Crossed | CellKind::Measured => false,
+    }
+}
+
+/// Crosses out the empty cells of the rows and columns in a single transaction so that one undo removes all of them.
+/// Returns whether any cells were crossed out.
+fn cross_lines(grid: &mut Grid, rows: Vec<u16>, columns: Vec<u16>) -> bool {
+    let row_points = rows
+        .into_iter()
+        .flat_map(|y| (0..grid.size.width).map(move |x| Point { x, y }));
+    let column_points = columns
+        .into_iter()
+        .flat_map(|x| (0..grid.size.height).map(move |y| Point { x, y }));
+    let points: Vec<Point> = row_points.chain(column_points).collect();
+
+    // A cell can be in both a crossed row and a crossed column, which is why they're crossed out right away
+    grid.transaction(|transaction| {
+        for point in points {
+            if is_crossable(transaction.get_cell(point).kind()) {
+                transaction.set_cell(point, Cell::Crossed);
+            }
+        }
+    })
+}
+
+/// Crosses out the empty cells of the rows and columns whose clues are solved, out of the given ones. See `cross_lines`.
+pub fn cross_solved_lines(
+    grid: &mut Grid,
+    rows: impl IntoIterator<Item = u16>,
+    columns: impl IntoIterator<Item = u16>,
+) -> bool {
+    let rows = rows
+        .into_iter()
+        .filter(|y| {
+            grid.get_horizontal_clues(*y)
+                .eq(grid.horizontal_clues_solutions[*y as usize].iter().copied())
+        })
+        .collect();
+    let columns = columns
+        .into_iter()
+        .filter(|x| {
+            grid.get_vertical_clues(*x)
+                .eq(grid.vertical_clues_solutions[*x as usize].iter().copied())
+        })
+        .collect();
+
+    cross_lines(grid, rows, columns)
+}
+
+/// Crosses out the empty cells of the row and the column of the point if their clues are solved. See `cross_solved_lines`.
+pub fn cross_solved_lines_at(grid: &mut Grid, point: Point) -> bool {
+    cross_solved_lines(grid, [point.y], [point.x])
+}
+
+/// Crosses out the cells of the rows and columns without any clues, which are empty in every solution.
+/// Like any other crossed out cells, they can be undone. See `cross_lines`.
+pub fn cross_empty_lines(grid: &mut Grid) -> bool {
+    let empty_lines = |clues_solutions: &[Vec<Clue>]| {
+        (0..clues_solutions.len() as u16)
+            .filter(|index| clues_solutions[*index as usize].is_empty())
+            .collect()
+    };
+    let rows = empty_lines(&grid.horizontal_clues_solutions);
+    let columns = empty_lines(&grid.vertical_clues_solutions);
+
+    cross_lines(grid, rows, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::undo_redo_buffer::Operation;
+
+    fn set(grid: &mut Grid, x: u16, y: u16, cell: Cell) {
+        grid.apply(Operation::SetCell {
+            point: Point { x, y },
+            cell,
+        });
+    }
+
+    #[test]
+    fn test_cross_solved_lines() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "1 1",
+            "  1",
+        ]).unwrap();
+        set(&mut grid, 0, 0, Cell::Filled);
+        set(&mut grid, 1, 1, Cell::Maybed);
+
+        // The first column is solved but the first row isn't yet
+        assert!(cross_solved_lines_at(&mut grid, Point { x: 0, y: 0 }));
+        assert_eq!(grid.cells[3], Cell::Crossed);
+        assert_eq!(grid.cells[1], Cell::Empty);
+        assert!(!cross_solved_lines_at(&mut grid, Point { x: 0, y: 0 }));
+
+        set(&mut grid, 2, 0, Cell::Filled);
+        assert!(cross_solved_lines_at(&mut grid, Point { x: 2, y: 0 }));
+        // Other marks are kept
+        assert_eq!(
+            grid.cells,
+            [
+                Cell::Filled,
+                Cell::Crossed,
+                Cell::Filled,
+                Cell::Crossed,
+                Cell::Maybed,
+                Cell::Empty
+            ]
+        );
+
+        // Even wrong cells solve a line if they give the same clues
+        set(&mut grid, 1, 1, Cell::Filled);
+        assert!(cross_solved_lines(&mut grid, [1], []));
+        assert_eq!(grid.cells[5], Cell::Crossed);
+
+        // All cells crossed out at once are uncrossed with one undo
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells[5], Cell::Empty);
+        assert_eq!(grid.cells[4], Cell::Filled);
+        assert!(grid.undo_last_cell());
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells[1], Cell::Empty);
+        assert_eq!(grid.cells[2], Cell::Filled);
+    }
+
+    #[test]
+    fn test_cross_empty_lines() {
+        let mut grid = Grid::from_clues(
+            vec![vec![1], vec![], vec![1]],
+            vec![vec![], vec![1, 1], vec![]],
+        )
+        .unwrap();
+        set(&mut grid, 1, 0, Cell::Maybed);
+
+        assert!(cross_empty_lines(&mut grid));
+        let crossed_points: Vec<usize> = (0..grid.cells.len())
+            .filter(|index| grid.cells[*index] == Cell::Crossed)
+            .collect();
+        assert_eq!(crossed_points, [0, 2, 3, 4, 5, 6, 8]);
+        assert_eq!(grid.cells[1], Cell::Maybed);
+        // The second row is solved just like that
+        assert_eq!(grid.solved_rows(), [false, true, false]);
+
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells[0], Cell::Empty);
+        assert!(!cross_empty_lines(&mut Grid::from_lines(&["1"]).unwrap()));
+    }
+}
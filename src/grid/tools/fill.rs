@@ -1,14 +1,22 @@
-use crate::grid::{Cell, Grid};
+use crate::grid::{Cell, CellKind, Grid};
 use terminal::util::Point;
 
+/// Returns whether all cells of the kind are filled as one, regardless of their data.
+/// Otherwise only cells equal to the first one are.
+pub const fn fills_as_one(kind: CellKind) -> bool {
+    match kind {
+        // We want to fill multiple measured cells as one, regardless of the index
+        CellKind::Measured => true,
+        CellKind::Empty | CellKind::Filled | CellKind::Maybed | CellKind::Crossed => false,
+    }
+}
+
 pub fn fill(grid: &mut Grid, point: Point, first_cell: Cell, fill_cell: Cell) {
     let cell = grid.get_mut_cell(point);
 
-    // We want to fill multiple measured cells as one, regardless of the index
-    let measured_cell =
-        matches!(*cell, Cell::Measured(_)) && matches!(first_cell, Cell::Measured(_));
+    let same_kind = cell.kind() == first_cell.kind() && fills_as_one(first_cell.kind());
 
-    if *cell == first_cell || measured_cell {
+    if *cell == first_cell || same_kind {
         *cell = fill_cell;
     } else {
         return;
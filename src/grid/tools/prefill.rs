@@ -0,0 +1,83 @@
+use crate::{
+    grid::{Cell, Grid},
+    solver::{self, LineDeduction},
+};
+use terminal::util::Point;
+
+/// Fills in the empty cells that the clues of their row or column force to be filled on their own,
+/// which are the overlaps of the leftmost and the rightmost placement of the clues.
+///
+/// The lines are only solved once each and on their own, as a head start and not as a solution.
+/// The cells are filled in a single transaction so that one undo removes all of them.
+/// Returns whether any cells were filled in.
+pub fn fill_overlaps(grid: &mut Grid) -> bool {
+    let mut points = Vec::<Point>::new();
+    for (clues, line_points) in solver::lines(grid) {
+        // Clues that contradict themselves force nothing
+        let deductions = match solver::solve_line(clues, &vec![Cell::Empty; line_points.len()]) {
+            Some(deductions) => deductions,
+            None => continue,
+        };
+        points.extend(
+            line_points
+                .into_iter()
+                .zip(deductions)
+                .filter(|(_, deduction)| *deduction == LineDeduction::Filled)
+                .map(|(point, _)| point),
+        );
+    }
+
+    // A cell can be forced by both its row and its column, which is why they're filled in right away
+    grid.transaction(|transaction| {
+        for point in points {
+            if transaction.get_cell(point) == Cell::Empty {
+                transaction.set_cell(point, Cell::Filled);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_overlaps() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "111 ",
+            " 1  ",
+            "    ",
+            "1   ",
+        ]).unwrap();
+        assert!(fill_overlaps(&mut grid));
+        // The 3 of the first row forces the middle two cells; no column forces anything
+        let filled: Vec<bool> = grid.cells.iter().map(|cell| cell.is_filled()).collect();
+        #[rustfmt::skip]
+        assert_eq!(filled, [
+            false, true, true, false,
+            false, false, false, false,
+            false, false, false, false,
+            false, false, false, false,
+        ]);
+
+        // Nothing is filled in twice
+        assert!(!fill_overlaps(&mut grid));
+
+        // All of them are removed with one undo
+        assert!(grid.undo_last_cell());
+        assert!(grid.cells.iter().all(|cell| *cell == Cell::Empty));
+    }
+
+    #[test]
+    fn test_fill_overlaps_solving() {
+        // A full grid is solved by the overlaps alone
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11",
+            "11",
+        ]).unwrap();
+        assert!(fill_overlaps(&mut grid));
+        assert_eq!(grid.solved_line_count(), 4);
+    }
+}
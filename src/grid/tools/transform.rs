@@ -0,0 +1,143 @@
+//! Mirroring and rotating the whole grid in the editor.
+//!
+//! Transforms keep the size of the grid, which is why only square grids can be rotated.
+//! Undoing one doesn't need its inverse because the changed cells are recorded like for any other operation.
+
+use crate::grid::Grid;
+use terminal::util::{Point, Size};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    /// Swaps the left and the right.
+    MirrorHorizontally,
+    /// Swaps the top and the bottom.
+    MirrorVertically,
+    /// Rotates by a quarter turn clockwise.
+    Rotate,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 3] = [
+        Transform::MirrorHorizontally,
+        Transform::MirrorVertically,
+        Transform::Rotate,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Transform::MirrorHorizontally => "mirror-horizontally",
+            Transform::MirrorVertically => "mirror-vertically",
+            Transform::Rotate => "rotate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|transform| transform.name() == name)
+    }
+
+    /// Returns whether a grid of the size can be transformed without changing its size.
+    pub const fn fits(self, size: &Size) -> bool {
+        match self {
+            Transform::MirrorHorizontally | Transform::MirrorVertically => true,
+            Transform::Rotate => size.width == size.height,
+        }
+    }
+
+    /// Returns where the cell at the point ends up.
+    const fn point(self, size: &Size, point: Point) -> Point {
+        match self {
+            Transform::MirrorHorizontally => Point {
+                x: size.width - 1 - point.x,
+                y: point.y,
+            },
+            Transform::MirrorVertically => Point {
+                x: point.x,
+                y: size.height - 1 - point.y,
+            },
+            Transform::Rotate => Point {
+                x: size.height - 1 - point.y,
+                y: point.x,
+            },
+        }
+    }
+}
+
+/// Transforms the cells and the tags of the grid, which must fit the transform, and makes the clues out of the cells again.
+pub fn transform(grid: &mut Grid, transform: Transform) {
+    debug_assert!(transform.fits(&grid.size));
+
+    let (cells, tags) = (grid.cells.clone(), grid.tags.clone());
+    let width = grid.size.width as usize;
+    for (index, (cell, tag)) in cells.into_iter().zip(tags).enumerate() {
+        let point = Point {
+            x: (index % width) as u16,
+            y: (index / width) as u16,
+        };
+        let point = transform.point(&grid.size, point);
+        *grid.get_mut_cell(point) = cell;
+        *grid.get_mut_tag(point) = tag;
+    }
+
+    grid.remake_clues_solutions();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+
+    #[test]
+    fn test_transform() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11 ",
+            "1  ",
+            "   ",
+        ]).unwrap();
+        grid.cells[0] = Cell::Filled;
+        grid.cells[1] = Cell::Filled;
+        grid.cells[3] = Cell::Crossed;
+        grid.tags[1] = Some(2);
+
+        transform(&mut grid, Transform::Rotate);
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, [
+            Cell::Empty, Cell::Crossed, Cell::Filled,
+            Cell::Empty, Cell::Empty, Cell::Filled,
+            Cell::Empty, Cell::Empty, Cell::Empty,
+        ]);
+        assert_eq!(grid.tags[5], Some(2));
+        assert_eq!(grid.horizontal_clues_solutions, [vec![1], vec![1], vec![]]);
+        assert_eq!(grid.vertical_clues_solutions, [vec![], vec![], vec![2]]);
+
+        transform(&mut grid, Transform::MirrorHorizontally);
+        transform(&mut grid, Transform::MirrorVertically);
+        #[rustfmt::skip]
+        assert_eq!(grid.cells, [
+            Cell::Empty, Cell::Empty, Cell::Empty,
+            Cell::Filled, Cell::Empty, Cell::Empty,
+            Cell::Filled, Cell::Crossed, Cell::Empty,
+        ]);
+        assert_eq!(grid.vertical_clues_solutions, [vec![2], vec![], vec![]]);
+    }
+
+    #[test]
+    fn test_fits() {
+        let size = Size {
+            width: 3,
+            height: 2,
+        };
+        assert!(Transform::MirrorVertically.fits(&size));
+        assert!(!Transform::Rotate.fits(&size));
+    }
+
+    #[test]
+    fn test_name() {
+        for transform in Transform::ALL.iter().copied() {
+            assert_eq!(Transform::from_name(transform.name()), Some(transform));
+        }
+    }
+}
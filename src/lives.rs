@@ -0,0 +1,159 @@
+//! The lives mode, where filling in a cell that is empty in the solution is a mistake and too many mistakes end the game.
+
+use crate::{
+    event::State,
+    grid::{
+        builder::{self, Builder},
+        Cell, Grid,
+    },
+    undo_redo_buffer::Operation,
+};
+use terminal::{
+    util::{Color, Point},
+    Terminal,
+};
+
+pub struct Lives {
+    /// How many mistakes end the game.
+    limit: u32,
+    mistakes: u32,
+    /// Which cells are filled in the solution.
+    solution: Vec<bool>,
+}
+
+impl Lives {
+    pub fn new(limit: u32, solution: Vec<bool>) -> Self {
+        Self {
+            limit,
+            mistakes: 0,
+            solution,
+        }
+    }
+
+    pub fn is_wrong(&self, grid: &Grid, point: Point) -> bool {
+        !self.solution[point.y as usize * grid.size.width as usize + point.x as usize]
+    }
+
+    /// Counts a mistake, returning the state telling about it.
+    pub fn add_mistake(&mut self) -> State {
+        self.mistakes += 1;
+        self.mistake_state()
+    }
+
+    /// Returns the state telling about the mistakes that were just made, which ends the game if there are too many.
+    fn mistake_state(&self) -> State {
+        if self.mistakes >= self.limit {
+            State::GameOver
+        } else {
+            State::Alert(format!("Mistake {} of {}", self.mistakes, self.limit).into())
+        }
+    }
+
+    /// Crosses out all filled cells that are empty in the solution as one operation, counting each as a mistake.
+    /// Returns the state telling about the mistakes if there were any.
+    ///
+    /// This is for placements that fill in many cells at once and can't be rejected cell by cell.
+    pub fn reject_wrong_cells(&mut self, grid: &mut Grid) -> Option<State> {
+        let width = grid.size.width as usize;
+        let cells: Vec<(Point, Cell)> = grid
+            .cells
+            .iter()
+            .zip(&self.solution)
+            .enumerate()
+            .filter(|(_, (cell, filled))| cell.is_filled() && !**filled)
+            .map(|(index, _)| {
+                let point = Point {
+                    x: (index % width) as u16,
+                    y: (index / width) as u16,
+                };
+                (point, Cell::Crossed)
+            })
+            .collect();
+
+        if cells.is_empty() {
+            return None;
+        }
+
+        self.mistakes += cells.len() as u32;
+        grid.apply(Operation::SetCells(cells));
+
+        Some(self.mistake_state())
+    }
+
+    /// Draws the mistakes out of the limit right of the completion percentage, where nothing else is drawn.
+    pub fn draw(&self, terminal: &mut Terminal, builder: &Builder) {
+        let text = format!("{}/{} mistakes", self.mistakes, self.limit);
+        let point = Point {
+            x: builder.point.x + builder.screen_size().width + builder::COMPLETION_WIDTH + 1,
+            y: builder.progress_bar_y(),
+        };
+        if point.x as usize + text.len() > terminal.size.width as usize {
+            return;
+        }
+
+        terminal.set_cursor(point);
+        terminal.set_foreground_color(Color::Red);
+        terminal.write(&text);
+        terminal.reset_colors();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reject_wrong_cells() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "1 1",
+            " 1 ",
+        ]).unwrap();
+        let mut lives = Lives::new(4, grid.solution.clone());
+        assert!(lives.is_wrong(&grid, Point { x: 1, y: 0 }));
+        assert!(!lives.is_wrong(&grid, Point { x: 1, y: 1 }));
+
+        let cells: Vec<(Point, Cell)> = (0..2)
+            .flat_map(|y| (0..3).map(move |x| (Point { x, y }, Cell::Filled)))
+            .collect();
+        crate::undo_redo_buffer::set_cells(&mut grid, &cells);
+        grid.undo_redo_buffer.push(Operation::SetCells(cells));
+        assert!(matches!(
+            lives.reject_wrong_cells(&mut grid),
+            Some(State::Alert(message)) if message == "Mistake 3 of 4"
+        ));
+        assert_eq!(
+            grid.cells,
+            [
+                Cell::Filled,
+                Cell::Crossed,
+                Cell::Filled,
+                Cell::Crossed,
+                Cell::Filled,
+                Cell::Crossed
+            ]
+        );
+
+        // Undoing brings back the wrong cells but not the lives
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells[1], Cell::Filled);
+        assert_eq!(lives.mistakes, 3);
+
+        assert!(matches!(
+            lives.reject_wrong_cells(&mut grid),
+            Some(State::GameOver)
+        ));
+        assert!(lives.reject_wrong_cells(&mut grid).is_none());
+        assert_eq!(lives.mistakes, 6);
+    }
+
+    #[test]
+    fn test_add_mistake() {
+        let mut lives = Lives::new(2, Vec::new());
+        assert!(matches!(
+            lives.add_mistake(),
+            State::Alert(message) if message == "Mistake 1 of 2"
+        ));
+        assert!(matches!(lives.add_mistake(), State::GameOver));
+    }
+}
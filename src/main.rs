@@ -1,14 +1,46 @@
 mod args;
+mod audit;
+mod bench;
+mod check;
+mod diff;
 mod editor;
 mod event;
+mod export;
+mod folder;
+mod format;
 mod grid;
+mod lives;
+mod menu;
+mod pack;
+mod paths;
+mod rng;
+mod sandbox;
+mod save;
+mod solver;
+mod source;
 mod undo_redo_buffer;
 mod util;
 
 use event::State;
-use grid::{builder::Builder, Grid};
-use std::{borrow::Cow, io, process, time::Duration};
+use folder::Folder;
+use grid::{
+    builder::{Builder, Settings},
+    colors::BuiltinPalette,
+    formats, Cell, Grid,
+};
+use lives::Lives;
+use pack::Pack;
+use save::Autosave;
+use solver::Solutions;
+use std::{
+    borrow::Cow,
+    fs, io,
+    path::Path,
+    process,
+    time::{Duration, Instant, SystemTime},
+};
 use terminal::{
+    event::{Event, Key},
     util::{Color, Point, Size},
     Terminal,
 };
@@ -16,8 +48,6 @@ use terminal::{
 // Things that could be implemented but might not be worth it:
 // -A main menu
 // -An interactive tutorial
-// -Currently whole clue rows are grayed out once all cells for those clues have been solved
-//  Make them gray out individually. (Maybe itertools' `pad_using` is helpful)
 // -Ability to change grid size and load grids (with F5?) within the game without the command line
 // -Ability to save records to a file and determine new records with that
 // -Ability to continue after solving the puzzle/ability to play it again
@@ -40,24 +70,188 @@ const HELP: &[&str] = &[
 ];
 
 fn run() -> Result<(), Cow<'static, str>> {
-    let arg = args::parse();
+    let (arg, options) = args::parse()?;
+
+    paths::init(options.data_dir.as_deref())?;
+    let mut settings = Settings {
+        half_blocks: options.half_blocks,
+        separation_interval: options
+            .separation_interval
+            .unwrap_or(grid::DEFAULT_SEPARATION_INTERVAL),
+        coordinates: options.coordinates,
+        border: options.border,
+        render_mode: if options.ascii {
+            grid::RenderMode::Ascii
+        } else {
+            grid::RenderMode::Colors
+        },
+        clue_layout: options.clue_layout.unwrap_or_default(),
+        true_color: grid::colors::true_color(options.no_truecolor),
+        palette: load_palette(&options)?,
+        theme: grid::colors::load_theme().unwrap_or_default(),
+        undo_limit: options
+            .undo_limit
+            .unwrap_or(undo_redo_buffer::DEFAULT_LIMIT),
+        merge_window: options.merge_window,
+    };
 
-    let grid = match arg {
-        Ok(Some(args::Arg::Help)) => {
+    let (grid, solutions, title) = match arg {
+        Some(args::Arg::Help) => {
             for line in HELP {
                 println!("{}", line);
             }
 
             return Ok(());
         }
-        Ok(Some(args::Arg::Version)) => {
+        Some(args::Arg::AuditCells) => {
+            for line in audit::matrix() {
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+        Some(args::Arg::Version) => {
             let version = env!("CARGO_PKG_VERSION");
 
             println!("{}", version);
 
             return Ok(());
         }
-        arg => match get_grid(arg) {
+        Some(args::Arg::PackAdd {
+            pack,
+            name,
+            content,
+        }) => {
+            let grid = load_grid_file(&name, &content)?;
+            pack::add(Path::new(&pack), &name, &grid)?;
+
+            println!("Added {} to {}", name, pack);
+
+            return Ok(());
+        }
+        Some(args::Arg::PackList(pack)) => {
+            for line in pack::list(Path::new(&pack))? {
+                println!("{}", line);
+            }
+
+            return Ok(());
+        }
+        Some(args::Arg::Pack(filename)) => {
+            let path = Path::new(&filename);
+            let mut pack = Pack::open(path)?;
+            let title = path
+                .file_stem()
+                .and_then(|title| title.to_str())
+                .unwrap_or_default();
+
+            return with_terminal(|terminal| {
+                play_pack(terminal, title, &mut pack, settings, options.lives)
+            });
+        }
+        Some(args::Arg::DiffPuzzles { old, new }) => {
+            let old_grid = load_grid_file(&old.0, &old.1)?;
+            let new_grid = load_grid_file(&new.0, &new.1)?;
+
+            let report = diff::diff(&old_grid, &new_grid);
+            if report.is_empty() {
+                return Ok(());
+            }
+            for line in &report {
+                println!("{}", line);
+            }
+
+            return Err(format!("{} and {} differ", old.0, new.0).into());
+        }
+        Some(args::Arg::Solve { name, content }) => {
+            let grid = load_grid_file(&name, &content)?;
+
+            // Scripts can tell the outcomes apart by the exit status
+            let (message, code) = match solver::solve(&grid) {
+                Ok(solution) => {
+                    for line in Grid::picture_lines(&grid.size, &solution) {
+                        println!("{}", line);
+                    }
+                    return Ok(());
+                }
+                Err(solver::Solutions::None) => ("unsolvable", 1),
+                Err(solver::Solutions::Multiple) => ("multiple solutions", 2),
+                Err(solver::Solutions::Unknown) => ("too hard to solve", 3),
+                Err(solver::Solutions::Unique) => unreachable!(),
+            };
+            println!("{}", message);
+            process::exit(code);
+        }
+        Some(args::Arg::Describe { name, content }) => {
+            let grid = if args::has_extension(&name, "txt") {
+                export::text::import(&content)
+                    .map_err(|err| format!("Invalid snapshot: {}", err))?
+            } else {
+                let mut grid = load_grid_file(&name, &content)?;
+                // Like when playing it, the autosaved progress is restored
+                if !options.no_autosave {
+                    if let Some(progress) = Autosave::new(&grid)
+                        .and_then(|autosave| autosave.load(&grid).ok())
+                        .flatten()
+                    {
+                        progress.restore(&mut grid);
+                    }
+                }
+                grid
+            };
+            print!("{}", export::description::describe(&grid));
+
+            return Ok(());
+        }
+        Some(args::Arg::SelfCheck { name, content }) => {
+            let mut grid = load_grid_file(&name, &content)?;
+            // The progress the player saved is what matters most
+            if !options.no_autosave {
+                if let Some(progress) = Autosave::new(&grid)
+                    .and_then(|autosave| autosave.load(&grid).ok())
+                    .flatten()
+                {
+                    progress.restore(&mut grid);
+                }
+            }
+
+            let sections = check::run(&grid);
+            let mut failed_count = 0;
+            for section in &sections {
+                if section.differences.is_empty() {
+                    println!("{}: verified", section.name);
+                } else {
+                    failed_count += 1;
+                    println!(
+                        "{}: {} differ",
+                        section.name,
+                        section.differences.join(", ")
+                    );
+                }
+            }
+
+            if failed_count != 0 {
+                return Err(
+                    format!("{} of {} sections differ", failed_count, sections.len()).into(),
+                );
+            }
+            println!("All {} sections verified", sections.len());
+
+            return Ok(());
+        }
+        Some(args::Arg::Bench(path)) => {
+            let folder = Folder::open(Path::new(&path))?;
+            for line in bench::run(&folder) {
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+        Some(args::Arg::Folder(path)) => {
+            let mut folder = Folder::open(Path::new(&path))?;
+
+            return with_terminal(|terminal| {
+                play_folder(terminal, &path, &mut folder, &options, settings)
+            });
+        }
+        arg => match get_grid(arg, &options) {
             Ok(grid) => grid,
             Err(err) => {
                 return Err(err);
@@ -65,37 +259,270 @@ fn run() -> Result<(), Cow<'static, str>> {
         },
     };
 
+    with_terminal(|terminal| {
+        play(
+            terminal,
+            grid,
+            solutions,
+            &title,
+            &mut settings,
+            !options.no_autosave,
+            options.lives,
+        );
+        Ok(())
+    })
+}
+
+/// Runs the function with an initialized terminal that is deinitialized again afterwards.
+fn with_terminal(
+    function: impl FnOnce(&mut Terminal) -> Result<(), Cow<'static, str>>,
+) -> Result<(), Cow<'static, str>> {
     let stdout = io::stdout();
     match get_terminal(stdout.lock()) {
         Ok(mut terminal) => {
-            if let State::Continue = event::input::window::await_fitting_size(&mut terminal, &grid)
-            {
-                let mut builder = Builder::new(&terminal, grid);
+            let result = function(&mut terminal);
 
-                let all_clues_solved = builder.draw_all(&mut terminal);
-                draw_basic_controls_help(&mut terminal, &builder);
+            terminal.deinitialize();
 
-                if all_clues_solved {
-                    solved_screen(&mut terminal, &builder, Duration::ZERO, true);
-                } else {
-                    terminal.flush();
+            result
+        }
+        Err(err) => Err(err.into()),
+    }
+}
 
-                    let state = event::r#loop(&mut terminal, &mut builder);
+/// Plays the grid until it is solved or the player exits, in the lives mode if there is a limit of mistakes.
+/// Returns the grid and, if it was solved, how long that took. The settings take on the theme that was switched to.
+///
+/// The solutions of the clues are passed if they are already known so that they aren't searched for again.
+/// If they have a problem, the player is asked whether to play anyway first.
+fn play(
+    terminal: &mut Terminal,
+    mut grid: Grid,
+    solutions: Option<Solutions>,
+    title: &str,
+    settings: &mut Settings,
+    autosave: bool,
+    mistake_limit: Option<u32>,
+) -> (Grid, Option<Duration>) {
+    if let State::Exit = event::input::window::await_fitting_size(terminal, &grid, *settings) {
+        return (grid, None);
+    }
 
-                    match state {
-                        State::Solved(duration) => {
-                            solved_screen(&mut terminal, &builder, duration, false);
-                        }
-                        State::Exit => {}
-                        _ => unreachable!(),
-                    }
+    grid.undo_redo_buffer.limit = settings.undo_limit;
+    grid.undo_redo_buffer.merge_window = settings.merge_window;
+    let mut builder = Builder::new(terminal, grid, *settings);
+
+    let mut all_clues_solved = builder.draw_all(terminal);
+    draw_controls_help(terminal, &builder, BASIC_CONTROLS_HELP);
+    terminal.flush();
+
+    // Rating can take a while for clues that solving lines doesn't solve, so the grid is drawn first
+    let difficulty = solver::rate(&builder.grid, solutions);
+    terminal.set_title(&format!("yayagram ({})", difficulty));
+
+    if let Some(problem) = solutions.and_then(formats::check) {
+        let question = format!("{}. Play anyway? (Y/N)", problem.message());
+        if !confirm_prompt(terminal, &builder, &question) {
+            return (builder.grid, None);
+        }
+    }
+
+    let lives = match mistake_limit {
+        Some(limit) => match solver::known_solution(&builder.grid) {
+            Some(solution) => Some(Lives::new(limit, solution.into_owned())),
+            None => {
+                const TEXT: &str =
+                    "Mistakes can't be told without the solution. Play anyway? (Y/N)";
+                if !confirm_prompt(terminal, &builder, TEXT) {
+                    return (builder.grid, None);
                 }
+                None
             }
+        },
+        None => None,
+    };
 
-            terminal.deinitialize();
+    // Restoring progress would start over without the mistakes
+    let mut autosave = if autosave && lives.is_none() {
+        Autosave::new(&builder.grid)
+    } else {
+        None
+    };
+
+    let mut recap = None;
+    if let (Some(autosave), false) = (&autosave, all_clues_solved) {
+        match autosave.load(&builder.grid) {
+            Ok(Some(progress)) => {
+                if restore_prompt(terminal, &builder) {
+                    progress.restore(&mut builder.grid);
+                    all_clues_solved = builder.draw_all(terminal);
+
+                    let since_saved = autosave
+                        .saved_time(&builder.grid)
+                        .and_then(|saved_time| SystemTime::now().duration_since(saved_time).ok());
+                    recap = Some(save::recap::recap(&builder.grid, since_saved));
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                if !start_fresh_prompt(terminal, &builder, &err) {
+                    return (builder.grid, None);
+                }
+                autosave.remove(&builder.grid);
+            }
         }
-        Err(err) => {
-            return Err(err.into());
+    }
+
+    if all_clues_solved {
+        solved_screen(
+            terminal,
+            &builder,
+            &solved_text(Duration::ZERO, true),
+            Some(title),
+            None,
+        );
+
+        return (builder.grid, Some(Duration::ZERO));
+    }
+
+    terminal.flush();
+
+    let state = event::r#loop(terminal, &mut builder, autosave.as_mut(), lives, recap);
+    // The next puzzle is drawn in the same theme
+    settings.theme = builder.settings.theme;
+
+    match state {
+        State::Solved(duration) => {
+            if let Some(autosave) = &autosave {
+                autosave.remove(&builder.grid);
+            }
+
+            // Leaving right away skips the rest too
+            if reveal_picture(terminal, &mut builder) {
+                return (builder.grid, Some(duration));
+            }
+
+            let intended_solution = builder
+                .grid
+                .is_alternate_solution()
+                .then_some(builder.grid.solution.as_slice());
+            solved_screen(
+                terminal,
+                &builder,
+                &solved_text(duration, false),
+                Some(title),
+                intended_solution,
+            );
+
+            (builder.grid, Some(duration))
+        }
+        State::Revealed => {
+            // Giving up ends the session so there is no progress left to continue
+            if let Some(autosave) = &autosave {
+                autosave.remove(&builder.grid);
+            }
+
+            solved_screen(terminal, &builder, "Solution revealed", None, None);
+
+            // The revealed cells aren't progress of the player's own
+            builder.grid.clear();
+
+            (builder.grid, None)
+        }
+        State::GameOver => {
+            solved_screen(
+                terminal,
+                &builder,
+                "Game over: too many mistakes",
+                None,
+                None,
+            );
+
+            // The game is lost so there is no progress left to continue
+            builder.grid.clear();
+
+            (builder.grid, None)
+        }
+        State::Exit => (builder.grid, None),
+        _ => unreachable!(),
+    }
+}
+
+/// Lets the player play the puzzles of the pack one after another until the player exits.
+fn play_pack(
+    terminal: &mut Terminal,
+    title: &str,
+    pack: &mut Pack,
+    mut settings: Settings,
+    mistake_limit: Option<u32>,
+) -> Result<(), Cow<'static, str>> {
+    let mut selected = 0;
+
+    while let Some(grid) = pack::picker::pick(terminal, title, pack, &mut selected) {
+        terminal.clear();
+
+        // The pack keeps track of the progress itself
+        let solutions = solver::solutions(&grid);
+        let title = pack.entries[selected].name.clone();
+        let (grid, solve_duration) = play(
+            terminal,
+            grid,
+            Some(solutions),
+            &title,
+            &mut settings,
+            false,
+            mistake_limit,
+        );
+
+        terminal.clear();
+
+        // Like autosaves, progress in the lives mode would start over without the mistakes
+        let started = mistake_limit.is_none() && grid.cells.iter().any(|cell| *cell != Cell::Empty);
+        if solve_duration.is_some() || started {
+            pack.set_progress(&grid, solve_duration);
+            pack.save_progress()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lets the player play the puzzles of the folder one after another until the player exits.
+fn play_folder(
+    terminal: &mut Terminal,
+    title: &str,
+    folder: &mut Folder,
+    options: &args::Options,
+    mut settings: Settings,
+) -> Result<(), Cow<'static, str>> {
+    let mut selected = 0;
+
+    while let Some(mut grid) = folder::picker::pick(terminal, title, folder, &mut selected) {
+        terminal.clear();
+
+        if !options.no_cross_empty {
+            grid::tools::auto_cross::cross_empty_lines(&mut grid);
+        }
+        if options.prefill {
+            grid::tools::prefill::fill_overlaps(&mut grid);
+        }
+
+        let solutions = solver::solutions(&grid);
+        let title = file_title(&folder.entries[selected].name);
+        let (grid, solve_duration) = play(
+            terminal,
+            grid,
+            Some(solutions),
+            &title,
+            &mut settings,
+            !options.no_autosave,
+            options.lives,
+        );
+
+        terminal.clear();
+
+        if solve_duration.is_some() {
+            folder.set_solved(&grid)?;
         }
     }
 
@@ -103,57 +530,190 @@ fn run() -> Result<(), Cow<'static, str>> {
 }
 
 pub const BASIC_CONTROLS_HELP: &[&str] = &["A: Undo, D: Redo, C: Clear", "X: Measure, F: Fill"];
+pub const SANDBOX_CONTROLS_HELP: &[&str] = &["B: Real board, Enter: Merge", "Backspace: Discard"];
 
-fn draw_basic_controls_help(terminal: &mut Terminal, builder: &Builder) {
+pub fn draw_controls_help(terminal: &mut Terminal, builder: &Builder, controls_help: &[&str]) {
     terminal.set_foreground_color(Color::DarkGray);
-    for (index, text) in BASIC_CONTROLS_HELP.iter().enumerate() {
-        set_cursor_for_bottom_text(terminal, &builder, text.len(), index as u16);
+    for (index, text) in controls_help.iter().enumerate() {
+        set_cursor_for_bottom_text(terminal, builder, text.len(), index as u16);
         terminal.write(text);
     }
     terminal.reset_colors();
 }
 
-fn get_grid(arg: Result<Option<args::Arg>, Cow<'static, str>>) -> Result<Grid, Cow<'static, str>> {
-    match arg {
-        Ok(arg) => match arg {
-            Some(args::Arg::File {
-                name: filename,
-                content,
-            }) => match editor::load_grid(&content) {
-                Ok(grid) => Ok(grid),
-                Err(err) => {
-                    if let Some(line_number) = err.line_number {
-                        Err(format!(
-                            "invalid grid data in {}:{}: {}",
-                            filename, line_number, err.message
-                        )
-                        .into())
-                    } else {
-                        Err(format!("invalid grid data in {}: {}", filename, err.message).into())
-                    }
-                }
-            },
-            arg => {
-                let grid_size = if let Some(args::Arg::GridSize(size)) = arg {
-                    size
+pub fn clear_controls_help(terminal: &mut Terminal, builder: &Builder, controls_help: &[&str]) {
+    for (index, text) in controls_help.iter().enumerate() {
+        set_cursor_for_bottom_text(terminal, builder, text.len(), index as u16);
+        terminal.write(&" ".repeat(text.len()));
+    }
+}
+
+const SOLVED_CONTROLS_HELP: &str = "P: Save picture as PNG, C: Copy picture as text";
+
+/// Draws the given text on the bottom below the solved controls help, replacing the previous one.
+fn draw_solved_screen_message(
+    terminal: &mut Terminal,
+    builder: &Builder,
+    message: &str,
+    previous_message_len: usize,
+) {
+    set_cursor_for_bottom_text(terminal, builder, previous_message_len, 1);
+    terminal.write(&" ".repeat(previous_message_len));
+    set_cursor_for_bottom_text(terminal, builder, message.len(), 1);
+    terminal.write(message);
+}
+
+/// Gets the palette chosen with `--palette`, with the colors of the `--palette-file` if there is one.
+fn load_palette(options: &args::Options) -> Result<grid::colors::Palette, Cow<'static, str>> {
+    let mut palette = options.palette.unwrap_or(BuiltinPalette::Default).palette();
+    if let Some(path) = &options.palette_file {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Palette file {} couldn't be read: {}", path.display(), err))?;
+        palette
+            .apply_file(&content)
+            .map_err(|(line_number, message)| {
+                format!(
+                    "invalid palette in {}:{}: {}",
+                    path.display(),
+                    line_number,
+                    message
+                )
+            })?;
+    }
+    Ok(palette)
+}
+
+fn load_grid_file(filename: &str, content: &str) -> Result<Grid, Cow<'static, str>> {
+    formats::load(filename, content).map_err(|err| {
+        if let Some(line_number) = err.line_number {
+            format!(
+                "invalid grid data in {}:{}: {}",
+                filename, line_number, err.message
+            )
+            .into()
+        } else {
+            format!("invalid grid data in {}: {}", filename, err.message).into()
+        }
+    })
+}
+
+/// Returns the title of the puzzle in the file, which is its name without the extension.
+fn file_title(filename: &str) -> String {
+    Path::new(filename)
+        .file_stem()
+        .and_then(|title| title.to_str())
+        .unwrap_or(filename)
+        .to_string()
+}
+
+/// Gets the grid to play along with the problem of its clues if it was imported and its title.
+fn get_grid(
+    arg: Option<args::Arg>,
+    options: &args::Options,
+) -> Result<(Grid, Option<Solutions>, String), Cow<'static, str>> {
+    // Random grids are made up on the spot and snapshots bring their own progress
+    let imported = !matches!(
+        arg,
+        None | Some(args::Arg::GridSize(_)) | Some(args::Arg::Snapshot(_))
+    );
+    let snapshot = matches!(arg, Some(args::Arg::Snapshot(_)));
+    let random = matches!(arg, None | Some(args::Arg::GridSize(_)));
+    let title = match &arg {
+        Some(args::Arg::File { name, .. }) => Some(file_title(name)),
+        _ => None,
+    };
+
+    let mut grid = match arg {
+        Some(args::Arg::File {
+            name: filename,
+            content,
+        }) => load_grid_file(&filename, &content)?,
+        Some(args::Arg::Code(code)) => {
+            Grid::decode(&code).map_err(|err| format!("Invalid puzzle code: {}", err))?
+        }
+        Some(args::Arg::Snapshot(snapshot)) => {
+            export::text::import(&snapshot).map_err(|err| format!("Invalid snapshot: {}", err))?
+        }
+        Some(args::Arg::Picture(picture)) => {
+            let lines: Vec<&str> = picture.lines().collect();
+            Grid::from_lines(&lines).map_err(|err| {
+                if let (Some(line_number), Some(column_number)) =
+                    (err.line_number, err.column_number)
+                {
+                    format!(
+                        "invalid picture at {}:{}: {}",
+                        line_number, column_number, err.message
+                    )
                 } else {
-                    Size {
-                        width: 5,
-                        height: 5,
-                    }
-                };
-                Ok(Grid::random(grid_size))
-            }
-        },
-        Err(err) => Err(err.clone()),
+                    format!("invalid picture: {}", err.message)
+                }
+            })?
+        }
+        arg => {
+            let grid_size = if let Some(args::Arg::GridSize(size)) = arg {
+                size
+            } else {
+                Size {
+                    width: 5,
+                    height: 5,
+                }
+            };
+            let mut rng = rng::Rng::new(options.seed.unwrap_or_else(rng::Rng::random_seed));
+            Grid::random(grid_size, &mut rng, options.logic_only).ok_or_else(|| {
+                format!(
+                    "No puzzle {} was found in {} attempts. Try another size or seed",
+                    if options.logic_only {
+                        "that can be solved one row or column at a time"
+                    } else {
+                        "with a single solution"
+                    },
+                    grid::random::MAX_ATTEMPTS
+                )
+            })?
+        }
+    };
+
+    if imported && !options.no_cross_empty {
+        grid::tools::auto_cross::cross_empty_lines(&mut grid);
     }
+    // The progress of snapshots is already past the head start
+    if options.prefill && !snapshot {
+        grid::tools::prefill::fill_overlaps(&mut grid);
+    }
+
+    // Hand-written clues can easily be wrong, while random grids only ever have one solution
+    let solutions = if imported {
+        Some(solver::solutions(&grid))
+    } else if random && !options.logic_only {
+        Some(Solutions::Unique)
+    } else {
+        None
+    };
+
+    let title = title.unwrap_or_else(|| {
+        let size = format!("{}x{}", grid.size.width, grid.size.height);
+        if random {
+            format!("Random {} puzzle", size)
+        } else {
+            format!("{} puzzle", size)
+        }
+    });
+
+    Ok((grid, solutions, title))
 }
 
+/// How many bytes of output are buffered before they are written, which is enough for everything that is drawn at once
+/// even for the biggest grids, so that nothing is written until the terminal is flushed at the end of a frame.
+/// Otherwise, slow terminals show half-drawn frames.
+const OUTPUT_BUFFER_CAPACITY: usize = 1 << 20;
+
 /// Creates a new initialized `Terminal` instance if possible and sets the window title.
 ///
 /// This `Terminal` is what allows us to manipulate the terminal in all kinds of ways such as setting colors, writing data, moving the cursor etc.
 fn get_terminal(stdout: io::StdoutLock) -> Result<Terminal, &'static str> {
     if let Ok(mut terminal) = Terminal::new(stdout) {
+        // Nothing was written yet, and the lock can be taken again by the same thread
+        terminal.stdout = io::BufWriter::with_capacity(OUTPUT_BUFFER_CAPACITY, io::stdout().lock());
         terminal.initialize(Some("yayagram"), true);
         Ok(terminal)
     } else {
@@ -164,13 +724,16 @@ fn get_terminal(stdout: io::StdoutLock) -> Result<Terminal, &'static str> {
 const PROGRESS_BAR_HEIGHT: u16 = 1;
 const TOP_TEXT_HEIGHT: u16 = 2;
 const BOTTOM_TEXT_HEIGHT: u16 = 2;
+/// How many lines the texts above and below the grid and the progress bar take up.
+pub const TEXT_HEIGHT: u16 = TOP_TEXT_HEIGHT + PROGRESS_BAR_HEIGHT + BOTTOM_TEXT_HEIGHT;
 
-pub fn total_height(grid: &Grid) -> u16 {
-    TOP_TEXT_HEIGHT
+/// Returns how many lines the whole grid takes up along with its clues, its picture, its border, its column numbers
+/// and the texts around it.
+pub fn total_height(grid: &Grid, settings: Settings) -> u16 {
+    TEXT_HEIGHT
         + std::cmp::max(crate::get_picture_height(grid), grid.max_clues_size.height)
-        + grid.size.height
-        + PROGRESS_BAR_HEIGHT
-        + BOTTOM_TEXT_HEIGHT
+        + settings.screen_height(grid)
+        + settings.frame_size().height
 }
 
 pub const fn get_picture_height(grid: &Grid) -> u16 {
@@ -189,8 +752,8 @@ pub enum TopTextPosition {
     AbovePicture,
 }
 
-const fn get_top_text_position(builder: &Builder, text_len: usize) -> TopTextPosition {
-    if text_len as u16 <= builder.grid.size.width * 2 {
+fn get_top_text_position(builder: &Builder, text_len: usize) -> TopTextPosition {
+    if text_len as u16 <= builder.screen_size().width {
         // Above the clues
         TopTextPosition::AboveClues
     } else {
@@ -207,17 +770,24 @@ pub fn set_cursor_for_top_text(
     y_alignment: u16,
     top_text_position: Option<TopTextPosition>,
 ) {
-    let picture_height = get_picture_height(&builder.grid);
+    let clues_height = builder.leading_clues_size().height + builder.settings.border_width();
+    // There is no picture if the grid is scrolled, and it is below the grid along with the clues if they are there
+    let picture_height =
+        if builder.viewport().picture && !builder.settings.clue_layout.columns_below() {
+            get_picture_height(&builder.grid) + builder.settings.border_width()
+        } else {
+            clues_height
+        };
 
     let height = match top_text_position.unwrap_or_else(|| get_top_text_position(builder, text_len))
     {
-        TopTextPosition::AboveClues => builder.grid.max_clues_size.height,
+        TopTextPosition::AboveClues => clues_height,
         TopTextPosition::AbovePicture => picture_height,
     };
 
     terminal.set_cursor(Point {
-        x: builder.point.x + builder.grid.size.width - text_len as u16 / 2,
-        y: ((builder.point.y - height) - 1) - y_alignment,
+        x: (builder.point.x + builder.screen_size().width / 2).saturating_sub(text_len as u16 / 2),
+        y: builder.point.y.saturating_sub(height + 1 + y_alignment),
     });
 }
 
@@ -228,24 +798,116 @@ pub fn set_cursor_for_bottom_text(
     text_len: usize,
     y_alignment: u16,
 ) {
-    let mut y = builder.point.y + builder.grid.size.height;
+    let mut y = builder.progress_bar_y();
     y += 1; // Make way for the progress bar
 
     terminal.set_cursor(Point {
-        x: builder.point.x + builder.grid.size.width - text_len as u16 / 2,
+        x: (builder.point.x + builder.screen_size().width / 2).saturating_sub(text_len as u16 / 2),
         y: y + y_alignment,
     });
 }
 
+/// Asks the question above the grid, returning whether it was answered with yes.
+pub fn confirm_prompt(terminal: &mut Terminal, builder: &Builder, question: &str) -> bool {
+    set_cursor_for_top_text(terminal, builder, question.len(), 0, None);
+    terminal.write(question);
+    terminal.flush();
+
+    let confirmed = matches!(
+        event::input::key::r#await(terminal),
+        Key::Char('y' | 'Y') | Key::Enter
+    );
+
+    set_cursor_for_top_text(terminal, builder, question.len(), 0, None);
+    terminal.write(&" ".repeat(question.len()));
+
+    confirmed
+}
+
+/// Asks whether the autosaved progress should be restored, returning the answer.
+fn restore_prompt(terminal: &mut Terminal, builder: &Builder) -> bool {
+    confirm_prompt(terminal, builder, "Restore previous progress? (Y/N)")
+}
+
+/// Tells that the autosave is corrupted and asks whether to start fresh instead, returning the answer.
+///
+/// Not starting fresh keeps the autosave as it is, e.g. to repair it by hand.
+fn start_fresh_prompt(terminal: &mut Terminal, builder: &Builder, err: &editor::LoadError) -> bool {
+    let text = if let Some(line_number) = err.line_number {
+        format!(
+            "Autosave is corrupted (line {}: {}). Start fresh? (Y/N)",
+            line_number, err.message
+        )
+    } else {
+        format!(
+            "Autosave is corrupted ({}). Start fresh? (Y/N)",
+            err.message
+        )
+    };
+
+    confirm_prompt(terminal, builder, &text)
+}
+
 /// One hour in seconds.
 const HOUR: u64 = 60 * 60;
 
-/// The screen that appears when the grid was solved.
+/// Returns the text telling how long solving the grid took.
+fn solved_text(duration: Duration, did_nothing: bool) -> Cow<'static, str> {
+    if did_nothing {
+        "You won by doing nothing".into()
+    } else {
+        let total_elapsed_seconds = duration.as_secs();
+        if total_elapsed_seconds > HOUR * 99 {
+            "That took too long".into()
+        } else {
+            let duration = format::duration(total_elapsed_seconds, usize::MAX).unwrap_or_default();
+            format!("Solved in {}", duration).into()
+        }
+    }
+}
+
+/// How long revealing each line of the picture of a solved grid takes.
+const REVEAL_LINE_DURATION: Duration = Duration::from_millis(30);
+
+/// Clears the clues and reveals the picture of the solved grid line by line, leaving out all but the filled cells.
+/// Any key skips to the end. Returns whether that key was `Esc`, which leaves the solved screen out as well.
+fn reveal_picture(terminal: &mut Terminal, builder: &mut Builder) -> bool {
+    terminal.reset_colors();
+    builder.clear_clues(terminal);
+
+    let rows: Vec<u16> = builder.drawn_rows().collect();
+    let mut skipped_with = None;
+    for y in rows {
+        builder.draw_revealed_row(terminal, y);
+        if skipped_with.is_some() {
+            continue;
+        }
+        terminal.flush();
+
+        let deadline = Instant::now() + REVEAL_LINE_DURATION;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            if let Some(Event::Key(key)) = terminal.poll_event(timeout) {
+                skipped_with = Some(key);
+                break;
+            }
+        }
+    }
+    terminal.flush();
+
+    matches!(skipped_with, Some(Key::Esc))
+}
+
+/// The screen that appears when the grid was solved or its solution was revealed, showing the text.
+///
+/// `title` is the title of the puzzle that is shown below the grid if it was solved.
+/// `intended_solution` is the solution the clues were made from if the grid was solved with a different one,
+/// which can then be shown next to the grid.
 fn solved_screen(
     terminal: &mut Terminal,
     builder: &Builder,
-    duration: Duration,
-    did_nothing: bool,
+    text: &str,
+    title: Option<&str>,
+    intended_solution: Option<&[bool]>,
 ) {
     terminal.reset_colors();
 
@@ -257,7 +919,7 @@ fn solved_screen(
 
     set_cursor_for_top_text(
         terminal,
-        &builder,
+        builder,
         TEXT.len(),
         y_alignment,
         Some(top_text_position),
@@ -266,46 +928,66 @@ fn solved_screen(
 
     y_alignment += 1;
 
-    let text: Cow<'static, str> = if did_nothing {
-        "You won by doing nothing".into()
-    } else {
-        let total_elapsed_seconds = duration.as_secs();
-        if total_elapsed_seconds > HOUR * 99 {
-            "That took too long".into()
-        } else {
-            format!("Solved in {}", format_seconds(total_elapsed_seconds)).into()
-        }
-    };
     terminal.set_foreground_color(Color::White);
     set_cursor_for_top_text(
         terminal,
-        &builder,
+        builder,
         text.len(),
         y_alignment,
         Some(top_text_position),
     );
-    terminal.write(&text);
+    terminal.write(text);
     terminal.reset_colors();
 
+    clear_controls_help(terminal, builder, BASIC_CONTROLS_HELP);
+    let controls_help: Cow<str> = if intended_solution.is_some() {
+        format!("{}, I: Show intended solution", SOLVED_CONTROLS_HELP).into()
+    } else {
+        SOLVED_CONTROLS_HELP.into()
+    };
+    terminal.set_foreground_color(Color::DarkGray);
+    set_cursor_for_bottom_text(terminal, builder, controls_help.len(), 0);
+    terminal.write(&controls_help);
+    terminal.reset_colors();
+
+    let mut previous_message_len = 0;
+    let message = match (title, intended_solution) {
+        (Some(title), Some(_)) => Some(format!("{}: solved with an alternate solution!", title)),
+        (None, Some(_)) => Some("Solved with an alternate solution!".to_string()),
+        (Some(title), None) => Some(title.to_string()),
+        (None, None) => None,
+    };
+    if let Some(message) = message {
+        terminal.set_foreground_color(Color::White);
+        draw_solved_screen_message(terminal, builder, &message, previous_message_len);
+        terminal.reset_colors();
+        previous_message_len = message.len();
+    }
+
     terminal.flush();
 
-    event::input::key::r#await(terminal);
-}
+    loop {
+        let message: Cow<'static, str> = match event::input::key::r#await(terminal) {
+            Key::Char('p' | 'P') => match export::png::save(&builder.grid) {
+                Ok(filename) => format!("Picture saved as {}", filename).into(),
+                Err(err) => err.into(),
+            },
+            Key::Char('c' | 'C') => match export::art::copy(terminal, &builder.grid) {
+                Ok(message) => message.into(),
+                Err(err) => err.into(),
+            },
+            Key::Char('i' | 'I') => match intended_solution {
+                Some(solution) if builder.draw_solution_picture(terminal, solution) => {
+                    "The intended solution is shown right of the grid".into()
+                }
+                Some(_) => "The intended solution doesn't fit on the screen".into(),
+                None => break,
+            },
+            _ => break,
+        };
+        draw_solved_screen_message(terminal, builder, &message, previous_message_len);
+        previous_message_len = message.len();
 
-/// Formats the given seconds to an hour, minute and second format.
-///
-/// # Examples
-///
-/// ```
-/// assert_eq!(format_seconds(60 * 70 + 5), "01:10:05");
-/// assert_eq!(format_seconds(45 * 60 + 15), "00:45:15");
-/// assert_eq!(format_seconds(60 * 60 * 99), "99:00:00");
-/// assert_eq!(format_seconds(60 * 80), "01:20:00");
-/// assert_eq!(format_seconds(60 * 60 + 60 * 5 + 30), "01:05:30");
-/// ```
-fn format_seconds(total_seconds: u64) -> String {
-    let seconds = total_seconds % 60;
-    let minutes = total_seconds / 60 % 60;
-    let hours = total_seconds / HOUR;
-    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+        terminal.flush();
+    }
 }
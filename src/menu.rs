@@ -0,0 +1,98 @@
+//! Drawing of full-screen menus that list entries to choose from.
+
+use std::borrow::Cow;
+use terminal::{
+    event::Key,
+    util::{Color, Point},
+    Terminal,
+};
+
+const SELECTED_BACKGROUND_COLOR: Color = Color::Byte(238);
+
+/// The lines above and below the list of entries.
+const MARGIN_HEIGHT: u16 = 3;
+
+/// The Y-coordinate of the first entry.
+pub const ENTRIES_Y: u16 = MARGIN_HEIGHT - 1;
+
+pub fn draw_centered(terminal: &mut Terminal, text: &str, y: u16) {
+    terminal.set_cursor(Point {
+        x: (terminal.size.width / 2).saturating_sub(text.len() as u16 / 2),
+        y,
+    });
+    terminal.write(text);
+}
+
+/// Returns the Y-coordinate of the line for messages and prompts below the entries.
+pub const fn bottom_text_y(terminal: &Terminal) -> u16 {
+    terminal.size.height.saturating_sub(2)
+}
+
+/// Returns how many entries fit between the title and the bottom text.
+pub fn visible_entry_count(terminal: &Terminal) -> usize {
+    terminal
+        .size
+        .height
+        .saturating_sub(MARGIN_HEIGHT * 2)
+        .max(1) as usize
+}
+
+/// Draws the menu with the entry at `selected` highlighted,
+/// scrolling the entries if there are too many to fit.
+pub fn draw<'a>(
+    terminal: &mut Terminal,
+    title: &str,
+    entries: impl Iterator<Item = Cow<'a, str>>,
+    selected: usize,
+    bottom_text: &str,
+    controls_help: &str,
+) {
+    terminal.clear();
+
+    draw_centered(terminal, title, 0);
+
+    let visible_entry_count = visible_entry_count(terminal);
+    let first_visible_entry = (selected + 1).saturating_sub(visible_entry_count);
+
+    for (index, entry) in entries
+        .enumerate()
+        .skip(first_visible_entry)
+        .take(visible_entry_count)
+    {
+        if index == selected {
+            terminal.set_background_color(SELECTED_BACKGROUND_COLOR);
+        }
+        let y = ENTRIES_Y + (index - first_visible_entry) as u16;
+        draw_centered(terminal, &entry, y);
+        terminal.reset_colors();
+    }
+
+    let bottom_y = bottom_text_y(terminal);
+    draw_centered(terminal, bottom_text, bottom_y);
+    terminal.set_foreground_color(Color::DarkGray);
+    draw_centered(terminal, controls_help, bottom_y + 1);
+    terminal.reset_colors();
+
+    terminal.flush();
+}
+
+/// Moves the selection up or down for the arrow keys, wrapping around. Returns whether the key was handled.
+pub fn move_selection(key: &Key, selected: &mut usize, entry_count: usize) -> bool {
+    match key {
+        Key::Up => *selected = selected.checked_sub(1).unwrap_or(entry_count - 1),
+        Key::Down => *selected = (*selected + 1) % entry_count,
+        _ => return false,
+    }
+    true
+}
+
+/// Asks the question below the entries, returning whether it was answered with yes.
+pub fn confirm(terminal: &mut Terminal, question: &str) -> bool {
+    draw_centered(terminal, question, bottom_text_y(terminal));
+    terminal.flush();
+
+    matches!(
+        crate::event::input::key::r#await(terminal),
+        Key::Char('y' | 'Y') | Key::Enter
+    )
+}
@@ -0,0 +1,364 @@
+//! A pack bundles multiple puzzles in a single `.yyp` file, made of a header and then one puzzle per line:
+//!
+//! ```text
+//! yayagram pack
+//! AQUFv376gA== Heart
+//! AQMDqwA= Smiley
+//! ```
+//!
+//! Every puzzle is a puzzle code followed by its name.
+//!
+//! The player's progress is not part of the pack so that the pack can be shared without it.
+//! Instead it is stored next to the pack in a file with `.progress` appended to the pack's filename,
+//! one line per puzzle the player started:
+//!
+//! ```text
+//! 5b630af62f6e7569 solved 95 1X1.1.1X1
+//! ```
+//!
+//! That is the grid's fingerprint, `solved` or `unsolved`, the seconds it took to solve it and the cells.
+
+pub mod picker;
+
+use crate::{
+    grid::{Cell, Grid},
+    save,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const HEADER: &str = "yayagram pack";
+
+pub struct Entry {
+    pub name: String,
+    code: String,
+}
+
+impl Entry {
+    /// Decodes the puzzle. Only this reveals whether an entry is corrupted.
+    pub fn grid(&self) -> Result<Grid, &'static str> {
+        Grid::decode(&self.code)
+    }
+}
+
+/// Reads the entries of a pack without decoding any of the puzzles.
+pub fn read_index(reader: impl BufRead) -> Result<Vec<Entry>, &'static str> {
+    let mut lines = reader.lines();
+
+    match lines.next() {
+        Some(Ok(header)) if header == HEADER => {}
+        _ => return Err("Not a puzzle pack"),
+    }
+
+    let mut entries = Vec::<Entry>::new();
+    for line in lines {
+        let line = line.map_err(|_| "Pack reading error")?;
+        if line.is_empty() {
+            continue;
+        }
+
+        let (code, name) = line.split_once(' ').unwrap_or((&line, ""));
+        let name = if name.is_empty() {
+            format!("Puzzle {}", entries.len() + 1)
+        } else {
+            name.to_string()
+        };
+
+        entries.push(Entry {
+            name,
+            code: code.to_string(),
+        });
+    }
+
+    Ok(entries)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Progress {
+    pub solved: bool,
+    /// How long it took to solve the puzzle the first time.
+    pub solve_duration: Duration,
+    pub cells: Vec<Cell>,
+}
+
+fn parse_progress_line(line: &str) -> Option<(u64, Progress)> {
+    let mut fields = line.split(' ');
+
+    let fingerprint = u64::from_str_radix(fields.next()?, 16).ok()?;
+    let solved = match fields.next()? {
+        "solved" => true,
+        "unsolved" => false,
+        _ => return None,
+    };
+    let solve_duration = Duration::from_secs(fields.next()?.parse().ok()?);
+    let cells = fields
+        .next()?
+        .chars()
+        .map(save::char_cell)
+        .collect::<Option<Vec<Cell>>>()?;
+
+    Some((
+        fingerprint,
+        Progress {
+            solved,
+            solve_duration,
+            cells,
+        },
+    ))
+}
+
+pub struct Pack {
+    pub entries: Vec<Entry>,
+    progress_path: PathBuf,
+    /// The progress on the puzzles by their fingerprint.
+    progress: HashMap<u64, Progress>,
+}
+
+fn progress_path(path: &Path) -> PathBuf {
+    let mut progress_path = path.as_os_str().to_owned();
+    progress_path.push(".progress");
+    PathBuf::from(progress_path)
+}
+
+impl Pack {
+    pub fn open(path: &Path) -> Result<Self, &'static str> {
+        let file = fs::File::open(path).map_err(|_| "Pack opening error")?;
+        let entries = read_index(io::BufReader::new(file))?;
+
+        let progress_path = progress_path(path);
+        // The player might not have started any puzzle yet and corrupted lines are ignored
+        let progress = fs::read_to_string(&progress_path)
+            .map(|content| content.lines().filter_map(parse_progress_line).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            entries,
+            progress_path,
+            progress,
+        })
+    }
+
+    pub fn progress(&self, grid: &Grid) -> Option<&Progress> {
+        self.progress
+            .get(&grid.fingerprint())
+            .filter(|progress| progress.cells.len() == grid.cells.len())
+    }
+
+    /// Updates the progress on the grid's puzzle. `solve_duration` is `Some` if it was just solved.
+    pub fn set_progress(&mut self, grid: &Grid, solve_duration: Option<Duration>) {
+        let previous_progress = self.progress(grid);
+        let previous_solve_duration = previous_progress
+            .filter(|progress| progress.solved)
+            .map(|progress| progress.solve_duration);
+
+        let progress = Progress {
+            solved: previous_solve_duration.is_some() || solve_duration.is_some(),
+            solve_duration: previous_solve_duration
+                .or(solve_duration)
+                .unwrap_or_default(),
+            cells: grid.cells.clone(),
+        };
+        self.progress.insert(grid.fingerprint(), progress);
+    }
+
+    /// Saves the progress on all puzzles, leaving the pack itself untouched.
+    ///
+    /// The progress is first written to a temporary file that then replaces the previous progress
+    /// so that it can't be lost halfway through.
+    pub fn save_progress(&self) -> Result<(), &'static str> {
+        let mut content = String::new();
+        for (fingerprint, progress) in &self.progress {
+            let solved = if progress.solved {
+                "solved"
+            } else {
+                "unsolved"
+            };
+            let cells: String = progress
+                .cells
+                .iter()
+                .map(|cell| save::cell_char(*cell))
+                .collect();
+            content += &format!(
+                "{:016x} {} {} {}\n",
+                fingerprint,
+                solved,
+                progress.solve_duration.as_secs(),
+                cells
+            );
+        }
+
+        let mut temporary_path = self.progress_path.clone().into_os_string();
+        temporary_path.push(".tmp");
+
+        fs::write(&temporary_path, content).map_err(|_| "Progress saving error")?;
+        fs::rename(&temporary_path, &self.progress_path).map_err(|_| "Progress saving error")
+    }
+}
+
+/// Adds a puzzle to the end of the pack, creating the pack if it doesn't exist.
+pub fn add(path: &Path, name: &str, grid: &Grid) -> Result<(), &'static str> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|_| "Pack opening error")?;
+
+    let is_new = file.metadata().map_err(|_| "Pack opening error")?.len() == 0;
+    if !is_new {
+        // Adding to something that isn't a pack would corrupt it
+        read_index(io::BufReader::new(
+            fs::File::open(path).map_err(|_| "Pack opening error")?,
+        ))?;
+    }
+
+    let mut content = String::new();
+    if is_new {
+        content.push_str(HEADER);
+        content.push('\n');
+    }
+    content += &format!("{} {}\n", grid.encode(), name);
+
+    file.write_all(content.as_bytes())
+        .map_err(|_| "Pack writing error")
+}
+
+/// Returns a line describing each puzzle of the pack.
+pub fn list(path: &Path) -> Result<Vec<String>, &'static str> {
+    let file = fs::File::open(path).map_err(|_| "Pack opening error")?;
+    let entries = read_index(io::BufReader::new(file))?;
+
+    Ok(entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let description = match entry.grid() {
+                Ok(grid) => format!("{}x{}", grid.size.width, grid.size.height),
+                Err(err) => format!("broken: {}", err),
+            };
+            format!("{}. {} ({})", index + 1, entry.name, description)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_pack_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("yayagram-{}-{}.yyp", name, std::process::id()))
+    }
+
+    fn remove_pack(path: &Path) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(progress_path(path));
+    }
+
+    #[test]
+    fn test_read_index() {
+        let pack = format!("{}\nAQMDqwA= Smiley\n\ngarbage\nAQMDqwA=\n", HEADER);
+
+        let entries = read_index(pack.as_bytes()).unwrap();
+
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["Smiley", "Puzzle 2", "Puzzle 3"]);
+
+        // A corrupted puzzle doesn't affect the others
+        assert!(entries[0].grid().is_ok());
+        assert!(entries[1].grid().is_err());
+        assert!(entries[2].grid().is_ok());
+
+        assert!(read_index("AQMDqwA= Smiley".as_bytes()).is_err());
+        assert!(read_index("".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_progress_isolation() {
+        let path = temporary_pack_path("progress");
+        remove_pack(&path);
+        let grid = Grid::from_lines(&["1 1", " 1 "]).unwrap();
+        add(&path, "X", &grid).unwrap();
+        let pack_content = fs::read(&path).unwrap();
+
+        let mut pack = Pack::open(&path).unwrap();
+        let mut played_grid = pack.entries[0].grid().unwrap();
+        assert_eq!(pack.progress(&played_grid), None);
+        played_grid.cells[1] = Cell::Crossed;
+        pack.set_progress(&played_grid, None);
+        pack.save_progress().unwrap();
+        played_grid.cells[0] = Cell::Filled;
+        pack.set_progress(&played_grid, Some(Duration::from_secs(7)));
+        pack.save_progress().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), pack_content);
+
+        let pack = Pack::open(&path).unwrap();
+        assert_eq!(
+            pack.progress(&played_grid),
+            Some(&Progress {
+                solved: true,
+                solve_duration: Duration::from_secs(7),
+                cells: played_grid.cells.clone(),
+            })
+        );
+
+        remove_pack(&path);
+    }
+
+    #[test]
+    fn test_corrupted_progress() {
+        let (fingerprint, progress) =
+            parse_progress_line("00000000000000ff unsolved 0 .1X?R").unwrap();
+        assert_eq!(fingerprint, 0xff);
+        assert_eq!(
+            progress.cells,
+            [
+                Cell::Empty,
+                Cell::Filled,
+                Cell::Crossed,
+                Cell::Maybed,
+                Cell::Measured(None)
+            ]
+        );
+
+        assert_eq!(parse_progress_line("ff done 0 ."), None);
+        assert_eq!(parse_progress_line("ff solved x ."), None);
+        assert_eq!(parse_progress_line("ff solved 0 .a"), None);
+        assert_eq!(parse_progress_line("ff solved 0"), None);
+    }
+
+    #[test]
+    fn test_add_and_list() {
+        let path = temporary_pack_path("list");
+        remove_pack(&path);
+
+        add(&path, "Small", &Grid::from_lines(&["1 1", " 1 "]).unwrap()).unwrap();
+        add(
+            &path,
+            "Big one",
+            &Grid::from_lines(&["11 1", "1  1", "1111"]).unwrap(),
+        )
+        .unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"??? Broken\n").unwrap();
+
+        assert_eq!(
+            list(&path).unwrap(),
+            [
+                "1. Small (3x2)",
+                "2. Big one (4x3)",
+                "3. Broken (broken: Code is not valid)"
+            ]
+        );
+
+        remove_pack(&path);
+        fs::write(&path, "not a pack\n").unwrap();
+        assert!(add(&path, "Small", &Grid::from_lines(&["1"]).unwrap()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not a pack\n");
+        remove_pack(&path);
+    }
+}
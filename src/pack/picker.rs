@@ -0,0 +1,100 @@
+//! The menu for picking a puzzle of a pack.
+
+use super::Pack;
+use crate::{event::input::key, format, grid::Grid, menu};
+use std::borrow::Cow;
+use terminal::{event::Key, Terminal};
+
+const CONTROLS_HELP: &str = "Enter: Play, Esc: Exit";
+
+/// The solve duration of solved puzzles takes up at most this many characters.
+const SOLVE_DURATION_WIDTH: usize = 8;
+
+fn format_entry(pack: &Pack, index: usize) -> String {
+    let entry = &pack.entries[index];
+
+    match entry.grid() {
+        Ok(grid) => {
+            let progress = pack.progress(&grid);
+            let solved = progress.is_some_and(|progress| progress.solved);
+            let status = if solved {
+                "✓"
+            } else if progress.is_some() {
+                "…"
+            } else {
+                " "
+            };
+
+            let solve_duration = progress
+                .filter(|progress| progress.solved)
+                .and_then(|progress| {
+                    format::duration(progress.solve_duration.as_secs(), SOLVE_DURATION_WIDTH)
+                })
+                .unwrap_or_default();
+
+            format!(
+                "{} {:<24} {:>5} {:>width$}",
+                status,
+                entry.name,
+                format!("{}x{}", grid.size.width, grid.size.height),
+                solve_duration,
+                width = SOLVE_DURATION_WIDTH
+            )
+        }
+        Err(_) => format!("  {:<24} broken", entry.name),
+    }
+}
+
+/// Lets the player pick a puzzle, returning its grid with the progress on it restored,
+/// or `None` if the player wants to exit.
+///
+/// `selected` is the index of the entry that is selected initially and then the picked one.
+pub fn pick(
+    terminal: &mut Terminal,
+    title: &str,
+    pack: &Pack,
+    selected: &mut usize,
+) -> Option<Grid> {
+    if pack.entries.is_empty() {
+        menu::draw(
+            terminal,
+            title,
+            std::iter::empty(),
+            0,
+            "This pack is empty",
+            CONTROLS_HELP,
+        );
+        key::r#await(terminal);
+        return None;
+    }
+
+    let mut bottom_text = "";
+    loop {
+        let entries = (0..pack.entries.len()).map(|index| Cow::from(format_entry(pack, index)));
+        menu::draw(
+            terminal,
+            title,
+            entries,
+            *selected,
+            bottom_text,
+            CONTROLS_HELP,
+        );
+
+        match key::r#await(terminal) {
+            key if menu::move_selection(&key, selected, pack.entries.len()) => {}
+            Key::Enter => match pack.entries[*selected].grid() {
+                Ok(mut grid) => {
+                    // Solved puzzles are played from the start again
+                    if let Some(progress) = pack.progress(&grid).filter(|progress| !progress.solved)
+                    {
+                        grid.cells.clone_from(&progress.cells);
+                    }
+                    break Some(grid);
+                }
+                Err(_) => bottom_text = "This puzzle is broken",
+            },
+            Key::Esc => break None,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Where yayagram stores its files.
+//!
+//! There are three kinds of directories:
+//!
+//! - The data directory for what the player wants to keep: save slots and the records of solved puzzles.
+//! - The state directory for autosaves.
+//! - The export directory for grid files, snapshots, sheets and pictures.
+//!
+//! Each of them is, from the highest to the lowest precedence:
+//!
+//! 1. The directory given with `--data-dir`, which holds all of them so that everything is in one place.
+//! 2. The directory in the `YAYAGRAM_DATA_DIR` environment variable, which does the same.
+//! 3. The directory in the environment variable for the kind: `XDG_DATA_HOME`, `XDG_STATE_HOME` or `YAYAGRAM_EXPORT_DIR`.
+//! 4. The default: `~/.local/share`, `~/.local/state` or the current directory.
+//!    On Windows the data and state directories are in `%APPDATA%` and `%LOCALAPPDATA%`.
+//!
+//! Directories are only created once something is saved to them and only the owner can access them.
+
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// The environment variable for the directory holding everything, if there is no `--data-dir`.
+const DATA_DIR_VARIABLE: &str = "YAYAGRAM_DATA_DIR";
+
+/// The older name of `DATA_DIR_VARIABLE`, which is still read.
+const SAVE_DIR_VARIABLE: &str = "YAYAGRAM_SAVE_DIR";
+
+const EXPORT_DIR_VARIABLE: &str = "YAYAGRAM_EXPORT_DIR";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Data,
+    State,
+    Export,
+}
+
+/// The directories of each kind, or `None` where no directory could be determined.
+#[derive(Debug, PartialEq)]
+pub struct Paths {
+    data: Option<PathBuf>,
+    state: Option<PathBuf>,
+    export: Option<PathBuf>,
+}
+
+impl Paths {
+    /// Determines the directories from the `--data-dir` directory, if any,
+    /// the environment variables as returned by `var` and the current directory.
+    pub fn resolve(
+        data_dir: Option<&Path>,
+        var: impl Fn(&str) -> Option<OsString>,
+        current_dir: Option<PathBuf>,
+        windows: bool,
+    ) -> Self {
+        let var = |name: &str| {
+            var(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        // A relative path is resolved now so that the directory stays the same no matter what happens later
+        let absolute = |path: PathBuf| match &current_dir {
+            Some(current_dir) if path.is_relative() => Some(current_dir.join(path)),
+            Some(_) => Some(path),
+            None if path.is_absolute() => Some(path),
+            None => None,
+        };
+
+        let root = data_dir
+            .map(Path::to_path_buf)
+            .or_else(|| var(DATA_DIR_VARIABLE))
+            .or_else(|| var(SAVE_DIR_VARIABLE));
+        if let Some(root) = root {
+            let root = absolute(root);
+            return Self {
+                data: root.clone(),
+                state: root.clone(),
+                export: root,
+            };
+        }
+
+        // Relative paths are to be ignored according to the XDG base directory specification
+        let xdg_dir = |name: &str, default: &[&str]| match var(name) {
+            Some(dir) if dir.is_absolute() => Some(dir),
+            _ => Some(
+                default
+                    .iter()
+                    .fold(var("HOME")?, |dir, part| dir.join(part)),
+            ),
+        };
+        let (data, state) = if windows {
+            (
+                var("APPDATA"),
+                var("LOCALAPPDATA").or_else(|| var("APPDATA")),
+            )
+        } else {
+            (
+                xdg_dir("XDG_DATA_HOME", &[".local", "share"]),
+                xdg_dir("XDG_STATE_HOME", &[".local", "state"]),
+            )
+        };
+
+        Self {
+            data: data.map(|dir| dir.join("yayagram")),
+            state: state.map(|dir| dir.join("yayagram")),
+            export: var(EXPORT_DIR_VARIABLE).and_then(absolute).or(current_dir),
+        }
+    }
+
+    pub fn get(&self, kind: Kind) -> Option<&Path> {
+        match kind {
+            Kind::Data => self.data.as_deref(),
+            Kind::State => self.state.as_deref(),
+            Kind::Export => self.export.as_deref(),
+        }
+    }
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| {
+        Paths::resolve(
+            None,
+            |name| env::var_os(name),
+            env::current_dir().ok(),
+            cfg!(windows),
+        )
+    })
+}
+
+/// Determines the directories, using the `--data-dir` directory if there is one.
+///
+/// That directory is created right away so that any problem with it shows up before anything is played.
+pub fn init(data_dir: Option<&Path>) -> Result<(), Cow<'static, str>> {
+    let paths = Paths::resolve(
+        data_dir,
+        |name| env::var_os(name),
+        env::current_dir().ok(),
+        cfg!(windows),
+    );
+
+    if data_dir.is_some() {
+        let dir = paths
+            .get(Kind::Data)
+            .ok_or("Current directory is not accessible")?;
+        create_dir(dir).map_err(|err| {
+            format!(
+                "Data directory {} couldn't be created: {}",
+                dir.display(),
+                err
+            )
+        })?;
+
+        // Permissions don't tell reliably whether files can be created so this tries it out
+        let probe_path = dir.join(".yayagram-probe");
+        fs::write(&probe_path, "")
+            .map_err(|_| format!("Data directory {} is not writable", dir.display()))?;
+        let _ = fs::remove_file(probe_path);
+    }
+
+    PATHS
+        .set(paths)
+        .map_err(|_| "Data directory was already set".into())
+}
+
+/// Returns the directory of the kind, if one could be determined. It might not exist yet.
+pub fn dir(kind: Kind) -> Option<&'static Path> {
+    paths().get(kind)
+}
+
+/// Creates the directory and all its parents that don't exist yet, making new ones accessible only to the owner.
+pub fn create_dir(path: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(data_dir: Option<&str>, vars: &[(&str, &str)], windows: bool) -> Paths {
+        let vars: HashMap<String, OsString> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), OsString::from(value)))
+            .collect();
+        Paths::resolve(
+            data_dir.map(Path::new),
+            |name| vars.get(name).cloned(),
+            Some(PathBuf::from("/work")),
+            windows,
+        )
+    }
+
+    fn dirs(paths: &Paths) -> [Option<&str>; 3] {
+        [Kind::Data, Kind::State, Kind::Export]
+            .map(|kind| paths.get(kind).map(|path| path.to_str().unwrap()))
+    }
+
+    const ALL_VARS: &[(&str, &str)] = &[
+        ("HOME", "/home/me"),
+        ("XDG_DATA_HOME", "/xdg/data"),
+        ("XDG_STATE_HOME", "/xdg/state"),
+        ("YAYAGRAM_EXPORT_DIR", "/exports"),
+        ("YAYAGRAM_DATA_DIR", "/everything"),
+    ];
+
+    #[test]
+    fn test_precedence() {
+        // The flag comes first, then the variable for everything
+        assert_eq!(
+            dirs(&resolve(Some("/flag"), ALL_VARS, false)),
+            [Some("/flag"); 3]
+        );
+        assert_eq!(
+            dirs(&resolve(Some("portable"), ALL_VARS, false)),
+            [Some("/work/portable"); 3]
+        );
+        assert_eq!(
+            dirs(&resolve(None, ALL_VARS, false)),
+            [Some("/everything"); 3]
+        );
+        assert_eq!(
+            dirs(&resolve(None, &[("YAYAGRAM_SAVE_DIR", "saves")], false)),
+            [Some("/work/saves"); 3]
+        );
+
+        // Then the variables for each kind
+        assert_eq!(
+            dirs(&resolve(None, &ALL_VARS[..4], false)),
+            [
+                Some("/xdg/data/yayagram"),
+                Some("/xdg/state/yayagram"),
+                Some("/exports")
+            ]
+        );
+
+        // And then the defaults
+        assert_eq!(
+            dirs(&resolve(None, &ALL_VARS[..1], false)),
+            [
+                Some("/home/me/.local/share/yayagram"),
+                Some("/home/me/.local/state/yayagram"),
+                Some("/work")
+            ]
+        );
+        assert_eq!(
+            dirs(&resolve(
+                None,
+                &[
+                    ("HOME", "/home/me"),
+                    ("XDG_DATA_HOME", "relative"),
+                    ("YAYAGRAM_DATA_DIR", "")
+                ],
+                false
+            )),
+            [
+                Some("/home/me/.local/share/yayagram"),
+                Some("/home/me/.local/state/yayagram"),
+                Some("/work")
+            ]
+        );
+        assert_eq!(
+            dirs(&resolve(None, &[], false)),
+            [None, None, Some("/work")]
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        assert_eq!(
+            dirs(&resolve(
+                None,
+                &[
+                    ("APPDATA", "/roaming"),
+                    ("LOCALAPPDATA", "/local"),
+                    ("HOME", "/home/me")
+                ],
+                true
+            )),
+            [
+                Some("/roaming/yayagram"),
+                Some("/local/yayagram"),
+                Some("/work")
+            ]
+        );
+        assert_eq!(
+            dirs(&resolve(None, &[("APPDATA", "/roaming")], true)),
+            [
+                Some("/roaming/yayagram"),
+                Some("/roaming/yayagram"),
+                Some("/work")
+            ]
+        );
+        assert_eq!(
+            dirs(&resolve(Some("/flag"), &[("APPDATA", "/roaming")], true)),
+            [Some("/flag"); 3]
+        );
+    }
+
+    #[test]
+    fn test_create_dir() {
+        let dir = std::env::temp_dir().join(format!("yayagram-paths-{}", std::process::id()));
+        let nested_dir = dir.join("a").join("b");
+        create_dir(&nested_dir).unwrap();
+        // Creating it again is fine
+        create_dir(&nested_dir).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&nested_dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0);
+        }
+
+        fs::write(dir.join("file"), "").unwrap();
+        assert!(create_dir(&dir.join("file")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
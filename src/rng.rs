@@ -0,0 +1,112 @@
+//! The random number generator used for everything random, so that it can be seeded to be deterministic.
+//!
+//! The algorithm is xoshiro256** (<https://prng.di.unimi.it/>), seeded with SplitMix64 as recommended by its authors.
+//! It is implemented here so that the same seed gives the same numbers on every platform and in every version.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+pub struct Rng {
+    state: [u64; 4],
+}
+
+fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut split_mix_state = seed;
+        let mut state = [0; 4];
+        for part in &mut state {
+            *part = split_mix_64(&mut split_mix_state);
+        }
+        Self { state }
+    }
+
+    /// Returns a seed that is different every time.
+    pub fn random_seed() -> u64 {
+        // The keys of the standard library's hash maps are random
+        RandomState::new().build_hasher().finish()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = &mut self.state;
+        let result = s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = *s1 << 17;
+
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= t;
+        *s3 = s3.rotate_left(45);
+
+        result
+    }
+
+    /// Returns `true` with the given probability between 0 and 1.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        // The upper 53 bits make up a float between 0 and 1 with every value equally likely
+        let float = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        float < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_answers() {
+        // The reference implementation's output for this state
+        let mut rng = Rng {
+            state: [1, 2, 3, 4],
+        };
+        let numbers: Vec<u64> = (0..10).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            numbers,
+            [
+                11520,
+                0,
+                1509978240,
+                1215971899390074240,
+                1216172134540287360,
+                607988272756665600,
+                16172922978634559625,
+                8476171486693032832,
+                10595114339597558777,
+                2904607092377533576,
+            ]
+        );
+
+        assert_eq!(split_mix_64(&mut 0), 0xe220_a839_7b1d_cdaf);
+
+        let mut rng = Rng::new(42);
+        let numbers: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            numbers,
+            [
+                1546998764402558742,
+                6990951692964543102,
+                12544586762248559009
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chance() {
+        let mut rng = Rng::new(0);
+        assert!((0..100).all(|_| rng.chance(1.0)));
+        assert!((0..100).all(|_| !rng.chance(0.0)));
+
+        let count = (0..10_000).filter(|_| rng.chance(0.75)).count();
+        assert!((7_000..8_000).contains(&count), "{}", count);
+    }
+}
@@ -0,0 +1,189 @@
+//! A sandbox is a copy of the board for trying things out without touching the real board.
+
+use crate::{
+    grid::{Cell, Grid},
+    undo_redo_buffer::Operation,
+};
+use std::time::Duration;
+use terminal::util::Point;
+
+#[derive(Default)]
+pub struct Sandbox {
+    /// The grid that is currently not shown: the real grid while the sandbox is shown and the sandbox grid otherwise.
+    inactive_grid: Option<Grid>,
+    /// Whether the sandbox is shown instead of the real grid.
+    pub toggled: bool,
+}
+
+impl Grid {
+    /// Creates a copy of this grid with a separate, empty undo redo buffer.
+    fn fork(&self) -> Grid {
+        Grid {
+            size: self.size.clone(),
+            cells: self.cells.clone(),
+            horizontal_clues_solutions: self.horizontal_clues_solutions.clone(),
+            vertical_clues_solutions: self.vertical_clues_solutions.clone(),
+            solution: self.solution.clone(),
+            max_clues_size: self.max_clues_size.clone(),
+            undo_redo_buffer: self.undo_redo_buffer.emptied(),
+            playing_time: self.playing_time,
+            tags: self.tags.clone(),
+        }
+    }
+}
+
+impl Sandbox {
+    pub fn controls_help(&self) -> &'static [&'static str] {
+        if self.toggled {
+            crate::SANDBOX_CONTROLS_HELP
+        } else {
+            crate::BASIC_CONTROLS_HELP
+        }
+    }
+
+    /// Switches between the real grid and the sandbox, forking the real grid if there is no sandbox yet.
+    pub fn toggle(&mut self, grid: &mut Grid) {
+        if let Some(inactive_grid) = &mut self.inactive_grid {
+            std::mem::swap(grid, inactive_grid);
+        } else {
+            let sandbox_grid = grid.fork();
+            self.inactive_grid = Some(std::mem::replace(grid, sandbox_grid));
+        }
+        self.toggled = !self.toggled;
+    }
+
+    /// Adds to the playing time of the shown grid and the inactive one, so that the time goes on in the sandbox too.
+    pub fn add_playing_time(&mut self, grid: &mut Grid, duration: Duration) {
+        grid.playing_time += duration;
+        if let Some(inactive_grid) = &mut self.inactive_grid {
+            inactive_grid.playing_time += duration;
+        }
+    }
+
+    /// Returns the real grid regardless of whether the sandbox is shown or not.
+    pub fn real_grid<'a>(&'a self, grid: &'a Grid) -> &'a Grid {
+        match &self.inactive_grid {
+            Some(inactive_grid) if self.toggled => inactive_grid,
+            _ => grid,
+        }
+    }
+
+    /// Throws away the sandbox and switches back to the real grid.
+    ///
+    /// `grid` must be the shown sandbox grid.
+    pub fn discard(&mut self, grid: &mut Grid) {
+        debug_assert!(self.toggled);
+
+        if let Some(real_grid) = self.inactive_grid.take() {
+            *grid = real_grid;
+        }
+        self.toggled = false;
+    }
+
+    /// Applies all cells that differ between the sandbox and the real grid to the real grid as one operation,
+    /// then throws away the sandbox and switches back to the real grid.
+    ///
+    /// `grid` must be the shown sandbox grid. Returns the amount of merged cells.
+    pub fn merge(&mut self, grid: &mut Grid) -> usize {
+        let sandbox_grid = grid.fork();
+        self.discard(grid);
+
+        let cells: Vec<(Point, Cell)> = (0..grid.size.height)
+            .flat_map(|y| (0..grid.size.width).map(move |x| Point { x, y }))
+            .zip(sandbox_grid.cells)
+            .filter(|(point, sandbox_cell)| grid.get_cell(*point) != *sandbox_cell)
+            .collect();
+
+        let merged_cell_count = cells.len();
+
+        if merged_cell_count != 0 {
+            grid.apply(Operation::SetCells(cells));
+        }
+
+        merged_cell_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminal::util::Size;
+
+    fn get_grid() -> Grid {
+        let size = Size {
+            width: 4,
+            height: 3,
+        };
+        let mut cells = vec![Cell::Empty; size.product() as usize];
+        cells[1] = Cell::Filled;
+        cells[6] = Cell::Filled;
+        let mut grid = Grid::new(size, cells);
+        let point = Point { x: 0, y: 0 };
+        *grid.get_mut_cell(point) = Cell::Crossed;
+        grid.undo_redo_buffer.push(Operation::SetCell {
+            point,
+            cell: Cell::Crossed,
+        });
+        grid
+    }
+
+    #[test]
+    fn test_fork_and_discard() {
+        let mut grid = get_grid();
+        let real_cells = grid.cells.clone();
+        let mut sandbox = Sandbox::default();
+
+        sandbox.toggle(&mut grid);
+        assert!(sandbox.toggled);
+        assert_eq!(grid.cells, real_cells);
+
+        *grid.get_mut_cell(Point { x: 3, y: 2 }) = Cell::Maybed;
+        assert_eq!(sandbox.real_grid(&grid).cells, real_cells);
+
+        // Switching back and forth keeps the sandbox
+        sandbox.toggle(&mut grid);
+        assert!(!sandbox.toggled);
+        assert_eq!(grid.cells, real_cells);
+        sandbox.toggle(&mut grid);
+        assert_eq!(grid.get_cell(Point { x: 3, y: 2 }), Cell::Maybed);
+
+        sandbox.discard(&mut grid);
+        assert!(!sandbox.toggled);
+        assert_eq!(grid.cells, real_cells);
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 1);
+    }
+
+    #[test]
+    fn test_fork_and_merge() {
+        let mut grid = get_grid();
+        let mut sandbox = Sandbox::default();
+
+        sandbox.toggle(&mut grid);
+        *grid.get_mut_cell(Point { x: 0, y: 0 }) = Cell::Empty;
+        *grid.get_mut_cell(Point { x: 1, y: 0 }) = Cell::Filled;
+        *grid.get_mut_cell(Point { x: 2, y: 1 }) = Cell::Filled;
+        let sandbox_cells = grid.cells.clone();
+
+        assert_eq!(sandbox.merge(&mut grid), 3);
+        assert!(!sandbox.toggled);
+        assert_eq!(grid.cells, sandbox_cells);
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 2);
+
+        // The merge is a single step that replays identically
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.get_cell(Point { x: 0, y: 0 }), Cell::Crossed);
+        assert_eq!(grid.get_cell(Point { x: 1, y: 0 }), Cell::Empty);
+        assert!(grid.redo_last_cell());
+        assert_eq!(grid.cells, sandbox_cells);
+    }
+
+    #[test]
+    fn test_merge_without_changes() {
+        let mut grid = get_grid();
+        let mut sandbox = Sandbox::default();
+
+        sandbox.toggle(&mut grid);
+        assert_eq!(sandbox.merge(&mut grid), 0);
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 1);
+    }
+}
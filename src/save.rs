@@ -0,0 +1,965 @@
+//! Saving and loading the player's progress on a grid.
+//!
+//! A save is a text file like this:
+//!
+//! ```text
+//! yayagram save 6
+//! size 4 2
+//! rows 2,1 1,1
+//! columns 2 1 - 2
+//! cells
+//! 11X1
+//! 1..?
+//! measured -
+//! time 83
+//! tags 1 1 2 2 1 4
+//! history 0
+//! ```
+//!
+//! The rows and columns are the clue solutions of each line, separated by spaces.
+//! A `-` stands for a line without clues.
+//! Then there is one line of cells per row, using the same characters as `.yaya` files except for `.` for empty cells.
+//! After that, the indices of the measured cells in the order they appear in, with `-` for a measured cell without index,
+//! how many seconds the grid has been played and the tagged cells as X- and Y-coordinates followed by their tag.
+//!
+//! The first line is the version of the format. Saves of all previous versions can still be loaded.
+//! After the cells follows the undo/redo history (see the `history` module).
+//!
+//! Large saves are written compressed (see the `compression` module).
+
+pub mod compression;
+pub mod history;
+pub mod recap;
+pub mod slot;
+
+use crate::{
+    args::MAX_GRID_SIZE,
+    editor::LoadError,
+    grid::{Cell, Grid, MAX_MEASURED_CELLS},
+    paths,
+    undo_redo_buffer::UndoRedoBuffer,
+};
+use std::{
+    fmt::Write,
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use terminal::util::Size;
+
+/// The progress on a grid as read from a save.
+#[derive(Debug)]
+pub struct Progress {
+    pub size: Size,
+    pub horizontal_clues_solutions: Vec<Vec<u16>>,
+    pub vertical_clues_solutions: Vec<Vec<u16>>,
+    pub cells: Vec<Cell>,
+    pub undo_redo_buffer: UndoRedoBuffer,
+    pub playing_time: Duration,
+    pub tags: Vec<Option<u8>>,
+}
+
+impl Progress {
+    /// Restores the progress on the grid.
+    ///
+    /// If the undo/redo history doesn't lead to the saved cells and tags, it starts over from them
+    /// so that undoing can't mess up the grid.
+    pub fn restore(self, grid: &mut Grid) {
+        grid.undo_redo_buffer.restore(self.undo_redo_buffer);
+        grid.rebuild();
+
+        let rebuilt_cells = grid.cells.iter().map(|cell| cell_char(*cell));
+        let mismatched = !rebuilt_cells.eq(self.cells.iter().map(|cell| cell_char(*cell)))
+            || grid.tags != self.tags;
+
+        grid.cells = self.cells;
+        grid.tags = self.tags;
+        grid.playing_time = self.playing_time;
+        if mismatched {
+            grid.restart_undo_redo_buffer();
+        }
+    }
+
+    /// Checks whether this is progress on the given grid's puzzle.
+    pub fn is_for(&self, grid: &Grid) -> bool {
+        self.size == grid.size
+            && self.horizontal_clues_solutions == grid.horizontal_clues_solutions
+            && self.vertical_clues_solutions == grid.vertical_clues_solutions
+    }
+}
+
+fn write_clues(string: &mut String, name: &str, clues_solutions: &[Vec<u16>]) {
+    string.push_str(name);
+    for clues in clues_solutions {
+        string.push(' ');
+        if clues.is_empty() {
+            string.push('-');
+        } else {
+            for (index, clue) in clues.iter().enumerate() {
+                if index != 0 {
+                    string.push(',');
+                }
+                write!(string, "{}", clue).unwrap();
+            }
+        }
+    }
+    string.push('\n');
+}
+
+/// The version of the save format written by this version of yayagram.
+///
+/// Version 5 is the same as version 6 except that it has no tags.
+/// Version 4 is the same as version 5 except that it has no playing time.
+/// Version 3 is the same as version 4 except that it has no indices of measured cells.
+/// Version 2 is the same as version 3 except that it has no undo/redo history.
+/// Version 1 is the same as version 2 except that it has no header with the version.
+const VERSION: u32 = 6;
+
+const HEADER: &str = "yayagram save ";
+
+/// Returns the character representing the cell in a save.
+pub const fn cell_char(cell: Cell) -> char {
+    match cell {
+        Cell::Empty => '.',
+        Cell::Filled => '1',
+        Cell::Crossed => 'X',
+        Cell::Maybed => '?',
+        Cell::Measured(_) => 'R',
+    }
+}
+
+/// Returns the cell represented by the character in a save.
+pub const fn char_cell(char: char) -> Option<Cell> {
+    Some(match char {
+        '.' => Cell::Empty,
+        '1' => Cell::Filled,
+        'X' => Cell::Crossed,
+        '?' => Cell::Maybed,
+        'R' => Cell::Measured(None),
+        _ => return None,
+    })
+}
+
+pub fn serialize(grid: &Grid) -> String {
+    let mut string = String::with_capacity(grid.cells.len() * 2);
+
+    writeln!(string, "{}{}", HEADER, VERSION).unwrap();
+    writeln!(string, "size {} {}", grid.size.width, grid.size.height).unwrap();
+    write_clues(&mut string, "rows", &grid.horizontal_clues_solutions);
+    write_clues(&mut string, "columns", &grid.vertical_clues_solutions);
+
+    string.push_str("cells\n");
+    for row in grid.cells.chunks(grid.size.width as usize) {
+        string.extend(row.iter().map(|cell| cell_char(*cell)));
+        string.push('\n');
+    }
+
+    string.push_str("measured");
+    for cell in &grid.cells {
+        match cell {
+            Cell::Measured(Some(index)) => write!(string, " {}", index).unwrap(),
+            Cell::Measured(None) => string.push_str(" -"),
+            _ => {}
+        }
+    }
+    string.push('\n');
+
+    writeln!(string, "time {}", grid.playing_time.as_secs()).unwrap();
+
+    string.push_str("tags");
+    for (index, tag) in grid.tags.iter().enumerate() {
+        if let Some(tag) = tag {
+            let x = index % grid.size.width as usize;
+            let y = index / grid.size.width as usize;
+            write!(string, " {} {} {}", x, y, tag).unwrap();
+        }
+    }
+    string.push('\n');
+
+    history::write(&mut string, grid);
+
+    string
+}
+
+fn error(message: &'static str, line_number: usize) -> LoadError {
+    LoadError {
+        message,
+        line_number: Some(line_number),
+    }
+}
+
+/// Returns the rest of the line after the given keyword.
+fn expect_keyword<'a>(
+    line: Option<&'a str>,
+    keyword: &str,
+    line_number: usize,
+) -> Result<&'a str, LoadError> {
+    line.and_then(|line| line.strip_prefix(keyword))
+        .ok_or_else(|| error("expected keyword", line_number))
+}
+
+fn parse_clues(str: &str, line_count: u16, line_number: usize) -> Result<Vec<Vec<u16>>, LoadError> {
+    let clues_solutions = str
+        .split_whitespace()
+        .map(|clues| {
+            if clues == "-" {
+                Ok(Vec::new())
+            } else {
+                clues
+                    .split(',')
+                    .map(|clue| clue.parse::<u16>())
+                    .collect::<Result<Vec<u16>, _>>()
+                    .map_err(|_| error("invalid clue", line_number))
+            }
+        })
+        .collect::<Result<Vec<Vec<u16>>, LoadError>>()?;
+
+    if clues_solutions.len() != line_count as usize {
+        return Err(error("wrong amount of clues", line_number));
+    }
+
+    Ok(clues_solutions)
+}
+
+/// Gives the measured cells their indices.
+fn parse_measured_indices(
+    str: &str,
+    cells: &mut [Cell],
+    line_number: usize,
+) -> Result<(), LoadError> {
+    let mut indices = str.split_whitespace();
+
+    for cell in cells.iter_mut() {
+        if let Cell::Measured(measured_index) = cell {
+            let index = indices
+                .next()
+                .ok_or_else(|| error("wrong amount of measured indices", line_number))?;
+            if index != "-" {
+                let index = index
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|index| (1..=MAX_MEASURED_CELLS).contains(index))
+                    .ok_or_else(|| error("invalid measured index", line_number))?;
+                *measured_index = Some(index);
+            }
+        }
+    }
+
+    if indices.next().is_some() {
+        return Err(error("wrong amount of measured indices", line_number));
+    }
+
+    Ok(())
+}
+
+fn parse_tags(str: &str, size: &Size, line_number: usize) -> Result<Vec<Option<u8>>, LoadError> {
+    let mut tags = vec![None; size.product() as usize];
+    let parts: Vec<&str> = str.split_whitespace().collect();
+
+    for triple in parts.chunks(3) {
+        let tag = match triple {
+            [x, y, tag] => x
+                .parse::<u16>()
+                .ok()
+                .zip(y.parse::<u16>().ok())
+                .filter(|(x, y)| *x < size.width && *y < size.height)
+                .zip(history::parse_tag(tag).flatten()),
+            _ => None,
+        };
+        let ((x, y), tag) = tag.ok_or_else(|| error("invalid tag", line_number))?;
+        tags[y as usize * size.width as usize + x as usize] = Some(tag);
+    }
+
+    Ok(tags)
+}
+
+pub fn deserialize(str: &str) -> Result<Progress, LoadError> {
+    let mut lines = str.lines().peekable();
+
+    let version = match lines.peek().and_then(|line| line.strip_prefix(HEADER)) {
+        Some(version) => {
+            lines.next();
+            version
+                .parse::<u32>()
+                .map_err(|_| error("invalid version", 1))?
+        }
+        None => 1,
+    };
+    if version > VERSION {
+        return Err(LoadError {
+            message: "save was created by a newer version of yayagram",
+            line_number: None,
+        });
+    }
+
+    let first_line_number = if version == 1 { 1 } else { 2 };
+    deserialize_progress(lines, version, first_line_number)
+}
+
+/// Deserializes everything after the header, starting with the given line.
+fn deserialize_progress<'a>(
+    mut lines: impl Iterator<Item = &'a str>,
+    version: u32,
+    first_line_number: usize,
+) -> Result<Progress, LoadError> {
+    let line_number = |line_index: usize| first_line_number + line_index;
+
+    let mut size = expect_keyword(lines.next(), "size ", line_number(0))?
+        .split(' ')
+        .map(|length| length.parse::<u16>());
+    let size = match (size.next(), size.next(), size.next()) {
+        (Some(Ok(width)), Some(Ok(height)), None)
+            if (1..=MAX_GRID_SIZE).contains(&width) && (1..=MAX_GRID_SIZE).contains(&height) =>
+        {
+            Size { width, height }
+        }
+        _ => return Err(error("invalid size", line_number(0))),
+    };
+
+    let horizontal_clues_solutions = parse_clues(
+        expect_keyword(lines.next(), "rows", line_number(1))?,
+        size.height,
+        line_number(1),
+    )?;
+    let vertical_clues_solutions = parse_clues(
+        expect_keyword(lines.next(), "columns", line_number(2))?,
+        size.width,
+        line_number(2),
+    )?;
+
+    if !expect_keyword(lines.next(), "cells", line_number(3))?.is_empty() {
+        return Err(error("expected keyword", line_number(3)));
+    }
+
+    let mut cells = Vec::<Cell>::with_capacity(size.product() as usize);
+    for y in 0..size.height as usize {
+        let line_number = line_number(4 + y);
+        let line = lines
+            .next()
+            .ok_or_else(|| error("expected cells", line_number))?;
+
+        let cell_count = cells.len();
+        for char in line.chars() {
+            cells.push(
+                char_cell(char)
+                    .ok_or_else(|| error("expected '.', '1', 'X', '?' or 'R'", line_number))?,
+            );
+        }
+        if cells.len() - cell_count != size.width as usize {
+            return Err(error("wrong amount of cells", line_number));
+        }
+    }
+
+    let mut next_line_index = 4 + size.height as usize;
+
+    if version >= 4 {
+        parse_measured_indices(
+            expect_keyword(lines.next(), "measured", line_number(next_line_index))?,
+            &mut cells,
+            line_number(next_line_index),
+        )?;
+        next_line_index += 1;
+    }
+
+    let playing_time = if version >= 5 {
+        let line_number = line_number(next_line_index);
+        let seconds = expect_keyword(lines.next(), "time ", line_number)?
+            .parse::<u64>()
+            .map_err(|_| error("invalid time", line_number))?;
+        next_line_index += 1;
+        Duration::from_secs(seconds)
+    } else {
+        Duration::ZERO
+    };
+
+    let tags = if version >= 6 {
+        let line_number = line_number(next_line_index);
+        next_line_index += 1;
+        parse_tags(
+            expect_keyword(lines.next(), "tags", line_number)?,
+            &size,
+            line_number,
+        )?
+    } else {
+        vec![None; size.product() as usize]
+    };
+
+    let undo_redo_buffer = if version >= 3 {
+        history::parse(lines, &size, line_number(next_line_index))?
+    } else {
+        UndoRedoBuffer::default()
+    };
+
+    Ok(Progress {
+        size,
+        horizontal_clues_solutions,
+        vertical_clues_solutions,
+        cells,
+        undo_redo_buffer,
+        playing_time,
+        tags,
+    })
+}
+
+/// After this many operations, the progress is saved automatically.
+const AUTOSAVE_INTERVAL: usize = 10;
+
+/// Saves the progress on a grid automatically so that it can be restored after a crash or exit.
+///
+/// Every puzzle has its own autosave file, identified by the grid's fingerprint.
+pub struct Autosave {
+    dir: PathBuf,
+    /// Where autosaves were stored before they were moved from the data directory to the state directory.
+    /// They are still loaded from there until they are saved again.
+    legacy_dir: Option<PathBuf>,
+    /// The value of the undo redo buffer's push count at the last save.
+    last_push_count: usize,
+}
+
+impl Autosave {
+    /// Returns `None` if there is no directory to save to.
+    pub fn new(grid: &Grid) -> Option<Self> {
+        let dir = paths::dir(paths::Kind::State)?.join("autosaves");
+        let legacy_dir = paths::dir(paths::Kind::Data)
+            .map(|data_dir| data_dir.join("autosaves"))
+            .filter(|legacy_dir| *legacy_dir != dir);
+
+        Some(Self {
+            dir,
+            legacy_dir,
+            last_push_count: grid.undo_redo_buffer.push_count,
+        })
+    }
+
+    fn file_name(grid: &Grid) -> String {
+        format!("{:016x}", grid.fingerprint())
+    }
+
+    /// The grid can change, e.g. when a save slot is loaded, so this is not stored.
+    fn path(&self, grid: &Grid) -> PathBuf {
+        self.dir.join(Self::file_name(grid))
+    }
+
+    fn legacy_path(&self, grid: &Grid) -> Option<PathBuf> {
+        Some(self.legacy_dir.as_ref()?.join(Self::file_name(grid)))
+    }
+
+    /// Returns the previously autosaved progress on this grid's puzzle, if any.
+    ///
+    /// An error means that there is an autosave but it is corrupted, e.g. because it was truncated or edited.
+    pub fn load(&self, grid: &Grid) -> Result<Option<Progress>, LoadError> {
+        let bytes = match fs::read(self.path(grid)).or_else(|err| match self.legacy_path(grid) {
+            Some(legacy_path) => fs::read(legacy_path),
+            None => Err(err),
+        }) {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(None),
+        };
+        let content = compression::decode(bytes).map_err(|message| LoadError {
+            message,
+            line_number: None,
+        })?;
+        let progress = deserialize(&content)?;
+
+        if progress.is_for(grid) {
+            Ok(Some(progress))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns when the progress on this grid's puzzle was last autosaved, if it was and that is known.
+    pub fn saved_time(&self, grid: &Grid) -> Option<SystemTime> {
+        fs::metadata(self.path(grid))
+            .or_else(|err| match self.legacy_path(grid) {
+                Some(legacy_path) => fs::metadata(legacy_path),
+                None => Err(err),
+            })
+            .ok()?
+            .modified()
+            .ok()
+    }
+
+    pub fn save(&mut self, grid: &Grid) -> Result<(), &'static str> {
+        self.last_push_count = grid.undo_redo_buffer.push_count;
+
+        paths::create_dir(&self.dir).map_err(|_| "Autosave directory creation failed")?;
+        fs::write(self.path(grid), compression::encode(serialize(grid)))
+            .map_err(|_| "Autosave failed")?;
+
+        // The legacy autosave is outdated now
+        if let Some(legacy_path) = self.legacy_path(grid) {
+            let _ = fs::remove_file(legacy_path);
+        }
+        Ok(())
+    }
+
+    /// Saves if enough operations were done since the last save.
+    pub fn save_if_due(&mut self, grid: &Grid) -> Result<(), &'static str> {
+        let push_count = grid.undo_redo_buffer.push_count;
+
+        // If the push count went down, the grid was replaced by another one with a new buffer
+        if push_count < self.last_push_count
+            || push_count - self.last_push_count >= AUTOSAVE_INTERVAL
+        {
+            self.save(grid)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Removes the grid's autosave, e.g. because the grid was solved.
+    pub fn remove(&self, grid: &Grid) {
+        // The files might not exist
+        let _ = fs::remove_file(self.path(grid));
+        if let Some(legacy_path) = self.legacy_path(grid) {
+            let _ = fs::remove_file(legacy_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::fixture,
+        undo_redo_buffer::{Measurement, Operation},
+    };
+    use terminal::util::Point;
+
+    fn get_grid() -> Grid {
+        let mut grid = fixture::example();
+        // The saves below were written with the first measured cell
+        grid.cells[6] = Cell::Measured(Some(1));
+        grid.playing_time = Duration::from_secs(83);
+        grid
+    }
+
+    const VERSION_1: &str = "size 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R?\n";
+    const VERSION_2: &str =
+        "yayagram save 2\nsize 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R?\n";
+    const VERSION_3: &str =
+        "yayagram save 3\nsize 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R?\nhistory 0\n";
+    const VERSION_4: &str = "yayagram save 4\nsize 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R?\nmeasured 1\nhistory 0\n";
+    const VERSION_5: &str = "yayagram save 5\nsize 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R?\nmeasured 1\ntime 83\nhistory 0\n";
+    const VERSION_6: &str = "yayagram save 6\nsize 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R?\nmeasured 1\ntime 83\ntags\nhistory 0\n";
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(serialize(&get_grid()), VERSION_6);
+    }
+
+    #[test]
+    fn test_versions() {
+        let grid = get_grid();
+
+        for save in [
+            VERSION_1, VERSION_2, VERSION_3, VERSION_4, VERSION_5, VERSION_6,
+        ] {
+            let progress = deserialize(save).unwrap();
+            assert!(progress.is_for(&grid));
+            assert_eq!(progress.cells[2], Cell::Crossed);
+            assert_eq!(progress.cells[7], Cell::Maybed);
+        }
+
+        // Older saves have no measured indices
+        assert_eq!(
+            deserialize(VERSION_3).unwrap().cells[6],
+            Cell::Measured(None)
+        );
+
+        // Older saves have no playing time
+        assert_eq!(deserialize(VERSION_4).unwrap().playing_time, Duration::ZERO);
+        assert_eq!(
+            deserialize(VERSION_5).unwrap().playing_time,
+            Duration::from_secs(83)
+        );
+
+        // A loaded version 1 save is saved as the current version
+        let mut migrated_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(VERSION_1).unwrap().restore(&mut migrated_grid);
+        assert_eq!(
+            serialize(&migrated_grid),
+            VERSION_6
+                .replace("measured 1", "measured -")
+                .replace("time 83", "time 0")
+                // The migrated cells have no history so it starts from them
+                .replace(
+                    "history 0",
+                    "history 1\nsnapshot 0 0 1 - 1 0 1 - 2 0 X - 2 1 R - 3 1 ? -"
+                )
+        );
+
+        let newer_save = VERSION_6.replace("save 6", "save 7");
+        let err = deserialize(&newer_save).unwrap_err();
+        assert_eq!(
+            err.message,
+            "save was created by a newer version of yayagram"
+        );
+        assert_eq!(err.line_number, None);
+
+        let invalid_version = VERSION_4.replace("save 4", "save four");
+        assert_eq!(
+            deserialize(&invalid_version).unwrap_err().line_number,
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let grid = get_grid();
+        let progress = deserialize(&serialize(&grid)).unwrap();
+
+        assert!(progress.is_for(&grid));
+        assert_eq!(progress.cells, grid.cells);
+        assert_eq!(progress.playing_time, grid.playing_time);
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut grid = get_grid();
+        for (index, tag) in [(0, 1), (3, 2), (4, 3), (7, 4)] {
+            grid.tags[index] = Some(tag);
+        }
+        let save = serialize(&grid);
+        assert!(save.contains("\ntags 0 0 1 3 0 2 0 1 3 3 1 4\n"));
+
+        let mut loaded_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(&save).unwrap().restore(&mut loaded_grid);
+        assert_eq!(loaded_grid.tags, grid.tags);
+        assert_eq!(loaded_grid.cells, grid.cells);
+
+        assert!(deserialize(&VERSION_5.replace("time 83", "time 83\ntags 0 0 1")).is_err());
+        assert!(deserialize(VERSION_5)
+            .unwrap()
+            .tags
+            .iter()
+            .all(Option::is_none));
+    }
+
+    fn push_operations(grid: &mut Grid, operations: Vec<Operation>) {
+        for operation in operations {
+            grid.undo_redo_buffer.push(operation);
+        }
+        grid.rebuild();
+    }
+
+    #[test]
+    fn test_history() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        let measurement = Measurement::new([
+            Point { x: 0, y: 1 },
+            Point { x: 1, y: 1 },
+            Point { x: 2, y: 1 },
+        ])
+        .unwrap();
+        push_operations(
+            &mut grid,
+            vec![
+                Operation::SetCell {
+                    point: Point { x: 1, y: 0 },
+                    cell: Cell::Crossed,
+                },
+                Operation::Clear,
+                Operation::Fill {
+                    point: Point { x: 0, y: 0 },
+                    first_cell: Cell::Empty,
+                    fill_cell: Cell::Maybed,
+                },
+                Operation::SetCells(vec![(Point { x: 3, y: 1 }, Cell::Filled)]),
+                Operation::Measure(measurement),
+                Operation::SetCell {
+                    point: Point { x: 0, y: 0 },
+                    cell: Cell::Filled,
+                },
+            ],
+        );
+        grid.undo_last_cell();
+        let save = serialize(&grid);
+
+        let mut loaded_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(&save).unwrap().restore(&mut loaded_grid);
+
+        // Rebuilding the history must lead to exactly the saved cells
+        assert_eq!(loaded_grid.cells, grid.cells);
+        assert_eq!(loaded_grid.undo_redo_buffer.index, 5);
+        assert_eq!(serialize(&loaded_grid), save);
+
+        loaded_grid.redo_last_cell();
+        assert_eq!(loaded_grid.get_cell(Point { x: 0, y: 0 }), Cell::Filled);
+        while loaded_grid.undo_last_cell() {}
+        assert!(loaded_grid.cells.iter().all(|cell| *cell == Cell::Empty));
+    }
+
+    #[test]
+    fn test_mismatched_history() {
+        // The cells were edited by hand so the history doesn't lead to them anymore
+        let save = VERSION_4.replace("history 0", "history 1\nclear");
+        let save = save.replace("11X.", "11XX");
+
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(&save).unwrap().restore(&mut grid);
+        assert_eq!(grid.cells[3], Cell::Crossed);
+        assert!(matches!(
+            grid.undo_redo_buffer.buffer[..],
+            [Operation::Snapshot { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_undo_clear_without_history() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(VERSION_6).unwrap().restore(&mut grid);
+        let cells = grid.cells.clone();
+        assert_eq!(cells[6], Cell::Measured(Some(1)));
+
+        grid.apply(Operation::Clear);
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells, cells);
+        // The loaded cells can't be undone
+        assert!(!grid.undo_last_cell());
+        assert_eq!(grid.cells, cells);
+
+        assert!(grid.redo_last_cell());
+        assert!(grid.cells.iter().all(|cell| *cell == Cell::Empty));
+    }
+
+    #[test]
+    fn test_measured_history() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        let measurement = Measurement::new((0..4).map(|x| Point { x, y: 1 })).unwrap();
+        push_operations(
+            &mut grid,
+            vec![
+                Operation::SetCell {
+                    point: Point { x: 1, y: 1 },
+                    cell: Cell::Filled,
+                },
+                Operation::Measure(measurement),
+                Operation::SetCell {
+                    point: Point { x: 3, y: 0 },
+                    cell: Cell::Measured(Some(4)),
+                },
+            ],
+        );
+        assert_eq!(grid.get_cell(Point { x: 2, y: 1 }), Cell::Measured(Some(3)));
+        let save = serialize(&grid);
+
+        let mut loaded_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(&save).unwrap().restore(&mut loaded_grid);
+        assert_eq!(loaded_grid.cells, grid.cells);
+
+        // Replaying the measurement gives the same indices
+        loaded_grid.undo_last_cell();
+        loaded_grid.redo_last_cell();
+        assert_eq!(loaded_grid.cells, grid.cells);
+    }
+
+    #[test]
+    fn test_truncated_history() {
+        let mut grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        let operations = (0..1500)
+            .map(|index| Operation::SetCell {
+                point: Point {
+                    x: index % 4,
+                    y: index / 4 % 2,
+                },
+                cell: if index % 3 == 0 {
+                    Cell::Filled
+                } else {
+                    Cell::Crossed
+                },
+            })
+            .collect();
+        push_operations(&mut grid, operations);
+        // Operations that can be redone are dropped first
+        grid.undo_last_cell();
+
+        let save = serialize(&grid);
+        assert!(save.lines().count() < 1100);
+
+        let mut loaded_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(&save).unwrap().restore(&mut loaded_grid);
+        assert_eq!(loaded_grid.cells, grid.cells);
+        assert_eq!(loaded_grid.undo_redo_buffer.buffer.len(), 1000);
+        assert_eq!(loaded_grid.undo_redo_buffer.index, 1000);
+        assert!(loaded_grid.undo_last_cell());
+
+        // The tags of the merged operations take up one of the saved ones too
+        grid.redo_last_cell();
+        grid.apply(Operation::Tag(vec![(Point { x: 0, y: 0 }, Some(1))]));
+        let operations = (0..1500)
+            .map(|index| Operation::SetCell {
+                point: Point { x: 1, y: 0 },
+                cell: if index % 2 == 0 {
+                    Cell::Filled
+                } else {
+                    Cell::Crossed
+                },
+            })
+            .collect();
+        push_operations(&mut grid, operations);
+
+        let mut loaded_grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        deserialize(&serialize(&grid))
+            .unwrap()
+            .restore(&mut loaded_grid);
+        assert_eq!(loaded_grid.cells, grid.cells);
+        assert_eq!(loaded_grid.tags, grid.tags);
+        assert!(matches!(
+            loaded_grid.undo_redo_buffer.buffer[..2],
+            [Operation::SetCells(_), Operation::Tag(_)]
+        ));
+        assert_eq!(loaded_grid.undo_redo_buffer.buffer.len(), 1000);
+    }
+
+    #[test]
+    fn test_is_for() {
+        let progress = deserialize(&serialize(&get_grid())).unwrap();
+
+        #[rustfmt::skip]
+        let other_grid = Grid::from_lines(&[
+            "11 1",
+            "1 1 ",
+        ]).unwrap();
+        assert!(!progress.is_for(&other_grid));
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        fn line_number(str: &str) -> Option<usize> {
+            deserialize(str).unwrap_err().line_number
+        }
+
+        assert_eq!(line_number(""), Some(1));
+        assert_eq!(line_number("size 0 2\n"), Some(1));
+        assert_eq!(line_number("size 4 2\nrows 2,1\n"), Some(2));
+        assert_eq!(line_number("size 4 2\nrows 2,1 1,a\n"), Some(2));
+        assert_eq!(
+            line_number("yayagram save 2\nsize 4 2\nrows 2,1 1,a\n"),
+            Some(3)
+        );
+        assert_eq!(
+            line_number("size 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n"),
+            Some(6)
+        );
+        assert_eq!(
+            line_number("size 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R\n"),
+            Some(6)
+        );
+        assert_eq!(
+            line_number("size 4 2\nrows 2,1 1,1\ncolumns 2 1 - 2\ncells\n11X.\n..R!\n"),
+            Some(6)
+        );
+
+        let measured_save = |measured: &str| VERSION_4.replace("measured 1", measured);
+        for measured in [
+            "measured",
+            "measured 1 2",
+            "measured 0",
+            "measured 100",
+            "measured x",
+        ] {
+            assert_eq!(
+                line_number(&measured_save(measured)),
+                Some(8),
+                "{}",
+                measured
+            );
+        }
+        assert!(deserialize(&measured_save("measured -")).is_ok());
+
+        for time in ["time", "time -1", "time x", "history 0"] {
+            let save = VERSION_5.replace("time 83", time);
+            assert_eq!(line_number(&save), Some(9), "{}", time);
+        }
+
+        for tags in [
+            "tag 0 0 1",
+            "tags 0 0",
+            "tags 0 0 5",
+            "tags 0 0 -",
+            "tags 4 0 1",
+            "tags 0 2 1",
+            "tags x 0 1",
+            "history 0",
+        ] {
+            let save = VERSION_6.replace("tags", tags);
+            assert_eq!(line_number(&save), Some(10), "{}", tags);
+        }
+
+        assert_eq!(line_number("size 100 2\n"), Some(1));
+        assert_eq!(line_number("size 4 2\nrows 2,1 1,65536\n"), Some(2));
+    }
+
+    #[test]
+    fn test_broken_bytes() {
+        let mut grid = get_grid();
+        grid.undo_redo_buffer.push(Operation::Fill {
+            point: Point { x: 3, y: 0 },
+            first_cell: Cell::Empty,
+            fill_cell: Cell::Crossed,
+        });
+        let save = serialize(&grid);
+
+        // No matter where the save is cut off or what bytes it was changed to, it must not panic
+        let mut broken_saves: Vec<Vec<u8>> = (0..save.len())
+            .map(|len| save.as_bytes()[..len].to_vec())
+            .collect();
+        for index in 0..save.len() {
+            for byte in [b'0', b'9', b' ', b'\n', b'-', 0xff] {
+                let mut broken_save = save.as_bytes().to_vec();
+                broken_save[index] = byte;
+                broken_saves.push(broken_save);
+            }
+        }
+
+        let autosave = Autosave {
+            dir: std::env::temp_dir().join(format!("yayagram-autosaves-{}", std::process::id())),
+            legacy_dir: None,
+            last_push_count: 0,
+        };
+        fs::create_dir_all(&autosave.dir).unwrap();
+        for broken_save in broken_saves {
+            fs::write(autosave.path(&grid), &broken_save).unwrap();
+            if let Ok(Some(progress)) = autosave.load(&grid) {
+                progress.restore(&mut Grid::from_lines(&["11 1", "1  1"]).unwrap());
+            }
+        }
+
+        fs::write(autosave.path(&grid), [0xfe, 0xff]).unwrap();
+        assert_eq!(
+            autosave.load(&grid).unwrap_err().message,
+            "save is not valid text"
+        );
+        autosave.remove(&grid);
+        assert!(autosave.load(&grid).unwrap().is_none());
+
+        fs::remove_dir(&autosave.dir).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_autosave() {
+        let dir = std::env::temp_dir().join(format!("yayagram-legacy-{}", std::process::id()));
+        let mut autosave = Autosave {
+            dir: dir.join("state"),
+            legacy_dir: Some(dir.join("data")),
+            last_push_count: 0,
+        };
+        let grid = get_grid();
+        let legacy_path = autosave.legacy_path(&grid).unwrap();
+        fs::create_dir_all(dir.join("data")).unwrap();
+        fs::write(&legacy_path, serialize(&grid)).unwrap();
+
+        // An autosave from before autosaves were moved is still found
+        assert_eq!(autosave.load(&grid).unwrap().unwrap().cells, grid.cells);
+
+        // and is replaced by the new one once saved again
+        autosave.save(&grid).unwrap();
+        assert!(!legacy_path.exists());
+        assert!(autosave.path(&grid).exists());
+        assert!(autosave.load(&grid).unwrap().is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+//! Compression of large saves, e.g. of big grids with a long undo/redo history.
+//!
+//! Small saves stay plain text so that they can still be read and edited by hand.
+//! A compressed save starts with a magic byte that can't start any valid UTF-8 text, followed by a list of tokens:
+//!
+//! - A control byte below 128 followed by that many plus one literal bytes.
+//! - A control byte of 128 or more followed by a distance of two bytes in little endian:
+//!   the bytes from that distance back are repeated for the lower 7 bits of the control byte plus `MIN_MATCH_LEN` bytes.
+//!
+//! Repeats may overlap with the bytes they produce so that long runs of the same cell take up only a few bytes.
+
+/// This can't be the first byte of any UTF-8 text, so saves that are not compressed are never mistaken for compressed ones.
+const MAGIC: u8 = 0xff;
+
+/// Saves shorter than this are not compressed.
+const MIN_COMPRESSED_LEN: usize = 4096;
+
+const MAX_LITERAL_LEN: usize = 128;
+const MIN_MATCH_LEN: usize = 4;
+const MAX_MATCH_LEN: usize = MIN_MATCH_LEN + 127;
+const MAX_DISTANCE: usize = u16::MAX as usize;
+
+/// The amount of bits of the hashes used for finding repeats.
+const HASH_BITS: u32 = 12;
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    (value.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+fn write_literals(output: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_LITERAL_LEN) {
+        output.push((chunk.len() - 1) as u8);
+        output.extend_from_slice(chunk);
+    }
+}
+
+fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut output = vec![MAGIC];
+    // The last position of every hash
+    let mut positions = vec![None; 1 << HASH_BITS];
+
+    let mut literal_start = 0;
+    let mut position = 0;
+    while position + MIN_MATCH_LEN <= bytes.len() {
+        let hash = hash(&bytes[position..]);
+        let distance = positions[hash]
+            .replace(position)
+            .map(|previous_position| position - previous_position)
+            .filter(|distance| *distance <= MAX_DISTANCE);
+        let match_len = distance.map_or(0, |distance| {
+            bytes[position..]
+                .iter()
+                .zip(&bytes[position - distance..])
+                .take(MAX_MATCH_LEN)
+                .take_while(|(byte, previous_byte)| byte == previous_byte)
+                .count()
+        });
+
+        match distance {
+            Some(distance) if match_len >= MIN_MATCH_LEN => {
+                write_literals(&mut output, &bytes[literal_start..position]);
+                output.push(0x80 | (match_len - MIN_MATCH_LEN) as u8);
+                output.extend_from_slice(&(distance as u16).to_le_bytes());
+
+                position += match_len;
+                literal_start = position;
+            }
+            _ => position += 1,
+        }
+    }
+    write_literals(&mut output, &bytes[literal_start..]);
+
+    output
+}
+
+fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+    let (magic, mut bytes) = bytes.split_first()?;
+    if *magic != MAGIC {
+        return None;
+    }
+
+    let mut output = Vec::<u8>::with_capacity(bytes.len() * 4);
+    while let Some((control, rest)) = bytes.split_first() {
+        if *control < 0x80 {
+            let len = *control as usize + 1;
+            output.extend_from_slice(rest.get(..len)?);
+            bytes = &rest[len..];
+        } else {
+            let len = (*control & 0x7f) as usize + MIN_MATCH_LEN;
+            let distance = u16::from_le_bytes([*rest.first()?, *rest.get(1)?]) as usize;
+            if distance == 0 {
+                return None;
+            }
+            let start = output.len().checked_sub(distance)?;
+            // This is byte by byte because the repeat may overlap with what it produces
+            for index in start..start + len {
+                output.push(output[index]);
+            }
+            bytes = &rest[2..];
+        }
+    }
+
+    Some(output)
+}
+
+/// Returns the bytes to write for the save, compressing it if it's large.
+pub fn encode(save: String) -> Vec<u8> {
+    if save.len() < MIN_COMPRESSED_LEN {
+        save.into_bytes()
+    } else {
+        compress(save.as_bytes())
+    }
+}
+
+/// Returns the text of a save written by [`encode`] or of a save from before saves were compressed.
+pub fn decode(bytes: Vec<u8>) -> Result<String, &'static str> {
+    let bytes = if bytes.first() == Some(&MAGIC) {
+        decompress(&bytes).ok_or("save is corrupted")?
+    } else {
+        bytes
+    };
+    String::from_utf8(bytes).map_err(|_| "save is not valid text")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::{Cell, Grid},
+        rng::Rng,
+        save::{deserialize, serialize},
+        undo_redo_buffer::Operation,
+    };
+    use terminal::util::{Point, Size};
+
+    fn get_big_grid() -> Grid {
+        let size = Size {
+            width: 50,
+            height: 50,
+        };
+        let mut grid = Grid::random(size, &mut Rng::new(7), false).unwrap();
+        for y in 0..50 {
+            // Long runs of crossed cells, crossed out one by one
+            for x in 0..y % 40 {
+                grid.undo_redo_buffer.push(Operation::SetCell {
+                    point: Point { x, y },
+                    cell: Cell::Crossed,
+                });
+            }
+        }
+        grid.rebuild();
+        grid
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let grid = get_big_grid();
+        let save = serialize(&grid);
+
+        let bytes = encode(save.clone());
+        assert_eq!(bytes[0], MAGIC);
+        assert!(
+            bytes.len() * 2 < save.len(),
+            "{} bytes compressed to {}",
+            save.len(),
+            bytes.len()
+        );
+
+        let decoded_save = decode(bytes).unwrap();
+        assert_eq!(decoded_save, save);
+        let progress = deserialize(&decoded_save).unwrap();
+        assert_eq!(progress.cells, grid.cells);
+        assert_eq!(
+            progress.undo_redo_buffer.buffer,
+            grid.undo_redo_buffer.buffer
+        );
+    }
+
+    #[test]
+    fn test_small_saves() {
+        let grid = Grid::from_lines(&["11 1", "1  1"]).unwrap();
+        let save = serialize(&grid);
+
+        // Saves from before compression and small ones are plain text
+        assert_eq!(encode(save.clone()), save.as_bytes());
+        assert_eq!(decode(save.clone().into_bytes()).unwrap(), save);
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let bytes = encode(serialize(&get_big_grid()));
+
+        for len in 1..bytes.len() {
+            // Truncated saves must not panic but might still decompress to a shorter save
+            let _ = decode(bytes[..len].to_vec());
+        }
+
+        // A repeat from before the start
+        assert_eq!(
+            decode(vec![MAGIC, 0, b'X', 0x80, 2, 0]).err(),
+            Some("save is corrupted")
+        );
+        assert_eq!(
+            decode(vec![MAGIC, 0, b'X', 0x80, 0, 0]).err(),
+            Some("save is corrupted")
+        );
+        assert_eq!(
+            decode(vec![MAGIC, 0x80, 1]).err(),
+            Some("save is corrupted")
+        );
+        assert_eq!(decode(vec![MAGIC, 0, b'X', 0x80, 1, 0]).unwrap(), "XXXXX");
+    }
+}
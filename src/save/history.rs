@@ -0,0 +1,458 @@
+//! Saving and loading the undo/redo history.
+//!
+//! The history follows the cells of a save:
+//!
+//! ```text
+//! history 3
+//! snapshot 0 0 1 - 1 0 X 2
+//! set 0 1 1
+//! fill 2 0 . X
+//! measure 0 1 R3 D1
+//! cells 1 1 ? 2 1 R2
+//! tag 3 0 2 3 1 -
+//! promote 2 X
+//! clear
+//! transform mirror-horizontally
+//! ```
+//!
+//! The number after `history` is how many of the operations are done. The ones after that can be redone.
+//! Then there is one operation per line. Points are X- and Y-coordinates followed by cells or tags if any.
+//! Measured cells are followed by their index, if they have one. A `-` stands for no tag.
+//! A snapshot, which is only ever the first operation, lists every cell that isn't empty or has a tag with that tag.
+
+use super::{cell_char, char_cell, error};
+use crate::{
+    editor::LoadError,
+    grid::{tag::TAG_COUNT, tools::transform::Transform, Cell, Grid, MAX_MEASURED_CELLS},
+    undo_redo_buffer::{self, Measurement, Operation, UndoRedoBuffer},
+};
+use std::fmt::Write;
+use terminal::util::{Point, Size};
+
+/// At most this many operations are saved. Older operations are merged into a single one.
+pub const MAX_SAVED_OPERATIONS: usize = 1000;
+
+fn write_cell(string: &mut String, cell: Cell) {
+    string.push(cell_char(cell));
+    if let Cell::Measured(Some(index)) = cell {
+        write!(string, "{}", index).unwrap();
+    }
+}
+
+/// Returns the point of the cell at the index.
+fn index_point(size: &Size, index: usize) -> Point {
+    Point {
+        x: (index % size.width as usize) as u16,
+        y: (index / size.width as usize) as u16,
+    }
+}
+
+fn write_operation(string: &mut String, operation: &Operation, size: &Size) {
+    match operation {
+        Operation::SetCell { point, cell } => {
+            write!(string, "set {} {} ", point.x, point.y).unwrap();
+            write_cell(string, *cell);
+            string.push('\n');
+        }
+        Operation::SetCells(cells) => {
+            string.push_str("cells");
+            for (point, cell) in cells {
+                write!(string, " {} {} ", point.x, point.y).unwrap();
+                write_cell(string, *cell);
+            }
+            string.push('\n');
+        }
+        Operation::Measure(measurement) => {
+            writeln!(string, "measure {}", measurement.encode()).unwrap()
+        }
+        Operation::Clear => string.push_str("clear\n"),
+        Operation::Transform(transform) => {
+            writeln!(string, "transform {}", transform.name()).unwrap()
+        }
+        Operation::Fill {
+            point,
+            first_cell,
+            fill_cell,
+        } => {
+            write!(string, "fill {} {} ", point.x, point.y).unwrap();
+            write_cell(string, *first_cell);
+            string.push(' ');
+            write_cell(string, *fill_cell);
+            string.push('\n');
+        }
+        Operation::Tag(tags) => {
+            string.push_str("tag");
+            for (point, tag) in tags {
+                write!(string, " {} {} ", point.x, point.y).unwrap();
+                write_tag(string, *tag);
+            }
+            string.push('\n');
+        }
+        Operation::Promote { tag, cell } => {
+            write!(string, "promote {} ", tag).unwrap();
+            write_cell(string, *cell);
+            string.push('\n');
+        }
+        Operation::Snapshot { cells, tags } => {
+            string.push_str("snapshot");
+            for (index, (cell, tag)) in cells.iter().zip(tags).enumerate() {
+                if *cell != Cell::Empty || tag.is_some() {
+                    let point = index_point(size, index);
+                    write!(string, " {} {} ", point.x, point.y).unwrap();
+                    write_cell(string, *cell);
+                    string.push(' ');
+                    write_tag(string, *tag);
+                }
+            }
+            string.push('\n');
+        }
+    }
+}
+
+/// Writes the tag or `-` if there is none.
+pub fn write_tag(string: &mut String, tag: Option<u8>) {
+    match tag {
+        Some(tag) => write!(string, "{}", tag).unwrap(),
+        None => string.push('-'),
+    }
+}
+
+/// Returns the grid with the cells and tags the given operations result in.
+fn replay(grid: &Grid, operations: &[Operation]) -> Grid {
+    // Replaying only needs the size and the cells
+    let mut replay_grid = Grid {
+        size: grid.size.clone(),
+        cells: vec![Cell::Empty; grid.cells.len()],
+        horizontal_clues_solutions: Vec::new(),
+        vertical_clues_solutions: Vec::new(),
+        solution: Vec::new(),
+        max_clues_size: grid.max_clues_size.clone(),
+        undo_redo_buffer: UndoRedoBuffer {
+            buffer: operations.to_vec(),
+            index: operations.len(),
+            push_count: 0,
+            changes: Default::default(),
+            group_start: 0,
+            checkpoints: Vec::new(),
+            branch: None,
+            last_push: None,
+            limit: undo_redo_buffer::DEFAULT_LIMIT,
+            merge_window: None,
+        },
+        playing_time: grid.playing_time,
+        tags: vec![None; grid.cells.len()],
+    };
+    replay_grid.rebuild();
+    replay_grid
+}
+
+/// Writes the grid's history, making sure not to write more than `MAX_SAVED_OPERATIONS` operations.
+pub fn write(string: &mut String, grid: &Grid) {
+    write_limited(string, grid, MAX_SAVED_OPERATIONS);
+}
+
+/// Returns the operations that set the cells and the tags that the given operations result in:
+/// one for the cells and, if there are any tags, one for them.
+fn merged_operations(grid: &Grid, operations: &[Operation]) -> Vec<Operation> {
+    let replay_grid = replay(grid, operations);
+    let point = |index: usize| index_point(&grid.size, index);
+
+    let cells: Vec<(Point, Cell)> = replay_grid
+        .cells
+        .into_iter()
+        .enumerate()
+        .filter(|(_, cell)| *cell != Cell::Empty)
+        .map(|(index, cell)| (point(index), cell))
+        .collect();
+    let mut merged_operations = vec![Operation::SetCells(cells)];
+
+    let tags: Vec<(Point, Option<u8>)> = replay_grid
+        .tags
+        .into_iter()
+        .enumerate()
+        .filter(|(_, tag)| tag.is_some())
+        .map(|(index, tag)| (point(index), tag))
+        .collect();
+    if !tags.is_empty() {
+        merged_operations.push(Operation::Tag(tags));
+    }
+    merged_operations
+}
+
+/// Writes the grid's history with at most `max_operations` operations, counting those that the dropped ones are merged into.
+pub fn write_limited(string: &mut String, grid: &Grid, max_operations: usize) {
+    let buffer = &grid.undo_redo_buffer.buffer;
+    let index = grid.undo_redo_buffer.index;
+
+    // Operations that can be redone are dropped first, then the oldest ones
+    let end = buffer.len().min(index.max(max_operations));
+    // If any are dropped, one is needed for the merged ones
+    let mut start = if end > max_operations {
+        end - (max_operations - 1)
+    } else {
+        0
+    };
+
+    let base_operations = if start == 0 {
+        Vec::new()
+    } else {
+        let mut base_operations = merged_operations(grid, &buffer[..start]);
+        // With tags, there is another one for them
+        if base_operations.len() + (end - start) > max_operations && start < end {
+            start += 1;
+            base_operations = merged_operations(grid, &buffer[..start]);
+        }
+        base_operations
+    };
+
+    let saved_index = index - start + base_operations.len();
+    writeln!(string, "history {}", saved_index).unwrap();
+
+    for operation in base_operations.iter().chain(&buffer[start..end]) {
+        write_operation(string, operation, &grid.size);
+    }
+}
+
+fn parse_point<'a>(parts: &mut impl Iterator<Item = &'a str>, size: &Size) -> Option<Point> {
+    let x = parts.next()?.parse::<u16>().ok()?;
+    let y = parts.next()?.parse::<u16>().ok()?;
+
+    if x < size.width && y < size.height {
+        Some(Point { x, y })
+    } else {
+        None
+    }
+}
+
+/// Parses a tag or `-` for no tag.
+pub fn parse_tag(str: &str) -> Option<Option<u8>> {
+    if str == "-" {
+        return Some(None);
+    }
+    str.parse::<u8>()
+        .ok()
+        .filter(|tag| (1..=TAG_COUNT).contains(tag))
+        .map(Some)
+}
+
+fn parse_cell<'a>(parts: &mut impl Iterator<Item = &'a str>) -> Option<Cell> {
+    let mut chars = parts.next()?.chars();
+    let cell = char_cell(chars.next()?)?;
+    let index = chars.as_str();
+
+    match cell {
+        _ if index.is_empty() => Some(cell),
+        Cell::Measured(None) => index
+            .parse::<usize>()
+            .ok()
+            .filter(|index| (1..=MAX_MEASURED_CELLS).contains(index))
+            .map(|index| Cell::Measured(Some(index))),
+        _ => None,
+    }
+}
+
+fn parse_operation(line: &str, size: &Size) -> Option<Operation> {
+    let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let mut parts = rest.split(' ').filter(|part| !part.is_empty()).peekable();
+
+    let operation = match keyword {
+        "set" => Operation::SetCell {
+            point: parse_point(&mut parts, size)?,
+            cell: parse_cell(&mut parts)?,
+        },
+        "cells" => {
+            let mut cells = Vec::<(Point, Cell)>::new();
+            while parts.peek().is_some() {
+                cells.push((parse_point(&mut parts, size)?, parse_cell(&mut parts)?));
+            }
+            Operation::SetCells(cells)
+        }
+        "measure" => {
+            let measurement = Measurement::decode(rest)?;
+            if !measurement.is_within(size) {
+                return None;
+            }
+            // The rest was consumed by the measurement
+            return Some(Operation::Measure(measurement));
+        }
+        "clear" => Operation::Clear,
+        "transform" => {
+            let transform = Transform::from_name(parts.next()?)?;
+            if !transform.fits(size) {
+                return None;
+            }
+            Operation::Transform(transform)
+        }
+        "fill" => Operation::Fill {
+            point: parse_point(&mut parts, size)?,
+            first_cell: parse_cell(&mut parts)?,
+            fill_cell: parse_cell(&mut parts)?,
+        },
+        "tag" => {
+            let mut tags = Vec::<(Point, Option<u8>)>::new();
+            while parts.peek().is_some() {
+                tags.push((parse_point(&mut parts, size)?, parse_tag(parts.next()?)?));
+            }
+            Operation::Tag(tags)
+        }
+        "promote" => Operation::Promote {
+            tag: parse_tag(parts.next()?)??,
+            cell: parse_cell(&mut parts)?,
+        },
+        "snapshot" => {
+            let cell_count = size.product() as usize;
+            let (mut cells, mut tags) = (vec![Cell::Empty; cell_count], vec![None; cell_count]);
+            while parts.peek().is_some() {
+                let point = parse_point(&mut parts, size)?;
+                let index = point.y as usize * size.width as usize + point.x as usize;
+                cells[index] = parse_cell(&mut parts)?;
+                tags[index] = parse_tag(parts.next()?)?;
+            }
+            Operation::Snapshot { cells, tags }
+        }
+        _ => return None,
+    };
+
+    if parts.next().is_none() {
+        Some(operation)
+    } else {
+        None
+    }
+}
+
+/// Parses the history, starting with the line after the cells.
+pub fn parse<'a>(
+    mut lines: impl Iterator<Item = &'a str>,
+    size: &Size,
+    first_line_number: usize,
+) -> Result<UndoRedoBuffer, LoadError> {
+    let index = lines
+        .next()
+        .and_then(|line| line.strip_prefix("history "))
+        .ok_or_else(|| error("expected keyword", first_line_number))?
+        .parse::<usize>()
+        .map_err(|_| error("invalid history index", first_line_number))?;
+
+    let buffer = lines
+        .enumerate()
+        .map(|(line_index, line)| {
+            parse_operation(line, size)
+                .ok_or_else(|| error("invalid operation", first_line_number + 1 + line_index))
+        })
+        .collect::<Result<Vec<Operation>, LoadError>>()?;
+
+    if index > buffer.len() {
+        return Err(error("invalid history index", first_line_number));
+    }
+
+    Ok(UndoRedoBuffer {
+        buffer,
+        index,
+        push_count: 0,
+        changes: Default::default(),
+        group_start: 0,
+        checkpoints: Vec::new(),
+        branch: None,
+        last_push: None,
+        // These aren't saved, they are those of the grid the history is restored on
+        limit: undo_redo_buffer::DEFAULT_LIMIT,
+        merge_window: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_size() -> Size {
+        Size {
+            width: 4,
+            height: 2,
+        }
+    }
+
+    #[test]
+    fn test_operations() {
+        let size = get_size();
+        let lines = [
+            "set 0 1 1",
+            "fill 2 0 . X",
+            "measure 0 1 R3",
+            "cells 1 1 ? 2 1 X",
+            "cells 0 0 R 3 1 R12",
+            "cells",
+            "tag 3 0 2 3 1 -",
+            "tag",
+            "promote 4 X",
+            "clear",
+            "transform mirror-vertically",
+            "snapshot 0 0 1 - 2 1 . 1 3 1 R12 2",
+            "snapshot",
+        ];
+
+        for line in lines {
+            let operation = parse_operation(line, &size).unwrap();
+            let mut string = String::new();
+            write_operation(&mut string, &operation, &size);
+            assert_eq!(string, format!("{}\n", line));
+        }
+    }
+
+    #[test]
+    fn test_invalid_operations() {
+        let size = get_size();
+        let lines = [
+            "",
+            "undo",
+            "set 4 0 1",
+            "set 0 2 1",
+            "set 0 0 A",
+            "set 0 0 11",
+            "set 0 0 1R",
+            "set 0 0 R0",
+            "set 0 0 R100",
+            "set 0 0 1 1",
+            "fill 0 0 .",
+            "measure 0 1 R4",
+            "measure 0 0 U1",
+            "cells 1 1",
+            "tag 0 0 5",
+            "tag 0 0 0",
+            "tag 0 0",
+            "promote - X",
+            "promote 1",
+            "clear 1",
+            "transform",
+            "transform flip",
+            // The grid isn't square
+            "transform rotate",
+        ];
+
+        for line in lines {
+            assert!(parse_operation(line, &size).is_none(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let size = get_size();
+
+        let buffer = parse(
+            ["history 1", "set 0 1 1", "clear"].iter().copied(),
+            &size,
+            7,
+        )
+        .unwrap();
+        assert_eq!(buffer.index, 1);
+        assert_eq!(buffer.buffer.len(), 2);
+
+        let line_number = |lines: &[&'static str]| {
+            parse(lines.iter().copied(), &size, 7)
+                .unwrap_err()
+                .line_number
+        };
+        assert_eq!(line_number(&[]), Some(7));
+        assert_eq!(line_number(&["history 2", "clear"]), Some(7));
+        assert_eq!(line_number(&["history 0", "clear", "set"]), Some(9));
+    }
+}
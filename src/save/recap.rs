@@ -0,0 +1,68 @@
+//! The recap of where the player left off, shown when autosaved progress is restored.
+
+use crate::{format, grid::Grid};
+use std::time::Duration;
+
+/// Summarizes the restored progress on the grid like `Last played 6 days ago, 61% solved, 3 tagged cells, 1h 42m played`.
+///
+/// `since_saved` is how long ago the progress was saved, if known. Parts with nothing to tell are left out.
+pub fn recap(grid: &Grid, since_saved: Option<Duration>) -> String {
+    let mut parts = Vec::<String>::new();
+
+    if let Some(since_saved) = since_saved {
+        parts.push(format!(
+            "Last played {}",
+            format::ago(since_saved.as_secs())
+        ));
+    }
+
+    let line_count = grid.size.width as usize + grid.size.height as usize;
+    let completion = format::percentage(grid.solved_line_count(), line_count);
+    parts.push(format!("{}% solved", completion));
+
+    match grid.tags.iter().filter(|tag| tag.is_some()).count() {
+        0 => {}
+        1 => parts.push("1 tagged cell".into()),
+        count => parts.push(format!("{} tagged cells", count)),
+    }
+
+    let playing_seconds = grid.playing_time.as_secs();
+    if playing_seconds != 0 {
+        if let Some(playing_time) = format::duration(playing_seconds, usize::MAX) {
+            parts.push(format!("{} played", playing_time));
+        }
+    }
+
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Cell;
+
+    #[test]
+    fn test_recap() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "11",
+            " 1",
+        ]).unwrap();
+        assert_eq!(recap(&grid, None), "0% solved");
+
+        grid.cells[1] = Cell::Filled;
+        grid.cells[3] = Cell::Filled;
+        grid.tags[0] = Some(1);
+        grid.playing_time = Duration::from_secs(6120);
+        assert_eq!(
+            recap(&grid, Some(Duration::from_secs(6 * 24 * 60 * 60))),
+            "Last played 6 days ago, 50% solved, 1 tagged cell, 1h 42m 0s played"
+        );
+
+        grid.tags[2] = Some(3);
+        assert_eq!(
+            recap(&grid, Some(Duration::from_secs(30))),
+            "Last played just now, 50% solved, 2 tagged cells, 1h 42m 0s played"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Named save slots the player can save to and load from at any time.
+//!
+//! Unlike autosaves, a slot can hold progress on any puzzle, so it starts with the puzzle code of the grid:
+//!
+//! ```text
+//! code AQQCrQ==
+//! yayagram save 3
+//! size 4 2
+//! ...
+//! ```
+//!
+//! The rest is the same as any other save, and large slots are compressed just like other saves.
+
+use super::{compression, deserialize, serialize};
+use crate::{format, grid::Grid, paths};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use terminal::util::Size;
+
+/// The maximum length of a slot name.
+pub const MAX_NAME_LEN: usize = 24;
+
+/// Returns whether the character may be part of a slot name.
+///
+/// As slot names are filenames, only very few characters are allowed.
+pub const fn is_name_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '-' || char == '_'
+}
+
+/// What is shown for a slot in the list of slots.
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+    pub size: Size,
+    /// How many percent of all rows and columns are solved.
+    pub completion: u8,
+    pub saved: SystemTime,
+}
+
+#[derive(Debug)]
+pub struct Slot {
+    pub name: String,
+    /// This is `None` if the slot is broken, i.e. it couldn't be read or is corrupted.
+    pub summary: Option<Summary>,
+}
+
+fn slots_dir() -> Result<PathBuf, &'static str> {
+    paths::dir(paths::Kind::Data)
+        .map(|data_dir| data_dir.join("slots"))
+        .ok_or("No directory to save slots to")
+}
+
+fn slot_to_string(grid: &Grid) -> String {
+    format!("code {}\n{}", grid.encode(), serialize(grid))
+}
+
+fn slot_from_str(str: &str) -> Result<Grid, &'static str> {
+    let (code_line, progress) = str.split_once('\n').ok_or("Slot is empty")?;
+    let code = code_line
+        .strip_prefix("code ")
+        .ok_or("Slot has no puzzle code")?;
+
+    let mut grid = Grid::decode(code)?;
+    let progress = deserialize(progress).map_err(|err| err.message)?;
+    if !progress.is_for(&grid) {
+        return Err("Slot progress is for a different puzzle");
+    }
+    progress.restore(&mut grid);
+
+    Ok(grid)
+}
+
+fn summarize(path: &Path) -> Option<Summary> {
+    let content = compression::decode(fs::read(path).ok()?).ok()?;
+    let grid = slot_from_str(&content).ok()?;
+    let saved = fs::metadata(path).ok()?.modified().ok()?;
+
+    let line_count = grid.size.width as usize + grid.size.height as usize;
+    let completion = format::percentage(grid.solved_line_count(), line_count);
+
+    Some(Summary {
+        size: grid.size,
+        completion,
+        saved,
+    })
+}
+
+fn list_in(dir: &Path) -> Vec<Slot> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // There are no slots yet
+        Err(_) => return Vec::new(),
+    };
+
+    let mut slots: Vec<Slot> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?.to_string();
+
+            Some(Slot {
+                summary: summarize(&path),
+                name,
+            })
+        })
+        .collect();
+    slots.sort_by(|a, b| a.name.cmp(&b.name));
+
+    slots
+}
+
+/// Returns all slots sorted by name.
+pub fn list() -> Vec<Slot> {
+    slots_dir().map(|dir| list_in(&dir)).unwrap_or_default()
+}
+
+pub fn save(name: &str, grid: &Grid) -> Result<(), &'static str> {
+    let dir = slots_dir()?;
+    paths::create_dir(&dir).map_err(|_| "Slot directory creation failed")?;
+    fs::write(dir.join(name), compression::encode(slot_to_string(grid)))
+        .map_err(|_| "Slot saving failed")
+}
+
+pub fn load(name: &str) -> Result<Grid, &'static str> {
+    let bytes = fs::read(slots_dir()?.join(name)).map_err(|_| "Slot reading failed")?;
+    let content = compression::decode(bytes).map_err(|_| "Slot is corrupted")?;
+    slot_from_str(&content)
+}
+
+pub fn delete(name: &str) -> Result<(), &'static str> {
+    fs::remove_file(slots_dir()?.join(name)).map_err(|_| "Slot deletion failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::fixture;
+
+    #[test]
+    fn test_round_trip() {
+        let grid = fixture::example();
+
+        let loaded_grid = slot_from_str(&slot_to_string(&grid)).unwrap();
+
+        assert_eq!(loaded_grid.cells, grid.cells);
+        assert_eq!(loaded_grid.solution, grid.solution);
+        assert_eq!(
+            loaded_grid.horizontal_clues_solutions,
+            grid.horizontal_clues_solutions
+        );
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let grid = fixture::example();
+        let other_grid = Grid::from_lines(&["1111", "1111"]).unwrap();
+
+        assert_eq!(slot_from_str("").unwrap_err(), "Slot is empty");
+        assert_eq!(
+            slot_from_str(&serialize(&grid)).unwrap_err(),
+            "Slot has no puzzle code"
+        );
+        assert_eq!(
+            slot_from_str(&format!(
+                "code {}\n{}",
+                other_grid.encode(),
+                serialize(&grid)
+            ))
+            .unwrap_err(),
+            "Slot progress is for a different puzzle"
+        );
+        assert!(slot_from_str(&format!("code {}\nsize 4", grid.encode())).is_err());
+    }
+
+    #[test]
+    fn test_list() {
+        let dir = std::env::temp_dir().join(format!("yayagram-slots-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(dir.join("b"), slot_to_string(&fixture::example())).unwrap();
+        fs::write(dir.join("a"), "garbage").unwrap();
+
+        let slots = list_in(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0].name, "a");
+        assert_eq!(slots[0].summary, None);
+        assert_eq!(slots[1].name, "b");
+        let summary = slots[1].summary.as_ref().unwrap();
+        assert_eq!(
+            summary.size,
+            Size {
+                width: 4,
+                height: 2
+            }
+        );
+        // The third column without clues and the last column are solved
+        assert_eq!(summary.completion, 33);
+
+        assert!(list_in(&dir).is_empty());
+    }
+
+    #[test]
+    fn test_is_name_char() {
+        assert!("my-slot_2".chars().all(is_name_char));
+        assert!(!is_name_char('/'));
+        assert!(!is_name_char('.'));
+        assert!(!is_name_char(' '));
+    }
+}
@@ -0,0 +1,1004 @@
+//! Solving single rows and columns on their own and whole grids with them.
+//!
+//! A line is solved by considering every placement of its clues that is consistent with the cells already marked:
+//! a cell is forced to be filled if it's filled in all of them and forced to be crossed out if it's empty in all of them.
+//! Only filled and crossed out cells are given. All other cells, including maybed ones, are unknown.
+//!
+//! Hints are found by solving the rows and columns of the player's grid one at a time.
+//!
+//! Whole grids are solved by solving lines until nothing changes and then guessing a cell,
+//! going back to the other possibility for it if the guess leads to a contradiction.
+
+use crate::grid::{Cell, CellKind, Clue, Grid};
+use std::{borrow::Cow, fmt};
+use terminal::util::Point;
+
+/// What is known about a cell of a line after solving it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineDeduction {
+    Filled,
+    Crossed,
+    Unknown,
+}
+
+/// Returns what the solver takes a cell of the kind to tell about the solution.
+pub const fn input(kind: CellKind) -> LineDeduction {
+    match kind {
+        CellKind::Filled => LineDeduction::Filled,
+        CellKind::Crossed => LineDeduction::Crossed,
+        // These are guesses or help at most
+        CellKind::Empty | CellKind::Maybed | CellKind::Measured => LineDeduction::Unknown,
+    }
+}
+
+const fn is_known(cell: Cell) -> bool {
+    !matches!(input(cell.kind()), LineDeduction::Unknown)
+}
+
+const fn can_be_filled(cell: Cell) -> bool {
+    !matches!(input(cell.kind()), LineDeduction::Crossed)
+}
+
+const fn can_be_empty(cell: Cell) -> bool {
+    !matches!(input(cell.kind()), LineDeduction::Filled)
+}
+
+/// Whether the block of the clue fits into the line at the start, including the empty cell after it, if any.
+fn block_fits(line: &[Cell], start: usize, clue: usize) -> bool {
+    start + clue <= line.len()
+        && line[start..start + clue]
+            .iter()
+            .all(|cell| can_be_filled(*cell))
+        && line
+            .get(start + clue)
+            .is_none_or(|cell| can_be_empty(*cell))
+}
+
+/// Returns the index after the block of the clue at the start and the empty cell after it.
+fn block_end(line: &[Cell], start: usize, clue: usize) -> usize {
+    (start + clue + 1).min(line.len())
+}
+
+/// Returns what is known about every cell of the line, or `None` if no placement of the clues is consistent with it.
+///
+/// Clues of zero are ignored, so `[0]` is the same as no clues.
+pub fn solve_line(clues: &[Clue], line: &[Cell]) -> Option<Vec<LineDeduction>> {
+    let clues: Vec<usize> = clues
+        .iter()
+        .filter(|clue| **clue != 0)
+        .map(|clue| *clue as usize)
+        .collect();
+    let len = line.len();
+    let clue_count = clues.len();
+
+    // `solvable[index][clue_index]` is whether the cells from `index` on can hold the clues from `clue_index` on
+    let mut solvable = vec![vec![false; clue_count + 1]; len + 1];
+    solvable[len][clue_count] = true;
+    for index in (0..len).rev() {
+        for clue_index in 0..=clue_count {
+            solvable[index][clue_index] = (can_be_empty(line[index])
+                && solvable[index + 1][clue_index])
+                || (clue_index < clue_count
+                    && block_fits(line, index, clues[clue_index])
+                    && solvable[block_end(line, index, clues[clue_index])][clue_index + 1]);
+        }
+    }
+    if !solvable[0][0] {
+        return None;
+    }
+
+    // Walk through every consistent placement at once, collecting what each cell can be
+    let mut reachable = vec![vec![false; clue_count + 1]; len + 1];
+    reachable[0][0] = true;
+    let mut can_be_empty_cells = vec![false; len];
+    // Incremented at the start and decremented after the end of each possible block
+    let mut filled_blocks = vec![0isize; len + 1];
+    for index in 0..len {
+        for clue_index in 0..=clue_count {
+            if !reachable[index][clue_index] || !solvable[index][clue_index] {
+                continue;
+            }
+
+            if can_be_empty(line[index]) && solvable[index + 1][clue_index] {
+                can_be_empty_cells[index] = true;
+                reachable[index + 1][clue_index] = true;
+            }
+
+            if clue_index < clue_count {
+                let clue = clues[clue_index];
+                let end = block_end(line, index, clue);
+                if block_fits(line, index, clue) && solvable[end][clue_index + 1] {
+                    filled_blocks[index] += 1;
+                    filled_blocks[index + clue] -= 1;
+                    if index + clue < len {
+                        can_be_empty_cells[index + clue] = true;
+                    }
+                    reachable[end][clue_index + 1] = true;
+                }
+            }
+        }
+    }
+
+    let mut block_count = 0;
+    Some(
+        can_be_empty_cells
+            .into_iter()
+            .zip(filled_blocks)
+            .map(|(can_be_empty, block_count_change)| {
+                block_count += block_count_change;
+                match (block_count > 0, can_be_empty) {
+                    (true, false) => LineDeduction::Filled,
+                    (false, true) => LineDeduction::Crossed,
+                    _ => LineDeduction::Unknown,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// How many placements of the clues are consistent with a line and how many of them fill each of its cells.
+///
+/// The counts are floating-point numbers because they grow exponentially with the length of the line.
+#[derive(Debug, PartialEq)]
+pub struct Placements {
+    pub count: f64,
+    pub filled: Vec<f64>,
+}
+
+impl Placements {
+    /// Returns the share of the placements that fill the cell at the index.
+    pub fn filled_share(&self, index: usize) -> f64 {
+        self.filled[index] / self.count
+    }
+}
+
+/// Counts the placements of the clues that are consistent with the line, or returns `None` if there are none.
+///
+/// This goes through the placements like [`solve_line`] but keeps how many ways lead to and away from each state
+/// rather than only whether there are any.
+pub fn count_placements(clues: &[Clue], line: &[Cell]) -> Option<Placements> {
+    let clues: Vec<usize> = clues
+        .iter()
+        .filter(|clue| **clue != 0)
+        .map(|clue| *clue as usize)
+        .collect();
+    let len = line.len();
+    let clue_count = clues.len();
+
+    // `ways_after[index][clue_index]` is how many ways the cells from `index` on can hold the clues from `clue_index` on
+    let mut ways_after = vec![vec![0.0; clue_count + 1]; len + 1];
+    ways_after[len][clue_count] = 1.0;
+    for index in (0..len).rev() {
+        for clue_index in 0..=clue_count {
+            let mut ways = 0.0;
+            if can_be_empty(line[index]) {
+                ways += ways_after[index + 1][clue_index];
+            }
+            if clue_index < clue_count && block_fits(line, index, clues[clue_index]) {
+                ways += ways_after[block_end(line, index, clues[clue_index])][clue_index + 1];
+            }
+            ways_after[index][clue_index] = ways;
+        }
+    }
+    let count = ways_after[0][0];
+    if count == 0.0 {
+        return None;
+    }
+
+    // `ways_before[index][clue_index]` is how many ways the cells before `index` can hold the clues before `clue_index`
+    let mut ways_before = vec![vec![0.0; clue_count + 1]; len + 1];
+    ways_before[0][0] = 1.0;
+    // Increased at the start and decreased after the end of each possible block by the placements with it
+    let mut filled_changes = vec![0.0; len + 1];
+    for index in 0..len {
+        for clue_index in 0..=clue_count {
+            let ways = ways_before[index][clue_index];
+            if ways == 0.0 {
+                continue;
+            }
+
+            if can_be_empty(line[index]) {
+                ways_before[index + 1][clue_index] += ways;
+            }
+
+            if clue_index < clue_count {
+                let clue = clues[clue_index];
+                if block_fits(line, index, clue) {
+                    let end = block_end(line, index, clue);
+                    let placements = ways * ways_after[end][clue_index + 1];
+                    filled_changes[index] += placements;
+                    filled_changes[index + clue] -= placements;
+                    ways_before[end][clue_index + 1] += ways;
+                }
+            }
+        }
+    }
+
+    let mut filled = 0.0;
+    Some(Placements {
+        count,
+        filled: filled_changes[..len]
+            .iter()
+            .map(|change| {
+                filled += change;
+                filled
+            })
+            .collect(),
+    })
+}
+
+/// Returns the clues and the points of every row and then every column.
+pub fn lines(grid: &Grid) -> impl Iterator<Item = (&[Clue], Vec<Point>)> {
+    let size = &grid.size;
+    let rows = grid
+        .horizontal_clues_solutions
+        .iter()
+        .zip(0..size.height)
+        .map(move |(clues, y)| {
+            let points = (0..size.width).map(|x| Point { x, y }).collect();
+            (clues.as_slice(), points)
+        });
+    let columns = grid
+        .vertical_clues_solutions
+        .iter()
+        .zip(0..size.width)
+        .map(move |(clues, x)| {
+            let points = (0..size.height).map(|y| Point { x, y }).collect();
+            (clues.as_slice(), points)
+        });
+    rows.chain(columns)
+}
+
+fn index(grid: &Grid, point: Point) -> usize {
+    point.y as usize * grid.size.width as usize + point.x as usize
+}
+
+/// The rules that deduce cells, from the easiest to the hardest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub enum Technique {
+    /// Solving a line of which no cells are known yet, so that only the overlaps of its clues' placements give cells.
+    #[default]
+    Overlap,
+    /// Solving a line with some known cells.
+    Line,
+}
+
+/// What solving lines took.
+#[derive(Debug, Default)]
+struct Log {
+    /// How many times the lines with new cells were solved.
+    rounds: usize,
+    /// The hardest technique that deduced a cell.
+    technique: Technique,
+    /// How many times a single line was solved.
+    line_solves: usize,
+}
+
+/// Solves lines until nothing changes, starting with the given cells of which only filled and crossed out ones count.
+/// Empty cells are left for the ones that can't be deduced.
+///
+/// Only the pending lines and the lines crossing cells deduced on the way are solved, one round at a time,
+/// as the other ones can't give new deductions. Returns `None` if the clues contradict the cells.
+fn propagate_cells(
+    grid: &Grid,
+    lines: &[(&[Clue], Vec<Point>)],
+    mut cells: Vec<Cell>,
+    mut pending: Vec<bool>,
+    log: &mut Log,
+) -> Option<Vec<Cell>> {
+    let height = grid.size.height as usize;
+
+    while pending.contains(&true) {
+        log.rounds += 1;
+        let round = std::mem::replace(&mut pending, vec![false; lines.len()]);
+
+        for ((clues, points), _) in lines.iter().zip(round).filter(|(_, pending)| *pending) {
+            let line: Vec<Cell> = points
+                .iter()
+                .map(|point| cells[index(grid, *point)])
+                .collect();
+            let technique = if line.iter().any(|cell| is_known(*cell)) {
+                Technique::Line
+            } else {
+                Technique::Overlap
+            };
+
+            log.line_solves += 1;
+            let deductions = solve_line(clues, &line)?;
+            for (point, deduction) in points.iter().zip(deductions) {
+                let cell = &mut cells[index(grid, *point)];
+                if let (Cell::Empty, Some(deduced_cell)) = (*cell, deduced_cell(deduction)) {
+                    *cell = deduced_cell;
+                    if technique > log.technique {
+                        log.technique = technique;
+                    }
+                    // The row and the column crossing at the cell
+                    pending[point.y as usize] = true;
+                    pending[height + point.x as usize] = true;
+                }
+            }
+        }
+    }
+
+    Some(cells)
+}
+
+/// Returns the cells that can be deduced from the clues alone by solving lines until nothing changes,
+/// with empty cells for the ones that can't. This holds for every grid with the clues.
+fn propagate(grid: &Grid) -> Vec<Cell> {
+    let lines: Vec<_> = lines(grid).collect();
+    let empty_cells = vec![Cell::Empty; grid.cells.len()];
+
+    let pending = vec![true; lines.len()];
+
+    // Clues that contradict each other give no deductions
+    propagate_cells(
+        grid,
+        &lines,
+        empty_cells.clone(),
+        pending,
+        &mut Log::default(),
+    )
+    .unwrap_or(empty_cells)
+}
+
+/// Returns whether the clues can be solved by solving one row or column at a time, without ever guessing.
+pub fn is_line_solvable(grid: &Grid) -> bool {
+    let lines: Vec<_> = lines(grid).collect();
+    let pending = vec![true; lines.len()];
+
+    let empty_cells = vec![Cell::Empty; grid.cells.len()];
+    propagate_cells(grid, &lines, empty_cells, pending, &mut Log::default())
+        .is_some_and(|cells| !cells.contains(&Cell::Empty))
+}
+
+/// At most this many guesses are made when lines alone don't solve a grid.
+/// Grids that take more than that are too hard to be fun anyway.
+const MAX_GUESSES: usize = 1000;
+
+/// How many solutions there are for the clues of a grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Solutions {
+    None,
+    Unique,
+    Multiple,
+    /// Finding out took more than `MAX_GUESSES` guesses.
+    Unknown,
+}
+
+/// What solving a whole grid took, for telling how fast the solver is.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub guesses: usize,
+    /// How many rounds of solving lines there were, over all guesses.
+    pub rounds: usize,
+    /// How many times a single line was solved, over all guesses.
+    pub line_solves: usize,
+}
+
+/// Finds up to two solutions by guessing cells that can't be deduced and backtracking when that leads to a contradiction.
+fn search(
+    grid: &Grid,
+    lines: &[(&[Clue], Vec<Point>)],
+    cells: Vec<Cell>,
+    pending: Vec<bool>,
+    solutions: &mut Vec<Vec<Cell>>,
+    stats: &mut Stats,
+) -> Result<(), ()> {
+    let mut log = Log::default();
+    let cells = propagate_cells(grid, lines, cells, pending, &mut log);
+    stats.rounds += log.rounds;
+    stats.line_solves += log.line_solves;
+    let cells = match cells {
+        Some(cells) => cells,
+        None => return Ok(()),
+    };
+
+    let width = grid.size.width as usize;
+    let height = grid.size.height as usize;
+    let guess_index = match cells.iter().position(|cell| *cell == Cell::Empty) {
+        Some(guess_index) => guess_index,
+        None => {
+            solutions.push(cells);
+            return Ok(());
+        }
+    };
+
+    for guess in [Cell::Filled, Cell::Crossed] {
+        if solutions.len() > 1 {
+            break;
+        }
+        stats.guesses += 1;
+        if stats.guesses > MAX_GUESSES {
+            return Err(());
+        }
+
+        let mut guessed_cells = cells.clone();
+        guessed_cells[guess_index] = guess;
+        let mut pending = vec![false; lines.len()];
+        pending[guess_index / width] = true;
+        pending[height + guess_index % width] = true;
+        search(grid, lines, guessed_cells, pending, solutions, stats)?;
+    }
+
+    Ok(())
+}
+
+/// Returns up to two solutions for the clues of the grid, ignoring its cells, or an error if that took too many guesses.
+fn find_solutions(grid: &Grid, stats: &mut Stats) -> Result<Vec<Vec<Cell>>, ()> {
+    let lines: Vec<_> = lines(grid).collect();
+    let mut solutions = Vec::<Vec<Cell>>::with_capacity(2);
+
+    search(
+        grid,
+        &lines,
+        vec![Cell::Empty; grid.cells.len()],
+        vec![true; lines.len()],
+        &mut solutions,
+        stats,
+    )?;
+
+    Ok(solutions)
+}
+
+/// Returns how many solutions the clues of the grid have, ignoring its cells.
+pub fn solutions(grid: &Grid) -> Solutions {
+    match solve(grid) {
+        Ok(_) => Solutions::Unique,
+        Err(solutions) => solutions,
+    }
+}
+
+/// Returns which cells are filled in the only solution of the clues of the grid,
+/// or what is known about the solutions if there isn't exactly one.
+pub fn solve(grid: &Grid) -> Result<Vec<bool>, Solutions> {
+    solve_with_stats(grid).0
+}
+
+/// Solves the grid like [`solve`], also returning what that took.
+pub fn solve_with_stats(grid: &Grid) -> (Result<Vec<bool>, Solutions>, Stats) {
+    let mut stats = Stats::default();
+    let result = match find_solutions(grid, &mut stats).as_deref() {
+        Ok([solution]) => Ok(solution.iter().map(|cell| cell.is_filled()).collect()),
+        Err(()) => Err(Solutions::Unknown),
+        Ok([]) => Err(Solutions::None),
+        Ok(_) => Err(Solutions::Multiple),
+    };
+    (result, stats)
+}
+
+/// Returns which cells are filled in the only solution of the clues of the grid, if they have exactly one.
+pub fn unique_solution(grid: &Grid) -> Option<Vec<bool>> {
+    solve(grid).ok()
+}
+
+/// Returns which cells are filled in the solution of the grid, if it is known.
+///
+/// Of grids of which only the clues are known, the solution can only be told if there is exactly one.
+pub fn known_solution(grid: &Grid) -> Option<Cow<'_, [bool]>> {
+    if grid.solution.is_empty() {
+        unique_solution(grid).map(Cow::Owned)
+    } else {
+        Some(Cow::Borrowed(&grid.solution))
+    }
+}
+
+/// Lines alone solve grids in at most this many rounds for them to be easy.
+const MAX_EASY_ROUNDS: usize = 3;
+
+/// How hard it is to solve the clues of a grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    /// Lines alone solve it in a few rounds, or only overlaps are needed.
+    Easy,
+    /// Lines alone solve it in more rounds.
+    Medium,
+    /// Guessing is needed but there is only one solution.
+    Hard,
+    /// There is more than one solution or none at all, or it's too hard to tell.
+    Unfair,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Unfair => "unfair",
+        })
+    }
+}
+
+/// Rates the clues of the grid by which techniques solving them takes and how many rounds of solving lines.
+///
+/// If solving lines doesn't solve the clues, they are searched for solutions unless those are already known.
+pub fn rate(grid: &Grid, known_solutions: Option<Solutions>) -> Difficulty {
+    let lines: Vec<_> = lines(grid).collect();
+    let mut log = Log::default();
+    let cells = propagate_cells(
+        grid,
+        &lines,
+        vec![Cell::Empty; grid.cells.len()],
+        vec![true; lines.len()],
+        &mut log,
+    );
+
+    match cells {
+        Some(cells) if !cells.contains(&Cell::Empty) => {
+            if log.technique == Technique::Overlap || log.rounds <= MAX_EASY_ROUNDS {
+                Difficulty::Easy
+            } else {
+                Difficulty::Medium
+            }
+        }
+        _ if known_solutions.unwrap_or_else(|| solutions(grid)) == Solutions::Unique => {
+            Difficulty::Hard
+        }
+        _ => Difficulty::Unfair,
+    }
+}
+
+const fn deduced_cell(deduction: LineDeduction) -> Option<Cell> {
+    match deduction {
+        LineDeduction::Filled => Some(Cell::Filled),
+        LineDeduction::Crossed => Some(Cell::Crossed),
+        LineDeduction::Unknown => None,
+    }
+}
+
+/// Returns what each cell is in the solution, or what the clues alone give if the solution is unknown.
+fn true_cells(grid: &Grid) -> Vec<Cell> {
+    if grid.solution.is_empty() {
+        propagate(grid)
+    } else {
+        grid.solution
+            .iter()
+            .map(|filled| if *filled { Cell::Filled } else { Cell::Crossed })
+            .collect()
+    }
+}
+
+/// Returns a cell of the line that isn't filled or crossed out yet but is forced to be by the line,
+/// along with what it's forced to be, if it agrees with the true cells.
+fn line_hint(
+    grid: &Grid,
+    true_cells: &[Cell],
+    clues: &[Clue],
+    points: &[Point],
+) -> Option<(Point, Cell)> {
+    let line: Vec<Cell> = points.iter().map(|point| grid.get_cell(*point)).collect();
+    let deductions = solve_line(clues, &line)?;
+
+    points
+        .iter()
+        .zip(line)
+        .zip(deductions)
+        .find_map(|((point, cell), deduction)| {
+            let deduced_cell = deduced_cell(deduction)?;
+            if is_known(cell) || true_cells[index(grid, *point)] != deduced_cell {
+                None
+            } else {
+                Some((*point, deduced_cell))
+            }
+        })
+}
+
+/// Returns a cell that isn't filled or crossed out yet but is forced to be by its row or column,
+/// given the player's filled and crossed out cells, along with what it's forced to be.
+///
+/// Wrong cells of the player can make a line force wrong cells, so every hint is checked against the solution,
+/// or against what the clues alone give if the solution is unknown. Hints never contradict the solution.
+pub fn hint(grid: &Grid) -> Option<(Point, Cell)> {
+    let true_cells = true_cells(grid);
+
+    lines(grid).find_map(|(clues, points)| line_hint(grid, &true_cells, clues, &points))
+}
+
+/// Returns a row or column that forces a cell like for [`hint`], as the index into every row and then every column.
+///
+/// The lines of `preferred_lines`, given by the same index, are tried first and in their order.
+pub fn hinted_line(grid: &Grid, preferred_lines: &[usize]) -> Option<usize> {
+    let true_cells = true_cells(grid);
+    let lines: Vec<_> = lines(grid).collect();
+
+    preferred_lines
+        .iter()
+        .copied()
+        .chain(0..lines.len())
+        .find(|line_index| {
+            lines.get(*line_index).is_some_and(|(clues, points)| {
+                line_hint(grid, &true_cells, clues, points).is_some()
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LineDeduction::{Crossed as X, Filled as F, Unknown as U};
+
+    /// Parses a line written like `"1.X?"`, with `.` for an empty cell.
+    fn line(str: &str) -> Vec<Cell> {
+        str.chars()
+            .map(|char| match char {
+                '1' => Cell::Filled,
+                'X' => Cell::Crossed,
+                '?' => Cell::Maybed,
+                'R' => Cell::Measured(None),
+                _ => Cell::Empty,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_clues() {
+        assert_eq!(solve_line(&[], &line("....")), Some(vec![X, X, X, X]));
+        assert_eq!(solve_line(&[0], &line(".X?")), Some(vec![X, X, X]));
+        assert_eq!(solve_line(&[], &line("..1.")), None);
+        assert_eq!(solve_line(&[], &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_exactly_filling_clues() {
+        assert_eq!(solve_line(&[4], &line("....")), Some(vec![F, F, F, F]));
+        assert_eq!(
+            solve_line(&[2, 1, 1], &line("......")),
+            Some(vec![F, F, X, F, X, F])
+        );
+        assert_eq!(solve_line(&[2, 2], &line("....")), None);
+        assert_eq!(solve_line(&[5], &line("....")), None);
+        assert_eq!(solve_line(&[1], &[]), None);
+    }
+
+    #[test]
+    fn test_overlap() {
+        // Every placement of a 3 in 5 cells covers the middle one
+        assert_eq!(solve_line(&[3], &line(".....")), Some(vec![U, U, F, U, U]));
+        assert_eq!(
+            solve_line(&[3, 1], &line("......")),
+            Some(vec![U, F, F, U, U, U])
+        );
+        assert_eq!(solve_line(&[1], &line("...")), Some(vec![U, U, U]));
+    }
+
+    #[test]
+    fn test_marked_cells() {
+        // The 2 must cover the filled cell, so it can't reach the first or last cells
+        assert_eq!(solve_line(&[2], &line("..1..")), Some(vec![X, U, F, U, X]));
+        // Crossed cells split the line so that only one gap can hold the 3
+        assert_eq!(
+            solve_line(&[3], &line(".X....X.")),
+            Some(vec![X, X, U, F, F, U, X, X])
+        );
+        assert_eq!(solve_line(&[1, 1], &line("1.1")), Some(vec![F, X, F]));
+        assert_eq!(solve_line(&[1], &line("11.")), None);
+        assert_eq!(solve_line(&[2], &line("1.X1")), None);
+    }
+
+    #[test]
+    fn test_unknown_cells() {
+        // Maybed and measured cells are just as unknown as empty ones
+        assert_eq!(
+            solve_line(&[3], &line("?R???")),
+            solve_line(&[3], &line("....."))
+        );
+        assert_eq!(solve_line(&[2], &line("X??X")), Some(vec![X, F, F, X]));
+    }
+
+    #[test]
+    fn test_count_placements() {
+        // A 3 in 5 cells can start at any of the first three
+        assert_eq!(
+            count_placements(&[3], &line(".....")),
+            Some(Placements {
+                count: 3.0,
+                filled: vec![1.0, 2.0, 3.0, 2.0, 1.0]
+            })
+        );
+        let placements = count_placements(&[1, 1], &line("..X.")).unwrap();
+        assert_eq!(placements.count, 2.0);
+        assert_eq!(placements.filled_share(0), 0.5);
+        assert_eq!(placements.filled_share(3), 1.0);
+        assert_eq!(
+            count_placements(&[], &line("??")).unwrap().filled,
+            [0.0, 0.0]
+        );
+        assert_eq!(count_placements(&[2], &line("1X1")), None);
+    }
+
+    /// Compares with trying every possible filling of short lines.
+    #[test]
+    fn test_brute_force() {
+        fn clues(filling: &[bool]) -> Vec<Clue> {
+            filling
+                .split(|filled| !filled)
+                .filter(|block| !block.is_empty())
+                .map(|block| block.len() as Clue)
+                .collect()
+        }
+
+        for len in 0..=6 {
+            let fillings: Vec<Vec<bool>> = (0..1u32 << len)
+                .map(|bits| (0..len).map(|index| bits & (1 << index) != 0).collect())
+                .collect();
+            let mut all_clues: Vec<Vec<Clue>> =
+                fillings.iter().map(|filling| clues(filling)).collect();
+            all_clues.sort();
+            all_clues.dedup();
+
+            for marks in 0..3u32.pow(len) {
+                let line: Vec<Cell> = (0..len)
+                    .map(|index| match marks / 3u32.pow(index) % 3 {
+                        0 => Cell::Empty,
+                        1 => Cell::Filled,
+                        _ => Cell::Crossed,
+                    })
+                    .collect();
+
+                for clues_solution in &all_clues {
+                    let consistent: Vec<&Vec<bool>> = fillings
+                        .iter()
+                        .filter(|filling| {
+                            clues(filling) == *clues_solution
+                                && filling.iter().zip(&line).all(|(filled, cell)| match cell {
+                                    Cell::Filled => *filled,
+                                    Cell::Crossed => !filled,
+                                    _ => true,
+                                })
+                        })
+                        .collect();
+
+                    let expected = if consistent.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            (0..len as usize)
+                                .map(|index| {
+                                    if consistent.iter().all(|filling| filling[index]) {
+                                        F
+                                    } else if consistent.iter().all(|filling| !filling[index]) {
+                                        X
+                                    } else {
+                                        U
+                                    }
+                                })
+                                .collect(),
+                        )
+                    };
+                    assert_eq!(
+                        solve_line(clues_solution, &line),
+                        expected,
+                        "{:?} {:?}",
+                        clues_solution,
+                        line
+                    );
+
+                    let expected_placements = (!consistent.is_empty()).then(|| Placements {
+                        count: consistent.len() as f64,
+                        filled: (0..len as usize)
+                            .map(|index| {
+                                consistent.iter().filter(|filling| filling[index]).count() as f64
+                            })
+                            .collect(),
+                    });
+                    assert_eq!(
+                        count_placements(clues_solution, &line),
+                        expected_placements,
+                        "{:?} {:?}",
+                        clues_solution,
+                        line
+                    );
+                }
+            }
+        }
+    }
+
+    /// Takes hints until there are none, checking each one against the solution.
+    fn take_hints(grid: &mut Grid, solution: &[bool]) -> usize {
+        let mut hint_count = 0;
+        while let Some((point, cell)) = hint(grid) {
+            let index = point.y as usize * grid.size.width as usize + point.x as usize;
+            assert_eq!(cell == Cell::Filled, solution[index], "{:?}", point);
+            *grid.get_mut_cell(point) = cell;
+            hint_count += 1;
+        }
+        hint_count
+    }
+
+    #[test]
+    fn test_hints_solve() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "111 1",
+            "1 111",
+            "11  1",
+            "  111",
+        ]).unwrap();
+        let solution = grid.solution.clone();
+
+        assert_eq!(take_hints(&mut grid, &solution), 20);
+        assert!(grid
+            .cells
+            .iter()
+            .all(|cell| matches!(cell, Cell::Filled | Cell::Crossed)));
+        assert!(hint(&grid).is_none());
+
+        // Hints for grids of which only the clues are known come from the clues
+        let mut clues_grid = Grid::from_clues(
+            grid.horizontal_clues_solutions.clone(),
+            grid.vertical_clues_solutions.clone(),
+        )
+        .unwrap();
+        assert_eq!(take_hints(&mut clues_grid, &solution), 20);
+    }
+
+    #[test]
+    fn test_hints_with_wrong_cells() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "111 1",
+            "1 111",
+            "11  1",
+            "  111",
+        ]).unwrap();
+        let solution = grid.solution.clone();
+        // A wrong filled cell makes the third row force its first cell to be crossed out
+        grid.cells[12] = Cell::Filled;
+        grid.cells[9] = Cell::Maybed;
+        assert_eq!(
+            solve_line(&[2, 1], &grid.cells[10..15]).unwrap()[0],
+            LineDeduction::Crossed
+        );
+
+        assert!(take_hints(&mut grid, &solution) > 0);
+        assert_ne!(grid.cells[10], Cell::Crossed);
+    }
+
+    #[test]
+    fn test_no_hints() {
+        // Both diagonals fit the clues so there is nothing to deduce
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "1 ",
+            " 1",
+        ]).unwrap();
+        assert_eq!(hint(&grid), None);
+
+        let mut clues_grid =
+            Grid::from_clues(vec![vec![1], vec![1]], vec![vec![1], vec![1]]).unwrap();
+        assert_eq!(take_hints(&mut clues_grid, &grid.solution), 0);
+    }
+
+    #[test]
+    fn test_hinted_line() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "111",
+            "1  ",
+            "1  ",
+        ]).unwrap();
+        assert_eq!(hinted_line(&grid, &[]), Some(0));
+        // The preferred lines come first, skipping ones without a hint
+        assert_eq!(hinted_line(&grid, &[4, 3]), Some(3));
+        assert_eq!(hinted_line(&grid, &[99]), Some(0));
+
+        // Only the second row and the last two columns are left
+        for index in [0, 1, 2, 3, 6] {
+            grid.cells[index] = if grid.solution[index] {
+                Cell::Filled
+            } else {
+                Cell::Crossed
+            };
+        }
+        assert_eq!(hinted_line(&grid, &[]), Some(1));
+
+        grid.cells = grid
+            .solution
+            .iter()
+            .map(|filled| if *filled { Cell::Filled } else { Cell::Crossed })
+            .collect();
+        assert_eq!(hinted_line(&grid, &[]), None);
+    }
+
+    #[test]
+    fn test_solutions() {
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "111 1",
+            "1 111",
+            "11  1",
+            "  111",
+        ]).unwrap();
+        assert!(is_line_solvable(&grid));
+        assert_eq!(solutions(&grid), Solutions::Unique);
+        assert!(grid.has_unique_solution());
+
+        // Solving lines gets stuck on this one, but only one of the guesses then works out
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "11  1",
+            " 11  ",
+            " 11  ",
+            "11  1",
+            "   11",
+        ]).unwrap();
+        assert!(!is_line_solvable(&grid));
+        assert_eq!(solutions(&grid), Solutions::Unique);
+        let clues_grid = Grid::from_clues(
+            grid.horizontal_clues_solutions.clone(),
+            grid.vertical_clues_solutions.clone(),
+        )
+        .unwrap();
+        assert_eq!(unique_solution(&clues_grid), Some(grid.solution));
+
+        // Both diagonals fit
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "1 ",
+            " 1",
+        ]).unwrap();
+        assert!(!is_line_solvable(&grid));
+        assert_eq!(solutions(&grid), Solutions::Multiple);
+        assert!(!grid.has_unique_solution());
+        assert_eq!(unique_solution(&grid), None);
+        assert_eq!(solve(&grid), Err(Solutions::Multiple));
+
+        // The filled row would leave a cell in the second column
+        let grid = Grid::from_clues(vec![vec![2], vec![]], vec![vec![2], vec![]]).unwrap();
+        assert_eq!(solutions(&grid), Solutions::None);
+        assert_eq!(solve(&grid), Err(Solutions::None));
+        assert!(!is_line_solvable(&grid));
+    }
+
+    #[test]
+    fn test_rate() {
+        // Every line is full or empty
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "111",
+            "   ",
+            "111",
+        ]).unwrap();
+        assert_eq!(rate(&grid, None), Difficulty::Easy);
+
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "111 1",
+            "1 111",
+            "11  1",
+            "  111",
+        ]).unwrap();
+        assert_eq!(rate(&grid, None), Difficulty::Easy);
+
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "1 1  ",
+            "1 1  ",
+            " 1   ",
+            "1  11",
+            "111  ",
+        ]).unwrap();
+        assert_eq!(rate(&grid, None), Difficulty::Medium);
+
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "11  1",
+            " 11  ",
+            " 11  ",
+            "11  1",
+            "   11",
+        ]).unwrap();
+        assert_eq!(rate(&grid, None), Difficulty::Hard);
+        // Solutions that are already known aren't searched for again
+        assert_eq!(rate(&grid, Some(Solutions::Multiple)), Difficulty::Unfair);
+
+        #[rustfmt::skip]
+        let grid = Grid::from_lines(&[
+            "1 ",
+            " 1",
+        ]).unwrap();
+        assert_eq!(rate(&grid, None), Difficulty::Unfair);
+        assert_eq!(Difficulty::Unfair.to_string(), "unfair");
+    }
+}
@@ -0,0 +1,42 @@
+//! Where the content of puzzles given on the command line is read from.
+//!
+//! Everything is read before the terminal is set up so that errors can be printed normally.
+
+pub mod url;
+
+use std::{borrow::Cow, fs, io, path::PathBuf};
+
+pub trait PuzzleSource {
+    /// The name of the puzzle, e.g. its filename, which decides its format.
+    fn name(&self) -> Cow<'_, str>;
+
+    /// Reads the whole content of the puzzle.
+    fn read(&mut self) -> Result<String, Cow<'static, str>>;
+}
+
+pub struct File(pub PathBuf);
+
+impl PuzzleSource for File {
+    fn name(&self) -> Cow<'_, str> {
+        self.0.to_string_lossy()
+    }
+
+    fn read(&mut self) -> Result<String, Cow<'static, str>> {
+        fs::read_to_string(&self.0).map_err(|_| "File reading error".into())
+    }
+}
+
+pub struct Stdin;
+
+impl PuzzleSource for Stdin {
+    fn name(&self) -> Cow<'_, str> {
+        "standard input".into()
+    }
+
+    fn read(&mut self) -> Result<String, Cow<'static, str>> {
+        let mut content = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut content)
+            .map_err(|_| "Standard input reading error")?;
+        Ok(content)
+    }
+}
@@ -0,0 +1,469 @@
+//! Fetching puzzles from `http://` URLs with `--url`.
+//!
+//! This is a minimal HTTP/1.0 client so that no dependency is needed. There is no TLS, so `https://` URLs are refused.
+//! It can be left out by building without the `url` feature.
+
+use super::PuzzleSource;
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Responses larger than this are refused before they are read to the end.
+pub const MAX_RESPONSE_LEN: usize = 1024 * 1024;
+
+/// The status line and headers together can't be longer than this.
+const MAX_HEAD_LEN: usize = 16 * 1024;
+
+const MAX_REDIRECTS: usize = 5;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Url {
+    host: String,
+    port: u16,
+    /// The path starting with `/`, including the query if any.
+    path: String,
+}
+
+impl Url {
+    pub fn parse(str: &str) -> Result<Self, Cow<'static, str>> {
+        let rest = if let Some(rest) = str.strip_prefix("http://") {
+            rest
+        } else if str.starts_with("https://") {
+            return Err(
+                "HTTPS is not supported, download the puzzle and open the file instead".into(),
+            );
+        } else {
+            return Err(format!("{} is not an http:// URL", str).into());
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| format!("{} has an invalid port", str))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() || host.contains(|char: char| char.is_whitespace() || char == '@') {
+            return Err(format!("{} has an invalid host", str).into());
+        }
+        if path.contains(char::is_whitespace) {
+            return Err(format!("{} has an invalid path", str).into());
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// Returns the URL a redirect with the `Location` header leads to. Redirects must stay on `http://`.
+    fn join(&self, location: &str) -> Result<Self, Cow<'static, str>> {
+        if location.starts_with('/') {
+            Self::parse(&format!("http://{}:{}{}", self.host, self.port, location))
+        } else if location.starts_with("http://") {
+            Self::parse(location)
+        } else {
+            Err(format!(
+                "{} redirected to an unsupported location: {}",
+                self, location
+            )
+            .into())
+        }
+    }
+
+    /// The last segment of the path without the query, e.g. `puzzle.yaya`.
+    fn file_name(&self) -> &str {
+        let path = self.path.split(['?', '#']).next().unwrap_or_default();
+        path.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.port == 80 {
+            write!(formatter, "http://{}{}", self.host, self.path)
+        } else {
+            write!(formatter, "http://{}:{}{}", self.host, self.port, self.path)
+        }
+    }
+}
+
+/// Sends the requests, so that tests don't need the network.
+pub trait Transport {
+    /// Sends the request and returns the response as it is received.
+    fn send(&mut self, url: &Url, request: &[u8]) -> io::Result<Box<dyn Read>>;
+}
+
+pub struct Tcp;
+
+impl Transport for Tcp {
+    fn send(&mut self, url: &Url, request: &[u8]) -> io::Result<Box<dyn Read>> {
+        let address = (url.host.as_str(), url.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host not found"))?;
+        let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        stream.write_all(request)?;
+        Ok(Box::new(stream))
+    }
+}
+
+enum Response {
+    Body(Vec<u8>),
+    Redirect(String),
+}
+
+fn read_head(reader: &mut impl BufRead, url: &Url) -> Result<Vec<String>, Cow<'static, str>> {
+    let mut lines = Vec::<String>::new();
+    let mut head_len = 0;
+    loop {
+        let mut line = Vec::<u8>::new();
+        let len = reader
+            .take((MAX_HEAD_LEN - head_len) as u64)
+            .read_until(b'\n', &mut line)
+            .map_err(|err| format!("Couldn't fetch {}: {}", url, err))?;
+        head_len += len;
+        if !line.ends_with(b"\n") {
+            return Err(format!("{} sent an invalid response", url).into());
+        }
+
+        let line =
+            String::from_utf8(line).map_err(|_| format!("{} sent an invalid response", url))?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(lines);
+        }
+        lines.push(line.to_string());
+    }
+}
+
+fn read_response(reader: impl Read, url: &Url) -> Result<Response, Cow<'static, str>> {
+    let mut reader = BufReader::new(reader);
+    let head = read_head(&mut reader, url)?;
+
+    let invalid_response = || Cow::from(format!("{} sent an invalid response", url));
+    let (status_line, headers) = head.split_first().ok_or_else(invalid_response)?;
+    let status = match status_line.split(' ').collect::<Vec<&str>>().as_slice() {
+        [version, status, ..] if version.starts_with("HTTP/") => {
+            status.parse::<u16>().map_err(|_| invalid_response())?
+        }
+        _ => return Err(invalid_response()),
+    };
+    let header = |name: &str| {
+        headers.iter().find_map(|header| {
+            let (header_name, value) = header.split_once(':')?;
+            if header_name.eq_ignore_ascii_case(name) {
+                Some(value.trim())
+            } else {
+                None
+            }
+        })
+    };
+
+    match status {
+        200 => {}
+        301 | 302 | 303 | 307 | 308 => {
+            let location = header("Location").ok_or_else(invalid_response)?;
+            return Ok(Response::Redirect(location.to_string()));
+        }
+        _ => return Err(format!("{} responded with HTTP status {}", url, status).into()),
+    }
+
+    let too_large = || {
+        Cow::from(format!(
+            "{} is larger than {} KiB",
+            url,
+            MAX_RESPONSE_LEN / 1024
+        ))
+    };
+    if let Some(content_length) = header("Content-Length") {
+        let content_length = content_length
+            .parse::<u64>()
+            .map_err(|_| invalid_response())?;
+        if content_length > MAX_RESPONSE_LEN as u64 {
+            return Err(too_large());
+        }
+    }
+
+    let mut body = Vec::<u8>::new();
+    reader
+        .take(MAX_RESPONSE_LEN as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| format!("Couldn't fetch {}: {}", url, err))?;
+    if body.len() > MAX_RESPONSE_LEN {
+        return Err(too_large());
+    }
+
+    Ok(Response::Body(body))
+}
+
+pub struct UrlSource<T: Transport = Tcp> {
+    pub url: Url,
+    transport: T,
+}
+
+impl UrlSource {
+    pub fn new(url: &str) -> Result<Self, Cow<'static, str>> {
+        Ok(Self {
+            url: Url::parse(url)?,
+            transport: Tcp,
+        })
+    }
+}
+
+impl<T: Transport> PuzzleSource for UrlSource<T> {
+    fn name(&self) -> Cow<'_, str> {
+        match self.url.file_name() {
+            "" => self.url.host.as_str().into(),
+            file_name => file_name.into(),
+        }
+    }
+
+    /// Fetches the puzzle, following redirects. Afterwards the URL is the one the puzzle was fetched from.
+    fn read(&mut self) -> Result<String, Cow<'static, str>> {
+        for _ in 0..=MAX_REDIRECTS {
+            let request = format!(
+                "GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: yayagram/{}\r\nConnection: close\r\n\r\n",
+                self.url.path,
+                self.url.host,
+                env!("CARGO_PKG_VERSION")
+            );
+            let response = self
+                .transport
+                .send(&self.url, request.as_bytes())
+                .map_err(|err| format!("Couldn't fetch {}: {}", self.url, err))?;
+
+            match read_response(response, &self.url)? {
+                Response::Body(body) => {
+                    return String::from_utf8(body)
+                        .map_err(|_| format!("{} is not a text file", self.url).into());
+                }
+                Response::Redirect(location) => self.url = self.url.join(&location)?,
+            }
+        }
+
+        Err(format!("{} redirected more than {} times", self.url, MAX_REDIRECTS).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{formats, Grid};
+    use std::{collections::HashMap, fs, io::Cursor, net::TcpListener, thread};
+
+    /// Responds with the response for the requested URL.
+    struct FakeTransport(HashMap<String, Vec<u8>>);
+
+    impl Transport for FakeTransport {
+        fn send(&mut self, url: &Url, request: &[u8]) -> io::Result<Box<dyn Read>> {
+            assert!(request.starts_with(format!("GET {} HTTP/1.0\r\n", url.path).as_bytes()));
+            match self.0.get(&url.to_string()) {
+                Some(response) => Ok(Box::new(Cursor::new(response.clone()))),
+                None => Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    "connection refused",
+                )),
+            }
+        }
+    }
+
+    fn ok(body: &str) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+
+    fn redirect(location: &str) -> Vec<u8> {
+        format!("HTTP/1.1 302 Found\r\nlocation: {}\r\n\r\n", location).into_bytes()
+    }
+
+    fn fetch(url: &str, responses: &[(&str, Vec<u8>)]) -> Result<Grid, Cow<'static, str>> {
+        let responses = responses
+            .iter()
+            .map(|(url, response)| (url.to_string(), response.clone()))
+            .collect();
+        let mut source = UrlSource {
+            url: Url::parse(url)?,
+            transport: FakeTransport(responses),
+        };
+        let content = source.read()?;
+        formats::load(&source.name(), &content).map_err(|err| err.message.into())
+    }
+
+    fn olsak_content() -> String {
+        fs::read_to_string(crate::grid::fixture::path("empty_row.g")).unwrap()
+    }
+
+    #[test]
+    fn test_url() {
+        let url = Url::parse("http://example.com:8080/puzzles/cat.yaya?v=2").unwrap();
+        assert_eq!(url.host, "example.com");
+        assert_eq!(url.port, 8080);
+        assert_eq!(url.file_name(), "cat.yaya");
+        assert_eq!(
+            url.to_string(),
+            "http://example.com:8080/puzzles/cat.yaya?v=2"
+        );
+        assert_eq!(
+            Url::parse("http://example.com").unwrap().to_string(),
+            "http://example.com/"
+        );
+
+        assert_eq!(
+            url.join("/cats/dog.g").unwrap().to_string(),
+            "http://example.com:8080/cats/dog.g"
+        );
+        assert!(url.join("https://example.com/cat.yaya").is_err());
+        assert!(url.join("cat.yaya").is_err());
+
+        for invalid_url in [
+            "https://example.com/cat.yaya",
+            "ftp://example.com/cat.yaya",
+            "example.com",
+            "http://",
+            "http://example.com:port/",
+            "http://user@example.com/",
+            "http://example.com/a cat",
+        ] {
+            assert!(Url::parse(invalid_url).is_err(), "{}", invalid_url);
+        }
+    }
+
+    #[test]
+    fn test_fetch() {
+        let grid = fetch(
+            "http://example.com/empty_row.g",
+            &[("http://example.com/empty_row.g", ok(&olsak_content()))],
+        )
+        .unwrap();
+        assert_eq!(grid.horizontal_clues_solutions[1], Vec::<u16>::new());
+
+        // Redirects keep the name of the file they lead to
+        let grid = fetch(
+            "http://example.com/latest",
+            &[
+                (
+                    "http://example.com/latest",
+                    redirect("/puzzles/empty_row.g"),
+                ),
+                (
+                    "http://example.com/puzzles/empty_row.g",
+                    ok(&olsak_content()),
+                ),
+            ],
+        )
+        .unwrap();
+        assert_eq!(grid.size.height, 3);
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let err = |url: &str, responses: &[(&str, Vec<u8>)]| fetch(url, responses).unwrap_err();
+        let url = "http://example.com/cat.yaya";
+
+        assert_eq!(
+            err(url, &[]),
+            "Couldn't fetch http://example.com/cat.yaya: connection refused"
+        );
+        assert_eq!(
+            err(url, &[(url, b"HTTP/1.1 404 Not Found\r\n\r\n".to_vec())]),
+            "http://example.com/cat.yaya responded with HTTP status 404"
+        );
+
+        // Too large responses are refused no matter whether they tell their length
+        let large_response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+            MAX_RESPONSE_LEN + 1
+        );
+        assert_eq!(
+            err(url, &[(url, large_response.into_bytes())]),
+            "http://example.com/cat.yaya is larger than 1024 KiB"
+        );
+        let large_body = "1".repeat(MAX_RESPONSE_LEN + 1);
+        let large_response = format!("HTTP/1.0 200 OK\r\n\r\n{}", large_body);
+        assert_eq!(
+            err(url, &[(url, large_response.into_bytes())]),
+            "http://example.com/cat.yaya is larger than 1024 KiB"
+        );
+
+        let redirect_loop = [
+            (url, redirect("http://example.com/dog.yaya")),
+            ("http://example.com/dog.yaya", redirect("/cat.yaya")),
+        ];
+        assert_eq!(
+            err(url, &redirect_loop),
+            "http://example.com/cat.yaya redirected more than 5 times"
+        );
+        assert!(err(url, &[(url, redirect("https://example.com/cat.yaya"))])
+            .contains("unsupported location"));
+
+        for malformed_response in [
+            &b""[..],
+            b"HTTP/1.1 200 OK\r\n",
+            b"200 OK\r\n\r\n",
+            b"HTTP/1.1 OK\r\n\r\n",
+            b"HTTP/1.1 302 Found\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: many\r\n\r\n",
+        ] {
+            assert_eq!(
+                err(url, &[(url, malformed_response.to_vec())]),
+                "http://example.com/cat.yaya sent an invalid response"
+            );
+        }
+        assert_eq!(
+            err(url, &[(url, b"HTTP/1.1 200 OK\r\n\r\n\xff\xfe".to_vec())]),
+            "http://example.com/cat.yaya is not a text file"
+        );
+
+        // The body is loaded like any other grid file
+        assert!(fetch(url, &[(url, ok("<html>Not a grid</html>"))]).is_err());
+    }
+
+    #[test]
+    fn test_server() {
+        let listener = match TcpListener::bind("127.0.0.1:0") {
+            Ok(listener) => listener,
+            // There might be no network at all
+            Err(_) => return,
+        };
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // The request ends with an empty line
+            let mut line = String::new();
+            while line != "\r\n" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            reader.get_mut().write_all(&ok(&olsak_content())).unwrap();
+            request_line
+        });
+
+        let mut source = UrlSource::new(&format!("http://127.0.0.1:{}/empty_row.g", port)).unwrap();
+        let content = source.read().unwrap();
+        assert_eq!(content, olsak_content());
+        assert_eq!(source.name(), "empty_row.g");
+        assert_eq!(server.join().unwrap(), "GET /empty_row.g HTTP/1.0\r\n");
+    }
+}
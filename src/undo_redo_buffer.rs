@@ -1,44 +1,334 @@
-use crate::grid::{Cell, Grid};
+mod measurement;
+
+use crate::grid::{tools::transform::Transform, Cell, Grid};
+pub use measurement::Measurement;
+use std::time::{Duration, Instant};
 use terminal::util::Point;
 
-#[derive(Clone, Debug)]
+/// How many operations a buffer keeps unless `--undo-limit` says otherwise.
+pub const DEFAULT_LIMIT: usize = 10_000;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Operation {
+    /// Sets multiple cells at once, e.g. when merging a sandbox.
+    SetCells(Vec<(Point, Cell)>),
     SetCell {
         point: Point,
         cell: Cell,
     },
-    Measure(Vec<Point>),
+    Measure(Measurement),
     Clear,
     Fill {
         point: Point,
         first_cell: Cell,
         fill_cell: Cell,
     },
+    /// Sets the tags of cells without changing the cells themselves.
+    Tag(Vec<(Point, Option<u8>)>),
+    /// Sets all cells with the tag to the cell and removes the tag from them, resolving a hypothesis at once.
+    Promote {
+        tag: u8,
+        cell: Cell,
+    },
+    /// Mirrors or rotates the grid in the editor, which changes the clues too.
+    Transform(Transform),
+    /// Sets all cells and tags at once. The oldest operations are merged into this once the buffer is full,
+    /// so it is always the first one and can't be undone.
+    Snapshot {
+        cells: Vec<Cell>,
+        tags: Vec<Option<u8>>,
+    },
+}
+
+/// How an operation changed a cell and its tag.
+#[derive(Clone, Debug, PartialEq)]
+struct Change {
+    index: usize,
+    before: (Cell, Option<u8>),
+    after: (Cell, Option<u8>),
 }
 
+/// What the operations changed, so that undoing and redoing only has to set the changed cells
+/// instead of replaying the whole buffer.
+///
+/// This is recorded by replaying the operations, so it is never saved and an empty one is always correct.
 #[derive(Default, Debug)]
+pub struct Changes {
+    /// The changes of the first operations of the buffer. Operations that were pushed since are missing.
+    operations: Vec<Vec<Change>>,
+    /// The cells and tags as of the last operation with changes or as of the index if that is before it,
+    /// or `None` if the buffer has to be replayed first.
+    applied: Option<(Vec<Cell>, Vec<Option<u8>>)>,
+}
+
+/// A point of the history to come back to, e.g. before trying out a guess.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// Checkpoints are numbered in the order they were added, starting with 1.
+    pub number: usize,
+    /// How many operations are done at the checkpoint, or `None` once the operations up to it are gone
+    /// because they were undone and replaced or merged into the snapshot.
+    pub index: Option<usize>,
+}
+
+/// The operations that were undone and then discarded by pushing another operation, so that they can still be redone.
+#[derive(Debug)]
+pub struct Branch {
+    /// The index at which the operations were discarded.
+    index: usize,
+    operations: Vec<Operation>,
+    /// The changes of the first of the operations, like [`Changes::operations`].
+    changes: Vec<Vec<Change>>,
+}
+
+#[derive(Debug)]
 pub struct UndoRedoBuffer {
     pub buffer: Vec<Operation>,
     pub index: usize,
+    /// The amount of operations ever pushed.
+    pub push_count: usize,
+    pub changes: Changes,
+    /// The index at which the operations that are to be grouped start.
+    pub group_start: usize,
+    pub checkpoints: Vec<Checkpoint>,
+    /// The operations that were discarded last. Only one branch is kept.
+    pub branch: Option<Branch>,
+    /// When the last operation was pushed, unless something was undone or redone since.
+    pub last_push: Option<Instant>,
+    /// How many operations are kept before the oldest ones are merged into a snapshot.
+    pub limit: usize,
+    /// Cell placements pushed within this of each other are merged, see [`Self::merge_recent`].
+    pub merge_window: Option<Duration>,
+}
+
+impl Default for UndoRedoBuffer {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            index: 0,
+            push_count: 0,
+            changes: Changes::default(),
+            group_start: 0,
+            checkpoints: Vec::new(),
+            branch: None,
+            last_push: None,
+            limit: DEFAULT_LIMIT,
+            merge_window: None,
+        }
+    }
 }
 
 impl UndoRedoBuffer {
+    /// Returns an empty buffer with the same limit and merge window.
+    pub fn emptied(&self) -> Self {
+        Self {
+            limit: self.limit,
+            merge_window: self.merge_window,
+            ..Self::default()
+        }
+    }
+
+    /// Takes on the operations of the buffer, e.g. one that was loaded, keeping the limit and the merge window of this one.
+    pub fn restore(&mut self, buffer: UndoRedoBuffer) {
+        *self = Self {
+            limit: self.limit,
+            merge_window: self.merge_window,
+            ..buffer
+        };
+    }
+
     pub fn push(&mut self, operation: Operation) {
         if self.index != self.buffer.len() {
-            self.buffer.truncate(self.index);
+            // The undone operations aren't the previous ones anymore
+            self.last_push = None;
+            let operations = self.buffer.split_off(self.index);
+            let changes = self
+                .changes
+                .operations
+                .split_off(self.index.min(self.changes.operations.len()));
+            self.branch = Some(Branch {
+                index: self.index,
+                operations,
+                changes,
+            });
+            let done = self.index;
+            for checkpoint in &mut self.checkpoints {
+                if checkpoint.index.is_some_and(|index| index > done) {
+                    checkpoint.index = None;
+                }
+            }
         }
+        // The pushed operation was already applied so its changes are recorded once it's undone
+        self.changes.operations.truncate(self.index);
         self.buffer.push(operation);
         self.index += 1;
+        self.push_count += 1;
+    }
+
+    /// Starts a group of operations at the index. See [`Self::group`].
+    pub fn start_group(&mut self) {
+        self.group_start = self.index;
+    }
+
+    /// Turns the operations since the group was started into a single one so that they are undone and redone at once,
+    /// e.g. all cells placed by one drag of the mouse.
+    ///
+    /// Nothing is grouped if any of them doesn't just set cells or if some of them were undone.
+    pub fn group(&mut self) {
+        let start = self.group_start;
+        if self.index != self.buffer.len() || start + 1 >= self.buffer.len() {
+            return;
+        }
+
+        let mut cells = Vec::new();
+        for operation in &self.buffer[start..] {
+            match operation {
+                Operation::SetCell { point, cell } => cells.push((*point, *cell)),
+                Operation::SetCells(set_cells) => cells.extend_from_slice(set_cells),
+                _ => return,
+            }
+        }
+
+        let operations = &mut self.changes.operations;
+        if operations.len() == self.buffer.len() {
+            // The changes of the group are those of its operations one after another
+            let changes = operations.drain(start..).flatten().collect();
+            operations.push(changes);
+        } else if operations.len() > start {
+            // Only some of them were recorded
+            self.changes = Changes::default();
+        }
+        self.buffer.truncate(start);
+        self.buffer.push(Operation::SetCells(cells));
+        self.index = self.buffer.len();
+        // A checkpoint in the middle of the group is at its end now
+        for checkpoint in &mut self.checkpoints {
+            if let Some(index) = &mut checkpoint.index {
+                *index = (*index).min(self.index);
+            }
+        }
+    }
+
+    /// Merges the last operation into the one before if that one was pushed within the merge window, if there is one,
+    /// so that bursts of cell placements with the keyboard are undone at once like typing in a text editor.
+    ///
+    /// Only operations that just set cells are merged. Returns whether the operations were merged.
+    pub fn merge_recent(&mut self, now: Instant) -> bool {
+        let window = match self.merge_window {
+            Some(window) => window,
+            None => return false,
+        };
+        let recent = self
+            .last_push
+            .is_some_and(|last_push| now.saturating_duration_since(last_push) <= window);
+        self.last_push = Some(now);
+        let len = self.buffer.len();
+        if !recent || self.index != len || len < 2 {
+            return false;
+        }
+
+        let mut cells = Vec::new();
+        for operation in &self.buffer[len - 2..] {
+            match operation {
+                Operation::SetCell { point, cell } => cells.push((*point, *cell)),
+                Operation::SetCells(set_cells) => cells.extend_from_slice(set_cells),
+                _ => return false,
+            }
+        }
+
+        let operations = &mut self.changes.operations;
+        if operations.len() == len - 1 {
+            // The changes of the previous operation are only part of the merged one,
+            // so the applied cells go back to before it for them to be recorded again
+            let changes = operations.pop().unwrap();
+            if let Some((applied_cells, applied_tags)) = &mut self.changes.applied {
+                for change in changes.iter().rev() {
+                    (applied_cells[change.index], applied_tags[change.index]) = change.before;
+                }
+            }
+        }
+        self.buffer.truncate(len - 2);
+        self.buffer.push(Operation::SetCells(cells));
+        self.index = self.buffer.len();
+        self.group_start = self.group_start.min(self.index - 1);
+        for checkpoint in &mut self.checkpoints {
+            if let Some(index) = &mut checkpoint.index {
+                *index = (*index).min(self.index);
+            }
+        }
+        true
+    }
+
+    /// Adds a checkpoint at the index and returns its number.
+    pub fn add_checkpoint(&mut self) -> usize {
+        let number = self
+            .checkpoints
+            .last()
+            .map_or(1, |checkpoint| checkpoint.number + 1);
+        self.checkpoints.push(Checkpoint {
+            number,
+            index: Some(self.index),
+        });
+        number
+    }
+}
+
+/// Cells that are set one after another and pushed as a single operation once the transaction is over.
+/// See [`Grid::transaction`].
+pub struct Transaction<'a> {
+    grid: &'a mut Grid,
+    cells: Vec<(Point, Cell)>,
+}
+
+impl Transaction<'_> {
+    pub fn get_cell(&self, point: Point) -> Cell {
+        self.grid.get_cell(point)
+    }
+
+    /// Sets the cell right away so that it is seen by what follows in the transaction. A cell that is already set is skipped.
+    pub fn set_cell(&mut self, point: Point, cell: Cell) {
+        let grid_cell = self.grid.get_mut_cell(point);
+        if *grid_cell != cell {
+            *grid_cell = cell;
+            self.cells.push((point, cell));
+        }
+    }
+}
+
+fn has_transform(operations: &[Operation]) -> bool {
+    operations
+        .iter()
+        .any(|operation| matches!(operation, Operation::Transform(_)))
+}
+
+pub fn set_cells(grid: &mut Grid, cells: &[(Point, Cell)]) {
+    for (point, cell) in cells {
+        *grid.get_mut_cell(*point) = *cell;
     }
 }
 
 impl Grid {
     /// Tries to undo the last placed cell and returns `true` if that was successful.
     pub fn undo_last_cell(&mut self) -> bool {
-        if self.undo_redo_buffer.index > 0 {
+        let index = self.undo_redo_buffer.index;
+        if index > 0
+            && !matches!(
+                self.undo_redo_buffer.buffer[index - 1],
+                Operation::Snapshot { .. }
+            )
+        {
+            self.record_changes();
             self.undo_redo_buffer.index -= 1;
+            self.undo_redo_buffer.last_push = None;
 
-            self.rebuild();
+            let changes = std::mem::take(
+                &mut self.undo_redo_buffer.changes.operations[self.undo_redo_buffer.index],
+            );
+            for change in changes.iter().rev() {
+                self.set_change(change.index, change.before);
+            }
+            self.undo_redo_buffer.changes.operations[self.undo_redo_buffer.index] = changes;
+            self.remake_transformed_clues_solutions(self.undo_redo_buffer.index);
             true
         } else {
             false
@@ -48,37 +338,962 @@ impl Grid {
     /// Tries to redo the last undone cell and returns `true` if that was successful.
     pub fn redo_last_cell(&mut self) -> bool {
         if self.undo_redo_buffer.index != self.undo_redo_buffer.buffer.len() {
+            self.record_changes();
+            let index = self.undo_redo_buffer.index;
             self.undo_redo_buffer.index += 1;
+            self.undo_redo_buffer.last_push = None;
 
-            self.rebuild();
+            if let Some(changes) = self.undo_redo_buffer.changes.operations.get_mut(index) {
+                let changes = std::mem::take(changes);
+                for change in &changes {
+                    self.set_change(change.index, change.after);
+                }
+                self.undo_redo_buffer.changes.operations[index] = changes;
+            } else {
+                // The operation was undone by a replay that didn't get to it
+                let operation = self.undo_redo_buffer.buffer[index].clone();
+                let changes = self.replay(&operation);
+                for change in &changes {
+                    self.set_change(change.index, change.after);
+                }
+                self.undo_redo_buffer.changes.operations.push(changes);
+            }
+            self.remake_transformed_clues_solutions(index);
             true
         } else {
             false
         }
     }
 
-    fn rebuild(&mut self) {
+    /// Makes the clue solutions out of the cells again if the operation at the index is a transform,
+    /// which changes the clues along with the cells in the editor.
+    fn remake_transformed_clues_solutions(&mut self, index: usize) {
+        if let Operation::Transform(_) = self.undo_redo_buffer.buffer[index] {
+            self.remake_clues_solutions();
+        }
+    }
+
+    /// Undoes or redoes operations one by one until `index` of them are done or none are left to,
+    /// returning whether any were.
+    pub fn jump_in_history(&mut self, index: usize) -> bool {
+        let mut jumped = false;
+        while self.undo_redo_buffer.index != index {
+            let moved = if self.undo_redo_buffer.index > index {
+                self.undo_last_cell()
+            } else {
+                self.redo_last_cell()
+            };
+            if !moved {
+                break;
+            }
+            jumped = true;
+        }
+        jumped
+    }
+
+    /// Undoes everything that can be undone at once, returning whether anything was.
+    pub fn undo_all(&mut self) -> bool {
+        let first_index = match self.undo_redo_buffer.buffer.first() {
+            Some(Operation::Snapshot { .. }) => 1,
+            _ => 0,
+        };
+        self.jump_in_history(first_index)
+    }
+
+    /// Redoes everything that can be redone at once, returning whether anything was.
+    pub fn redo_all(&mut self) -> bool {
+        self.jump_in_history(self.undo_redo_buffer.buffer.len())
+    }
+
+    /// Undoes or redoes to where the operations of the branch were discarded and switches them with the ones
+    /// that replaced them, which become the branch in turn. Returns whether there was a branch.
+    ///
+    /// Nothing is redone, so that the first operation of the branch is redone next.
+    pub fn switch_branch(&mut self) -> bool {
+        let index = match &self.undo_redo_buffer.branch {
+            Some(branch) => branch.index,
+            None => return false,
+        };
+        self.jump_in_history(index);
+        // Now the operations up to the index are recorded
+        self.record_changes();
+
+        let buffer = &mut self.undo_redo_buffer;
+        let branch = buffer.branch.as_mut().unwrap();
+        let operations = buffer.buffer.split_off(index);
+        let changes = buffer.changes.operations.split_off(index);
+        buffer.buffer.append(&mut branch.operations);
+        buffer.changes.operations.append(&mut branch.changes);
+        branch.operations = operations;
+        branch.changes = changes;
+
+        // The checkpoints ahead are on the other branch now
+        for checkpoint in &mut buffer.checkpoints {
+            if checkpoint
+                .index
+                .is_some_and(|checkpoint_index| checkpoint_index > index)
+            {
+                checkpoint.index = None;
+            }
+        }
+        true
+    }
+
+    /// Applies the operation to the grid and pushes it to the undo redo buffer,
+    /// so that what is done is always what is replayed.
+    pub fn apply(&mut self, operation: Operation) {
+        self.perform(&operation);
+        self.push_operation(operation);
+    }
+
+    /// Runs the closure with a transaction and pushes the cells it set as a single operation
+    /// so that one undo removes all of them. Returns whether any cells were set.
+    pub fn transaction(&mut self, f: impl FnOnce(&mut Transaction)) -> bool {
+        let mut transaction = Transaction {
+            grid: self,
+            cells: Vec::new(),
+        };
+        f(&mut transaction);
+        let cells = transaction.cells;
+        if cells.is_empty() {
+            false
+        } else {
+            self.push_operation(Operation::SetCells(cells));
+            true
+        }
+    }
+
+    /// Pushes the operation, which was already applied to the grid, to the undo redo buffer.
+    ///
+    /// Once the buffer is longer than the limit, its oldest operations are merged into a snapshot.
+    fn push_operation(&mut self, operation: Operation) {
+        self.undo_redo_buffer.push(operation);
+        self.undo_redo_buffer.merge_recent(Instant::now());
+        self.limit_undo_redo_buffer(self.undo_redo_buffer.limit);
+    }
+
+    /// Merges the oldest operations into the snapshot at the start of the buffer until it has at most `limit` operations.
+    fn limit_undo_redo_buffer(&mut self, limit: usize) {
+        let len = self.undo_redo_buffer.buffer.len();
+        if len <= limit {
+            return;
+        }
+        self.record_changes();
+
+        let buffer = &mut self.undo_redo_buffer;
+        let (mut cells, mut tags, first) = match &mut buffer.buffer[0] {
+            Operation::Snapshot { cells, tags } => (std::mem::take(cells), std::mem::take(tags), 1),
+            _ => (
+                vec![Cell::Empty; self.cells.len()],
+                vec![None; self.tags.len()],
+                0,
+            ),
+        };
+        // What remains is the snapshot and the newest operations
+        let end = len - limit + 1;
+        for change in buffer.changes.operations[first..end].iter().flatten() {
+            (cells[change.index], tags[change.index]) = change.after;
+        }
+
+        buffer
+            .buffer
+            .splice(..end, [Operation::Snapshot { cells, tags }]);
+        // The snapshot is never undone or redone
+        buffer.changes.operations.splice(..end, [Vec::new()]);
+        buffer.index -= end - 1;
+        // The limit is for everything that can be redone
+        buffer.branch = None;
+        buffer.group_start = buffer.group_start.saturating_sub(end - 1).max(1);
+        for checkpoint in &mut buffer.checkpoints {
+            checkpoint.index = checkpoint
+                .index
+                .and_then(|index| index.checked_sub(end - 1))
+                .filter(|index| *index >= 1);
+        }
+    }
+
+    /// Sets the cell and the tag at the index on the grid and on the applied cells and tags.
+    fn set_change(&mut self, index: usize, (cell, tag): (Cell, Option<u8>)) {
+        self.cells[index] = cell;
+        self.tags[index] = tag;
+        if let Some((applied_cells, applied_tags)) = &mut self.undo_redo_buffer.changes.applied {
+            applied_cells[index] = cell;
+            applied_tags[index] = tag;
+        }
+    }
+
+    /// Applies the operation without pushing it.
+    fn perform(&mut self, operation: &Operation) {
+        match operation {
+            Operation::SetCell { point, cell } => {
+                let grid_cell = self.get_mut_cell(*point);
+                *grid_cell = *cell;
+            }
+            Operation::SetCells(cells) => set_cells(self, cells),
+            Operation::Measure(measurement) => {
+                crate::grid::set_measured_cells(self, measurement.points());
+            }
+            Operation::Fill {
+                point,
+                first_cell,
+                fill_cell,
+            } => crate::grid::tools::fill::fill(self, *point, *first_cell, *fill_cell),
+            Operation::Clear => {
+                self.clear();
+            }
+            Operation::Tag(tags) => {
+                for (point, tag) in tags {
+                    *self.get_mut_tag(*point) = *tag;
+                }
+            }
+            Operation::Promote { tag, cell } => crate::grid::tag::promote(self, *tag, *cell),
+            Operation::Transform(transform) => {
+                crate::grid::tools::transform::transform(self, *transform)
+            }
+            Operation::Snapshot { cells, tags } => {
+                self.cells.clone_from(cells);
+                self.tags.clone_from(tags);
+            }
+        }
+    }
+
+    /// Returns the indices of the cells that the operation can change, in order, or `None` if it can change any of them.
+    fn changeable_indices(&self, operation: &Operation) -> Option<Vec<usize>> {
+        let index = |point: &Point| point.y as usize * self.size.width as usize + point.x as usize;
+        let mut indices: Vec<usize> = match operation {
+            Operation::SetCell { point, .. } => vec![index(point)],
+            Operation::SetCells(cells) => cells.iter().map(|(point, _)| index(point)).collect(),
+            Operation::Tag(tags) => tags.iter().map(|(point, _)| index(point)).collect(),
+            Operation::Measure(measurement) => {
+                measurement.points().map(|point| index(&point)).collect()
+            }
+            Operation::Clear => (0..self.cells.len())
+                .filter(|index| self.cells[*index] != Cell::Empty || self.tags[*index].is_some())
+                .collect(),
+            Operation::Promote { tag, .. } => (0..self.tags.len())
+                .filter(|index| self.tags[*index] == Some(*tag))
+                .collect(),
+            Operation::Fill { .. } | Operation::Transform(_) | Operation::Snapshot { .. } => {
+                return None
+            }
+        };
+        indices.sort_unstable();
+        indices.dedup();
+        Some(indices)
+    }
+
+    /// Applies the operation without pushing it and returns the cells and tags it changed.
+    ///
+    /// Only the cells that the operation can change are kept from before it, unless it can change any of them.
+    fn replay(&mut self, operation: &Operation) -> Vec<Change> {
+        let before: Vec<(usize, (Cell, Option<u8>))> = match self.changeable_indices(operation) {
+            Some(indices) => indices
+                .into_iter()
+                .map(|index| (index, (self.cells[index], self.tags[index])))
+                .collect(),
+            None => self
+                .cells
+                .iter()
+                .copied()
+                .zip(self.tags.iter().copied())
+                .enumerate()
+                .collect(),
+        };
+        self.perform(operation);
+        before
+            .into_iter()
+            .filter(|(index, before)| *before != (self.cells[*index], self.tags[*index]))
+            .map(|(index, before)| Change {
+                index,
+                before,
+                after: (self.cells[index], self.tags[index]),
+            })
+            .collect()
+    }
+
+    /// Records the changes of the operations that were pushed since the last undo or redo.
+    ///
+    /// They are replayed on the cells and tags from before them, which also drops anything changed without an operation,
+    /// just like replaying the whole buffer would.
+    fn record_changes(&mut self) {
+        let changes = &mut self.undo_redo_buffer.changes;
+        let (cells, tags) = match changes.applied.take() {
+            Some(applied) => applied,
+            None => return self.rebuild(),
+        };
+        let start = changes.operations.len();
+        if start < self.undo_redo_buffer.index {
+            self.cells = cells;
+            self.tags = tags;
+
+            let buffer = std::mem::take(&mut self.undo_redo_buffer.buffer);
+            let operations = &buffer[start..self.undo_redo_buffer.index];
+            for operation in operations {
+                let changes = self.replay(operation);
+                self.undo_redo_buffer.changes.operations.push(changes);
+            }
+            if has_transform(operations) {
+                self.remake_clues_solutions();
+            }
+            self.undo_redo_buffer.buffer = buffer;
+
+            self.undo_redo_buffer.changes.applied = Some((self.cells.clone(), self.tags.clone()));
+        } else {
+            self.undo_redo_buffer.changes.applied = Some((cells, tags));
+        }
+    }
+
+    /// Replays all operations that are done on a cleared grid, recording their changes again.
+    pub fn rebuild(&mut self) {
         self.clear();
 
-        for operation in self.undo_redo_buffer.buffer.clone()[..self.undo_redo_buffer.index].iter()
+        // Taken out temporarily so that we don't have to clone the whole buffer
+        let buffer = std::mem::take(&mut self.undo_redo_buffer.buffer);
+        let mut operations = Vec::with_capacity(self.undo_redo_buffer.index);
+        for operation in &buffer[..self.undo_redo_buffer.index] {
+            operations.push(self.replay(operation));
+        }
+        if has_transform(&buffer[..self.undo_redo_buffer.index]) {
+            self.remake_clues_solutions();
+        }
+        self.undo_redo_buffer.buffer = buffer;
+
+        self.undo_redo_buffer.changes = Changes {
+            operations,
+            applied: Some((self.cells.clone(), self.tags.clone())),
+        };
+    }
+
+    /// Starts the undo redo buffer over from the current cells and tags, e.g. once they were loaded without a history.
+    ///
+    /// Unless the grid is empty, the buffer starts with a snapshot of them so that undoing, e.g. a clear,
+    /// brings them back instead of what replaying from an empty grid would lead to.
+    pub fn restart_undo_redo_buffer(&mut self) {
+        self.undo_redo_buffer = self.undo_redo_buffer.emptied();
+        if self.cells.iter().all(|cell| *cell == Cell::Empty)
+            && self.tags.iter().all(Option::is_none)
         {
-            match operation {
-                Operation::SetCell { point, cell } => {
-                    let grid_cell = self.get_mut_cell(*point);
-                    *grid_cell = *cell;
-                }
-                Operation::Measure(line_points) => {
-                    crate::grid::set_measured_cells(self, line_points);
+            return;
+        }
+        self.undo_redo_buffer.buffer.push(Operation::Snapshot {
+            cells: self.cells.clone(),
+            tags: self.tags.clone(),
+        });
+        self.undo_redo_buffer.index = 1;
+        self.undo_redo_buffer.group_start = 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rng::Rng, util};
+
+    #[rustfmt::skip]
+    const LINES: [&str; 4] = [
+        "1 11 ",
+        "11  1",
+        "  111",
+        "1 1 1",
+    ];
+
+    fn random_point(rng: &mut Rng, grid: &Grid) -> Point {
+        Point {
+            x: (rng.next_u64() % grid.size.width as u64) as u16,
+            y: (rng.next_u64() % grid.size.height as u64) as u16,
+        }
+    }
+
+    fn random_cell(rng: &mut Rng) -> Cell {
+        [Cell::Empty, Cell::Filled, Cell::Maybed, Cell::Crossed][(rng.next_u64() % 4) as usize]
+    }
+
+    fn random_operation(rng: &mut Rng, grid: &Grid) -> Operation {
+        match rng.next_u64() % 7 {
+            0 => Operation::SetCell {
+                point: random_point(rng, grid),
+                cell: random_cell(rng),
+            },
+            1 => Operation::SetCells(
+                (0..3)
+                    .map(|_| (random_point(rng, grid), random_cell(rng)))
+                    .collect(),
+            ),
+            2 => {
+                let points =
+                    util::get_line_points(random_point(rng, grid), random_point(rng, grid));
+                Operation::Measure(Measurement::new(points).unwrap())
+            }
+            3 => Operation::Clear,
+            4 => {
+                let point = random_point(rng, grid);
+                let first_cell = grid.get_cell(point);
+                // Filling with the same kind of cell would never end
+                let mut fill_cell = random_cell(rng);
+                while fill_cell.kind() == first_cell.kind() {
+                    fill_cell = random_cell(rng);
                 }
                 Operation::Fill {
                     point,
                     first_cell,
                     fill_cell,
-                } => crate::grid::tools::fill::fill(self, *point, *first_cell, *fill_cell),
-                Operation::Clear => {
-                    self.clear();
                 }
             }
+            5 => Operation::Tag(vec![
+                (random_point(rng, grid), Some(1)),
+                (random_point(rng, grid), None),
+            ]),
+            _ => Operation::Promote {
+                tag: 1,
+                cell: random_cell(rng),
+            },
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let point = |x| Point { x, y: 0 };
+        let set = |grid: &mut Grid, x, cell| {
+            *grid.get_mut_cell(point(x)) = cell;
+            grid.undo_redo_buffer.push(Operation::SetCell {
+                point: point(x),
+                cell,
+            });
+        };
+        set(&mut grid, 0, Cell::Maybed);
+        grid.undo_redo_buffer.start_group();
+        set(&mut grid, 1, Cell::Filled);
+        set(&mut grid, 2, Cell::Filled);
+        grid.undo_redo_buffer
+            .push(Operation::SetCells(vec![(point(4), Cell::Crossed)]));
+        *grid.get_mut_cell(point(4)) = Cell::Crossed;
+
+        grid.undo_redo_buffer.group();
+        assert_eq!(
+            grid.undo_redo_buffer.buffer[1..],
+            [Operation::SetCells(vec![
+                (point(1), Cell::Filled),
+                (point(2), Cell::Filled),
+                (point(4), Cell::Crossed)
+            ])]
+        );
+        assert!(grid.undo_last_cell());
+        assert_eq!(
+            grid.cells[..5],
+            [
+                Cell::Maybed,
+                Cell::Empty,
+                Cell::Empty,
+                Cell::Empty,
+                Cell::Empty
+            ]
+        );
+        assert!(grid.redo_last_cell());
+        assert_eq!(
+            grid.cells[..5],
+            [
+                Cell::Maybed,
+                Cell::Filled,
+                Cell::Filled,
+                Cell::Empty,
+                Cell::Crossed
+            ]
+        );
+
+        // A single operation stays as it is
+        grid.undo_redo_buffer.start_group();
+        set(&mut grid, 3, Cell::Crossed);
+        grid.undo_redo_buffer.group();
+        assert!(matches!(
+            grid.undo_redo_buffer.buffer[2],
+            Operation::SetCell { .. }
+        ));
+        // Clears and the like are never grouped
+        grid.undo_redo_buffer.push(Operation::Clear);
+        set(&mut grid, 0, Cell::Filled);
+        grid.undo_redo_buffer.group();
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 5);
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(1);
+        let mut operations = Vec::new();
+        for _ in 0..50 {
+            let operation = random_operation(&mut rng, &grid);
+            grid.perform(&operation);
+            grid.undo_redo_buffer.push(operation.clone());
+            grid.limit_undo_redo_buffer(10);
+            operations.push(operation);
+            assert!(grid.undo_redo_buffer.buffer.len() <= 10);
+            assert_eq!(
+                grid.undo_redo_buffer.index,
+                grid.undo_redo_buffer.buffer.len()
+            );
+        }
+        assert!(matches!(
+            grid.undo_redo_buffer.buffer[0],
+            Operation::Snapshot { .. }
+        ));
+
+        // The snapshot and the replay of all operations lead to the same grid
+        let mut replayed_grid = Grid::from_lines(&LINES).unwrap();
+        replayed_grid.undo_redo_buffer.buffer = operations;
+        replayed_grid.undo_redo_buffer.index = 50;
+        replayed_grid.rebuild();
+        let (cells, tags) = (grid.cells.clone(), grid.tags.clone());
+        grid.rebuild();
+        assert_eq!(grid.cells, replayed_grid.cells);
+        assert_eq!(grid.tags, replayed_grid.tags);
+        assert_eq!((&cells, &tags), (&grid.cells, &grid.tags));
+
+        // Undoing stops at the snapshot
+        for _ in 0..9 {
+            assert!(grid.undo_last_cell());
+        }
+        assert!(!grid.undo_last_cell());
+        assert_eq!(grid.undo_redo_buffer.index, 1);
+        for _ in 0..9 {
+            assert!(grid.redo_last_cell());
+        }
+        assert_eq!((&cells, &tags), (&grid.cells, &grid.tags));
+
+        // Each grid has its own limit, which it keeps when its buffer starts over or is restored
+        grid.undo_redo_buffer.limit = 5;
+        grid.restart_undo_redo_buffer();
+        grid.undo_redo_buffer.restore(UndoRedoBuffer::default());
+        for _ in 0..20 {
+            grid.apply(random_operation(&mut rng, &grid));
+        }
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 5);
+        assert_eq!(replayed_grid.undo_redo_buffer.limit, DEFAULT_LIMIT);
+    }
+
+    #[test]
+    fn test_jump_in_history() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(2);
+        let mut states = vec![(grid.cells.clone(), grid.tags.clone())];
+        for _ in 0..20 {
+            let operation = random_operation(&mut rng, &grid);
+            grid.perform(&operation);
+            grid.undo_redo_buffer.push(operation);
+            states.push((grid.cells.clone(), grid.tags.clone()));
+        }
+
+        for index in [3, 17, 0, 20, 20] {
+            grid.jump_in_history(index);
+            assert_eq!(grid.undo_redo_buffer.index, index);
+            assert_eq!(
+                (&grid.cells, &grid.tags),
+                (&states[index].0, &states[index].1)
+            );
+        }
+        assert!(!grid.jump_in_history(20));
+        // Nothing is beyond the end
+        assert!(!grid.jump_in_history(25));
+        assert_eq!(grid.undo_redo_buffer.index, 20);
+    }
+
+    #[test]
+    fn test_undo_redo_all() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(5);
+        assert!(!grid.undo_all());
+        for _ in 0..20 {
+            let operation = random_operation(&mut rng, &grid);
+            grid.perform(&operation);
+            grid.undo_redo_buffer.push(operation);
+        }
+        let cells = grid.cells.clone();
+        assert!(!grid.redo_all());
+
+        let mut stepped_grid = Grid::from_lines(&LINES).unwrap();
+        stepped_grid.undo_redo_buffer.buffer = grid.undo_redo_buffer.buffer.clone();
+        stepped_grid.undo_redo_buffer.index = 20;
+        stepped_grid.rebuild();
+        while stepped_grid.undo_last_cell() {}
+
+        assert!(grid.undo_all());
+        assert_eq!(grid.undo_redo_buffer.index, 0);
+        assert_eq!(grid.cells, stepped_grid.cells);
+        assert!(!grid.undo_all());
+        assert!(grid.redo_all());
+        assert_eq!(grid.cells, cells);
+
+        // The snapshot stays
+        grid.limit_undo_redo_buffer(10);
+        assert!(grid.undo_all());
+        assert_eq!(grid.undo_redo_buffer.index, 1);
+    }
+
+    #[test]
+    fn test_merge_recent() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let window = Some(Duration::from_millis(300));
+        grid.undo_redo_buffer.merge_window = window;
+        let start = Instant::now();
+        let push = |grid: &mut Grid, operation: Operation, milliseconds: u64| {
+            grid.perform(&operation);
+            grid.undo_redo_buffer.push(operation);
+            grid.undo_redo_buffer
+                .merge_recent(start + Duration::from_millis(milliseconds))
+        };
+        let set_cell = |x: u16, cell: Cell| Operation::SetCell {
+            point: Point { x, y: 0 },
+            cell,
+        };
+
+        assert!(!push(&mut grid, set_cell(0, Cell::Filled), 0));
+        assert!(push(&mut grid, set_cell(1, Cell::Crossed), 200));
+        assert!(push(&mut grid, set_cell(2, Cell::Filled), 400));
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 1);
+        // Too late
+        assert!(!push(&mut grid, set_cell(3, Cell::Filled), 1000));
+        // Clears and the like are never merged
+        grid.clear();
+        assert!(!push(&mut grid, Operation::Clear, 1100));
+        assert!(!push(&mut grid, set_cell(0, Cell::Maybed), 1200));
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 4);
+
+        // The merged cells are undone and redone at once
+        let cells = grid.cells.clone();
+        grid.undo_all();
+        assert!(grid.cells.iter().all(|cell| *cell == Cell::Empty));
+        assert!(grid.redo_last_cell());
+        assert_eq!(grid.cells[..3], [Cell::Filled, Cell::Crossed, Cell::Filled]);
+        assert_eq!(grid.cells[3], Cell::Empty);
+        grid.redo_all();
+        assert_eq!(grid.cells, cells);
+
+        // Nothing is merged into a redone operation
+        grid.undo_last_cell();
+        grid.redo_last_cell();
+        assert!(!push(&mut grid, set_cell(1, Cell::Maybed), 1300));
+
+        // The changes of the previous operation might be recorded already
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        grid.undo_redo_buffer.merge_window = window;
+        push(&mut grid, set_cell(0, Cell::Filled), 0);
+        grid.record_changes();
+        assert!(push(&mut grid, set_cell(0, Cell::Crossed), 100));
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells[0], Cell::Empty);
+
+        // Without a window, nothing is merged
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        push(&mut grid, set_cell(0, Cell::Filled), 0);
+        assert!(!push(&mut grid, set_cell(1, Cell::Filled), 0));
+    }
+
+    #[test]
+    fn test_transform() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_lines(&[
+            "111",
+            "1  ",
+            "   ",
+        ]).unwrap();
+        // In the editor, the cells are the solution
+        grid.cells = grid
+            .solution
+            .iter()
+            .map(|filled| if *filled { Cell::Filled } else { Cell::Empty })
+            .collect();
+        grid.restart_undo_redo_buffer();
+        let (cells, clues_solutions) =
+            (grid.cells.clone(), grid.horizontal_clues_solutions.clone());
+
+        let operation = Operation::Transform(Transform::Rotate);
+        grid.perform(&operation);
+        grid.undo_redo_buffer.push(operation);
+        let rotated_clues_solutions = grid.horizontal_clues_solutions.clone();
+        assert_ne!(rotated_clues_solutions, clues_solutions);
+
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.cells, cells);
+        assert_eq!(grid.horizontal_clues_solutions, clues_solutions);
+        assert!(grid.redo_last_cell());
+        assert_eq!(grid.horizontal_clues_solutions, rotated_clues_solutions);
+
+        // Replaying makes the same clues
+        let set_cell = Operation::SetCell {
+            point: Point { x: 0, y: 2 },
+            cell: Cell::Filled,
+        };
+        grid.perform(&set_cell);
+        grid.undo_redo_buffer.push(set_cell);
+        grid.remake_clues_solutions();
+        let edited_clues_solutions = grid.horizontal_clues_solutions.clone();
+        grid.horizontal_clues_solutions = clues_solutions;
+        grid.rebuild();
+        assert_eq!(grid.horizontal_clues_solutions, edited_clues_solutions);
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(3);
+        let mut push = |grid: &mut Grid| {
+            let operation = random_operation(&mut rng, grid);
+            grid.perform(&operation);
+            grid.undo_redo_buffer.push(operation);
+        };
+        let index =
+            |grid: &Grid, number: usize| grid.undo_redo_buffer.checkpoints[number - 1].index;
+
+        for _ in 0..5 {
+            push(&mut grid);
+        }
+        assert_eq!(grid.undo_redo_buffer.add_checkpoint(), 1);
+        for _ in 0..5 {
+            push(&mut grid);
+        }
+        assert_eq!(grid.undo_redo_buffer.add_checkpoint(), 2);
+        let cells = grid.cells.clone();
+
+        // Undoing keeps the checkpoints ahead so that they can be redone
+        grid.jump_in_history(7);
+        assert_eq!(index(&grid, 2), Some(10));
+        assert!(grid.jump_in_history(index(&grid, 2).unwrap()));
+        assert_eq!(grid.cells, cells);
+
+        // Until the undone operations are replaced
+        grid.jump_in_history(7);
+        push(&mut grid);
+        assert_eq!(index(&grid, 1), Some(5));
+        assert_eq!(index(&grid, 2), None);
+
+        // Merging the operations up to a checkpoint into the snapshot removes it too
+        grid.limit_undo_redo_buffer(5);
+        assert_eq!(index(&grid, 1), Some(2));
+        grid.limit_undo_redo_buffer(2);
+        assert_eq!(index(&grid, 1), None);
+    }
+
+    #[test]
+    fn test_branch() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(4);
+        assert!(!grid.switch_branch());
+
+        let mut push = |grid: &mut Grid| {
+            let operation = Operation::SetCells(vec![(random_point(&mut rng, grid), Cell::Filled)]);
+            grid.perform(&operation);
+            grid.undo_redo_buffer.push(operation);
+        };
+        for _ in 0..10 {
+            push(&mut grid);
+        }
+        let discarded_cells = grid.cells.clone();
+        grid.jump_in_history(6);
+        let cells = grid.cells.clone();
+        push(&mut grid);
+        let replacing_cells = grid.cells.clone();
+
+        // Back to the discarded operations
+        assert!(grid.switch_branch());
+        assert_eq!(grid.undo_redo_buffer.index, 6);
+        assert_eq!(grid.cells, cells);
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 10);
+        grid.jump_in_history(10);
+        assert_eq!(grid.cells, discarded_cells);
+
+        // And to the one that replaced them
+        assert!(grid.switch_branch());
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 7);
+        assert!(grid.redo_last_cell());
+        assert_eq!(grid.cells, replacing_cells);
+
+        // The branch is dropped once the buffer is full
+        grid.limit_undo_redo_buffer(5);
+        assert!(!grid.switch_branch());
+    }
+
+    /// Neither undoing nor redoing copies the buffer or replays it, however long it is.
+    #[test]
+    fn test_long_buffer() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(0);
+        for _ in 0..10_000 {
+            let point = random_point(&mut rng, &grid);
+            let operation = if rng.chance(0.5) {
+                Operation::SetCell {
+                    point,
+                    cell: random_cell(&mut rng),
+                }
+            } else {
+                let points = util::get_line_points(point, random_point(&mut rng, &grid));
+                Operation::Measure(Measurement::new(points).unwrap())
+            };
+            grid.undo_redo_buffer.push(operation);
+        }
+        // The buffer stays where it is, uncloned
+        let buffer = |grid: &Grid| {
+            let buffer = &grid.undo_redo_buffer.buffer;
+            (buffer.as_ptr(), buffer.capacity())
+        };
+        let pushed_buffer = buffer(&grid);
+        grid.rebuild();
+        assert_eq!(buffer(&grid), pushed_buffer);
+
+        // The recorded changes stay the same too, as nothing is replayed to record them again
+        let changes = |grid: &Grid| {
+            let operations = &grid.undo_redo_buffer.changes.operations;
+            (operations.as_ptr(), operations.len())
+        };
+        let rebuilt_changes = changes(&grid);
+        for _ in 0..1_000 {
+            assert!(grid.undo_last_cell());
+        }
+        for _ in 0..1_000 {
+            assert!(grid.redo_last_cell());
+        }
+        assert_eq!(buffer(&grid), pushed_buffer);
+        assert_eq!(changes(&grid), rebuilt_changes);
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let point = |x| Point { x, y: 0 };
+        grid.apply(Operation::SetCell {
+            point: point(0),
+            cell: Cell::Filled,
+        });
+
+        assert!(grid.transaction(|transaction| {
+            for x in 0..3 {
+                transaction.set_cell(point(x), Cell::Filled);
+            }
+            // What was set before is seen right away
+            assert_eq!(transaction.get_cell(point(2)), Cell::Filled);
+            transaction.set_cell(point(2), Cell::Crossed);
+        }));
+        assert_eq!(
+            grid.undo_redo_buffer.buffer[1..],
+            [Operation::SetCells(vec![
+                (point(1), Cell::Filled),
+                (point(2), Cell::Filled),
+                (point(2), Cell::Crossed),
+            ])]
+        );
+
+        // Nothing is pushed for cells that are already set
+        assert!(!grid.transaction(|transaction| transaction.set_cell(point(0), Cell::Filled)));
+        assert_eq!(grid.undo_redo_buffer.buffer.len(), 2);
+
+        assert!(grid.undo_last_cell());
+        assert_eq!(grid.get_cell(point(1)), Cell::Empty);
+        assert_eq!(grid.get_cell(point(2)), Cell::Empty);
+        assert_eq!(grid.get_cell(point(0)), Cell::Filled);
+    }
+
+    /// Replays the history recorded by the tools and checks that it leads to the cells and tags they left.
+    #[test]
+    fn test_replay() {
+        let mut grid = Grid::from_lines(&LINES).unwrap();
+        let mut rng = Rng::new(3);
+        for _ in 0..500 {
+            let operation = random_operation(&mut rng, &grid);
+            let (cells, tags) = (grid.cells.clone(), grid.tags.clone());
+            let changes = grid.replay(&operation);
+
+            // Every changed cell is recorded, and only those
+            let changed: Vec<usize> = (0..cells.len())
+                .filter(|index| {
+                    (cells[*index], tags[*index]) != (grid.cells[*index], grid.tags[*index])
+                })
+                .collect();
+            assert_eq!(
+                changes
+                    .iter()
+                    .map(|change| change.index)
+                    .collect::<Vec<_>>(),
+                changed
+            );
+        }
+    }
+
+    #[test]
+    fn test_replay_tools() {
+        use crate::grid::tools::{auto_cross, prefill};
+
+        let mut rng = Rng::new(0);
+        for _ in 0..20 {
+            let mut grid = Grid::from_lines(&LINES).unwrap();
+
+            for _ in 0..30 {
+                match rng.next_u64() % 4 {
+                    0 => {
+                        prefill::fill_overlaps(&mut grid);
+                    }
+                    1 => {
+                        let point = random_point(&mut rng, &grid);
+                        auto_cross::cross_solved_lines_at(&mut grid, point);
+                    }
+                    2 => {
+                        auto_cross::cross_empty_lines(&mut grid);
+                    }
+                    _ => {
+                        let operation = random_operation(&mut rng, &grid);
+                        grid.apply(operation);
+                    }
+                }
+            }
+
+            let mut replayed_grid = Grid::from_lines(&LINES).unwrap();
+            replayed_grid.undo_redo_buffer.buffer = grid.undo_redo_buffer.buffer.clone();
+            replayed_grid.undo_redo_buffer.index = grid.undo_redo_buffer.index;
+            replayed_grid.rebuild();
+            assert_eq!(grid.cells, replayed_grid.cells);
+            assert_eq!(grid.tags, replayed_grid.tags);
+        }
+    }
+
+    /// Compares undoing, redoing, grouping, merging, limiting and switching branches with replaying the operations on another grid.
+    #[test]
+    fn test_changes() {
+        let mut rng = Rng::new(0);
+        for _ in 0..20 {
+            let mut grid = Grid::from_lines(&LINES).unwrap();
+            // Every push is within the window of the previous one
+            grid.undo_redo_buffer.merge_window = Some(Duration::MAX);
+
+            for _ in 0..200 {
+                match rng.next_u64() % 7 {
+                    0 => {
+                        grid.undo_last_cell();
+                    }
+                    1 => {
+                        grid.redo_last_cell();
+                    }
+                    2 => grid.undo_redo_buffer.start_group(),
+                    3 => grid.undo_redo_buffer.group(),
+                    4 => {
+                        grid.switch_branch();
+                    }
+                    _ => {
+                        // The operations are applied before they are pushed, just like when playing
+                        let operation = random_operation(&mut rng, &grid);
+                        grid.perform(&operation);
+                        grid.undo_redo_buffer.push(operation);
+                        if rng.next_u64().is_multiple_of(2) {
+                            grid.undo_redo_buffer.merge_recent(Instant::now());
+                        }
+                        grid.limit_undo_redo_buffer(30);
+                    }
+                }
+
+                let mut replayed_grid = Grid::from_lines(&LINES).unwrap();
+                replayed_grid.undo_redo_buffer.buffer = grid.undo_redo_buffer.buffer.clone();
+                replayed_grid.undo_redo_buffer.index = grid.undo_redo_buffer.index;
+                replayed_grid.rebuild();
+                assert_eq!(grid.cells, replayed_grid.cells);
+                assert_eq!(grid.tags, replayed_grid.tags);
+            }
         }
     }
 }
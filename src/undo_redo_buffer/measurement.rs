@@ -0,0 +1,339 @@
+//! Measured cells always form a contiguous path. Instead of storing every point of that path,
+//! we store where it starts and then runs of steps in the same direction.
+
+use terminal::util::{Point, Size};
+
+/// A step from a cell to one of its eight neighbors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    fn between(from: Point, to: Point) -> Option<Self> {
+        let offset = (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32);
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|direction| direction.offset() == offset)
+    }
+
+    const fn abbreviation(self) -> &'static str {
+        match self {
+            Direction::Up => "U",
+            Direction::UpRight => "UR",
+            Direction::Right => "R",
+            Direction::DownRight => "DR",
+            Direction::Down => "D",
+            Direction::DownLeft => "DL",
+            Direction::Left => "L",
+            Direction::UpLeft => "UL",
+        }
+    }
+
+    fn from_abbreviation(abbreviation: &str) -> Option<Self> {
+        Some(match abbreviation {
+            "U" => Direction::Up,
+            "UR" => Direction::UpRight,
+            "R" => Direction::Right,
+            "DR" => Direction::DownRight,
+            "D" => Direction::Down,
+            "DL" => Direction::DownLeft,
+            "L" => Direction::Left,
+            "UL" => Direction::UpLeft,
+            _ => return None,
+        })
+    }
+
+    const fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::UpRight => (1, -1),
+            Direction::Right => (1, 0),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (0, 1),
+            Direction::DownLeft => (-1, 1),
+            Direction::Left => (-1, 0),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+
+    const fn step(self, point: Point) -> Point {
+        let (dx, dy) = self.offset();
+        Point {
+            x: (point.x as i32 + dx) as u16,
+            y: (point.y as i32 + dy) as u16,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    start_point: Point,
+    /// How many times in a row a step is taken in the same direction.
+    runs: Vec<(Direction, u8)>,
+}
+
+impl Measurement {
+    /// Encodes the points of a path, returning `None` if there are no points or
+    /// two consecutive points are not neighbors.
+    pub fn new(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let start_point = points.next()?;
+
+        let mut runs = Vec::<(Direction, u8)>::new();
+        let mut previous_point = start_point;
+        for point in points {
+            let direction = Direction::between(previous_point, point)?;
+
+            match runs.last_mut() {
+                Some((last_direction, count))
+                    if *last_direction == direction && *count != u8::MAX =>
+                {
+                    *count += 1
+                }
+                _ => runs.push((direction, 1)),
+            }
+
+            previous_point = point;
+        }
+
+        Some(Self { start_point, runs })
+    }
+
+    /// Encodes the measurement as text: the start point followed by the runs, e.g. `3 4 R5 DL2`.
+    pub fn encode(&self) -> String {
+        let mut string = format!("{} {}", self.start_point.x, self.start_point.y);
+        for (direction, count) in &self.runs {
+            string.push(' ');
+            string.push_str(direction.abbreviation());
+            string.push_str(&count.to_string());
+        }
+        string
+    }
+
+    pub fn decode(str: &str) -> Option<Self> {
+        let mut parts = str.split(' ');
+        let start_point = Point {
+            x: parts.next()?.parse().ok()?,
+            y: parts.next()?.parse().ok()?,
+        };
+
+        let runs = parts
+            .map(|run| {
+                let count_start = run.find(|char: char| char.is_ascii_digit())?;
+                let (abbreviation, count) = run.split_at(count_start);
+                let count = count.parse::<u8>().ok().filter(|count| *count != 0)?;
+
+                Some((Direction::from_abbreviation(abbreviation)?, count))
+            })
+            .collect::<Option<Vec<(Direction, u8)>>>()?;
+
+        Some(Self { start_point, runs })
+    }
+
+    /// Checks whether all points of the path are on a grid of the given size.
+    pub fn is_within(&self, size: &Size) -> bool {
+        let mut x = self.start_point.x as i32;
+        let mut y = self.start_point.y as i32;
+        let is_within = |x: i32, y: i32| {
+            (0..size.width as i32).contains(&x) && (0..size.height as i32).contains(&y)
+        };
+
+        if !is_within(x, y) {
+            return false;
+        }
+        for (direction, count) in &self.runs {
+            let (dx, dy) = direction.offset();
+            x += dx * *count as i32;
+            y += dy * *count as i32;
+
+            // A run is a straight line so if both ends are within, everything in between is too
+            if !is_within(x, y) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the amount of points on the path.
+    pub fn len(&self) -> usize {
+        1 + self
+            .runs
+            .iter()
+            .map(|(_, count)| *count as usize)
+            .sum::<usize>()
+    }
+
+    /// Reconstructs the points of the path.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        let steps = self
+            .runs
+            .iter()
+            .flat_map(|(direction, count)| std::iter::repeat_n(*direction, *count as usize));
+
+        std::iter::once(self.start_point).chain(steps.scan(self.start_point, |point, direction| {
+            *point = direction.step(*point);
+            Some(*point)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(points: &[(u16, u16)]) -> Vec<Point> {
+        points.iter().map(|(x, y)| Point { x: *x, y: *y }).collect()
+    }
+
+    fn assert_round_trip(points: &[Point], run_count: usize) {
+        let measurement = Measurement::new(points.iter().copied()).unwrap();
+
+        assert_eq!(measurement.runs.len(), run_count);
+        assert_eq!(measurement.len(), points.len());
+        assert_eq!(measurement.points().collect::<Vec<Point>>(), points);
+    }
+
+    #[test]
+    fn test_single_point() {
+        assert_round_trip(&points(&[(3, 4)]), 0);
+    }
+
+    #[test]
+    fn test_straight_lines() {
+        assert_round_trip(&points(&[(0, 0), (1, 0), (2, 0), (3, 0)]), 1);
+        assert_round_trip(&points(&[(2, 3), (2, 2), (2, 1), (2, 0)]), 1);
+        assert_round_trip(&points(&[(3, 0), (2, 1), (1, 2), (0, 3)]), 1);
+        assert_round_trip(&points(&[(2, 2), (1, 1), (0, 0)]), 1);
+    }
+
+    #[test]
+    fn test_l_shape() {
+        assert_round_trip(&points(&[(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]), 2);
+    }
+
+    #[test]
+    fn test_revisited_cells() {
+        assert_round_trip(
+            &points(&[(0, 0), (1, 0), (2, 0), (1, 0), (0, 0), (1, 1)]),
+            3,
+        );
+    }
+
+    #[test]
+    fn test_bresenham_line() {
+        let line_points: Vec<Point> =
+            crate::util::get_line_points(Point { x: 0, y: 0 }, Point { x: 7, y: 3 }).collect();
+
+        let measurement = Measurement::new(line_points.iter().copied()).unwrap();
+        assert_eq!(measurement.points().collect::<Vec<Point>>(), line_points);
+    }
+
+    #[test]
+    fn test_long_runs() {
+        let line_points: Vec<Point> = (0..600).map(|x| Point { x, y: 0 }).collect();
+
+        // 599 steps don't fit into a single run
+        assert_round_trip(&line_points, 3);
+    }
+
+    #[test]
+    fn test_encode() {
+        let measurement =
+            Measurement::new(points(&[(3, 4), (4, 4), (5, 4), (4, 5), (3, 6), (3, 5)])).unwrap();
+
+        assert_eq!(measurement.encode(), "3 4 R2 DL2 U1");
+        assert_eq!(
+            Measurement::decode("3 4 R2 DL2 U1").as_ref(),
+            Some(&measurement)
+        );
+        assert_eq!(
+            Measurement::decode("3 4")
+                .unwrap()
+                .points()
+                .collect::<Vec<Point>>(),
+            points(&[(3, 4)])
+        );
+
+        let size = Size {
+            width: 6,
+            height: 7,
+        };
+        assert!(measurement.is_within(&size));
+        assert!(!Measurement::decode("3 4 L4").unwrap().is_within(&size));
+        assert!(!Measurement::decode("3 4 D3").unwrap().is_within(&size));
+        assert!(!Measurement::decode("6 0").unwrap().is_within(&size));
+
+        assert_eq!(Measurement::decode("3"), None);
+        assert_eq!(Measurement::decode("3 4 X2"), None);
+        assert_eq!(Measurement::decode("3 4 R"), None);
+        assert_eq!(Measurement::decode("3 4 R0"), None);
+        assert_eq!(Measurement::decode("3 4 R256"), None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(Measurement::new(Vec::new()), None);
+        assert_eq!(Measurement::new(points(&[(0, 0), (2, 0)])), None);
+        assert_eq!(Measurement::new(points(&[(0, 0), (0, 0)])), None);
+    }
+
+    #[test]
+    fn test_size() {
+        // A careless measurement dragged back and forth across a big grid
+        let drag_points: Vec<Point> = (0..20)
+            .flat_map(|round| {
+                let xs: Vec<u16> = if round % 2 == 0 {
+                    (0..99).collect()
+                } else {
+                    (0..99).rev().collect()
+                };
+                xs.into_iter().map(|x| Point { x, y: 50 })
+            })
+            .collect();
+
+        // Every round starts on the cell the previous round ended on so there is no step in between
+        let drag_points: Vec<Point> =
+            drag_points
+                .into_iter()
+                .fold(Vec::new(), |mut points, point| {
+                    if points.last() != Some(&point) {
+                        points.push(point);
+                    }
+                    points
+                });
+
+        let measurement = Measurement::new(drag_points.iter().copied()).unwrap();
+        assert_eq!(measurement.points().collect::<Vec<Point>>(), drag_points);
+
+        let naive_size = std::mem::size_of_val(drag_points.as_slice());
+        let compact_size =
+            std::mem::size_of::<Point>() + std::mem::size_of_val(measurement.runs.as_slice());
+        assert!(
+            compact_size * 10 <= naive_size,
+            "{} vs {}",
+            compact_size,
+            naive_size
+        );
+    }
+}
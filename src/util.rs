@@ -1,6 +1,8 @@
+use crate::paths;
 use std::{
     fs,
     io::{self, Read, Seek},
+    time::{SystemTime, UNIX_EPOCH},
 };
 use terminal::util::Point;
 
@@ -50,7 +52,7 @@ pub fn optimal_string_capacity(file: &fs::File) -> io::Result<usize> {
 
 /// Reads the file's content into a string.
 pub fn read_file_content(file: &mut fs::File) -> io::Result<String> {
-    let mut string = String::with_capacity(optimal_string_capacity(&file)?);
+    let mut string = String::with_capacity(optimal_string_capacity(file)?);
     file.read_to_string(&mut string)?;
     Ok(string)
 }
@@ -73,3 +75,161 @@ pub fn clear_file(writer: &mut io::BufWriter<fs::File>) -> Result<(), &'static s
         Err(_) => Err("File clear failed"),
     }
 }
+
+/// Creates a new file named `grid-N.extension` with the lowest N that is not taken yet in the export directory,
+/// returning the full path of the file and the file opened for writing.
+pub fn create_numbered_file(extension: &str) -> Result<(String, fs::File), &'static str> {
+    let dir = paths::dir(paths::Kind::Export).ok_or("Current directory is not accessible")?;
+    paths::create_dir(dir).map_err(|_| "Export directory creation failed")?;
+
+    let mut open_options = fs::OpenOptions::new();
+    open_options.create_new(true).write(true);
+
+    let mut index = 1;
+    loop {
+        let path = dir.join(format!("grid-{}.{}", index, extension));
+        match open_options.open(&path) {
+            Err(err) => match err.kind() {
+                io::ErrorKind::AlreadyExists => {
+                    if index == 9 {
+                        return Err("Too many grid files");
+                    }
+                    index += 1;
+                }
+                io::ErrorKind::PermissionDenied => return Err("Permission denied"),
+                _ => return Err("File saving error"),
+            },
+            Ok(file) => break Ok((path.display().to_string(), file)),
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes the bytes as padded base64 using the standard alphabet.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut group = [0; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (bits >> (18 - index * 6)) & 0b11_1111;
+                string.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                string.push('=');
+            }
+        }
+    }
+    string
+}
+
+/// Decodes base64 using the standard alphabet. The padding is optional.
+///
+/// Returns `None` if the string is not valid base64.
+pub fn base64_decode(str: &str) -> Option<Vec<u8>> {
+    let str = str.trim_end_matches('=');
+    let mut bytes = Vec::<u8>::with_capacity(str.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for char in str.bytes() {
+        let sextet = BASE64_ALPHABET.iter().position(|byte| *byte == char)? as u32;
+        bits = (bits << 6) | sextet;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    // A single leftover sextet cannot be produced by encoding
+    if bit_count == 6 {
+        return None;
+    }
+    Some(bytes)
+}
+
+/// Puts the text into the system clipboard if the terminal supports OSC 52.
+///
+/// Returns `false` without copying anything if yayagram was built without the `clipboard` feature.
+pub fn copy_to_clipboard(terminal: &mut terminal::Terminal, text: &str) -> bool {
+    if !cfg!(feature = "clipboard") {
+        return false;
+    }
+
+    terminal.write(&format!(
+        "\u{1b}]52;c;{}\u{7}",
+        base64_encode(text.as_bytes())
+    ));
+    true
+}
+
+/// Formats the time as a UTC date and time like `2021-07-31 18:05 UTC`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let days = (seconds / (24 * 60 * 60)) as i64;
+    let minutes = seconds / 60 % 60;
+    let hours = seconds / (60 * 60) % 24;
+
+    // This converts the days since 1970-01-01 to a date in the proleptic Gregorian calendar.
+    // See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        year, month, day, hours, minutes
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xff, 0xfe]), "//4=");
+
+        assert_eq!(base64_decode("Zg==").unwrap(), b"f");
+        assert_eq!(base64_decode("Zm8").unwrap(), b"fo");
+        assert_eq!(base64_decode("Zm9vYmFy").unwrap(), b"foobar");
+        assert_eq!(base64_decode("//4=").unwrap(), [0xff, 0xfe]);
+        assert!(base64_decode("Zm9v!").is_none());
+        assert!(base64_decode("Z").is_none());
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        use std::time::Duration;
+
+        let timestamp = |seconds| format_timestamp(UNIX_EPOCH + Duration::from_secs(seconds));
+
+        assert_eq!(timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(timestamp(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(timestamp(1_627_754_700), "2021-07-31 18:05 UTC");
+        assert_eq!(timestamp(4_102_444_799), "2099-12-31 23:59 UTC");
+    }
+}
@@ -0,0 +1,11 @@
+# A grid with an empty row
+: rows
+2 1
+0
+1 2
+
+: columns
+1 1
+1
+1
+1 1
@@ -0,0 +1,19 @@
+# Clues with two digits
+: rows
+12
+11
+1 1 1 1 1 1
+1, 1
+: columns
+4
+2
+3
+2
+3
+2
+3
+2
+3
+2
+3
+1 1